@@ -0,0 +1,100 @@
+//! Plan context store/get for degraded-mode `memory_context` handling.
+//!
+//! Mirrors `context-db.ts`: one row per `(parent_type, parent_id, type)`,
+//! upserted on store.  The local handler only serves plan-scoped context,
+//! like the server's `store` and `get` actions.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+
+use super::plan::require_plan;
+use super::{new_id, now_iso};
+
+/// Store (or replace) a plan's context of `context_type`.
+pub fn store_plan_context(conn: &Connection, plan_id: &str, context_type: &str, data: &Value) -> Result<Value> {
+    require_plan(conn, plan_id)?;
+    if !data.is_object() {
+        bail!("'data' must be a JSON object");
+    }
+
+    let now = now_iso();
+    let data_json = serde_json::to_string(data)?;
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM context_items WHERE parent_type = 'plan' AND parent_id = ?1 AND type = ?2",
+            [plan_id, context_type],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let id = match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE context_items SET data = ?1, updated_at = ?2 WHERE id = ?3",
+                params![data_json, now, id],
+            )
+            .context("failed to update context")?;
+            id
+        }
+        None => {
+            let id = new_id();
+            conn.execute(
+                "INSERT INTO context_items (id, parent_type, parent_id, type, data, created_at, updated_at)
+                 VALUES (?1, 'plan', ?2, ?3, ?4, ?5, ?5)",
+                params![id, plan_id, context_type, data_json, now],
+            )
+            .context("failed to store context")?;
+            id
+        }
+    };
+
+    Ok(json!({ "id": id, "plan_id": plan_id, "type": context_type, "updated_at": now }))
+}
+
+/// Get a plan's context of `context_type`, parsed back into JSON.
+pub fn get_plan_context(conn: &Connection, plan_id: &str, context_type: &str) -> Result<Value> {
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM context_items WHERE parent_type = 'plan' AND parent_id = ?1 AND type = ?2",
+            [plan_id, context_type],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match data {
+        Some(data) => Ok(serde_json::from_str(&data)?),
+        None => bail!("Context not found: {context_type}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixture;
+
+    #[test]
+    fn store_upserts_by_type_and_get_round_trips() {
+        let conn = fixture::open();
+        let plan_id = &fixture::plan(&conn);
+
+        let first = store_plan_context(&conn, plan_id, "research", &json!({ "v": 1 })).unwrap();
+        let second = store_plan_context(&conn, plan_id, "research", &json!({ "v": 2 })).unwrap();
+        assert_eq!(first["id"], second["id"]);
+        assert_eq!(get_plan_context(&conn, plan_id, "research").unwrap(), json!({ "v": 2 }));
+
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM context_items WHERE parent_id = ?1", [plan_id], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        assert_eq!(
+            get_plan_context(&conn, plan_id, "design").unwrap_err().to_string(),
+            "Context not found: design"
+        );
+        assert!(store_plan_context(&conn, plan_id, "x", &json!("not an object")).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Test fixture: an in-memory database built from the server's own schema.
+//!
+//! Every server migration up to [`MAX_SCHEMA_VERSION`] is compiled in
+//! straight from the server tree and applied in order with the rules of the
+//! server's `migration-runner.ts`, so a column or constraint change there
+//! shows up here as a failing parity test rather than as a silent divergence
+//! at runtime.
+//!
+//! [`MAX_SCHEMA_VERSION`]: super::schema::MAX_SCHEMA_VERSION
+
+use regex::Regex;
+use rusqlite::Connection;
+use std::collections::HashSet;
+
+use super::plan::{create_plan, NewPlan};
+
+macro_rules! migrations {
+    ($($file:literal),* $(,)?) => {
+        &[$(($file, include_str!(concat!("../../../server/src/db/migrations/", $file)))),*]
+    };
+}
+
+/// Server migrations `001..=MAX_SCHEMA_VERSION`, in the order they apply.
+const MIGRATIONS: &[(&str, &str)] = migrations![
+    "001-initial-schema.sql",
+    "002-gap-closure.sql",
+    "003-program-extended.sql",
+    "004-agent-deployments.sql",
+    "005-instruction-skill-deployments.sql",
+    "006-dynamic-hub-agent-model.sql",
+    "007-deployable-workflow-definitions.sql",
+    "008-workspace-instruction-skill-assignments.sql",
+    "009-workflow-mode.sql",
+    "010-architecture-slices.sql",
+    "011-fix-deployable-agent-role-shell.sql",
+    "012-sprints.sql",
+    "013-instruction-priority.sql",
+    "014-user-sessions.sql",
+];
+
+pub const WORKSPACE_ID: &str = "ws_fixture";
+
+/// Open an in-memory database with the server schema and one workspace.
+pub fn open() -> Connection {
+    let conn = Connection::open_in_memory().expect("in-memory database");
+    conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS _migrations (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             filename   TEXT    NOT NULL UNIQUE,
+             applied_at TEXT    NOT NULL DEFAULT (datetime('now'))
+         );",
+    )
+    .unwrap();
+    for (filename, sql) in MIGRATIONS {
+        let tx = conn.unchecked_transaction().unwrap();
+        apply_migration(&tx, sql).unwrap_or_else(|e| panic!("server migration {filename} applies: {e}"));
+        tx.execute("INSERT INTO _migrations (filename) VALUES (?1)", [filename]).unwrap();
+        tx.commit().unwrap();
+    }
+    conn.execute(
+        "INSERT INTO workspaces (id, path, name) VALUES (?1, '/tmp/fixture', 'fixture')",
+        [WORKSPACE_ID],
+    )
+    .unwrap();
+    conn
+}
+
+/// Run one migration statement by statement, as `runMigrations()` does.
+///
+/// A data-copy statement that fails with "no such column" is skipped, along
+/// with the later `DROP TABLE` / `RENAME TO` of the tables it would have
+/// replaced: those migrations upgrade schemas that `001` already includes.
+fn apply_migration(conn: &Connection, sql: &str) -> rusqlite::Result<()> {
+    let split = Regex::new(r";[ \t]*(?:\n|$)").unwrap();
+    let comments = Regex::new(r"(?s)--[^\n]*|/\*.*?\*/").unwrap();
+    let drop_table = Regex::new(r#"(?i)^DROP\s+TABLE\s+(?:IF\s+EXISTS\s+)?[`"\[]?(\w+)"#).unwrap();
+    let rename_to = Regex::new(r#"(?i)^ALTER\s+TABLE\s+\S+\s+RENAME\s+TO\s+[`"\[]?(\w+)"#).unwrap();
+    let drop_column = Regex::new(r"(?i)DROP\s+COLUMN").unwrap();
+    let into = Regex::new(r#"(?i)INTO\s+[`"\[]?(\w+)"#).unwrap();
+
+    let normalised = sql.replace("\r\n", "\n");
+    let mut skip_ddl_for: HashSet<String> = HashSet::new();
+    for stmt in split.split(&normalised).map(str::trim) {
+        if comments.replace_all(stmt, "").trim().is_empty() {
+            continue;
+        }
+        let cleaned = strip_leading_comments(stmt);
+        let keyword: String = cleaned
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect::<String>()
+            .to_ascii_uppercase();
+
+        let ddl_target = match keyword.as_str() {
+            "DROP"  => drop_table.captures(cleaned),
+            "ALTER" => rename_to.captures(cleaned),
+            _       => None,
+        };
+        if ddl_target.is_some_and(|c| skip_ddl_for.contains(&c[1])) {
+            continue;
+        }
+
+        if let Err(e) = conn.execute_batch(stmt) {
+            let is_dml = matches!(keyword.as_str(), "INSERT" | "SELECT" | "UPDATE" | "DELETE");
+            let is_column_drop = keyword == "ALTER" && drop_column.is_match(stmt);
+            if (is_dml || is_column_drop) && e.to_string().contains("no such column") {
+                if let Some(dest) = into.captures(stmt) {
+                    let dest = dest[1].to_string();
+                    if let Some(target) = dest.strip_suffix("_v2") {
+                        skip_ddl_for.insert(target.to_string());
+                    }
+                    skip_ddl_for.insert(dest);
+                }
+                continue;
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+fn strip_leading_comments(mut s: &str) -> &str {
+    loop {
+        s = s.trim_start();
+        if s.starts_with("--") {
+            s = s.find('\n').map_or("", |i| &s[i + 1..]);
+        } else if let Some(rest) = s.strip_prefix("/*") {
+            s = rest.find("*/").map_or("", |i| &rest[i + 2..]);
+        } else {
+            return s;
+        }
+    }
+}
+
+/// Create a quick-task plan in the fixture workspace and return its id.
+pub fn plan(conn: &Connection) -> String {
+    let plan = create_plan(conn, &NewPlan {
+        workspace_id: WORKSPACE_ID,
+        title: "Fixture",
+        description: "offline",
+        category: "quick_task",
+        ..Default::default()
+    })
+    .unwrap();
+    plan["id"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn fixture_is_at_the_newest_supported_schema() {
+    let status = super::schema::detect(&open());
+    assert_eq!(status.version, Some(super::schema::MAX_SCHEMA_VERSION));
+    assert!(status.writable());
+}
@@ -4,15 +4,32 @@
 //! platform equivalent.  Respects the `PM_DATA_ROOT` env var override used by
 //! the server.
 
+pub mod context;
 pub mod instructions;
+pub mod lineage;
 pub mod plan;
+pub mod sanitize;
+pub mod schema;
 pub mod steps;
 pub mod workspace;
 
+#[cfg(test)]
+pub mod fixture;
+
 use anyhow::{Context, Result};
 use rusqlite::Connection;
 use std::path::PathBuf;
 
+/// Current UTC time in the server's `nowIso()` format (`YYYY-MM-DD HH:MM:SS`).
+pub fn now_iso() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// New row id in the server's `newId()` format: 16 lowercase hex characters.
+pub fn new_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
 /// Resolve the path to `project-memory.db`.
 pub fn db_path() -> PathBuf {
     // Honour the same override the Node server uses.
@@ -46,8 +63,12 @@ pub fn db_path() -> PathBuf {
     base.join("ProjectMemory").join("project-memory.db")
 }
 
-/// Open a read-write connection to the Project Memory SQLite database.
-pub fn open() -> Result<Connection> {
+/// Open a connection to the Project Memory SQLite database.
+///
+/// The connection is read-write only when the server schema is within the
+/// range this build supports (see [`schema`]); otherwise it is opened with
+/// `query_only` so a mismatched schema can never be written to.
+pub fn open() -> Result<(Connection, schema::SchemaStatus)> {
     let path = db_path();
     let conn = Connection::open(&path)
         .with_context(|| format!("failed to open Project Memory database at {}", path.display()))?;
@@ -60,5 +81,7 @@ pub fn open() -> Result<Connection> {
     )
     .context("failed to set database pragmas")?;
 
-    Ok(conn)
+    let status = schema::enforce(&conn).context("failed to set database read-only mode")?;
+
+    Ok((conn, status))
 }
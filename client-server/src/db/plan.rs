@@ -1,9 +1,157 @@
-//! Plan + phase read queries for degraded-mode `memory_plan` handling.
+//! Plan + phase queries for degraded-mode `memory_plan` handling.
+//!
+//! Writes mirror `plan-db.ts`, `phase-db.ts` and `plan-note-db.ts` in the
+//! server, including the validation rules of the `create` and `add_note`
+//! actions.
 
-use anyhow::Result;
-use rusqlite::Connection;
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde_json::{json, Value};
 
+use super::{new_id, now_iso};
+
+/// Categories that require at least one goal and one success criterion
+/// (`formalCategories` in the server's `createPlan`).
+const FORMAL_CATEGORIES: &[&str] = &["feature", "bugfix", "refactor", "orchestration", "program"];
+/// Values accepted by the `plans.category` CHECK constraint.
+pub const PLAN_CATEGORIES: &[&str] = &["feature", "bugfix", "refactor", "orchestration", "quick_task", "advisory"];
+/// Values accepted by the `plans.priority` CHECK constraint.
+pub const PLAN_PRIORITIES: &[&str] = &["low", "medium", "high", "critical"];
+/// Note types accepted by `memory_plan(action: add_note)`.
+pub const NOTE_TYPES: &[&str] = &["info", "warning", "instruction"];
+
+/// Fields for a new plan; `None` takes the server default.
+#[derive(Debug, Default)]
+pub struct NewPlan<'a> {
+    pub workspace_id:     &'a str,
+    pub title:            &'a str,
+    pub description:      &'a str,
+    pub category:         &'a str,
+    pub priority:         Option<&'a str>,
+    pub goals:            Vec<String>,
+    pub success_criteria: Vec<String>,
+}
+
+/// Create a plan and return it (with its empty phase list).
+pub fn create_plan(conn: &Connection, plan: &NewPlan<'_>) -> Result<Value> {
+    let workspace_exists = conn
+        .query_row("SELECT 1 FROM workspaces WHERE id = ?1", [plan.workspace_id], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if !workspace_exists {
+        bail!("Workspace not found: {}", plan.workspace_id);
+    }
+    if FORMAL_CATEGORIES.contains(&plan.category)
+        && (plan.goals.is_empty() || plan.success_criteria.is_empty())
+    {
+        bail!("{} plans require at least 1 goal and 1 success criterion", plan.category);
+    }
+    if !PLAN_CATEGORIES.contains(&plan.category) {
+        bail!("invalid category '{}'; expected one of {}", plan.category, PLAN_CATEGORIES.join(", "));
+    }
+    let priority = plan.priority.unwrap_or("medium");
+    if !PLAN_PRIORITIES.contains(&priority) {
+        bail!("invalid priority '{priority}'; expected one of {}", PLAN_PRIORITIES.join(", "));
+    }
+
+    let id  = new_id();
+    let now = now_iso();
+    conn.execute(
+        "INSERT INTO plans
+           (id, workspace_id, title, description, category, priority, status,
+            schema_version, goals, success_criteria, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', '2.0', ?7, ?8, ?9, ?9)",
+        params![
+            id,
+            plan.workspace_id,
+            plan.title,
+            plan.description,
+            plan.category,
+            priority,
+            serde_json::to_string(&plan.goals)?,
+            serde_json::to_string(&plan.success_criteria)?,
+            now,
+        ],
+    )
+    .context("failed to create plan")?;
+
+    get_plan(conn, &id)?.context("created plan not found")
+}
+
+/// Fail with the server's "Plan not found" error unless `plan_id` exists.
+pub fn require_plan(conn: &Connection, plan_id: &str) -> Result<()> {
+    let exists = conn
+        .query_row("SELECT 1 FROM plans WHERE id = ?1", [plan_id], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if !exists {
+        bail!("Plan not found: {plan_id}");
+    }
+    Ok(())
+}
+
+/// Bump a plan's `updated_at`, as the server's `savePlanState` does on every
+/// plan mutation.
+pub fn touch_plan(conn: &Connection, plan_id: &str) -> Result<()> {
+    conn.execute("UPDATE plans SET updated_at = ?1 WHERE id = ?2", params![now_iso(), plan_id])
+        .context("failed to update plan")?;
+    Ok(())
+}
+
+/// Add a note to a plan and return the new row.
+pub fn add_plan_note(conn: &Connection, plan_id: &str, content: &str, note_type: &str) -> Result<Value> {
+    require_plan(conn, plan_id)?;
+    if !NOTE_TYPES.contains(&note_type) {
+        bail!("invalid note_type '{note_type}'; expected one of {}", NOTE_TYPES.join(", "));
+    }
+
+    let id  = new_id();
+    let now = now_iso();
+    conn.execute(
+        "INSERT INTO plan_notes (id, plan_id, content, note_type, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, plan_id, content, note_type, now],
+    )
+    .context("failed to add plan note")?;
+    touch_plan(conn, plan_id)?;
+
+    Ok(json!({
+        "id":         id,
+        "plan_id":    plan_id,
+        "content":    content,
+        "note_type":  note_type,
+        "created_at": now,
+    }))
+}
+
+/// Get-or-create a phase by name, appending new phases at the end
+/// (`getOrCreatePhase` in the server).
+pub fn get_or_create_phase(conn: &Connection, plan_id: &str, name: &str) -> Result<String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM phases WHERE plan_id = ?1 AND name = ?2",
+            [plan_id, name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let order_index: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM phases WHERE plan_id = ?1",
+        [plan_id],
+        |row| row.get(0),
+    )?;
+    let id = new_id();
+    conn.execute(
+        "INSERT INTO phases (id, plan_id, name, order_index, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, plan_id, name, order_index, now_iso()],
+    )
+    .context("failed to create phase")?;
+    Ok(id)
+}
+
 /// List plans for a workspace, optionally filtered by status.
 pub fn list_plans(conn: &Connection, workspace_id: &str, status: Option<&str>) -> Result<Vec<Value>> {
     let mapper = |row: &rusqlite::Row<'_>| Ok(json!({
@@ -144,3 +292,78 @@ fn get_phase_steps(conn: &Connection, phase_id: &str) -> Result<Vec<Value>> {
     }
     Ok(result)
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixture;
+
+    fn quick_task(title: &str) -> NewPlan<'_> {
+        NewPlan {
+            workspace_id: fixture::WORKSPACE_ID,
+            title,
+            description: "offline",
+            category: "quick_task",
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn create_plan_writes_server_defaults() {
+        let conn = fixture::open();
+        let plan = create_plan(&conn, &quick_task("Offline plan")).unwrap();
+
+        let id = plan["id"].as_str().unwrap();
+        assert_eq!(id.len(), 16);
+        assert_eq!(plan["status"], "active");
+        assert_eq!(plan["priority"], "medium");
+        assert_eq!(plan["goals"], "[]");
+        assert_eq!(plan["phases"], json!([]));
+        let schema_version: String = conn
+            .query_row("SELECT schema_version FROM plans WHERE id = ?1", [id], |r| r.get(0))
+            .unwrap();
+        assert_eq!(schema_version, "2.0");
+    }
+
+    #[test]
+    fn create_plan_applies_server_validation() {
+        let conn = fixture::open();
+
+        let mut feature = quick_task("Feature");
+        feature.category = "feature";
+        let err = create_plan(&conn, &feature).unwrap_err().to_string();
+        assert_eq!(err, "feature plans require at least 1 goal and 1 success criterion");
+
+        feature.goals = vec!["ship it".into()];
+        feature.success_criteria = vec!["it shipped".into()];
+        assert!(create_plan(&conn, &feature).is_ok());
+
+        let mut unknown_ws = quick_task("Elsewhere");
+        unknown_ws.workspace_id = "ws_missing";
+        let err = create_plan(&conn, &unknown_ws).unwrap_err().to_string();
+        assert_eq!(err, "Workspace not found: ws_missing");
+
+        let mut bad_priority = quick_task("Urgent");
+        bad_priority.priority = Some("urgent");
+        assert!(create_plan(&conn, &bad_priority).is_err());
+    }
+
+    #[test]
+    fn add_plan_note_validates_plan_and_type() {
+        let conn = fixture::open();
+        let plan = create_plan(&conn, &quick_task("Notes")).unwrap();
+        let plan_id = plan["id"].as_str().unwrap();
+
+        let note = add_plan_note(&conn, plan_id, "remember this", "warning").unwrap();
+        assert_eq!(note["note_type"], "warning");
+        assert!(add_plan_note(&conn, plan_id, "x", "shout").is_err());
+        assert_eq!(
+            add_plan_note(&conn, "nope", "x", "info").unwrap_err().to_string(),
+            "Plan not found: nope"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Prompt-injection scrubbing for stored agent data.
+//!
+//! Port of `sanitizeJsonData()` from the server's `security/sanitize.ts`:
+//! the first match of each injection pattern in every string value is
+//! replaced with a redaction marker before handoff data is stored.  The
+//! server's warning-only patterns change nothing and are not ported.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+const REDACTED: &str = "[CONTENT REDACTED - SECURITY]";
+
+static INJECTION_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        // Direct instruction overrides
+        r"(?i)ignore\s+(all\s+)?(previous|prior|above)\s+(instructions?|prompts?|rules?)",
+        r"(?i)disregard\s+(all\s+)?(previous|prior|above)",
+        r"(?i)forget\s+(everything|all|your)\s+(instructions?|rules?|training)",
+        // Role manipulation
+        r"(?i)you\s+are\s+now\s+(a|an|the)\s+",
+        r"(?i)act\s+as\s+(a|an|if)\s+",
+        r"(?i)pretend\s+(to\s+be|you're|you\s+are)",
+        r"(?i)roleplay\s+as",
+        r"(?i)switch\s+(to|into)\s+.*\s+mode",
+        // System prompt extraction
+        r"(?i)what\s+(are|is)\s+your\s+(system\s+)?(prompt|instructions?)",
+        r"(?i)reveal\s+your\s+(system\s+)?(prompt|instructions?)",
+        r"(?i)show\s+(me\s+)?your\s+(hidden\s+)?(prompt|instructions?)",
+        // Delimiter attacks
+        r"(?i)```\s*(system|assistant|user)\s*\n",
+        r"(?i)<\|?(system|im_start|im_end|endoftext)\|?>",
+        r"(?i)\[INST\]",
+        r"(?i)\[/INST\]",
+        // Agent impersonation
+        r"(?i)as\s+the\s+(coordinator|researcher|architect|executor|revisionist|reviewer|tester|archivist)",
+        r"(?i)i\s+am\s+(the\s+)?(coordinator|researcher|architect|executor|revisionist|reviewer|tester|archivist)",
+    ]
+    .iter()
+    .map(|p| Regex::new(p).expect("injection pattern compiles"))
+    .collect()
+});
+
+/// Redact injection attempts in one string.
+pub fn sanitize_content(content: &str) -> String {
+    let mut sanitized = content.to_string();
+    for pattern in INJECTION_PATTERNS.iter() {
+        if pattern.is_match(content) {
+            sanitized = pattern.replace(&sanitized, REDACTED).into_owned();
+        }
+    }
+    sanitized
+}
+
+/// Redact injection attempts in every string of a JSON object, recursing
+/// into nested objects and arrays.  Like the server, an array nested directly
+/// in an array comes back as an object keyed by index.
+pub fn sanitize_json_data(data: &Map<String, Value>) -> Map<String, Value> {
+    data.iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => Value::String(sanitize_content(s)),
+                Value::Array(items) => Value::Array(
+                    items
+                        .iter()
+                        .map(|item| match item {
+                            Value::String(s) => Value::String(sanitize_content(s)),
+                            Value::Object(o) => Value::Object(sanitize_json_data(o)),
+                            Value::Array(inner) => Value::Object(sanitize_json_data(
+                                &inner.iter().enumerate().map(|(i, v)| (i.to_string(), v.clone())).collect(),
+                            )),
+                            other => other.clone(),
+                        })
+                        .collect(),
+                ),
+                Value::Object(o) => Value::Object(sanitize_json_data(o)),
+                other => other.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
@@ -0,0 +1,180 @@
+//! Schema version detection for the server database.
+//!
+//! The server records each applied migration file (`001-initial-schema.sql`,
+//! …) in `_migrations`.  The queries in this module tree were written against
+//! a specific range of those migrations; outside it, columns may have been
+//! renamed or added and local writes could fail or write partial rows.  The
+//! proxy therefore checks the version at open time, and again before every
+//! local write because the server may migrate the file while the proxy is
+//! running, and drops to read-only when it is not in
+//! [`MIN_SCHEMA_VERSION`]..=[`MAX_SCHEMA_VERSION`].
+
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{json, Value};
+
+/// Oldest migration the local queries need (`workspace_instruction_assignments`).
+pub const MIN_SCHEMA_VERSION: u32 = 8;
+/// Newest migration the local queries have been checked against.
+pub const MAX_SCHEMA_VERSION: u32 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    Supported,
+    /// The database predates the oldest supported migration.
+    TooOld,
+    /// The server has applied migrations this build does not know about.
+    TooNew,
+    /// No `_migrations` table, or no numbered migrations in it.
+    Unknown,
+}
+
+impl Compatibility {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Compatibility::Supported => "supported",
+            Compatibility::TooOld    => "too_old",
+            Compatibility::TooNew    => "too_new",
+            Compatibility::Unknown   => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaStatus {
+    /// Highest applied migration number, if any.
+    pub version:       Option<u32>,
+    pub compatibility: Compatibility,
+}
+
+impl SchemaStatus {
+    /// Local writes are only allowed against a supported schema.
+    pub fn writable(&self) -> bool {
+        self.compatibility == Compatibility::Supported
+    }
+
+    /// One-line explanation for logs and write refusals.
+    pub fn describe(&self) -> String {
+        let range = format!("{MIN_SCHEMA_VERSION}–{MAX_SCHEMA_VERSION}");
+        match (self.compatibility, self.version) {
+            (Compatibility::Supported, Some(v)) => format!("schema version {v} (supported range {range})"),
+            (Compatibility::Unknown, _) | (_, None) => {
+                format!("schema version could not be determined (supported range {range})")
+            }
+            (_, Some(v)) => format!("schema version {v} is outside the supported range {range}"),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version":       self.version,
+            "compatibility": self.compatibility.as_str(),
+            "supported_min": MIN_SCHEMA_VERSION,
+            "supported_max": MAX_SCHEMA_VERSION,
+            "read_only":     !self.writable(),
+        })
+    }
+}
+
+/// Read the highest applied migration number and classify it.
+pub fn detect(conn: &Connection) -> SchemaStatus {
+    let version = highest_migration(conn);
+    let compatibility = match version {
+        None                                  => Compatibility::Unknown,
+        Some(v) if v < MIN_SCHEMA_VERSION     => Compatibility::TooOld,
+        Some(v) if v > MAX_SCHEMA_VERSION     => Compatibility::TooNew,
+        Some(_)                               => Compatibility::Supported,
+    };
+    SchemaStatus { version, compatibility }
+}
+
+/// Re-detect the schema and set `query_only` to match, so a connection that
+/// was writable stops accepting writes once the server applies a migration
+/// this build does not know about.
+pub fn enforce(conn: &Connection) -> rusqlite::Result<SchemaStatus> {
+    let status = detect(conn);
+    let pragma = if status.writable() { "PRAGMA query_only = OFF;" } else { "PRAGMA query_only = ON;" };
+    conn.execute_batch(pragma)?;
+    Ok(status)
+}
+
+fn highest_migration(conn: &Connection) -> Option<u32> {
+    let has_table = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_migrations'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()
+        .ok()?
+        .is_some();
+    if !has_table {
+        return None;
+    }
+
+    let mut stmt = conn.prepare("SELECT filename FROM _migrations").ok()?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0)).ok()?;
+    rows.filter_map(|r| r.ok())
+        .filter_map(|name| {
+            let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<u32>().ok()
+        })
+        .max()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_migrations(files: &[&str]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE _migrations (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 filename TEXT NOT NULL UNIQUE,
+                 applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+             );",
+        )
+        .unwrap();
+        for f in files {
+            conn.execute("INSERT INTO _migrations (filename) VALUES (?1)", [f]).unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn classifies_by_highest_applied_migration() {
+        let supported = detect(&with_migrations(&["001-initial-schema.sql", "014-user-sessions.sql"]));
+        assert_eq!(supported.version, Some(14));
+        assert!(supported.writable());
+
+        let too_new = detect(&with_migrations(&["014-user-sessions.sql", "015-rename-things.sql"]));
+        assert_eq!(too_new.compatibility, Compatibility::TooNew);
+        assert!(!too_new.writable());
+
+        let too_old = detect(&with_migrations(&["001-initial-schema.sql"]));
+        assert_eq!(too_old.compatibility, Compatibility::TooOld);
+    }
+
+    #[test]
+    fn enforce_picks_up_migrations_applied_after_open() {
+        let conn = with_migrations(&["014-user-sessions.sql"]);
+        assert!(enforce(&conn).unwrap().writable());
+        conn.execute("INSERT INTO _migrations (filename) VALUES ('015-rename-things.sql')", []).unwrap();
+
+        let status = enforce(&conn).unwrap();
+        assert_eq!(status.compatibility, Compatibility::TooNew);
+        assert!(conn.execute("INSERT INTO _migrations (filename) VALUES ('016-more.sql')", []).is_err());
+    }
+
+    #[test]
+    fn missing_migration_table_is_unknown() {
+        let status = detect(&Connection::open_in_memory().unwrap());
+        assert_eq!(status.compatibility, Compatibility::Unknown);
+        assert_eq!(status.version, None);
+        assert_eq!(status.to_json()["read_only"], true);
+    }
+}
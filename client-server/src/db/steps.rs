@@ -1,12 +1,14 @@
 //! Step read/write queries for degraded-mode `memory_steps` handling.
 
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
 use serde_json::{json, Value};
 
-fn now_iso() -> String {
-    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
-}
+use super::now_iso;
+use super::plan::{get_or_create_phase, require_plan, touch_plan};
+
+/// Step statuses accepted by `memory_steps`.
+pub const STEP_STATUSES: &[&str] = &["pending", "active", "done", "blocked"];
 
 /// Get the next pending step for a plan (respects dependency ordering).
 pub fn get_next_pending(conn: &Connection, plan_id: &str) -> Result<Option<Value>> {
@@ -54,6 +56,13 @@ pub fn get_all_steps(conn: &Connection, plan_id: &str) -> Result<Vec<Value>> {
     Ok(result)
 }
 
+/// Current `updated_at` of a step, or `None` if the step no longer exists.
+pub fn step_updated_at(conn: &Connection, step_id: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT updated_at FROM steps WHERE id = ?1")?;
+    let mut rows = stmt.query_map([step_id], |row| row.get::<_, String>(0))?;
+    Ok(rows.next().transpose()?)
+}
+
 fn step_row_mapper(row: &rusqlite::Row<'_>) -> rusqlite::Result<Value> {
     Ok(json!({
         "id":                           row.get::<_, String>(0)?,
@@ -74,6 +83,131 @@ fn step_row_mapper(row: &rusqlite::Row<'_>) -> rusqlite::Result<Value> {
     }))
 }
 
+/// A step to add, parsed from a `memory_steps` `steps[]` / `step` argument.
+#[derive(Debug)]
+pub struct NewStep {
+    pub phase:                      String,
+    pub task:                       String,
+    pub step_type:                  String,
+    pub status:                     String,
+    pub assignee:                   Option<String>,
+    pub notes:                      Option<String>,
+    pub requires_confirmation:      bool,
+    pub requires_user_confirmation: bool,
+    pub requires_validation:        bool,
+}
+
+impl NewStep {
+    /// Parse and validate a step object (`phase` and `task` are required).
+    pub fn from_value(v: &Value) -> Result<Self> {
+        let text = |key: &str| v[key].as_str().filter(|s| !s.is_empty()).map(str::to_string);
+        let (Some(phase), Some(task)) = (text("phase"), text("task")) else {
+            bail!("each step requires 'phase' and 'task'");
+        };
+        let status = text("status").unwrap_or_else(|| "pending".to_string());
+        if !STEP_STATUSES.contains(&status.as_str()) {
+            bail!("invalid step status '{status}'; expected one of {}", STEP_STATUSES.join(", "));
+        }
+        Ok(NewStep {
+            phase,
+            task,
+            step_type:                  text("type").unwrap_or_else(|| "standard".to_string()),
+            status,
+            assignee:                   text("assignee"),
+            notes:                      text("notes"),
+            requires_confirmation:      v["requires_confirmation"].as_bool().unwrap_or(false),
+            requires_user_confirmation: v["requires_user_confirmation"].as_bool().unwrap_or(false),
+            requires_validation:        v["requires_validation"].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+/// Append steps to the end of a plan.  Returns the new step ids.
+///
+/// `order_index` is plan-global, matching how the server's `savePlanState`
+/// numbers steps.
+pub fn add_steps(conn: &Connection, plan_id: &str, steps: &[NewStep]) -> Result<Vec<String>> {
+    require_plan(conn, plan_id)?;
+    let tx = conn.unchecked_transaction()?;
+    let start: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM steps WHERE plan_id = ?1",
+        [plan_id],
+        |row| row.get(0),
+    )?;
+    let mut ids = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        ids.push(insert_step_row(conn, plan_id, step, start + i as i64)?);
+    }
+    touch_plan(conn, plan_id)?;
+    tx.commit()?;
+    Ok(ids)
+}
+
+/// Insert a step at plan-global index `at_index`, shifting later steps up.
+pub fn insert_step(conn: &Connection, plan_id: &str, at_index: i64, step: &NewStep) -> Result<String> {
+    require_plan(conn, plan_id)?;
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM steps WHERE plan_id = ?1",
+        [plan_id],
+        |row| row.get(0),
+    )?;
+    if at_index < 0 || at_index > count {
+        bail!("Invalid at_index: {at_index}. Must be between 0 and {count}");
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    // Renumber first so gaps or duplicates left by older writers cannot
+    // put the new step in the wrong place.
+    let ordered: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT s.id FROM steps s
+             JOIN phases p ON p.id = s.phase_id
+             WHERE s.plan_id = ?1
+             ORDER BY p.order_index, s.order_index",
+        )?;
+        let rows = stmt.query_map([plan_id], |row| row.get::<_, String>(0))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (i, id) in ordered.iter().enumerate() {
+        let index = if (i as i64) < at_index { i as i64 } else { i as i64 + 1 };
+        conn.execute("UPDATE steps SET order_index = ?1 WHERE id = ?2", params![index, id])?;
+    }
+    let id = insert_step_row(conn, plan_id, step, at_index)?;
+    touch_plan(conn, plan_id)?;
+    tx.commit()?;
+    Ok(id)
+}
+
+fn insert_step_row(conn: &Connection, plan_id: &str, step: &NewStep, order_index: i64) -> Result<String> {
+    let phase_id = get_or_create_phase(conn, plan_id, &step.phase)?;
+    let id  = super::new_id();
+    let now = now_iso();
+    conn.execute(
+        "INSERT INTO steps
+           (id, phase_id, plan_id, task, type, status, assignee, notes,
+            order_index, requires_confirmation, requires_user_confirmation,
+            requires_validation, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?13)",
+        params![
+            id,
+            phase_id,
+            plan_id,
+            step.task,
+            step.step_type,
+            step.status,
+            step.assignee,
+            step.notes,
+            order_index,
+            step.requires_confirmation,
+            step.requires_user_confirmation,
+            step.requires_validation,
+            now,
+        ],
+    )
+    .context("failed to insert step")?;
+    Ok(id)
+}
+
 /// Update a single step's status (and optionally notes / completed fields).
 pub fn update_step(
     conn: &Connection,
@@ -188,3 +322,61 @@ pub struct BatchUpdate {
     pub status: Option<String>,
     pub notes:  Option<Option<String>>,
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixture;
+
+    fn step(phase: &str, task: &str) -> NewStep {
+        NewStep::from_value(&json!({ "phase": phase, "task": task })).unwrap()
+    }
+
+    fn tasks(conn: &Connection, plan_id: &str) -> Vec<String> {
+        get_all_steps(conn, plan_id)
+            .unwrap()
+            .iter()
+            .map(|s| s["task"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn add_steps_appends_with_plan_global_order() {
+        let conn = fixture::open();
+        let plan_id = fixture::plan(&conn);
+
+        add_steps(&conn, &plan_id, &[step("Build", "a"), step("Build", "b")]).unwrap();
+        add_steps(&conn, &plan_id, &[step("Test", "c")]).unwrap();
+
+        assert_eq!(tasks(&conn, &plan_id), ["a", "b", "c"]);
+        let next = get_next_pending(&conn, &plan_id).unwrap().unwrap();
+        assert_eq!(next["task"], "a");
+        assert_eq!(next["type"], "standard");
+        assert_eq!(next["phase_name"], "Build");
+    }
+
+    #[test]
+    fn insert_step_shifts_later_steps() {
+        let conn = fixture::open();
+        let plan_id = fixture::plan(&conn);
+        add_steps(&conn, &plan_id, &[step("Build", "a"), step("Build", "c")]).unwrap();
+
+        insert_step(&conn, &plan_id, 1, &step("Build", "b")).unwrap();
+        assert_eq!(tasks(&conn, &plan_id), ["a", "b", "c"]);
+
+        let err = insert_step(&conn, &plan_id, 9, &step("Build", "z")).unwrap_err().to_string();
+        assert_eq!(err, "Invalid at_index: 9. Must be between 0 and 3");
+    }
+
+    #[test]
+    fn new_step_requires_phase_task_and_known_status() {
+        assert!(NewStep::from_value(&json!({ "task": "a" })).is_err());
+        assert!(NewStep::from_value(&json!({ "phase": "P", "task": "a", "status": "later" })).is_err());
+        let s = NewStep::from_value(&json!({ "phase": "P", "task": "a", "status": "active" })).unwrap();
+        assert_eq!(s.status, "active");
+    }
+}
@@ -0,0 +1,315 @@
+//! Client message dispatch — the stdio loop between the MCP client and [`Proxy`].
+//!
+//! Each request runs in its own task so a slow tools/call never blocks ping
+//! or tools/list.  Responses go to `out_tx` in the order they complete;
+//! clients correlate them by JSON-RPC id.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::{AbortHandle, JoinSet};
+
+use crate::log::clog;
+use crate::proxy::{json_error, Proxy};
+
+/// Tasks for requests that have not responded yet, keyed by serialised JSON-RPC id.
+type InFlight = Arc<Mutex<HashMap<String, AbortHandle>>>;
+
+/// The side of [`Proxy`] the dispatch loop drives.
+pub trait Handler: Send + Sync + 'static {
+    /// Serve one request or notification; `Null` means no response.
+    fn handle(&self, req: &Value) -> impl Future<Output = Value> + Send;
+    /// Send the client's answer to a server-initiated request back upstream.
+    fn forward_client_response(&self, msg: &Value) -> impl Future<Output = ()> + Send;
+}
+
+impl Handler for Proxy {
+    fn handle(&self, req: &Value) -> impl Future<Output = Value> + Send {
+        Proxy::handle(self, req)
+    }
+
+    fn forward_client_response(&self, msg: &Value) -> impl Future<Output = ()> + Send {
+        Proxy::forward_client_response(self, msg)
+    }
+}
+
+/// Read JSON-RPC messages (one per line) from `input` until EOF and dispatch
+/// them through `handler`.  Returns once every in-flight request has
+/// finished and its response has been queued on `out_tx`.
+pub async fn run<R, H>(input: R, handler: Arc<H>, out_tx: UnboundedSender<Value>)
+where
+    R: AsyncBufRead + Unpin,
+    H: Handler,
+{
+    let in_flight: InFlight = Arc::new(Mutex::new(HashMap::new()));
+    let mut tasks = JoinSet::new();
+    let mut lines = input.lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        // Reap finished tasks so the set does not grow for the whole session.
+        while tasks.try_join_next().is_some() {}
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let request: Value = match serde_json::from_str(line) {
+            Ok(v)  => v,
+            Err(e) => {
+                clog!("[client-proxy] parse error: {e}");
+                continue;
+            }
+        };
+
+        if request["method"] == "notifications/cancelled" {
+            cancel_request(&in_flight, &request["params"]["requestId"]);
+        }
+
+        // The client's responses to server-initiated requests have no
+        // "method"; they go back to the upstream session that asked.
+        if request.get("method").is_none() {
+            if request.get("id").is_some() {
+                let handler = Arc::clone(&handler);
+                tasks.spawn(async move {
+                    handler.forward_client_response(&request).await;
+                });
+            } else {
+                clog!("[client-proxy] ignoring message with neither method nor id");
+            }
+            continue;
+        }
+
+        // Notifications have no "id" field — forward silently, no response.
+        // (notifications/cancelled is still forwarded so the upstream can
+        // stop too.)
+        let Some(id) = request.get("id").cloned() else {
+            let handler = Arc::clone(&handler);
+            tasks.spawn(async move {
+                handler.handle(&request).await;
+            });
+            continue;
+        };
+
+        // Hold the lock across spawn + insert so a fast task cannot try to
+        // deregister itself before it has been registered.
+        let key = id.to_string();
+        let mut registered = in_flight.lock().unwrap();
+        if registered.contains_key(&key) {
+            // A second handle under the same key would make the first
+            // request uncancellable, so the newcomer is refused instead.
+            clog!("[client-proxy] rejecting duplicate in-flight request id {key}");
+            let _ = out_tx.send(json_error(id, -32600, "Request id is already in flight"));
+            continue;
+        }
+        let task = {
+            let handler   = Arc::clone(&handler);
+            let out_tx    = out_tx.clone();
+            let in_flight = Arc::clone(&in_flight);
+            let key       = key.clone();
+            tasks.spawn(async move {
+                let response = handler.handle(&request).await;
+                {
+                    // Only drop our own entry: once cancelled, the id may
+                    // already belong to a newer request.
+                    let mut in_flight = in_flight.lock().unwrap();
+                    if in_flight.get(&key).is_some_and(|t| t.id() == tokio::task::id()) {
+                        in_flight.remove(&key);
+                    }
+                }
+                if !response.is_null() {
+                    let _ = out_tx.send(response);
+                }
+            })
+        };
+        registered.insert(key, task);
+    }
+
+    // Let in-flight requests finish and their responses drain.
+    while tasks.join_next().await.is_some() {}
+}
+
+/// Abort the task serving `request_id` (from `notifications/cancelled`).
+///
+/// Aborting drops the task's upstream future, which cancels the HTTP request.
+/// Per MCP, a cancelled request gets no response.
+fn cancel_request(in_flight: &InFlight, request_id: &Value) {
+    if request_id.is_null() {
+        return;
+    }
+    match in_flight.lock().unwrap().remove(&request_id.to_string()) {
+        Some(task) => {
+            task.abort();
+            clog!("[client-proxy] cancelled request {request_id}");
+        }
+        None => clog!("[client-proxy] cancel for unknown or finished request {request_id}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio::io::{AsyncWriteExt, BufReader, DuplexStream};
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
+    use tokio::sync::Notify;
+
+    /// Answers `{"echo": id}` after sleeping `params.delay_ms`, and records
+    /// every request whose future was dropped before it finished.
+    #[derive(Default)]
+    struct Scripted {
+        dropped: Mutex<Vec<Value>>,
+        started: Notify,
+    }
+
+    struct DropGuard<'a> {
+        handler: &'a Scripted,
+        id:      Value,
+        done:    bool,
+    }
+
+    impl Drop for DropGuard<'_> {
+        fn drop(&mut self) {
+            if !self.done {
+                self.handler.dropped.lock().unwrap().push(self.id.clone());
+            }
+        }
+    }
+
+    impl Handler for Scripted {
+        async fn handle(&self, req: &Value) -> Value {
+            let Some(id) = req.get("id").cloned() else { return Value::Null };
+            let mut guard = DropGuard { handler: self, id: id.clone(), done: false };
+            self.started.notify_one();
+            let delay = req["params"]["delay_ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            guard.done = true;
+            json!({ "jsonrpc": "2.0", "id": id, "result": { "echo": id } })
+        }
+
+        async fn forward_client_response(&self, _msg: &Value) {}
+    }
+
+    fn request(id: Value, delay_ms: u64) -> String {
+        json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call", "params": { "delay_ms": delay_ms } })
+            .to_string()
+    }
+
+    /// Start the loop on a pipe; returns the write end and the response receiver.
+    fn start(handler: Arc<Scripted>) -> (DuplexStream, UnboundedReceiver<Value>, tokio::task::JoinHandle<()>) {
+        let (client, server) = tokio::io::duplex(4096);
+        let (out_tx, out_rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(BufReader::new(server), handler, out_tx));
+        (client, out_rx, task)
+    }
+
+    async fn send(client: &mut DuplexStream, line: &str) {
+        client.write_all(format!("{line}\n").as_bytes()).await.unwrap();
+    }
+
+    async fn finish(client: DuplexStream, task: tokio::task::JoinHandle<()>, mut out_rx: UnboundedReceiver<Value>) -> Vec<Value> {
+        drop(client);
+        tokio::time::timeout(Duration::from_secs(5), task).await.unwrap().unwrap();
+        let mut responses = Vec::new();
+        while let Ok(response) = out_rx.try_recv() {
+            responses.push(response);
+        }
+        responses
+    }
+
+    #[tokio::test]
+    async fn slow_request_does_not_hold_back_a_fast_one() {
+        let handler = Arc::new(Scripted::default());
+        let (mut client, mut out_rx, task) = start(Arc::clone(&handler));
+        send(&mut client, &request(json!(1), 500)).await;
+        send(&mut client, &request(json!(2), 0)).await;
+
+        let first = tokio::time::timeout(Duration::from_millis(400), out_rx.recv())
+            .await
+            .expect("fast request waited for the slow one")
+            .unwrap();
+        assert_eq!(first["id"], 2);
+
+        let rest = finish(client, task, out_rx).await;
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0]["id"], 1);
+    }
+
+    #[tokio::test]
+    async fn each_response_carries_its_request_id() {
+        let handler = Arc::new(Scripted::default());
+        let (mut client, out_rx, task) = start(handler);
+        let ids = [json!(7), json!("abc"), json!(3), json!("7")];
+        for (i, id) in ids.iter().enumerate() {
+            send(&mut client, &request(id.clone(), 40 * (ids.len() - i) as u64)).await;
+        }
+
+        let responses = finish(client, task, out_rx).await;
+        assert_eq!(responses.len(), ids.len());
+        for response in &responses {
+            assert_eq!(response["result"]["echo"], response["id"]);
+        }
+        for id in &ids {
+            assert!(responses.iter().any(|r| &r["id"] == id), "no response for {id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn cancellation_aborts_the_in_flight_call() {
+        let handler = Arc::new(Scripted::default());
+        let (mut client, out_rx, task) = start(Arc::clone(&handler));
+        send(&mut client, &request(json!(5), 10_000)).await;
+        handler.started.notified().await;
+        send(
+            &mut client,
+            &json!({ "jsonrpc": "2.0", "method": "notifications/cancelled", "params": { "requestId": 5 } })
+                .to_string(),
+        )
+        .await;
+
+        let responses = finish(client, task, out_rx).await;
+        assert!(responses.is_empty(), "cancelled request answered: {responses:?}");
+        assert_eq!(*handler.dropped.lock().unwrap(), vec![json!(5)]);
+    }
+
+    #[tokio::test]
+    async fn duplicate_in_flight_id_is_rejected_and_the_first_stays_cancellable() {
+        let handler = Arc::new(Scripted::default());
+        let (mut client, mut out_rx, task) = start(Arc::clone(&handler));
+        send(&mut client, &request(json!(9), 10_000)).await;
+        handler.started.notified().await;
+        send(&mut client, &request(json!(9), 0)).await;
+
+        let rejected = tokio::time::timeout(Duration::from_secs(5), out_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(rejected["id"], 9);
+        assert_eq!(rejected["error"]["code"], -32600);
+
+        send(
+            &mut client,
+            &json!({ "jsonrpc": "2.0", "method": "notifications/cancelled", "params": { "requestId": 9 } })
+                .to_string(),
+        )
+        .await;
+        let responses = finish(client, task, out_rx).await;
+        assert!(responses.is_empty(), "cancelled request answered: {responses:?}");
+        assert_eq!(*handler.dropped.lock().unwrap(), vec![json!(9)]);
+    }
+
+    #[tokio::test]
+    async fn id_can_be_reused_once_the_earlier_request_finished() {
+        let handler = Arc::new(Scripted::default());
+        let (mut client, mut out_rx, task) = start(handler);
+        send(&mut client, &request(json!(4), 0)).await;
+        let first = tokio::time::timeout(Duration::from_secs(5), out_rx.recv()).await.unwrap().unwrap();
+        assert!(first.get("result").is_some());
+        send(&mut client, &request(json!(4), 0)).await;
+
+        let responses = finish(client, task, out_rx).await;
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].get("result").is_some(), "reused id rejected: {responses:?}");
+    }
+}
@@ -6,6 +6,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use crate::client_detect::ClientProfile;
+use crate::db::schema::SchemaStatus;
 
 pub fn handle_ping() -> Value {
     json!({ "content": [{ "type": "text", "text": "pong" }] })
@@ -17,6 +18,8 @@ pub fn handle_runtime_mode(
     profile: &ClientProfile,
     upstream_url: &str,
     last_connected_secs: Option<u64>,
+    pending_writes: usize,
+    schema: &SchemaStatus,
 ) -> Value {
     let is_connected = connected.load(Ordering::Relaxed);
     let uptime_secs  = started_at.elapsed().as_secs();
@@ -32,6 +35,8 @@ pub fn handle_runtime_mode(
         "client_version":       profile.client_version,
         "tools_available":      true,
         "local_tools_only":     !is_connected,
+        "pending_writes":       pending_writes,
+        "schema":               schema.to_json(),
     });
 
     json!({
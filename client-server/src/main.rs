@@ -1,8 +1,9 @@
 //! client-proxy — stdio MCP proxy for Project Memory
 //!
 //! Reads JSON-RPC messages (one per line) from stdin, dispatches them through
-//! [`Proxy`], and writes responses to stdout.  All diagnostic output goes to
-//! stderr so it never pollutes the MCP stream.
+//! [`Proxy`], and writes responses and relayed upstream notifications to
+//! stdout.  All diagnostic output goes to stderr so it never pollutes the MCP
+//! stream.
 //!
 //! Upstream URL resolution order (highest priority first):
 //!   1. `PM_MCP_URL` environment variable (explicit override)
@@ -11,15 +12,18 @@
 
 mod client_detect;
 mod db;
+mod dispatch;
 mod log;
 mod local_tools;
+mod outbox;
 mod proxy;
 mod upstream;
 
 use anyhow::Result;
 use log::clog;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
 
 const DEFAULT_MCP_URL: &str = "http://127.0.0.1:3466/mcp";
 
@@ -69,12 +73,14 @@ async fn main() -> Result<()> {
         (DEFAULT_MCP_URL.to_string(), "default")
     };
 
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<serde_json::Value>();
     let http  = upstream::build_client();
-    let proxy = Arc::new(proxy::Proxy::new(mcp_url.clone(), http.clone())?);
+    let proxy = Arc::new(proxy::Proxy::new(mcp_url.clone(), http.clone(), out_tx.clone())?);
 
     clog!("[client-proxy] started — upstream: {mcp_url} (source: {url_source})");
     clog!("[client-proxy] db: {}", db::db_path().display());
     clog!("[client-proxy] log: {}", log_path.display());
+    clog!("[client-proxy] outbox: {}", outbox::outbox_path().display());
 
     // Background reconnect loop: health-check every 5 s, re-establish session when status changes.
     {
@@ -87,33 +93,27 @@ async fn main() -> Result<()> {
         });
     }
 
-    // ── Main stdio loop ───────────────────────────────────────────────────────
-
-    let stdin  = BufReader::new(tokio::io::stdin());
-    let mut stdout = tokio::io::stdout();
-    let mut lines = stdin.lines();
-
-    while let Ok(Some(line)) = lines.next_line().await {
-        let line = line.trim().to_string();
-        if line.is_empty() {
-            continue;
-        }
+    // Standing upstream event stream: relays server notifications to stdout.
+    {
+        let proxy = Arc::clone(&proxy);
+        tokio::spawn(async move {
+            proxy.run_event_stream().await;
+        });
+    }
 
-        let request: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(v)  => v,
-            Err(e) => {
-                clog!("[client-proxy] parse error: {e}");
-                continue;
+    // ── Main stdio loop ───────────────────────────────────────────────────────
+    //
+    // `dispatch::run` serves each request in its own task.  Responses and
+    // relayed upstream messages go through a single writer task in the order
+    // they arrive; clients correlate responses by JSON-RPC id.  `Null` tells
+    // the writer to stop.
+
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(response) = out_rx.recv().await {
+            if response.is_null() {
+                break;
             }
-        };
-
-        // Notifications have no "id" field — handle silently, no response.
-        let is_notification = request.get("id").is_none();
-
-        let response = proxy.handle(&request).await;
-
-        // Only write a response for requests (not notifications / null returns).
-        if !is_notification && response != serde_json::Value::Null {
             let mut bytes = serde_json::to_vec(&response).unwrap_or_default();
             bytes.push(b'\n');
             if let Err(e) = stdout.write_all(&bytes).await {
@@ -122,7 +122,13 @@ async fn main() -> Result<()> {
             }
             stdout.flush().await.ok();
         }
-    }
+    });
+
+    let stdin = BufReader::new(tokio::io::stdin());
+    dispatch::run(stdin, Arc::clone(&proxy), out_tx.clone()).await;
+
+    let _ = out_tx.send(serde_json::Value::Null);
+    let _ = writer.await;
 
     clog!("[client-proxy] stdin closed, exiting");
     Ok(())
@@ -0,0 +1,273 @@
+//! Write-ahead outbox for mutations made while the supervisor is unreachable.
+//!
+//! Degraded-mode `memory_steps` writes go straight into SQLite, so the server
+//! never emits events or runs its lineage/session bookkeeping for them.  Each
+//! such write is journaled here together with the `updated_at` it left on
+//! every step it touched.  When the upstream is reachable again the proxy
+//! replays pending entries through the real MCP tools; an entry whose steps
+//! have been modified by someone else in the meantime is marked as a
+//! conflict instead of being replayed over the newer data.
+//!
+//! The journal lives in its own database next to `project-memory.db` so the
+//! server's schema is never touched.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::clog;
+use crate::db;
+
+pub const STATUS_PENDING:  &str = "pending";
+pub const STATUS_CONFLICT: &str = "conflict";
+pub const STATUS_FAILED:   &str = "failed";
+
+/// Resolve the path to the outbox database (alongside `project-memory.db`).
+pub fn outbox_path() -> PathBuf {
+    crate::db::db_path().with_file_name("client-proxy-outbox.db")
+}
+
+/// A journaled offline mutation awaiting replay.
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id:        i64,
+    pub tool:      String,
+    pub arguments: Value,
+    /// Step id → `updated_at` the offline write left behind.
+    pub baselines: BTreeMap<String, String>,
+}
+
+pub struct Outbox {
+    conn: Connection,
+}
+
+impl Outbox {
+    /// Open (or create) the outbox database at [`outbox_path`].
+    pub fn open() -> Result<Self> {
+        Self::open_at(&outbox_path())
+    }
+
+    /// Open (or create) an outbox database at `path`.
+    pub fn open_at(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open outbox database at {}", path.display()))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA busy_timeout = 5000;",
+        )
+        .context("failed to set outbox pragmas")?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> Self {
+        Self::init(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS outbox (
+                 id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                 tool        TEXT    NOT NULL,
+                 arguments   TEXT    NOT NULL,
+                 baselines   TEXT    NOT NULL,
+                 status      TEXT    NOT NULL DEFAULT 'pending',
+                 attempts    INTEGER NOT NULL DEFAULT 0,
+                 last_error  TEXT,
+                 created_at  TEXT    NOT NULL,
+                 updated_at  TEXT    NOT NULL
+             );",
+        )
+        .context("failed to initialise outbox schema")?;
+
+        Ok(Outbox { conn })
+    }
+
+    /// Journal a mutation and return its outbox id.
+    pub fn enqueue(
+        &self,
+        tool: &str,
+        arguments: &Value,
+        baselines: &BTreeMap<String, String>,
+    ) -> Result<i64> {
+        let now = db::now_iso();
+        self.conn
+            .execute(
+                "INSERT INTO outbox (tool, arguments, baselines, status, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![
+                    tool,
+                    serde_json::to_string(arguments)?,
+                    serde_json::to_string(baselines)?,
+                    STATUS_PENDING,
+                    now,
+                ],
+            )
+            .context("failed to journal offline write")?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Pending entries in the order they were written.
+    pub fn pending(&self) -> Result<Vec<OutboxEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tool, arguments, baselines FROM outbox
+             WHERE status = ?1
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map([STATUS_PENDING], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            let (id, tool, arguments, baselines) = row?;
+            result.push(OutboxEntry {
+                id,
+                tool,
+                arguments: serde_json::from_str(&arguments).unwrap_or(Value::Null),
+                baselines: serde_json::from_str(&baselines).unwrap_or_default(),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Number of entries still waiting to be replayed.
+    pub fn pending_count(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM outbox WHERE status = ?1",
+            [STATUS_PENDING],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Drop an entry once it has been replayed upstream.
+    pub fn remove(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM outbox WHERE id = ?1", [id])
+            .context("failed to remove outbox entry")?;
+        Ok(())
+    }
+
+    /// Park an entry as `conflict` or `failed`; it is kept for inspection but
+    /// no longer replayed.
+    pub fn mark(&self, id: i64, status: &str, error: &str) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE outbox
+                 SET status = ?2, last_error = ?3, attempts = attempts + 1, updated_at = ?4
+                 WHERE id = ?1",
+                params![id, status, error, db::now_iso()],
+            )
+            .context("failed to update outbox entry")?;
+        Ok(())
+    }
+
+    /// Move the baseline of `step_id` in every pending entry to `updated_at`.
+    ///
+    /// Called whenever the proxy itself changes the step (a later offline
+    /// write, or a successful replay) so those changes are not mistaken for
+    /// upstream conflicts.
+    pub fn rebase(&self, step_id: &str, updated_at: &str) -> Result<()> {
+        for mut entry in self.pending()? {
+            if let Some(ts) = entry.baselines.get_mut(step_id) {
+                *ts = updated_at.to_string();
+                self.conn.execute(
+                    "UPDATE outbox SET baselines = ?2 WHERE id = ?1",
+                    params![entry.id, serde_json::to_string(&entry.baselines)?],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the first step in `entry` whose row in `db` no longer matches the
+/// baseline recorded by the offline write.  A step that cannot be read counts
+/// as a conflict.
+pub fn conflicting_step(db: &Connection, entry: &OutboxEntry) -> Option<String> {
+    entry.baselines.iter().find_map(|(step_id, baseline)| {
+        match db::steps::step_updated_at(db, step_id) {
+            Ok(Some(current)) if &current == baseline => None,
+            Ok(_) => Some(step_id.clone()),
+            Err(e) => {
+                clog!("[outbox] failed to read step {step_id}: {e}");
+                Some(step_id.clone())
+            }
+        }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixture;
+    use crate::db::steps::{add_steps, NewStep};
+    use serde_json::json;
+
+    fn baselines(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn pending_entries_replay_in_write_order() {
+        let outbox = Outbox::open_in_memory();
+        let first  = outbox.enqueue("memory_steps", &json!({ "action": "update", "n": 1 }), &baselines(&[("s1", "t1")])).unwrap();
+        let second = outbox.enqueue("memory_steps", &json!({ "action": "update", "n": 2 }), &baselines(&[])).unwrap();
+        let third  = outbox.enqueue("memory_steps", &json!({ "action": "update", "n": 3 }), &baselines(&[])).unwrap();
+
+        let pending = outbox.pending().unwrap();
+        assert_eq!(pending.iter().map(|e| e.id).collect::<Vec<_>>(), [first, second, third]);
+        assert_eq!(pending[0].tool, "memory_steps");
+        assert_eq!(pending[0].arguments["n"], 1);
+        assert_eq!(pending[0].baselines, baselines(&[("s1", "t1")]));
+
+        outbox.remove(first).unwrap();
+        outbox.mark(second, STATUS_CONFLICT, "changed upstream").unwrap();
+        assert_eq!(outbox.pending().unwrap().iter().map(|e| e.id).collect::<Vec<_>>(), [third]);
+        assert_eq!(outbox.pending_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn rebase_only_moves_the_named_step() {
+        let outbox = Outbox::open_in_memory();
+        outbox.enqueue("memory_steps", &json!({}), &baselines(&[("s1", "t1"), ("s2", "t1")])).unwrap();
+
+        outbox.rebase("s1", "t2").unwrap();
+        assert_eq!(outbox.pending().unwrap()[0].baselines, baselines(&[("s1", "t2"), ("s2", "t1")]));
+    }
+
+    #[test]
+    fn conflict_is_detected_when_a_step_changes_after_the_offline_write() {
+        let db = fixture::open();
+        let plan_id = fixture::plan(&db);
+        let step = NewStep::from_value(&json!({ "phase": "Build", "task": "a" })).unwrap();
+        let step_id = add_steps(&db, &plan_id, &[step]).unwrap().remove(0);
+        let written = db::steps::step_updated_at(&db, &step_id).unwrap().unwrap();
+
+        let outbox = Outbox::open_in_memory();
+        outbox.enqueue("memory_steps", &json!({}), &baselines(&[(&step_id, &written)])).unwrap();
+        let entry = outbox.pending().unwrap().remove(0);
+        assert_eq!(conflicting_step(&db, &entry), None);
+
+        db.execute("UPDATE steps SET updated_at = '2099-01-01 00:00:00' WHERE id = ?1", [&step_id]).unwrap();
+        assert_eq!(conflicting_step(&db, &entry), Some(step_id.clone()));
+
+        db.execute("DELETE FROM steps WHERE id = ?1", [&step_id]).unwrap();
+        assert_eq!(conflicting_step(&db, &entry), Some(step_id));
+    }
+}
@@ -2,16 +2,20 @@
 
 use crate::client_detect::{from_initialize_params, ClientProfile};
 use crate::db;
+use crate::db::schema::{self, Compatibility, SchemaStatus};
 use crate::local_tools;
 use crate::clog;
+use crate::outbox::{self, Outbox, OutboxEntry};
 use crate::upstream;
 use anyhow::Result;
 use reqwest::Client;
 use rusqlite::Connection;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::UnboundedSender;
 
 // ── Tools that are always handled locally ────────────────────────────────────
 
@@ -25,6 +29,41 @@ const LOCAL_CAPABLE: &[&str] = &[
     "memory_plan",
     "memory_steps",
     "memory_instructions",
+    "memory_context",
+    "memory_agent",
+];
+
+/// Timings for the standing upstream event stream.
+struct EventStreamTimings {
+    /// Limit for the upstream to answer the stream request with headers.
+    connect:           Duration,
+    /// Reopen the stream after this long without any bytes from it, so a
+    /// half-open connection cannot hold up server messages indefinitely.
+    idle:              Duration,
+    /// Delay between attempts to (re)open the stream.
+    retry:             Duration,
+    /// Delay between attempts when the upstream does not offer a stream.
+    unsupported_retry: Duration,
+}
+
+const EVENT_STREAM_TIMINGS: EventStreamTimings = EventStreamTimings {
+    connect:           Duration::from_secs(10),
+    idle:              Duration::from_secs(120),
+    retry:             Duration::from_secs(5),
+    unsupported_retry: Duration::from_secs(60),
+};
+
+/// Local actions that write to the database; refused when the schema is
+/// outside the supported range.
+const LOCAL_WRITE_ACTIONS: &[(&str, &str)] = &[
+    ("memory_plan",    "create"),
+    ("memory_plan",    "add_note"),
+    ("memory_steps",   "update"),
+    ("memory_steps",   "batch_update"),
+    ("memory_steps",   "add"),
+    ("memory_steps",   "insert"),
+    ("memory_context", "store"),
+    ("memory_agent",   "handoff"),
 ];
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -38,6 +77,8 @@ pub struct Proxy {
     client_profile:        Mutex<ClientProfile>,
     started_at:            Instant,
     db:                    Mutex<Connection>,
+    /// Server schema compatibility, re-detected before each local write.
+    schema:                Mutex<SchemaStatus>,
     http:                  Client,
     /// True after the first session_start call has been fired to upstream.
     session_init_done:     AtomicBool,
@@ -47,12 +88,51 @@ pub struct Proxy {
     pending_instructions:  Mutex<Option<Value>>,
     /// Proxy-local session ID sent as _session_id to session_start.
     proxy_session_id:      String,
+    /// Journal of offline writes waiting to be replayed upstream; `None` when
+    /// it could not be opened, in which case offline writes are not replayed.
+    outbox:                Option<Mutex<Outbox>>,
+    /// True while the outbox may hold pending entries.
+    outbox_dirty:          AtomicBool,
+    /// Messages for the client that are not responses to a request
+    /// (relayed upstream notifications, tools/list_changed).
+    outbound:              UnboundedSender<Value>,
+    /// True once the client has fetched the tool list at least once.
+    tools_listed:          AtomicBool,
 }
 
 impl Proxy {
     /// Create a new Proxy.  Opens the local database immediately.
-    pub fn new(upstream_url: String, http: Client) -> Result<Self> {
-        let db = db::open()?;
+    ///
+    /// `outbound` receives server-initiated messages destined for the client.
+    pub fn new(upstream_url: String, http: Client, outbound: UnboundedSender<Value>) -> Result<Self> {
+        let (db, schema) = db::open()?;
+        if schema.writable() {
+            clog!("[proxy] database {}", schema.describe());
+        } else {
+            clog!("[proxy] database {}; local writes disabled", schema.describe());
+        }
+        let outbox = match Outbox::open() {
+            Ok(outbox) => Some(outbox),
+            Err(e) => {
+                clog!("[proxy] {e:#}; offline writes will not be replayed upstream");
+                None
+            }
+        };
+        Ok(Self::with_parts(upstream_url, http, outbound, db, schema, outbox))
+    }
+
+    fn with_parts(
+        upstream_url: String,
+        http: Client,
+        outbound: UnboundedSender<Value>,
+        db: Connection,
+        schema: SchemaStatus,
+        outbox: Option<Outbox>,
+    ) -> Self {
+        let outbox_dirty = outbox
+            .as_ref()
+            .and_then(|o| o.pending_count().ok())
+            .is_some_and(|n| n > 0);
         // Generate a stable proxy-local session ID for this process lifetime.
         let proxy_session_id = {
             let secs = SystemTime::now()
@@ -61,7 +141,7 @@ impl Proxy {
                 .as_secs();
             format!("proxy-{secs:x}")
         };
-        Ok(Proxy {
+        Proxy {
             upstream_url,
             upstream_connected:    Arc::new(AtomicBool::new(false)),
             last_connected_secs:   Arc::new(AtomicU64::new(0)),
@@ -70,12 +150,17 @@ impl Proxy {
             client_profile:        Mutex::new(ClientProfile::default()),
             started_at:            Instant::now(),
             db:                    Mutex::new(db),
+            schema:                Mutex::new(schema),
             http,
             session_init_done:     AtomicBool::new(false),
             instructions_surfaced: AtomicBool::new(false),
             pending_instructions:  Mutex::new(None),
             proxy_session_id,
-        })
+            outbox:                outbox.map(Mutex::new),
+            outbox_dirty:          AtomicBool::new(outbox_dirty),
+            outbound,
+            tools_listed:          AtomicBool::new(false),
+        }
     }
 
     /// Shared upstream-connected flag (cloned into the background reconnect task).
@@ -114,7 +199,7 @@ impl Proxy {
 
         json_result(id, json!({
             "protocolVersion": "2024-11-05",
-            "capabilities": { "tools": {} },
+            "capabilities": { "tools": { "listChanged": true } },
             "serverInfo": {
                 "name":    "client-proxy",
                 "version": env!("CARGO_PKG_VERSION")
@@ -138,7 +223,7 @@ impl Proxy {
                 "params": {}
             });
             let session = self.session_id.lock().unwrap().clone();
-            match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), &list_req).await {
+            match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), &list_req, &|msg| self.relay_upstream_message(msg)).await {
                 Ok((resp, new_sid)) => {
                     self.update_session(new_sid);
                     tools = resp["result"]["tools"]
@@ -159,6 +244,7 @@ impl Proxy {
 
         // Always inject our always-local tools if not already present.
         inject_local_tools(&mut tools);
+        self.tools_listed.store(true, Ordering::Relaxed);
 
         json_result(id, json!({ "tools": tools }))
     }
@@ -211,6 +297,7 @@ impl Proxy {
                 let session = self.session_id.lock().unwrap().clone();
                 if let Ok((resp, new_sid)) = upstream::forward(
                     &self.http, &self.upstream_url, session.as_deref(), &init_req,
+                    &|msg| self.relay_upstream_message(msg),
                 ).await {
                     self.update_session(new_sid);
                     // Extract priority_instructions from session_start result.
@@ -237,7 +324,7 @@ impl Proxy {
         if connected {
             // Forward to upstream for ALL tool calls (single source of truth).
             let session = self.session_id.lock().unwrap().clone();
-            match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), req).await {
+            match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), req, &|msg| self.relay_upstream_message(msg)).await {
                 Ok((resp, new_sid)) => {
                     self.update_session(new_sid);
                     let mut result = extract_tool_result(resp);
@@ -266,8 +353,8 @@ impl Proxy {
                 "text": format!(
                     "'{name}' requires the Project Memory supervisor, which is not currently reachable.\n\
                      The supervisor will reconnect automatically. \
-                     Use 'runtime_mode' to check status, or 'memory_plan'/'memory_steps'/'memory_workspace'/'memory_instructions' \
-                     for operations that work without the supervisor."
+                     Use 'runtime_mode' to check status, or 'memory_plan'/'memory_steps'/'memory_workspace'/'memory_instructions'/\
+                     'memory_context'/'memory_agent' for operations that work without the supervisor."
                 )
             }],
             "isError": true
@@ -309,6 +396,92 @@ impl Proxy {
         }
     }
 
+    // ── server-initiated messages ─────────────────────────────────────────────
+
+    /// Keep a standing server-to-client SSE stream open for the upstream
+    /// session and relay everything it delivers.  Runs for the life of the
+    /// process; the stream is reopened whenever it drops or goes quiet.
+    pub async fn run_event_stream(&self) {
+        self.run_event_stream_with(&EVENT_STREAM_TIMINGS).await;
+    }
+
+    async fn run_event_stream_with(&self, timings: &EventStreamTimings) {
+        let mut warned_unsupported = false;
+        loop {
+            let session = self.session_id.lock().unwrap().clone();
+            let session = session.filter(|_| self.upstream_connected.load(Ordering::Relaxed));
+            let Some(sid) = session else {
+                tokio::time::sleep(timings.retry).await;
+                continue;
+            };
+
+            match upstream::open_event_stream(&self.http, &self.upstream_url, &sid, timings.connect).await {
+                Ok(Some(mut stream)) => {
+                    clog!("[proxy] upstream event stream open sid={sid}");
+                    loop {
+                        match stream.next_message(Some(timings.idle)).await {
+                            Ok(Some(msg)) => self.relay_upstream_message(msg),
+                            Ok(None) => break,
+                            Err(e) => {
+                                clog!("[proxy] upstream event stream error: {e}");
+                                break;
+                            }
+                        }
+                    }
+                    clog!("[proxy] upstream event stream closed");
+                }
+                Ok(None) => {
+                    if !warned_unsupported {
+                        clog!("[proxy] upstream has no standing event stream; relaying per-call messages only");
+                        warned_unsupported = true;
+                    }
+                    tokio::time::sleep(timings.unsupported_retry).await;
+                    continue;
+                }
+                Err(e) => clog!("[proxy] failed to open upstream event stream: {e}"),
+            }
+            tokio::time::sleep(timings.retry).await;
+        }
+    }
+
+    /// Pass an upstream notification or server-to-client request on to the
+    /// client.  A tool-list change also invalidates the cached tool list.
+    fn relay_upstream_message(&self, msg: Value) {
+        if msg["method"] == "notifications/tools/list_changed" {
+            *self.cached_tools.lock().unwrap() = None;
+        }
+        let _ = self.outbound.send(msg);
+    }
+
+    /// Tell the client to re-fetch the tool list (only once it has one).
+    fn notify_tools_changed(&self) {
+        if self.tools_listed.load(Ordering::Relaxed) {
+            let _ = self.outbound.send(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/tools/list_changed"
+            }));
+        }
+    }
+
+    /// Forward the client's response to a server-initiated request
+    /// (sampling, elicitation, roots) to the upstream session that sent it.
+    ///
+    /// Nobody is waiting on the result, and a rejected response says nothing
+    /// about the connection, so failures are only logged and the upstream is
+    /// not marked disconnected.
+    pub async fn forward_client_response(&self, msg: &Value) {
+        let id = &msg["id"];
+        let session = self.session_id.lock().unwrap().clone();
+        let Some(sid) = session else {
+            clog!("[proxy] dropping client response id={id}: no upstream session");
+            return;
+        };
+        match upstream::forward(&self.http, &self.upstream_url, Some(&sid), msg, &|m| self.relay_upstream_message(m)).await {
+            Ok((_, new_sid)) => self.update_session(new_sid),
+            Err(e) => clog!("[proxy] failed to forward client response id={id}: {e}"),
+        }
+    }
+
     // ── fallback: forward arbitrary methods ──────────────────────────────────
 
     async fn forward_raw(&self, req: &Value, id: Value) -> Value {
@@ -317,7 +490,7 @@ impl Proxy {
             return json_error(id, -32603, "Supervisor is unreachable");
         }
         let session = self.session_id.lock().unwrap().clone();
-        match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), req).await {
+        match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), req, &|msg| self.relay_upstream_message(msg)).await {
             Ok((resp, new_sid)) => {
                 self.update_session(new_sid);
                 resp
@@ -332,6 +505,24 @@ impl Proxy {
     // ── local dispatch ────────────────────────────────────────────────────────
 
     fn dispatch_local(&self, name: &str, args: &Value) -> Value {
+        let action = args["action"].as_str().unwrap_or("");
+        if LOCAL_WRITE_ACTIONS.contains(&(name, action)) {
+            let schema = self.recheck_schema();
+            if !schema.writable() {
+                return json!({
+                    "content": [{
+                        "type": "text",
+                        "text": format!(
+                            "'{name}' action '{action}' is unavailable offline: the local database {}. \
+                             Reads still work; start the supervisor to make changes.",
+                            schema.describe()
+                        )
+                    }],
+                    "isError": true
+                });
+            }
+        }
+
         match name {
             "ping" => local_tools::handle_ping(),
 
@@ -339,12 +530,18 @@ impl Proxy {
                 let last = self.last_connected_secs.load(Ordering::Relaxed);
                 let last_opt = if last == 0 { None } else { Some(last) };
                 let profile  = self.client_profile.lock().unwrap().clone();
+                let pending  = self.outbox.as_ref()
+                    .and_then(|o| o.lock().unwrap().pending_count().ok())
+                    .unwrap_or(0);
+                let schema   = self.schema.lock().unwrap().clone();
                 local_tools::handle_runtime_mode(
                     &self.upstream_connected,
                     &self.started_at,
                     &profile,
                     &self.upstream_url,
                     last_opt,
+                    pending,
+                    &schema,
                 )
             }
 
@@ -352,6 +549,8 @@ impl Proxy {
             "memory_plan"      => self.handle_local_plan(args),
             "memory_steps"     => self.handle_local_steps(args),
             "memory_instructions" => self.handle_local_instructions(args),
+            "memory_context"   => self.handle_local_context(args),
+            "memory_agent"     => self.handle_local_agent(args),
 
             _ => json!({
                 "content": [{ "type": "text", "text": format!("No local handler for '{name}'") }],
@@ -360,6 +559,24 @@ impl Proxy {
         }
     }
 
+    /// Re-detect the schema before a local write: the server may have
+    /// migrated the database since it was opened.
+    fn recheck_schema(&self) -> SchemaStatus {
+        let status = {
+            let db = self.db.lock().unwrap();
+            schema::enforce(&db).unwrap_or_else(|e| {
+                clog!("[proxy] schema re-check failed: {e}");
+                SchemaStatus { version: None, compatibility: Compatibility::Unknown }
+            })
+        };
+        let mut current = self.schema.lock().unwrap();
+        if *current != status {
+            clog!("[proxy] database now {}", status.describe());
+            *current = status.clone();
+        }
+        status
+    }
+
     // ── memory_workspace (local) ──────────────────────────────────────────────
 
     fn handle_local_workspace(&self, args: &Value) -> Value {
@@ -408,9 +625,31 @@ impl Proxy {
                 let plan_id = args["plan_id"].as_str().unwrap_or("");
                 db::plan::get_plan(&db, plan_id).map(|p| p.unwrap_or(Value::Null))
             }
+            "create" => require_args(args, &["workspace_id", "title", "description", "category"], "create")
+                .and_then(|_| {
+                    let plan = db::plan::NewPlan {
+                        workspace_id:     args["workspace_id"].as_str().unwrap_or(""),
+                        title:            args["title"].as_str().unwrap_or(""),
+                        description:      args["description"].as_str().unwrap_or(""),
+                        category:         args["category"].as_str().unwrap_or(""),
+                        priority:         args["priority"].as_str(),
+                        goals:            string_list(&args["goals"]),
+                        success_criteria: string_list(&args["success_criteria"]),
+                    };
+                    db::plan::create_plan(&db, &plan)
+                }),
+            "add_note" => require_args(args, &["workspace_id", "plan_id", "note"], "add_note")
+                .and_then(|_| {
+                    db::plan::add_plan_note(
+                        &db,
+                        args["plan_id"].as_str().unwrap_or(""),
+                        args["note"].as_str().unwrap_or(""),
+                        args["note_type"].as_str().unwrap_or("info"),
+                    )
+                }),
             other => Ok(json!({
                 "error": format!("action '{other}' is not supported by the local handler; start the supervisor for full access"),
-                "available_actions": ["list", "get"]
+                "available_actions": ["list", "get", "create", "add_note"]
             })),
         };
 
@@ -437,8 +676,10 @@ impl Proxy {
                 let status  = args["status"].as_str();
                 let notes   = args.get("notes").map(|n| n.as_str());
                 let agent   = args["completed_by_agent"].as_str();
-                db::steps::update_step(&db, step_id, status, notes, agent)
-                    .map(|_| json!({ "updated": step_id }))
+                db::steps::update_step(&db, step_id, status, notes, agent).map(|_| {
+                    let outbox_id = self.journal_offline_write("memory_steps", args, &db, &[step_id]);
+                    with_pending_sync(json!({ "updated": step_id }), outbox_id)
+                })
             }
             "batch_update" => {
                 let updates_val = args["updates"].as_array().cloned().unwrap_or_default();
@@ -453,18 +694,227 @@ impl Proxy {
                     })
                     .collect();
                 let count = updates.len();
-                db::steps::batch_update_steps(&db, &updates)
-                    .map(|_| json!({ "updated": count }))
+                db::steps::batch_update_steps(&db, &updates).map(|_| {
+                    let ids: Vec<&str> = updates.iter().map(|u| u.id.as_str()).collect();
+                    let outbox_id = self.journal_offline_write("memory_steps", args, &db, &ids);
+                    with_pending_sync(json!({ "updated": count }), outbox_id)
+                })
+            }
+            "add" => {
+                let plan_id = args["plan_id"].as_str().unwrap_or("");
+                match args["steps"].as_array().filter(|s| !s.is_empty()) {
+                    None => Err(anyhow::anyhow!("steps array is required for action: add")),
+                    Some(steps) => steps
+                        .iter()
+                        .map(db::steps::NewStep::from_value)
+                        .collect::<Result<Vec<_>, _>>()
+                        .and_then(|steps| db::steps::add_steps(&db, plan_id, &steps))
+                        .map(|ids| json!({ "added": ids.len(), "step_ids": ids })),
+                }
+            }
+            "insert" => match args["at_index"].as_i64() {
+                Some(at_index) if args["step"].is_object() => {
+                    let plan_id = args["plan_id"].as_str().unwrap_or("");
+                    db::steps::NewStep::from_value(&args["step"])
+                        .and_then(|step| db::steps::insert_step(&db, plan_id, at_index, &step))
+                        .map(|id| json!({ "inserted": id, "at_index": at_index }))
+                }
+                _ => Err(anyhow::anyhow!("at_index and step are required for action: insert")),
+            },
+            other => Ok(json!({
+                "error": format!("action '{other}' is not supported by the local handler; start the supervisor for full access"),
+                "available_actions": ["list", "next", "update", "batch_update", "add", "insert"]
+            })),
+        };
+
+        text_result(result)
+    }
+
+    /// Journal an offline write so it can be replayed through the upstream
+    /// tool later.  Returns the outbox id, or `None` if journaling failed (the
+    /// local write still stands).
+    ///
+    /// Only in-place updates are journaled.  Offline inserts (plans, steps,
+    /// notes, handoffs) already live in the shared database, and replaying
+    /// them upstream would create duplicates.
+    fn journal_offline_write(
+        &self,
+        tool: &str,
+        args: &Value,
+        db: &Connection,
+        step_ids: &[&str],
+    ) -> Option<i64> {
+        let outbox = self.outbox.as_ref()?.lock().unwrap();
+        let mut baselines = BTreeMap::new();
+        for step_id in step_ids {
+            match db::steps::step_updated_at(db, step_id) {
+                Ok(Some(ts)) => {
+                    // Earlier entries for this step must not see our own write as a conflict.
+                    if let Err(e) = outbox.rebase(step_id, &ts) {
+                        clog!("[outbox] failed to rebase step {step_id}: {e}");
+                    }
+                    baselines.insert(step_id.to_string(), ts);
+                }
+                Ok(None) => {}
+                Err(e) => clog!("[outbox] failed to read step {step_id}: {e}"),
+            }
+        }
+        match outbox.enqueue(tool, args, &baselines) {
+            Ok(id) => {
+                self.outbox_dirty.store(true, Ordering::Relaxed);
+                Some(id)
             }
+            Err(e) => {
+                clog!("[outbox] failed to journal {tool} write: {e}");
+                None
+            }
+        }
+    }
+
+    // ── memory_context (local) ────────────────────────────────────────────────
+
+    fn handle_local_context(&self, args: &Value) -> Value {
+        let action = args["action"].as_str().unwrap_or("get");
+        let db = self.db.lock().unwrap();
+
+        let result: Result<Value, anyhow::Error> = match action {
+            "store" => require_args(args, &["plan_id", "type", "data"], "store").and_then(|_| {
+                db::context::store_plan_context(
+                    &db,
+                    args["plan_id"].as_str().unwrap_or(""),
+                    args["type"].as_str().unwrap_or(""),
+                    &args["data"],
+                )
+            }),
+            "get" => require_args(args, &["plan_id", "type"], "get").and_then(|_| {
+                db::context::get_plan_context(
+                    &db,
+                    args["plan_id"].as_str().unwrap_or(""),
+                    args["type"].as_str().unwrap_or(""),
+                )
+            }),
+            other => Ok(json!({
+                "error": format!("action '{other}' is not supported by the local handler; start the supervisor for full access"),
+                "available_actions": ["store", "get"]
+            })),
+        };
+
+        text_result(result)
+    }
+
+    // ── memory_agent (local) ──────────────────────────────────────────────────
+
+    fn handle_local_agent(&self, args: &Value) -> Value {
+        let action = args["action"].as_str().unwrap_or("");
+        let db = self.db.lock().unwrap();
+
+        let result: Result<Value, anyhow::Error> = match action {
+            "handoff" => require_args(
+                args,
+                &["workspace_id", "plan_id", "from_agent", "to_agent", "reason"],
+                "handoff",
+            )
+            .and_then(|_| {
+                let handoff = db::lineage::Handoff {
+                    from_agent: args["from_agent"].as_str().unwrap_or(""),
+                    to_agent:   args["to_agent"].as_str().unwrap_or(""),
+                    reason:     args["reason"].as_str().unwrap_or(""),
+                    data:       args.get("data"),
+                };
+                db::lineage::record_handoff(&db, args["plan_id"].as_str().unwrap_or(""), &handoff)
+            }),
             other => Ok(json!({
                 "error": format!("action '{other}' is not supported by the local handler; start the supervisor for full access"),
-                "available_actions": ["list", "next", "update", "batch_update"]
+                "available_actions": ["handoff"]
             })),
         };
 
         text_result(result)
     }
 
+    // ── outbox replay ─────────────────────────────────────────────────────────
+
+    /// Replay journaled offline writes through the upstream MCP tools.
+    ///
+    /// Entries are replayed in order.  A transport failure stops the replay
+    /// and leaves the remaining entries pending for the next reconnect.
+    async fn replay_outbox(&self) {
+        let Some(outbox) = &self.outbox else { return };
+        let entries = match outbox.lock().unwrap().pending() {
+            Ok(entries) => entries,
+            Err(e) => {
+                clog!("[outbox] failed to read pending entries: {e}");
+                return;
+            }
+        };
+        if entries.is_empty() {
+            return;
+        }
+        clog!("[outbox] replaying {} offline write(s)", entries.len());
+
+        for entry in entries {
+            let conflict = outbox::conflicting_step(&self.db.lock().unwrap(), &entry);
+            if let Some(step_id) = conflict {
+                let msg = format!("step {step_id} changed upstream after the offline write");
+                clog!("[outbox] entry {} not replayed: {msg}", entry.id);
+                self.park_entry(entry.id, outbox::STATUS_CONFLICT, &msg);
+                continue;
+            }
+
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": format!("outbox-{}", entry.id),
+                "method": "tools/call",
+                "params": { "name": entry.tool, "arguments": entry.arguments }
+            });
+            let session = self.session_id.lock().unwrap().clone();
+            match upstream::forward(&self.http, &self.upstream_url, session.as_deref(), &req, &|msg| self.relay_upstream_message(msg)).await {
+                Ok((resp, new_sid)) => {
+                    self.update_session(new_sid);
+                    let result = extract_tool_result(resp);
+                    if result["isError"].as_bool().unwrap_or(false) {
+                        let msg = result["content"][0]["text"].as_str().unwrap_or("upstream error");
+                        clog!("[outbox] entry {} rejected upstream: {msg}", entry.id);
+                        self.park_entry(entry.id, outbox::STATUS_FAILED, msg);
+                    } else {
+                        self.finish_entry(&entry);
+                    }
+                }
+                Err(e) => {
+                    clog!("[outbox] replay of entry {} failed: {e}", entry.id);
+                    self.mark_upstream_disconnected();
+                    self.outbox_dirty.store(true, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Remove a replayed entry and rebase later entries on the timestamps the
+    /// upstream write left behind.
+    fn finish_entry(&self, entry: &OutboxEntry) {
+        let Some(outbox) = &self.outbox else { return };
+        let db = self.db.lock().unwrap();
+        let outbox = outbox.lock().unwrap();
+        if let Err(e) = outbox.remove(entry.id) {
+            clog!("[outbox] failed to remove entry {}: {e}", entry.id);
+        }
+        for step_id in entry.baselines.keys() {
+            if let Ok(Some(ts)) = db::steps::step_updated_at(&db, step_id) {
+                if let Err(e) = outbox.rebase(step_id, &ts) {
+                    clog!("[outbox] failed to rebase step {step_id}: {e}");
+                }
+            }
+        }
+    }
+
+    fn park_entry(&self, id: i64, status: &str, msg: &str) {
+        let Some(outbox) = &self.outbox else { return };
+        if let Err(e) = outbox.lock().unwrap().mark(id, status, msg) {
+            clog!("[outbox] failed to mark entry {id} as {status}: {e}");
+        }
+    }
+
     // ── memory_instructions (local) ───────────────────────────────────────────
 
     fn handle_local_instructions(&self, args: &Value) -> Value {
@@ -506,13 +956,24 @@ impl Proxy {
 
     // ── helpers ───────────────────────────────────────────────────────────────
 
+    /// Return the upstream connection state, replaying the outbox first if
+    /// offline writes are waiting and the supervisor is reachable.
+    async fn ping_upstream(&self) -> bool {
+        let connected = self.check_upstream().await;
+        if connected && self.outbox_dirty.swap(false, Ordering::Relaxed) {
+            self.replay_outbox().await;
+            return self.upstream_connected.load(Ordering::Relaxed);
+        }
+        connected
+    }
+
     /// Return the cached upstream connection state.
     ///
     /// When already known-connected, no network call is made — the background
     /// reconnect loop and call-failure detection keep the flag accurate.
     /// When known-disconnected, a real health check is done so the proxy can
     /// detect when the supervisor comes back online mid-session.
-    async fn ping_upstream(&self) -> bool {
+    async fn check_upstream(&self) -> bool {
         if self.upstream_connected.load(Ordering::Relaxed) {
             // Fast path: already connected, no HTTP call needed.
             return true;
@@ -534,10 +995,13 @@ impl Proxy {
             let session  = self.session_id.lock().unwrap().clone();
             if let Ok((_, new_sid)) = upstream::forward(
                 &self.http, &self.upstream_url, session.as_deref(), &init_req,
+                &|msg| self.relay_upstream_message(msg),
             ).await {
                 self.update_session(new_sid);
             }
             clog!("[proxy] upstream reconnected, session established");
+            // The client may be holding the degraded tool list.
+            self.notify_tools_changed();
         }
 
         now_connected
@@ -633,7 +1097,10 @@ fn static_tool_list() -> Vec<Value> {
                 "status":       { "type": "string" },
                 "category":     { "type": "string" },
                 "priority":     { "type": "string" },
-                "goals":        { "type": "array", "items": { "type": "string" } }
+                "goals":        { "type": "array", "items": { "type": "string" } },
+                "success_criteria": { "type": "array", "items": { "type": "string" } },
+                "note":         { "type": "string" },
+                "note_type":    { "type": "string", "enum": ["info","warning","instruction"] }
             },
             "required": ["action"]
         }
@@ -652,7 +1119,10 @@ fn static_tool_list() -> Vec<Value> {
                 "status":             { "type": "string", "enum": ["pending","active","done","blocked"] },
                 "notes":              { "type": "string" },
                 "completed_by_agent": { "type": "string" },
-                "updates":            { "type": "array" }
+                "updates":            { "type": "array" },
+                "steps":              { "type": "array" },
+                "step":               { "type": "object" },
+                "at_index":           { "type": "number" }
             },
             "required": ["action"]
         }
@@ -682,6 +1152,9 @@ fn static_tool_list() -> Vec<Value> {
             "properties": {
                 "action":       { "type": "string" },
                 "workspace_id": { "type": "string" },
+                "plan_id":      { "type": "string" },
+                "type":         { "type": "string" },
+                "data":         { "type": "object" },
                 "key":          { "type": "string" },
                 "content":      { "type": "string" },
                 "category":     { "type": "string" }
@@ -711,7 +1184,12 @@ fn static_tool_list() -> Vec<Value> {
             "properties": {
                 "action":       { "type": "string" },
                 "workspace_id": { "type": "string" },
-                "agent_id":     { "type": "string" }
+                "agent_id":     { "type": "string" },
+                "plan_id":      { "type": "string" },
+                "from_agent":   { "type": "string" },
+                "to_agent":     { "type": "string" },
+                "reason":       { "type": "string" },
+                "data":         { "type": "object" }
             },
             "required": ["action"]
         }
@@ -798,6 +1276,43 @@ fn extract_tool_result(resp: Value) -> Value {
     }
 }
 
+/// Fail with the server's "x, y, and z are required for action: …" error if
+/// any of `keys` is missing, null or an empty string.
+fn require_args(args: &Value, keys: &[&str], action: &str) -> anyhow::Result<()> {
+    let present = |key: &&str| match &args[*key] {
+        Value::Null          => false,
+        Value::String(s)     => !s.is_empty(),
+        _                    => true,
+    };
+    if keys.iter().all(present) {
+        return Ok(());
+    }
+    let list = match keys {
+        [only]            => return Err(anyhow::anyhow!("{only} is required for action: {action}")),
+        [a, b]            => format!("{a} and {b}"),
+        [rest @ .., last] => format!("{}, and {last}", rest.join(", ")),
+        []                => return Ok(()),
+    };
+    anyhow::bail!("{list} are required for action: {action}")
+}
+
+/// Collect the string items of a JSON array argument.
+fn string_list(v: &Value) -> Vec<String> {
+    v.as_array()
+        .map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Tag a local write result with its outbox id so the caller knows the
+/// server has not seen it yet.
+fn with_pending_sync(mut v: Value, outbox_id: Option<i64>) -> Value {
+    if let Some(id) = outbox_id {
+        v["pending_sync"] = json!(true);
+        v["outbox_id"]    = json!(id);
+    }
+    v
+}
+
 fn text_result(r: Result<Value, anyhow::Error>) -> Value {
     match r {
         Ok(v)  => json!({ "content": [{ "type": "text", "text": serde_json::to_string_pretty(&v).unwrap_or_default() }] }),
@@ -809,6 +1324,203 @@ fn json_result(id: Value, result: Value) -> Value {
     json!({ "jsonrpc": "2.0", "id": id, "result": result })
 }
 
-fn json_error(id: Value, code: i64, msg: &str) -> Value {
+pub(crate) fn json_error(id: Value, code: i64, msg: &str) -> Value {
     json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": msg } })
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixture;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    /// A proxy with an open upstream session, and the receiver for the
+    /// messages it relays to the client.
+    fn proxy_with_outbound(upstream_url: String) -> (Proxy, UnboundedReceiver<Value>) {
+        let db = fixture::open();
+        let schema = schema::detect(&db);
+        let (outbound, rx) = tokio::sync::mpsc::unbounded_channel();
+        let proxy = Proxy::with_parts(upstream_url, Client::new(), outbound, db, schema, None);
+        *proxy.session_id.lock().unwrap() = Some("sid-1".to_string());
+        proxy.upstream_connected.store(true, Ordering::Relaxed);
+        (proxy, rx)
+    }
+
+    fn proxy(upstream_url: String) -> Proxy {
+        proxy_with_outbound(upstream_url).0
+    }
+
+    /// Accept one HTTP request, answer it with `status`, and return the
+    /// request head (lowercased) and JSON body.
+    async fn serve_once(listener: TcpListener, status: &str) -> (String, Value) {
+        let (mut sock, _) = listener.accept().await.unwrap();
+        let (head, body) = read_request(&mut sock).await;
+        sock.write_all(format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        (head, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn read_request(sock: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let head_end = loop {
+            let n = sock.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..head_end]).to_ascii_lowercase();
+        let len: usize = head
+            .lines()
+            .find_map(|l| l.strip_prefix("content-length:"))
+            .map_or(0, |v| v.trim().parse().unwrap());
+        while buf.len() < head_end + len {
+            let n = sock.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        (head, buf[head_end..head_end + len].to_vec())
+    }
+
+    /// Accept an event-stream request and answer with SSE headers followed
+    /// by one `data:` line per message.  The socket is returned open.
+    async fn accept_event_stream(listener: &TcpListener, messages: &[Value]) -> TcpStream {
+        let (mut sock, _) = listener.accept().await.unwrap();
+        let (head, _) = read_request(&mut sock).await;
+        assert!(head.starts_with("get /mcp "));
+        let mut reply = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n".to_string();
+        for msg in messages {
+            reply.push_str(&format!("data: {msg}\n\n"));
+        }
+        sock.write_all(reply.as_bytes()).await.unwrap();
+        sock
+    }
+
+    fn notification(n: u32) -> Value {
+        json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": { "n": n } })
+    }
+
+    const TEST_TIMINGS: EventStreamTimings = EventStreamTimings {
+        connect:           Duration::from_millis(300),
+        idle:              Duration::from_millis(300),
+        retry:             Duration::from_millis(50),
+        unsupported_retry: Duration::from_millis(50),
+    };
+
+    /// Run the event stream against an upstream that misbehaves on the first
+    /// connection (per `first`) and delivers `notification(2)` on the second.
+    async fn assert_reopens_after<F, Fut>(first: F)
+    where
+        F: FnOnce(TcpListener) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = TcpListener> + Send,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (proxy, mut rx) = proxy_with_outbound(format!("http://{}/mcp", listener.local_addr().unwrap()));
+        let proxy = Arc::new(proxy);
+        let stream = tokio::spawn({
+            let proxy = Arc::clone(&proxy);
+            async move { proxy.run_event_stream_with(&TEST_TIMINGS).await }
+        });
+
+        let upstream = tokio::spawn(async move {
+            let listener = first(listener).await;
+            let _held = accept_event_stream(&listener, &[notification(2)]).await;
+            std::future::pending::<()>().await;
+        });
+
+        let relayed = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut relayed = Vec::new();
+            while relayed.last() != Some(&notification(2)) {
+                relayed.push(rx.recv().await.unwrap());
+            }
+            relayed
+        })
+        .await
+        .expect("event stream was not reopened");
+        stream.abort();
+        upstream.abort();
+        assert_eq!(relayed.last(), Some(&notification(2)));
+    }
+
+    #[tokio::test]
+    async fn event_stream_reopens_after_the_upstream_closes_it() {
+        assert_reopens_after(|listener| async move {
+            drop(accept_event_stream(&listener, &[notification(1)]).await);
+            listener
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn event_stream_reopens_after_the_upstream_never_answers() {
+        assert_reopens_after(|listener| async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            read_request(&mut sock).await;
+            // No response: the connect timeout has to give up on it.
+            tokio::spawn(async move {
+                let _held = sock;
+                std::future::pending::<()>().await;
+            });
+            listener
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn event_stream_reopens_after_going_idle() {
+        assert_reopens_after(|listener| async move {
+            let sock = accept_event_stream(&listener, &[notification(1)]).await;
+            // Headers and one event, then silence on an open socket.
+            tokio::spawn(async move {
+                let _held = sock;
+                std::future::pending::<()>().await;
+            });
+            listener
+        })
+        .await;
+    }
+
+    fn client_response() -> Value {
+        json!({ "jsonrpc": "2.0", "id": "srv-1", "result": { "action": "accept" } })
+    }
+
+    #[tokio::test]
+    async fn client_response_is_posted_to_the_upstream_session() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = proxy(format!("http://{}/mcp", listener.local_addr().unwrap()));
+        let upstream = tokio::spawn(serve_once(listener, "202 Accepted"));
+
+        proxy.forward_client_response(&client_response()).await;
+
+        let (head, body) = upstream.await.unwrap();
+        assert!(head.starts_with("post /mcp "));
+        assert!(head.contains("mcp-session-id: sid-1\r\n"));
+        assert_eq!(body, client_response());
+        assert!(proxy.upstream_connected.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn failed_client_response_does_not_mark_upstream_disconnected() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = proxy(format!("http://{}/mcp", listener.local_addr().unwrap()));
+        let upstream = tokio::spawn(serve_once(listener, "400 Bad Request"));
+
+        proxy.forward_client_response(&client_response()).await;
+        upstream.await.unwrap();
+        assert!(proxy.upstream_connected.load(Ordering::Relaxed));
+
+        // Nothing listening at all.
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = self::proxy(format!("http://{}/mcp", closed.local_addr().unwrap()));
+        drop(closed);
+        proxy.forward_client_response(&client_response()).await;
+        assert!(proxy.upstream_connected.load(Ordering::Relaxed));
+    }
+}
@@ -1,9 +1,12 @@
 //! HTTP upstream client — forwards JSON-RPC calls to the supervisor's MCP server.
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use futures_util::stream::BoxStream;
 use futures_util::StreamExt as _;
 use reqwest::Client;
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::OnceLock;
 use std::time::{Duration, Instant};
 
 use crate::log::clog;
@@ -12,11 +15,71 @@ const CONNECT_TIMEOUT_MS: u64  = 500;
 const CALL_TIMEOUT_SECS:  u64  = 10;   // was 60 — shorter so freezes are obvious
 const HEALTH_TIMEOUT_MS:  u64  = 400;
 
+/// Built-in timeouts for tools that routinely outlive `CALL_TIMEOUT_SECS`.
+const DEFAULT_TOOL_TIMEOUTS: &[(&str, u64)] = &[
+    ("memory_cartographer", 120),
+];
+
+/// Per-call timeout table, resolved once from the environment:
+///   - `PM_CALL_TIMEOUT_SECS` replaces the default for every call;
+///   - `PM_TOOL_TIMEOUTS` (`tool=secs,tool=secs`) overrides individual tools.
+struct CallTimeouts {
+    default:  Duration,
+    per_tool: HashMap<String, Duration>,
+}
+
+fn call_timeouts() -> &'static CallTimeouts {
+    static TIMEOUTS: OnceLock<CallTimeouts> = OnceLock::new();
+    TIMEOUTS.get_or_init(|| {
+        parse_call_timeouts(
+            std::env::var("PM_CALL_TIMEOUT_SECS").ok().as_deref(),
+            std::env::var("PM_TOOL_TIMEOUTS").ok().as_deref(),
+        )
+    })
+}
+
+/// Build the timeout table from the raw `PM_CALL_TIMEOUT_SECS` and
+/// `PM_TOOL_TIMEOUTS` values.  Invalid entries are logged and ignored.
+fn parse_call_timeouts(default_secs: Option<&str>, tool_spec: Option<&str>) -> CallTimeouts {
+    let default = default_secs
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(CALL_TIMEOUT_SECS);
+
+    let mut per_tool: HashMap<String, Duration> = DEFAULT_TOOL_TIMEOUTS
+        .iter()
+        .map(|(tool, secs)| (tool.to_string(), Duration::from_secs(*secs)))
+        .collect();
+    for pair in tool_spec.unwrap_or_default().split(',') {
+        let Some((tool, secs)) = pair.split_once('=') else { continue };
+        match secs.trim().parse::<u64>() {
+            Ok(secs) if secs > 0 => {
+                per_tool.insert(tool.trim().to_string(), Duration::from_secs(secs));
+            }
+            _ => clog!("[upstream] ignoring invalid PM_TOOL_TIMEOUTS entry '{pair}'"),
+        }
+    }
+
+    CallTimeouts { default: Duration::from_secs(default), per_tool }
+}
+
+impl CallTimeouts {
+    fn get(&self, tool: &str) -> Duration {
+        self.per_tool.get(tool).copied().unwrap_or(self.default)
+    }
+}
+
+/// Timeout for a single upstream call to `tool` (empty for non-tool methods).
+pub fn call_timeout(tool: &str) -> Duration {
+    call_timeouts().get(tool)
+}
+
 /// Build a shared reqwest Client.
+///
+/// No client-wide timeout is set; [`forward`] applies [`call_timeout`] per request.
 pub fn build_client() -> Client {
     Client::builder()
         .connect_timeout(Duration::from_millis(CONNECT_TIMEOUT_MS))
-        .timeout(Duration::from_secs(CALL_TIMEOUT_SECS))
         .build()
         .expect("failed to build reqwest client")
 }
@@ -40,11 +103,18 @@ pub async fn health_check(client: &Client, base_url: &str) -> bool {
 ///
 /// Returns `Err` only on network/transport failures; an upstream error response
 /// is returned as `Ok(value)` so the proxy can pass it through transparently.
+/// Dropping the returned future aborts the HTTP request.
+///
+/// Server-initiated messages (progress, log messages, requests) that the
+/// upstream streams ahead of the response are handed to `relay`.  When the
+/// request carries a `progressToken`, the call timeout becomes an idle
+/// timeout: every message from the upstream keeps the call alive.
 pub async fn forward(
     client: &Client,
     mcp_url: &str,
     session_id: Option<&str>,
     request: &Value,
+    relay: &(dyn Fn(Value) + Sync),
 ) -> Result<(Value, Option<String>)> {
     let method = request["method"].as_str().unwrap_or("?");
     let tool   = request["params"]["name"].as_str().unwrap_or("");
@@ -52,20 +122,30 @@ pub async fn forward(
     clog!("[upstream] → {label} sid={}", session_id.unwrap_or("none"));
     let t0 = Instant::now();
 
+    let timeout      = call_timeout(tool);
+    let idle         = progress_idle_limit(request, timeout);
+
     let mut builder = client
         .post(mcp_url)
         .header("Content-Type", "application/json")
         .header("Accept", "application/json, text/event-stream")
         .json(request);
 
+    if idle.is_none() {
+        builder = builder.timeout(timeout);
+    }
     if let Some(sid) = session_id {
         builder = builder.header("Mcp-Session-Id", sid);
     }
 
-    let response = builder.send().await.map_err(|e| {
-        clog!("[upstream] ✗ send failed after {}ms: {e}", t0.elapsed().as_millis());
-        e
-    })?;
+    let response = tokio::time::timeout(timeout, builder.send())
+        .await
+        .map_err(|_| anyhow!("no response headers within {}s", timeout.as_secs()))
+        .and_then(|r| r.map_err(anyhow::Error::from))
+        .map_err(|e| {
+            clog!("[upstream] ✗ send failed after {}ms: {e}", t0.elapsed().as_millis());
+            e
+        })?;
     let status   = response.status();
 
     // Capture new session ID if the server assigns one.
@@ -96,11 +176,11 @@ pub async fn forward(
     clog!("[upstream] ← {label} HTTP {status} content-type={content_type} after {}ms — reading body", t0.elapsed().as_millis());
 
     if content_type.contains("text/event-stream") {
-        // Stream line-by-line and return as soon as the first data: event arrives.
+        // Stream line-by-line and return as soon as the response event arrives.
         // DO NOT use response.text() — it reads the entire body, which blocks until
         // the server closes the SSE stream (which it may never do for long-lived
         // connections), causing every tool call to hang for up to CALL_TIMEOUT_SECS.
-        let result = read_first_sse_event(response).await.map_err(|e| {
+        let result = read_sse_response(SseStream::new(response), idle, relay).await.map_err(|e| {
             clog!("[upstream] ✗ SSE read failed for {label} after {}ms: {e}", t0.elapsed().as_millis());
             e
         })?;
@@ -116,30 +196,109 @@ pub async fn forward(
     }
 }
 
-/// Read an SSE response body chunk-by-chunk and return as soon as the first
-/// non-empty `data:` line is found — without waiting for the stream to close.
-async fn read_first_sse_event(response: reqwest::Response) -> Result<Value> {
-    let mut stream = response.bytes_stream();
-    let mut buf = String::new();
+/// Idle limit for the SSE body: `timeout` when the call carries a
+/// `progressToken`, otherwise none (the whole call is bounded instead).
+fn progress_idle_limit(request: &Value, timeout: Duration) -> Option<Duration> {
+    let has_progress = !request["params"]["_meta"]["progressToken"].is_null();
+    has_progress.then_some(timeout)
+}
+
+/// Read an SSE response body until the JSON-RPC response arrives, relaying
+/// any server-initiated messages seen before it — without waiting for the
+/// stream to close.
+async fn read_sse_response(
+    mut stream: SseStream,
+    idle: Option<Duration>,
+    relay: &(dyn Fn(Value) + Sync),
+) -> Result<Value> {
+    while let Some(message) = stream.next_message(idle).await? {
+        if is_response(&message) {
+            return Ok(message);
+        }
+        relay(message);
+    }
+
+    bail!("no response event found in SSE response")
+}
+
+/// A JSON-RPC response has an id and a result or error, but no method.
+fn is_response(message: &Value) -> bool {
+    message.get("method").is_none()
+        && (message.get("result").is_some() || message.get("error").is_some())
+}
+
+/// Open the session's standing server-to-client SSE stream (`GET` on the MCP
+/// endpoint).  Returns `Ok(None)` when the upstream does not offer one, and
+/// fails if no response headers arrive within `timeout`.
+pub async fn open_event_stream(
+    client: &Client,
+    mcp_url: &str,
+    session_id: &str,
+    timeout: Duration,
+) -> Result<Option<SseStream>> {
+    let request = client
+        .get(mcp_url)
+        .header("Accept", "text/event-stream")
+        .header("Mcp-Session-Id", session_id)
+        .send();
+    let response = tokio::time::timeout(timeout, request)
+        .await
+        .map_err(|_| anyhow!("no response headers within {}s", timeout.as_secs()))??;
+    let status = response.status();
+
+    if status == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        bail!("upstream event stream returned HTTP {}", status);
+    }
+    Ok(Some(SseStream::new(response)))
+}
+
+/// Incremental reader over an SSE body, yielding one JSON message per
+/// non-empty `data:` line.
+pub struct SseStream {
+    body: BoxStream<'static, reqwest::Result<Vec<u8>>>,
+    buf:  String,
+}
+
+impl SseStream {
+    fn new(response: reqwest::Response) -> Self {
+        Self::from_body(response.bytes_stream().map(|chunk| chunk.map(|b| b.to_vec())).boxed())
+    }
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        buf.push_str(&String::from_utf8_lossy(&chunk));
+    fn from_body(body: BoxStream<'static, reqwest::Result<Vec<u8>>>) -> Self {
+        SseStream { body, buf: String::new() }
+    }
 
-        // Scan complete lines accumulated so far.
-        while let Some(pos) = buf.find('\n') {
-            let line = buf[..pos].trim_end_matches('\r').to_string();
-            buf = buf[pos + 1..].to_string();
-            if let Some(data) = line.strip_prefix("data:") {
-                let data = data.trim();
-                if !data.is_empty() && data != "[DONE]" {
-                    return Ok(serde_json::from_str(data)?);
+    /// Next message, or `None` once the upstream closes the stream.  With
+    /// `idle` set, fails if no bytes arrive for that long.
+    pub async fn next_message(&mut self, idle: Option<Duration>) -> Result<Option<Value>> {
+        loop {
+            // Scan complete lines accumulated so far.
+            while let Some(pos) = self.buf.find('\n') {
+                let line = self.buf[..pos].trim_end_matches('\r').to_string();
+                self.buf.drain(..=pos);
+                if let Some(data) = line.strip_prefix("data:") {
+                    let data = data.trim();
+                    if !data.is_empty() && data != "[DONE]" {
+                        return Ok(Some(serde_json::from_str(data)?));
+                    }
                 }
             }
+
+            let chunk = match idle {
+                Some(limit) => tokio::time::timeout(limit, self.body.next())
+                    .await
+                    .map_err(|_| anyhow!("no upstream activity for {}s", limit.as_secs()))?,
+                None => self.body.next().await,
+            };
+            match chunk {
+                Some(chunk) => self.buf.push_str(&String::from_utf8_lossy(&chunk?)),
+                None        => return Ok(None),
+            }
         }
     }
-
-    bail!("no data event found in SSE response")
 }
 
 /// Build an `initialize` request to establish a session with the upstream.
@@ -158,3 +317,129 @@ pub fn make_initialize_request() -> Value {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+    use std::sync::Mutex;
+
+    /// An SSE body that delivers `chunks` and then either ends or, with
+    /// `hang`, stays open without sending anything more.
+    fn canned(chunks: &[&str], hang: bool) -> SseStream {
+        let chunks: Vec<reqwest::Result<Vec<u8>>> =
+            chunks.iter().map(|c| Ok(c.as_bytes().to_vec())).collect();
+        let body = stream::iter(chunks);
+        if hang {
+            SseStream::from_body(body.chain(stream::pending()).boxed())
+        } else {
+            SseStream::from_body(body.boxed())
+        }
+    }
+
+    fn progress(n: u32) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": "tok-1", "progress": n },
+        })
+    }
+
+    fn call_result() -> Value {
+        json!({ "jsonrpc": "2.0", "id": 3, "result": { "content": [] } })
+    }
+
+    /// Read the canned stream as a tools/call would, collecting relayed messages.
+    async fn read(stream: SseStream, idle: Option<Duration>) -> (Result<Value>, Vec<Value>) {
+        let relayed = Mutex::new(Vec::new());
+        let result = read_sse_response(stream, idle, &|m| relayed.lock().unwrap().push(m)).await;
+        (result, relayed.into_inner().unwrap())
+    }
+
+    #[tokio::test]
+    async fn sse_relays_progress_and_notifications_during_a_call() {
+        let log = json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": { "data": "x" } });
+        let body = format!("event: message\ndata: {}\n\ndata: {log}\r\n\r\n", progress(1));
+        let final_event = format!("data: {}\n\n", call_result());
+        let (result, relayed) = read(canned(&[&body, &final_event], true), None).await;
+
+        assert_eq!(result.unwrap(), call_result());
+        assert_eq!(relayed, vec![progress(1), log]);
+    }
+
+    #[tokio::test]
+    async fn sse_result_arriving_after_progress_is_returned() {
+        let event = |v: Value| format!("data: {v}\n\n");
+        let whole = format!("{}{}{}", event(progress(1)), event(progress(2)), event(call_result()));
+        // Split mid-line so the reader has to reassemble events across chunks.
+        let (a, b) = whole.split_at(whole.len() - 20);
+        let (c, d) = a.split_at(7);
+        let (result, relayed) = read(canned(&[c, d, b], false), Some(Duration::from_secs(5))).await;
+
+        assert_eq!(result.unwrap(), call_result());
+        assert_eq!(relayed, vec![progress(1), progress(2)]);
+    }
+
+    #[tokio::test]
+    async fn sse_idle_timeout_fails_a_stalled_call() {
+        let call = json!({
+            "jsonrpc": "2.0", "id": 3, "method": "tools/call",
+            "params": { "name": "memory_plan", "_meta": { "progressToken": "tok-1" } },
+        });
+        let idle = progress_idle_limit(&call, Duration::from_millis(100));
+        assert_eq!(idle, Some(Duration::from_millis(100)));
+        let body = format!("data: {}\n\n", progress(1));
+        let (result, relayed) = read(canned(&[&body], true), idle).await;
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("no upstream activity"), "unexpected error: {err}");
+        assert_eq!(relayed, vec![progress(1)]);
+    }
+
+    #[test]
+    fn no_idle_limit_without_a_progress_token() {
+        let call = json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": { "name": "memory_plan" } });
+        assert_eq!(progress_idle_limit(&call, Duration::from_secs(10)), None);
+    }
+
+    #[tokio::test]
+    async fn sse_stream_closing_without_a_result_is_an_error() {
+        let body = format!("data: {}\n\n", progress(1));
+        let (result, _) = read(canned(&[&body], false), None).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn call_timeouts_default_without_env() {
+        let timeouts = parse_call_timeouts(None, None);
+        assert_eq!(timeouts.get(""), Duration::from_secs(CALL_TIMEOUT_SECS));
+        assert_eq!(timeouts.get("memory_plan"), Duration::from_secs(CALL_TIMEOUT_SECS));
+        assert_eq!(timeouts.get("memory_cartographer"), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn call_timeouts_apply_per_tool_overrides() {
+        let timeouts = parse_call_timeouts(
+            Some(" 30 "),
+            Some("memory_plan=45, memory_cartographer = 300,memory_steps=5"),
+        );
+        assert_eq!(timeouts.get("memory_context"), Duration::from_secs(30));
+        assert_eq!(timeouts.get("memory_plan"), Duration::from_secs(45));
+        assert_eq!(timeouts.get("memory_cartographer"), Duration::from_secs(300));
+        assert_eq!(timeouts.get("memory_steps"), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn call_timeouts_ignore_invalid_entries() {
+        let timeouts = parse_call_timeouts(
+            Some("0"),
+            Some("memory_plan=abc,memory_steps=0,memory_agent,,memory_context=12"),
+        );
+        assert_eq!(timeouts.default, Duration::from_secs(CALL_TIMEOUT_SECS));
+        assert_eq!(timeouts.get("memory_plan"), Duration::from_secs(CALL_TIMEOUT_SECS));
+        assert_eq!(timeouts.get("memory_steps"), Duration::from_secs(CALL_TIMEOUT_SECS));
+        assert_eq!(timeouts.get("memory_agent"), Duration::from_secs(CALL_TIMEOUT_SECS));
+        assert_eq!(timeouts.get("memory_context"), Duration::from_secs(12));
+        assert_eq!(timeouts.get("memory_cartographer"), Duration::from_secs(120));
+    }
+}
@@ -0,0 +1,168 @@
+//! Persistent per-file scan cache.
+//!
+//! Each scanned file is remembered by path with its mtime, size and SHA-256
+//! content hash, alongside its parsed symbols and (unresolved) imports.  A
+//! later scan reuses an entry when mtime and size still match, or when the
+//! file was touched but its content hash is unchanged; only the remaining
+//! files are parsed again.  A cache belongs to one root, scan mode and set
+//! of walk options, since changing the options changes which files a scan
+//! sees.
+
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::walker::WalkOptions;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Bumped whenever the cached data or parser output changes shape, so stale
+/// caches are discarded instead of misread.
+pub const CACHE_VERSION: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub mtime_ns: u64,
+    pub size_bytes: u64,
+    pub content_hash: String,
+    pub language: String,
+    pub symbols: Vec<SymbolRecord>,
+    pub imports: Vec<ImportRecord>,
+}
+
+/// Cached results of the previous scan of one root in one scan mode with
+/// one set of walk options.
+///
+/// The default value is an empty cache that matches no scan.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    pub version: u32,
+    pub root: String,
+    pub scan_mode: String,
+    /// [`walk_options_key`] of the options the scan walked with.
+    pub walk_options: String,
+    pub entries: HashMap<String, CacheEntry>,
+}
+
+impl ScanCache {
+    pub fn new(root: &str, scan_mode: &str, options: &WalkOptions) -> Self {
+        ScanCache {
+            version: CACHE_VERSION,
+            root: root.to_string(),
+            scan_mode: scan_mode.to_string(),
+            walk_options: walk_options_key(options),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Whether this cache holds a previous scan of `root` in `scan_mode`
+    /// walked with `options`.
+    pub fn matches(&self, root: &str, scan_mode: &str, options: &WalkOptions) -> bool {
+        self.version == CACHE_VERSION
+            && self.root == root
+            && self.scan_mode == scan_mode
+            && self.walk_options == walk_options_key(options)
+    }
+
+    /// Load the cache at `path`, or an empty one if it is missing,
+    /// unreadable or for a different root, mode, walk options or cache
+    /// version.
+    pub fn load(path: &Path, root: &str, scan_mode: &str, options: &WalkOptions) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<ScanCache>(&text).ok())
+            .filter(|cache| cache.matches(root, scan_mode, options))
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to `path` via a temp file and rename, so a crash
+    /// never leaves a half-written cache behind.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create cache dir {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| format!("Cannot serialise scan cache: {}", e))?;
+        let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+        std::fs::write(&tmp, json).map_err(|e| format!("Cannot write scan cache {}: {}", tmp.display(), e))?;
+        std::fs::rename(&tmp, path).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("Cannot replace scan cache {}: {}", path.display(), e)
+        })
+    }
+}
+
+/// Default cache file for `root`, `scan_mode` and `options`: one file per
+/// root, mode and walk options under the user cache directory, so nothing is
+/// written into the workspace and differently filtered scans of the same root
+/// do not overwrite each other.
+pub fn default_cache_path(root: &Path, scan_mode: &str, options: &WalkOptions) -> Option<PathBuf> {
+    let canonical = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let key = content_hash(canonical.to_string_lossy().as_bytes());
+    let dir = dirs::cache_dir()?.join("cartographer");
+    Some(dir.join(format!("{}-{}-{}.json", &key[..16], walk_options_key(options), scan_mode)))
+}
+
+/// Short hash identifying `options`, so scans that walk different files
+/// never share a cache.
+pub fn walk_options_key(options: &WalkOptions) -> String {
+    content_hash(format!("{:?}", options).as_bytes())[..16].to_string()
+}
+
+/// Hex SHA-256 of `bytes`.
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Modification time in nanoseconds since the epoch, 0 when unavailable.
+pub fn mtime_ns(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_discards_mismatched_caches() {
+        let dir = std::env::temp_dir().join(format!("cart_cache_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("cache.json");
+
+        let options = WalkOptions::default();
+        let mut cache = ScanCache::new("/w", "summary", &options);
+        cache.entries.insert(
+            "/w/a.rs".to_string(),
+            CacheEntry {
+                mtime_ns: 1,
+                size_bytes: 2,
+                content_hash: content_hash(b"fn a() {}"),
+                language: "rust".to_string(),
+                symbols: vec![],
+                imports: vec![],
+            },
+        );
+        cache.save(&path).unwrap();
+
+        assert_eq!(ScanCache::load(&path, "/w", "summary", &options).entries.len(), 1);
+        assert!(ScanCache::load(&path, "/w", "full", &options).entries.is_empty());
+        assert!(ScanCache::load(&path, "/other", "summary", &options).entries.is_empty());
+        assert!(ScanCache::load(&dir.join("missing.json"), "/w", "summary", &options).entries.is_empty());
+        let filtered = WalkOptions { include_extensions: Some(vec!["rs".to_string()]), ..WalkOptions::default() };
+        assert!(ScanCache::load(&path, "/w", "summary", &filtered).entries.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_content_hash_is_hex_sha256() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
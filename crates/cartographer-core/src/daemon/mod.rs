@@ -0,0 +1,531 @@
+//! `--serve`: a long-lived process answering NDJSON requests.
+//!
+//! Each stdin line is a request object with an `id` (any JSON value, echoed
+//! back) and an `action`; each stdout line is a [`DaemonResponse`].  Requests
+//! run concurrently on a fixed pool of workers, queueing while all are busy,
+//! and responses are written as they finish, so clients match them up by
+//! `id`.  A request reusing the id of one still in flight is rejected;
+//! requests without an id cannot be cancelled.
+//!
+//! Actions:
+//! - `scan` — (re)scan `root`, reusing the workspace's in-memory cache.
+//! - `symbols_in_file` — the scanned [`FileResult`] for `path`.
+//! - `find_symbol` — symbols named like `name` (case-insensitive substring,
+//!   or exact with `"exact": true`), optionally filtered by `kind`.
+//! - `deps` — dependencies and dependents of `target`.
+//! - `invalidate` — forget `paths` (or the whole workspace) so the next
+//!   request re-parses them.
+//! - `cancel` — stop the in-flight request whose id is `cancel_id`; it
+//!   answers with the error `"Cancelled"`.
+//!
+//! Query actions scan the workspace first if it has not been scanned yet.
+//! Workspaces are keyed by root, scan mode and walk options.  On stdin EOF the daemon stops
+//! reading, lets in-flight requests finish and exits.
+
+use crate::cache::{default_cache_path, walk_options_key, ScanCache};
+use crate::graph;
+use crate::output::{DaemonResponse, FileResult, ScanResult, SymbolMatch};
+use crate::scanner::engine::scan_with_cache;
+use crate::scanner::lang::ScanMode;
+use crate::scanner::walker::{WalkOptions, DEFAULT_MAX_FILE_BYTES};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+
+const DEFAULT_FIND_LIMIT: usize = 100;
+
+/// Bounds on the number of requests handled at once.
+const MIN_WORKERS: usize = 2;
+const MAX_WORKERS: usize = 8;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DaemonRequest {
+    #[serde(default)]
+    pub id: Value,
+    pub action: String,
+    pub root: Option<String>,
+    pub scan_mode: Option<String>,
+    pub max_files: Option<usize>,
+    pub max_seconds: Option<f64>,
+    pub include_extensions: Option<Vec<String>>,
+    /// Directory or file names excluded at any depth; kept for older callers,
+    /// equivalent to `exclude_globs`.
+    pub exclude_paths: Option<Vec<String>>,
+    /// Gitignore-style patterns relative to `root`; when given, only matching
+    /// files are scanned.
+    pub include_globs: Option<Vec<String>>,
+    /// Gitignore-style patterns relative to `root`, excluded even when no
+    /// ignore file mentions them.
+    pub exclude_globs: Option<Vec<String>>,
+    /// Skip files larger than this (default 1 MiB, `0` for no limit).
+    pub max_file_bytes: Option<u64>,
+    /// Honour `.gitignore`, `.ignore` and `.cartographerignore` (default `true`).
+    pub use_ignore_files: Option<bool>,
+    /// Persist the workspace cache to disk (default `true`).
+    pub cache: Option<bool>,
+    pub cache_path: Option<String>,
+    /// `symbols_in_file`: the file, absolute or relative to `root`.
+    pub path: Option<String>,
+    /// `find_symbol`
+    pub name: Option<String>,
+    pub kind: Option<String>,
+    pub exact: Option<bool>,
+    pub limit: Option<usize>,
+    /// `deps`
+    pub target: Option<String>,
+    pub transitive: Option<bool>,
+    /// `invalidate`: files to forget; all of them when absent.
+    pub paths: Option<Vec<String>>,
+    /// `cancel`: id of the request to cancel.
+    pub cancel_id: Option<Value>,
+}
+
+impl DaemonRequest {
+    fn root(&self) -> Result<&str, String> {
+        match self.root.as_deref().map(str::trim) {
+            Some(root) if !root.is_empty() && Path::new(root).exists() => Ok(root),
+            Some(root) if !root.is_empty() => Err(format!("Root path not found: {}", root)),
+            _ => Err(format!("{} requires a root", self.action)),
+        }
+    }
+
+    fn walk_options(&self) -> WalkOptions {
+        let exclude_globs: Vec<String> = self.exclude_paths.iter().chain(&self.exclude_globs).flatten().cloned().collect();
+        WalkOptions {
+            include_extensions: self.include_extensions.clone(),
+            include_globs: self.include_globs.clone(),
+            exclude_globs: Some(exclude_globs),
+            max_file_bytes: match self.max_file_bytes {
+                Some(0) => None,
+                Some(limit) => Some(limit),
+                None => Some(DEFAULT_MAX_FILE_BYTES),
+            },
+            use_ignore_files: self.use_ignore_files.unwrap_or(true),
+        }
+    }
+
+    fn mode(&self, default: ScanMode) -> Result<ScanMode, String> {
+        match self.scan_mode.as_deref() {
+            Some(mode) => ScanMode::from_str(mode),
+            None => Ok(default),
+        }
+    }
+}
+
+/// A scanned workspace held between requests.
+struct Workspace {
+    cache: ScanCache,
+    cache_path: Option<PathBuf>,
+    last: Option<ScanResult>,
+}
+
+/// Root, scan mode and [`walk_options_key`].
+type WorkspaceKey = (String, &'static str, String);
+
+#[derive(Default)]
+pub struct Daemon {
+    workspaces: Mutex<HashMap<WorkspaceKey, Arc<Mutex<Workspace>>>>,
+    in_flight: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn id_key(id: &Value) -> String {
+    serde_json::to_string(id).unwrap_or_default()
+}
+
+impl Daemon {
+    pub fn new() -> Self {
+        Daemon::default()
+    }
+
+    /// Mark `id` as in flight and return its cancellation flag, or an error
+    /// if a request with the same id is still in flight.  A null id is not
+    /// tracked, so its request cannot be cancelled.
+    pub fn register(&self, id: &Value) -> Result<Arc<AtomicBool>, String> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if id.is_null() {
+            return Ok(flag);
+        }
+        match lock(&self.in_flight).entry(id_key(id)) {
+            Entry::Occupied(_) => Err(format!("Duplicate request id: {}", id)),
+            Entry::Vacant(slot) => Ok(slot.insert(flag).clone()),
+        }
+    }
+
+    pub fn unregister(&self, id: &Value) {
+        lock(&self.in_flight).remove(&id_key(id));
+    }
+
+    /// Handle a `cancel` request.
+    pub fn cancel(&self, req: &DaemonRequest) -> Result<Value, String> {
+        let target = req.cancel_id.as_ref().ok_or("cancel requires a cancel_id")?;
+        let cancelled = match lock(&self.in_flight).get(&id_key(target)) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        };
+        Ok(serde_json::json!({ "cancelled": cancelled }))
+    }
+
+    fn workspace(&self, req: &DaemonRequest, root: &str, mode: &ScanMode) -> Arc<Mutex<Workspace>> {
+        let options = req.walk_options();
+        let key = (root.to_string(), mode.as_str(), walk_options_key(&options));
+        lock(&self.workspaces)
+            .entry(key)
+            .or_insert_with(|| {
+                let cache_path = if req.cache.unwrap_or(true) {
+                    req.cache_path
+                        .as_deref()
+                        .map(PathBuf::from)
+                        .or_else(|| default_cache_path(Path::new(root), mode.as_str(), &options))
+                } else {
+                    None
+                };
+                let cache = cache_path
+                    .as_deref()
+                    .map(|path| ScanCache::load(path, root, mode.as_str(), &options))
+                    .unwrap_or_default();
+                Arc::new(Mutex::new(Workspace { cache, cache_path, last: None }))
+            })
+            .clone()
+    }
+
+    /// Run one request other than `cancel`.
+    pub fn handle(&self, req: &DaemonRequest, cancel: &AtomicBool) -> Result<Value, String> {
+        match req.action.as_str() {
+            "scan" => {
+                let (root, mode) = (req.root()?, req.mode(ScanMode::Summary)?);
+                let workspace = self.workspace(req, root, &mode);
+                let mut ws = lock(&workspace);
+                to_value(scan(&mut ws, root, mode, req, cancel)?)
+            }
+            "symbols_in_file" => {
+                let (root, mode) = (req.root()?, req.mode(ScanMode::FileContext)?);
+                let path = req.path.as_deref().ok_or("symbols_in_file requires a path")?;
+                let workspace = self.workspace(req, root, &mode);
+                let mut ws = lock(&workspace);
+                let result = scanned(&mut ws, root, mode, req, cancel)?;
+                let file: &FileResult = graph::locate(result, path).ok_or_else(|| format!("File not found in scan: {}", path))?;
+                to_value(file)
+            }
+            "find_symbol" => {
+                let (root, mode) = (req.root()?, req.mode(ScanMode::FileContext)?);
+                let name = req.name.as_deref().ok_or("find_symbol requires a name")?;
+                let workspace = self.workspace(req, root, &mode);
+                let mut ws = lock(&workspace);
+                let result = scanned(&mut ws, root, mode, req, cancel)?;
+                to_value(&find_symbol(result, name, req))
+            }
+            "deps" => {
+                let (root, mode) = (req.root()?, req.mode(ScanMode::Summary)?);
+                let target = req.target.as_deref().ok_or("deps requires a target")?;
+                let workspace = self.workspace(req, root, &mode);
+                let mut ws = lock(&workspace);
+                let result = scanned(&mut ws, root, mode, req, cancel)?;
+                to_value(&graph::deps(result, target, req.transitive.unwrap_or(false))?)
+            }
+            "invalidate" => {
+                let root = req.root()?;
+                let only_mode = req.scan_mode.as_deref().map(ScanMode::from_str).transpose()?;
+                let held: Vec<Arc<Mutex<Workspace>>> = lock(&self.workspaces)
+                    .iter()
+                    .filter(|((r, m, _), _)| r == root && only_mode.as_ref().is_none_or(|mode| mode.as_str() == *m))
+                    .map(|(_, ws)| ws.clone())
+                    .collect();
+                for workspace in &held {
+                    invalidate(&mut lock(workspace), root, req.paths.as_deref());
+                }
+                Ok(serde_json::json!({ "invalidated": held.len() }))
+            }
+            other => Err(format!("Unknown action: {}", other)),
+        }
+    }
+}
+
+fn to_value<T: serde::Serialize + ?Sized>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Cannot serialise result: {}", e))
+}
+
+fn scan<'a>(ws: &'a mut Workspace, root: &str, mode: ScanMode, req: &DaemonRequest, cancel: &AtomicBool) -> Result<&'a ScanResult, String> {
+    let mut result = scan_with_cache(
+        Path::new(root),
+        mode,
+        req.max_files.unwrap_or(5000),
+        req.max_seconds.unwrap_or(30.0),
+        &req.walk_options(),
+        Some(&mut ws.cache),
+        Some(cancel),
+    );
+    if cancel.load(Ordering::Relaxed) {
+        return Err("Cancelled".to_string());
+    }
+    if let Some(path) = &ws.cache_path {
+        if let Err(e) = ws.cache.save(path) {
+            result.diagnostics.errors.push(e);
+        }
+    }
+    Ok(ws.last.insert(result))
+}
+
+/// The held scan, scanning first if there is none.
+fn scanned<'a>(ws: &'a mut Workspace, root: &str, mode: ScanMode, req: &DaemonRequest, cancel: &AtomicBool) -> Result<&'a ScanResult, String> {
+    if ws.last.is_none() {
+        scan(ws, root, mode, req, cancel)?;
+    }
+    ws.last.as_ref().ok_or_else(|| "Workspace not scanned".to_string())
+}
+
+fn find_symbol(result: &ScanResult, name: &str, req: &DaemonRequest) -> Vec<SymbolMatch> {
+    let exact = req.exact.unwrap_or(false);
+    let needle = name.to_lowercase();
+    result
+        .files
+        .iter()
+        .flat_map(|file| file.symbols.iter().map(move |symbol| (file, symbol)))
+        .filter(|(_, symbol)| {
+            if exact {
+                symbol.name == name
+            } else {
+                symbol.name.to_lowercase().contains(&needle)
+            }
+        })
+        .filter(|(_, symbol)| req.kind.as_deref().is_none_or(|kind| symbol.kind == kind))
+        .take(req.limit.unwrap_or(DEFAULT_FIND_LIMIT))
+        .map(|(file, symbol)| SymbolMatch {
+            path: file.path.clone(),
+            symbol: symbol.clone(),
+        })
+        .collect()
+}
+
+fn invalidate(ws: &mut Workspace, root: &str, paths: Option<&[String]>) {
+    ws.last = None;
+    let Some(paths) = paths else {
+        ws.cache = ScanCache::default();
+        return;
+    };
+    for path in paths {
+        let path = Path::new(path);
+        let wanted = graph::resolve::normalize(&if path.is_absolute() { path.to_path_buf() } else { Path::new(root).join(path) });
+        ws.cache.entries.retain(|key, _| graph::resolve::normalize(Path::new(key)) != wanted);
+    }
+}
+
+fn write_response<W: Write>(output: &Mutex<W>, response: &DaemonResponse) {
+    let line = serde_json::to_string(response).unwrap_or_else(|e| format!("{{\"id\":null,\"ok\":false,\"error\":\"{}\"}}", e));
+    let mut out = lock(output);
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+fn respond(id: Value, outcome: Result<Value, String>) -> DaemonResponse {
+    match outcome {
+        Ok(result) => DaemonResponse { id, ok: true, result: Some(result), error: None },
+        Err(error) => DaemonResponse { id, ok: false, result: None, error: Some(error) },
+    }
+}
+
+/// Serve requests from `input` until EOF, then wait for in-flight requests
+/// and hand back `output`.
+pub fn serve<R: BufRead, W: Write + Send + 'static>(input: R, output: W) -> W {
+    let daemon = Arc::new(Daemon::new());
+    let output = Arc::new(Mutex::new(output));
+    let (jobs, queue) = mpsc::channel::<(DaemonRequest, Arc<AtomicBool>)>();
+    let queue = Arc::new(Mutex::new(queue));
+    let count = thread::available_parallelism().map_or(MIN_WORKERS, |n| n.get()).clamp(MIN_WORKERS, MAX_WORKERS);
+    let workers: Vec<thread::JoinHandle<()>> = (0..count)
+        .map(|_| {
+            let (daemon, output, queue) = (daemon.clone(), output.clone(), queue.clone());
+            thread::spawn(move || loop {
+                let job = lock(&queue).recv();
+                let Ok((req, cancel)) = job else {
+                    break;
+                };
+                // A request cancelled while queued is answered without running.
+                let outcome = if cancel.load(Ordering::Relaxed) {
+                    Err("Cancelled".to_string())
+                } else {
+                    daemon.handle(&req, &cancel)
+                };
+                daemon.unregister(&req.id);
+                write_response(&output, &respond(req.id, outcome));
+            })
+        })
+        .collect();
+
+    for line in input.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let req: DaemonRequest = match serde_json::from_str(line) {
+            Ok(req) => req,
+            Err(e) => {
+                write_response(&output, &respond(Value::Null, Err(format!("Parse error: {}", e))));
+                continue;
+            }
+        };
+        if req.action == "cancel" {
+            write_response(&output, &respond(req.id.clone(), daemon.cancel(&req)));
+            continue;
+        }
+
+        // Registered before it is queued so a cancel on the next line always
+        // finds it.
+        match daemon.register(&req.id) {
+            Ok(cancel) => {
+                let _ = jobs.send((req, cancel));
+            }
+            Err(e) => write_response(&output, &respond(req.id, Err(e))),
+        }
+    }
+
+    drop(jobs);
+    for worker in workers {
+        let _ = worker.join();
+    }
+    let output = Arc::into_inner(output).expect("all workers have finished");
+    output.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn workspace(name: &str) -> PathBuf {
+        let tmp = std::env::temp_dir().join(format!("cart_daemon_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("a.ts"), "import { helper } from './b';\nexport function run() { helper(); }\n").unwrap();
+        fs::write(tmp.join("b.ts"), "export function helper() {}\n").unwrap();
+        tmp
+    }
+
+    fn request(action: &str, root: &Path) -> DaemonRequest {
+        DaemonRequest {
+            action: action.to_string(),
+            root: Some(root.to_string_lossy().to_string()),
+            cache: Some(false),
+            ..DaemonRequest::default()
+        }
+    }
+
+    #[test]
+    fn test_queries_share_one_scan_until_invalidated() {
+        let root = workspace("queries");
+        let daemon = Daemon::new();
+        let never = AtomicBool::new(false);
+
+        let symbols = daemon
+            .handle(&DaemonRequest { path: Some("b.ts".to_string()), ..request("symbols_in_file", &root) }, &never)
+            .unwrap();
+        assert!(symbols["symbols"].as_array().unwrap().iter().any(|s| s["name"] == "helper"));
+
+        let found = daemon
+            .handle(&DaemonRequest { name: Some("HELP".to_string()), kind: Some("function".to_string()), ..request("find_symbol", &root) }, &never)
+            .unwrap();
+        assert_eq!(found.as_array().unwrap().len(), 1);
+        assert!(found[0]["path"].as_str().unwrap().ends_with("b.ts"));
+
+        // Held scan: a new file is invisible until the workspace is invalidated.
+        fs::write(root.join("c.ts"), "export function helperTwo() {}\n").unwrap();
+        let find = DaemonRequest { name: Some("helper".to_string()), ..request("find_symbol", &root) };
+        assert_eq!(daemon.handle(&find, &never).unwrap().as_array().unwrap().len(), 1);
+        let invalidated = daemon.handle(&request("invalidate", &root), &never).unwrap();
+        assert_eq!(invalidated["invalidated"], 1);
+        assert_eq!(daemon.handle(&find, &never).unwrap().as_array().unwrap().len(), 2);
+
+        let exact = DaemonRequest { exact: Some(true), ..find };
+        assert_eq!(daemon.handle(&exact, &never).unwrap().as_array().unwrap().len(), 1);
+
+        let deps = daemon
+            .handle(&DaemonRequest { target: Some("b.ts".to_string()), scan_mode: Some("file_context".to_string()), ..request("deps", &root) }, &never)
+            .unwrap();
+        assert!(deps["dependents"][0].as_str().unwrap().ends_with("a.ts"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_walk_options_get_their_own_workspace() {
+        let root = workspace("options");
+        let daemon = Daemon::new();
+        let never = AtomicBool::new(false);
+        let files = |req: &DaemonRequest| daemon.handle(req, &never).unwrap()["files"].as_array().unwrap().len();
+
+        let filtered = DaemonRequest { exclude_globs: Some(vec!["b.ts".to_string()]), ..request("scan", &root) };
+        assert_eq!(files(&filtered), 1);
+        assert_eq!(files(&request("scan", &root)), 2);
+        assert_eq!(files(&filtered), 1);
+        assert_eq!(lock(&daemon.workspaces).len(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_cancelled_scan_is_not_kept() {
+        let root = workspace("cancel");
+        let daemon = Daemon::new();
+        let cancelled = AtomicBool::new(true);
+        let err = daemon.handle(&request("scan", &root), &cancelled).unwrap_err();
+        assert_eq!(err, "Cancelled");
+
+        let unknown = DaemonRequest { cancel_id: Some(Value::from(42)), ..DaemonRequest::default() };
+        assert_eq!(daemon.cancel(&unknown).unwrap()["cancelled"], false);
+        let flag = daemon.register(&Value::from(42)).unwrap();
+        assert_eq!(daemon.cancel(&unknown).unwrap()["cancelled"], true);
+        assert!(flag.load(Ordering::Relaxed));
+
+        // Ids are unique while in flight; null ids are never tracked.
+        assert_eq!(daemon.register(&Value::from(42)).unwrap_err(), "Duplicate request id: 42");
+        daemon.unregister(&Value::from(42));
+        assert!(daemon.register(&Value::from(42)).is_ok());
+        assert!(daemon.register(&Value::Null).is_ok());
+        assert!(daemon.register(&Value::Null).is_ok());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_serve_answers_each_line_by_id_and_drains_on_eof() {
+        let root = workspace("serve");
+        let root_str = root.to_string_lossy();
+        let input = format!(
+            "{}\nnot json\n{}\n{}\n",
+            serde_json::json!({"id": 1, "action": "scan", "root": root_str, "cache": false}),
+            serde_json::json!({"id": "two", "action": "frobnicate"}),
+            serde_json::json!({"id": 3, "action": "cancel", "cancel_id": 99}),
+        );
+        let output = serve(std::io::Cursor::new(input), Vec::new());
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 4);
+        let by_id = |id: Value| responses.iter().find(|r| r["id"] == id).unwrap();
+        assert_eq!(by_id(Value::from(1))["ok"], true);
+        assert_eq!(by_id(Value::from(1))["result"]["files"].as_array().unwrap().len(), 2);
+        assert_eq!(by_id(Value::from("two"))["error"], "Unknown action: frobnicate");
+        assert_eq!(by_id(Value::from(3))["result"]["cancelled"], false);
+        assert!(by_id(Value::Null)["error"].as_str().unwrap().starts_with("Parse error"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
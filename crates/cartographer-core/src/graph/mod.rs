@@ -0,0 +1,196 @@
+//! File-level dependency graph over a scan's resolved imports.
+
+pub mod resolve;
+
+use crate::output::{DependencyEdge, DependencyGraph, DepsResult, FileResult, ScanResult};
+use resolve::FileIndex;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::Path;
+
+/// Fill in `ImportRecord::resolved` for every import in `files`.
+pub fn resolve_imports(root: &Path, files: &mut [FileResult]) {
+    let index = FileIndex::new(files.iter().map(|f| f.path.as_str()));
+    for file in files.iter_mut() {
+        for import in file.imports.iter_mut() {
+            import.resolved = resolve::resolve(&file.path, &file.language, import, &index, root);
+        }
+    }
+}
+
+/// Build the graph from already-resolved imports.
+pub fn build(files: &[FileResult]) -> DependencyGraph {
+    let edges: BTreeSet<DependencyEdge> = files
+        .iter()
+        .flat_map(|file| {
+            file.imports.iter().flat_map(move |import| {
+                import.resolved.iter().map(move |to| DependencyEdge {
+                    from: file.path.clone(),
+                    to: to.clone(),
+                })
+            })
+        })
+        .collect();
+    let edges: Vec<DependencyEdge> = edges.into_iter().collect();
+    let cycles = find_cycles(&edges);
+    DependencyGraph { edges, cycles }
+}
+
+/// Strongly connected components with more than one file (Tarjan, iterative
+/// so deep import chains cannot overflow the stack).
+fn find_cycles(edges: &[DependencyEdge]) -> Vec<Vec<String>> {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut names: Vec<&str> = Vec::new();
+    for edge in edges {
+        for node in [edge.from.as_str(), edge.to.as_str()] {
+            ids.entry(node).or_insert_with(|| {
+                names.push(node);
+                names.len() - 1
+            });
+        }
+    }
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+    for edge in edges {
+        adjacency[ids[edge.from.as_str()]].push(ids[edge.to.as_str()]);
+    }
+
+    let n = names.len();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut cycles = Vec::new();
+
+    for start in 0..n {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        // (node, next neighbour position)
+        let mut work = vec![(start, 0)];
+        while let Some(&mut (node, ref mut pos)) = work.last_mut() {
+            if *pos == 0 && index[node] == usize::MAX {
+                index[node] = next_index;
+                lowlink[node] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+            if let Some(&next) = adjacency[node].get(*pos) {
+                *pos += 1;
+                if index[next] == usize::MAX {
+                    work.push((next, 0));
+                } else if on_stack[next] {
+                    lowlink[node] = lowlink[node].min(index[next]);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if lowlink[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(names[member].to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    cycles.push(component);
+                }
+            }
+        }
+    }
+    cycles.sort();
+    cycles
+}
+
+impl DependencyGraph {
+    /// Files `file` imports.
+    pub fn dependencies_of(&self, file: &str) -> Vec<String> {
+        self.edges.iter().filter(|e| e.from == file).map(|e| e.to.clone()).collect()
+    }
+
+    /// Files importing `file` directly.
+    pub fn dependents_of(&self, file: &str) -> Vec<String> {
+        let dependents: BTreeSet<String> = self.edges.iter().filter(|e| e.to == file).map(|e| e.from.clone()).collect();
+        dependents.into_iter().collect()
+    }
+
+    /// Files that reach `file` through any chain of imports, excluding `file`.
+    pub fn transitive_dependents_of(&self, file: &str) -> Vec<String> {
+        let mut reverse: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            reverse.entry(edge.to.as_str()).or_default().push(edge.from.as_str());
+        }
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([file]);
+        while let Some(current) = queue.pop_front() {
+            for &importer in reverse.get(current).into_iter().flatten() {
+                if importer != file && seen.insert(importer.to_string()) {
+                    queue.push_back(importer);
+                }
+            }
+        }
+        seen.into_iter().collect()
+    }
+}
+
+/// The scanned file for `target`, absolute or relative to the scan root.
+pub fn locate<'a>(result: &'a ScanResult, target: &str) -> Option<&'a FileResult> {
+    let target_path = Path::new(target);
+    let wanted = if target_path.is_absolute() {
+        resolve::normalize(target_path)
+    } else {
+        resolve::normalize(&Path::new(&result.root).join(target_path))
+    };
+    result.files.iter().find(|f| resolve::normalize(Path::new(&f.path)) == wanted)
+}
+
+/// Answer a reverse-dependency query for `target` (absolute, or relative to
+/// the scan root) against a finished scan.
+pub fn deps(result: &ScanResult, target: &str, transitive: bool) -> Result<DepsResult, String> {
+    let Some(file) = locate(result, target).map(|f| &f.path) else {
+        return Err(format!("Target not found in scan: {}", target));
+    };
+
+    let graph = &result.dependency_graph;
+    Ok(DepsResult {
+        root: result.root.clone(),
+        target: file.clone(),
+        dependencies: graph.dependencies_of(file),
+        dependents: graph.dependents_of(file),
+        transitive_dependents: transitive.then(|| graph.transitive_dependents_of(file)),
+        cycles: graph.cycles.iter().filter(|c| c.contains(file)).cloned().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: &str, to: &str) -> DependencyEdge {
+        DependencyEdge { from: from.to_string(), to: to.to_string() }
+    }
+
+    #[test]
+    fn test_cycles_are_strongly_connected_components() {
+        let edges = vec![edge("a", "b"), edge("b", "c"), edge("c", "a"), edge("c", "d"), edge("d", "e"), edge("e", "d")];
+        assert_eq!(
+            find_cycles(&edges),
+            vec![vec!["a".to_string(), "b".to_string(), "c".to_string()], vec!["d".to_string(), "e".to_string()]]
+        );
+        assert!(find_cycles(&[edge("a", "b"), edge("b", "c")]).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_dependencies() {
+        let graph = DependencyGraph { edges: vec![edge("a", "c"), edge("b", "c"), edge("d", "a"), edge("c", "b")], cycles: vec![] };
+        assert_eq!(graph.dependents_of("c"), vec!["a", "b"]);
+        assert_eq!(graph.dependencies_of("d"), vec!["a"]);
+        assert_eq!(graph.transitive_dependents_of("c"), vec!["a", "b", "d"]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Mapping import specifiers onto scanned workspace files.
+//!
+//! Resolution is purely lexical against the set of scanned files: nothing is
+//! read from disk, and an import only resolves if its target was scanned too.
+
+use crate::output::ImportRecord;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+const TS_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".d.ts", ".js", ".jsx", ".mjs", ".cjs"];
+
+/// Scanned files keyed by their lexically normalised path.
+pub struct FileIndex {
+    by_path: HashMap<PathBuf, String>,
+}
+
+impl FileIndex {
+    pub fn new<'a>(paths: impl IntoIterator<Item = &'a str>) -> Self {
+        let by_path = paths
+            .into_iter()
+            .map(|p| (normalize(Path::new(p)), p.to_string()))
+            .collect();
+        FileIndex { by_path }
+    }
+
+    /// The scanned path for `path`, if it was scanned.
+    pub fn get(&self, path: &Path) -> Option<&String> {
+        self.by_path.get(&normalize(path))
+    }
+
+    fn first(&self, candidates: impl IntoIterator<Item = PathBuf>) -> Option<String> {
+        candidates.into_iter().find_map(|c| self.get(&c).cloned())
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Workspace files `import` (found in `file`) refers to.
+pub fn resolve(file: &str, language: &str, import: &ImportRecord, index: &FileIndex, root: &Path) -> Vec<String> {
+    let file = Path::new(file);
+    let resolved = match language {
+        "typescript" | "javascript" => resolve_ts(file, &import.module, index).into_iter().collect(),
+        "rust" => resolve_rust(file, import, index).into_iter().collect(),
+        "python" => resolve_python(file, import, index, root),
+        _ => vec![],
+    };
+    let own = index.get(file);
+    resolved.into_iter().filter(|r| Some(r) != own).collect()
+}
+
+// ─── TypeScript / JavaScript ─────────────────────────────────────────────────
+
+fn resolve_ts(file: &Path, spec: &str, index: &FileIndex) -> Option<String> {
+    // Bare specifiers are packages.
+    if !spec.starts_with('.') {
+        return None;
+    }
+    let dir = file.parent()?;
+    let base = normalize(&dir.join(spec));
+    let base_str = base.to_string_lossy().to_string();
+
+    let mut candidates = vec![base.clone()];
+    candidates.extend(TS_EXTENSIONS.iter().map(|ext| PathBuf::from(format!("{base_str}{ext}"))));
+    // ESM-style TypeScript imports name the emitted `.js` file.
+    for js_ext in [".js", ".jsx", ".mjs", ".cjs"] {
+        if let Some(stem) = base_str.strip_suffix(js_ext) {
+            candidates.extend([".ts", ".tsx", ".mts", ".cts"].iter().map(|ext| PathBuf::from(format!("{stem}{ext}"))));
+        }
+    }
+    candidates.extend(TS_EXTENSIONS.iter().map(|ext| base.join(format!("index{ext}"))));
+    index.first(candidates)
+}
+
+// ─── Rust ────────────────────────────────────────────────────────────────────
+
+fn is_module_root(file: &Path) -> bool {
+    matches!(
+        file.file_name().and_then(|n| n.to_str()),
+        Some("mod.rs") | Some("lib.rs") | Some("main.rs")
+    )
+}
+
+/// Directory holding the child modules of the module defined by `file`.
+fn child_module_dir(file: &Path) -> Option<PathBuf> {
+    let parent = file.parent()?;
+    if is_module_root(file) {
+        Some(parent.to_path_buf())
+    } else {
+        Some(parent.join(file.file_stem()?))
+    }
+}
+
+/// The nearest ancestor directory holding a scanned `lib.rs` or `main.rs`.
+fn crate_root_dir(file: &Path, index: &FileIndex) -> Option<PathBuf> {
+    file.ancestors()
+        .skip(1)
+        .find(|dir| index.get(&dir.join("lib.rs")).is_some() || index.get(&dir.join("main.rs")).is_some())
+        .map(Path::to_path_buf)
+}
+
+fn module_file_candidates(dir: &Path, name: &str) -> [PathBuf; 2] {
+    [dir.join(format!("{name}.rs")), dir.join(name).join("mod.rs")]
+}
+
+/// The file defining the module whose children live in `dir`.
+fn module_file_for_dir(dir: &Path, index: &FileIndex) -> Option<String> {
+    let mut candidates = vec![dir.join("mod.rs"), dir.join("lib.rs"), dir.join("main.rs")];
+    if let (Some(parent), Some(name)) = (dir.parent(), dir.file_name()) {
+        candidates.push(parent.join(format!("{}.rs", name.to_string_lossy())));
+    }
+    index.first(candidates)
+}
+
+fn resolve_rust(file: &Path, import: &ImportRecord, index: &FileIndex) -> Option<String> {
+    let module_dir = child_module_dir(file)?;
+    if import.kind == "mod" {
+        let mut segments: Vec<&str> = import.module.split("::").collect();
+        let name = segments.pop()?;
+        let dir = segments.iter().fold(module_dir, |dir, s| dir.join(s));
+        return index.first(module_file_candidates(&dir, name));
+    }
+
+    let segments: Vec<&str> = import.module.split("::").filter(|s| !s.is_empty()).collect();
+    let (mut dir, rest) = match segments.first().copied()? {
+        "crate" => (crate_root_dir(file, index)?, &segments[1..]),
+        "self" => (module_dir, &segments[1..]),
+        "super" => {
+            let supers = segments.iter().take_while(|s| **s == "super").count();
+            let mut dir = module_dir;
+            for _ in 0..supers {
+                dir = dir.parent()?.to_path_buf();
+            }
+            (dir, &segments[supers..])
+        }
+        // A path starting with a child module of this file; anything else
+        // is an external crate.
+        first => {
+            index.first(module_file_candidates(&module_dir, first))?;
+            (module_dir, &segments[..])
+        }
+    };
+
+    // The deepest prefix that names a module file; the rest are items.
+    let mut found = None;
+    for segment in rest {
+        match index.first(module_file_candidates(&dir, segment)) {
+            Some(path) => found = Some(path),
+            None => break,
+        }
+        dir = dir.join(segment);
+    }
+    found.or_else(|| {
+        let base = match segments.first().copied() {
+            Some("crate") => crate_root_dir(file, index)?,
+            _ => dir,
+        };
+        module_file_for_dir(&base, index)
+    })
+}
+
+// ─── Python ──────────────────────────────────────────────────────────────────
+
+fn python_module(base: &Path, dotted: &str, index: &FileIndex) -> Option<String> {
+    if dotted.is_empty() {
+        return index.get(&base.join("__init__.py")).cloned();
+    }
+    let relative: PathBuf = dotted.split('.').collect();
+    let module = base.join(&relative);
+    let module_str = module.to_string_lossy().to_string();
+    index.first([PathBuf::from(format!("{module_str}.py")), module.join("__init__.py")])
+}
+
+fn resolve_python(file: &Path, import: &ImportRecord, index: &FileIndex, root: &Path) -> Vec<String> {
+    let Some(dir) = file.parent() else {
+        return vec![];
+    };
+    let dots = import.module.chars().take_while(|c| *c == '.').count();
+    let dotted = &import.module[dots..];
+
+    let bases: Vec<PathBuf> = if dots > 0 {
+        let mut base = dir.to_path_buf();
+        for _ in 1..dots {
+            match base.parent() {
+                Some(parent) => base = parent.to_path_buf(),
+                None => return vec![],
+            }
+        }
+        vec![base]
+    } else {
+        // Absolute imports: the importing file's directory and each parent up
+        // to the scan root stand in for `sys.path`.
+        let root = normalize(root);
+        let mut bases = Vec::new();
+        for ancestor in dir.ancestors() {
+            bases.push(ancestor.to_path_buf());
+            if normalize(ancestor) == root {
+                break;
+            }
+        }
+        bases
+    };
+
+    for base in &bases {
+        // `from pkg import sub` may name submodules rather than attributes.
+        let submodules: Vec<String> = import
+            .names
+            .iter()
+            .filter_map(|name| {
+                let joined = if dotted.is_empty() { name.clone() } else { format!("{dotted}.{name}") };
+                python_module(base, &joined, index)
+            })
+            .collect();
+        if !submodules.is_empty() {
+            return submodules;
+        }
+        let parts: Vec<&str> = dotted.split('.').collect();
+        for len in (0..=parts.len()).rev() {
+            if len == 0 && dots == 0 {
+                break;
+            }
+            if let Some(module) = python_module(base, &parts[..len].join("."), index) {
+                return vec![module];
+            }
+        }
+    }
+    vec![]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(module: &str, kind: &str) -> ImportRecord {
+        ImportRecord { module: module.to_string(), kind: kind.to_string(), line: 0, names: vec![], resolved: vec![] }
+    }
+
+    #[test]
+    fn test_normalize_is_lexical() {
+        assert_eq!(normalize(Path::new("/w/src/./a/../b.ts")), PathBuf::from("/w/src/b.ts"));
+        assert_eq!(normalize(Path::new("../x")), PathBuf::from("../x"));
+    }
+
+    #[test]
+    fn test_resolve_ts_relative_specifiers() {
+        let index = FileIndex::new(["/w/src/a.ts", "/w/src/util/index.ts", "/w/src/c.ts", "/w/lib/d.js"]);
+        let resolve = |spec: &str| resolve("/w/src/main.ts", "typescript", &import(spec, "import"), &index, Path::new("/w"));
+        assert_eq!(resolve("./a"), vec!["/w/src/a.ts"]);
+        assert_eq!(resolve("./util"), vec!["/w/src/util/index.ts"]);
+        assert_eq!(resolve("./c.js"), vec!["/w/src/c.ts"]);
+        assert_eq!(resolve("../lib/d.js"), vec!["/w/lib/d.js"]);
+        assert!(resolve("react").is_empty());
+        assert!(resolve("./missing").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_rust_mod_and_use_paths() {
+        let index = FileIndex::new([
+            "/w/src/lib.rs",
+            "/w/src/scanner/mod.rs",
+            "/w/src/scanner/lang.rs",
+            "/w/src/output.rs",
+        ]);
+        let root = Path::new("/w");
+        let from_lib = |i: ImportRecord| resolve("/w/src/lib.rs", "rust", &i, &index, root);
+        assert_eq!(from_lib(import("scanner", "mod")), vec!["/w/src/scanner/mod.rs"]);
+        assert_eq!(from_lib(import("scanner::lang", "mod")), vec!["/w/src/scanner/lang.rs"]);
+        assert_eq!(from_lib(import("output::Thing", "use")), vec!["/w/src/output.rs"]);
+        assert!(from_lib(import("serde::Serialize", "use")).is_empty());
+
+        let from_lang = |i: ImportRecord| resolve("/w/src/scanner/lang.rs", "rust", &i, &index, root);
+        assert_eq!(from_lang(import("crate::output::Thing", "use")), vec!["/w/src/output.rs"]);
+        assert_eq!(from_lang(import("crate::scanner::lang::Language", "use")), Vec::<String>::new());
+        assert_eq!(from_lang(import("super::Walker", "use")), vec!["/w/src/scanner/mod.rs"]);
+        assert_eq!(from_lang(import("crate::Root", "use")), vec!["/w/src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_resolve_python_relative_and_absolute() {
+        let index = FileIndex::new([
+            "/w/app/__init__.py",
+            "/w/app/models.py",
+            "/w/app/views/__init__.py",
+            "/w/app/views/home.py",
+            "/w/app/views/sibling.py",
+        ]);
+        let root = Path::new("/w");
+        let from_home = |i: ImportRecord| resolve("/w/app/views/home.py", "python", &i, &index, root);
+        assert_eq!(from_home(import("..models", "from")), vec!["/w/app/models.py"]);
+        assert_eq!(from_home(import("app.models", "import")), vec!["/w/app/models.py"]);
+        assert!(from_home(import("os", "import")).is_empty());
+
+        let mut sibling = import(".", "from");
+        sibling.names = vec!["sibling".to_string(), "helper".to_string()];
+        assert_eq!(from_home(sibling), vec!["/w/app/views/sibling.py"]);
+
+        let mut attribute = import(".", "from");
+        attribute.names = vec!["helper".to_string()];
+        assert_eq!(from_home(attribute), vec!["/w/app/views/__init__.py"]);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod cache;
+pub mod daemon;
+pub mod graph;
 pub mod output;
 pub mod parser;
 pub mod scanner;
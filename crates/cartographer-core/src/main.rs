@@ -1,9 +1,13 @@
-use cartographer_core::output::ScanResponse;
-use cartographer_core::scanner::engine::scan;
+use cartographer_core::cache::{default_cache_path, ScanCache};
+use cartographer_core::daemon;
+use cartographer_core::graph::deps;
+use cartographer_core::output::{DepsResponse, ScanResponse};
+use cartographer_core::scanner::engine::scan_with_cache;
 use cartographer_core::scanner::lang::ScanMode;
+use cartographer_core::scanner::walker::{WalkOptions, DEFAULT_MAX_FILE_BYTES};
 use serde::Deserialize;
 use std::io::{self, BufRead};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 #[derive(Deserialize)]
@@ -14,11 +18,53 @@ struct ScanRequest {
     pub max_files: Option<usize>,
     pub max_seconds: Option<f64>,
     pub include_extensions: Option<Vec<String>>,
+    /// Directory or file names excluded at any depth; kept for older callers,
+    /// equivalent to `exclude_globs`.
     pub exclude_paths: Option<Vec<String>>,
+    /// Gitignore-style patterns relative to `root`; when given, only matching
+    /// files are scanned.
+    pub include_globs: Option<Vec<String>>,
+    /// Gitignore-style patterns relative to `root`, excluded even when no
+    /// ignore file mentions them.
+    pub exclude_globs: Option<Vec<String>>,
+    /// Skip files larger than this (default 1 MiB, `0` for no limit).
+    pub max_file_bytes: Option<u64>,
+    /// Honour `.gitignore`, `.ignore` and `.cartographerignore` (default `true`).
+    pub use_ignore_files: Option<bool>,
+    /// `deps` only: the file to report dependents for.
+    pub target: Option<String>,
+    /// `deps` only: also report transitive dependents.
+    pub transitive: Option<bool>,
+    /// Reuse and update the on-disk scan cache (default `true`).
+    pub cache: Option<bool>,
+    /// Cache file to use instead of the per-root default.
+    pub cache_path: Option<String>,
+}
+
+impl ScanRequest {
+    fn walk_options(&self) -> WalkOptions {
+        let exclude_globs: Vec<String> = self.exclude_paths.iter().chain(&self.exclude_globs).flatten().cloned().collect();
+        WalkOptions {
+            include_extensions: self.include_extensions.clone(),
+            include_globs: self.include_globs.clone(),
+            exclude_globs: Some(exclude_globs),
+            max_file_bytes: match self.max_file_bytes {
+                Some(0) => None,
+                Some(limit) => Some(limit),
+                None => Some(DEFAULT_MAX_FILE_BYTES),
+            },
+            use_ignore_files: self.use_ignore_files.unwrap_or(true),
+        }
+    }
 }
 
 fn main() {
     let stdin = io::stdin();
+    if std::env::args().skip(1).any(|arg| arg == "--serve") {
+        daemon::serve(stdin.lock(), io::stdout());
+        return;
+    }
+
     let mut line = String::new();
     stdin.lock().read_line(&mut line).expect("Failed to read stdin");
 
@@ -38,7 +84,7 @@ fn main() {
         }
     };
 
-    if req.action != "scan" {
+    if req.action != "scan" && req.action != "deps" {
         let resp = ScanResponse { ok: false, result: None, error: Some(format!("Unknown action: {}", req.action)) };
         println!("{}", serde_json::to_string(&resp).unwrap());
         return;
@@ -59,7 +105,42 @@ fn main() {
         return;
     }
 
-    let result = scan(root, mode, max_files, max_seconds, req.include_extensions, req.exclude_paths);
+    if req.action == "deps" && req.target.as_deref().is_none_or(|t| t.trim().is_empty()) {
+        let resp = DepsResponse { ok: false, result: None, error: Some("deps requires a target".to_string()) };
+        println!("{}", serde_json::to_string(&resp).unwrap());
+        return;
+    }
+
+    let walk_options = req.walk_options();
+    let cache_path = if req.cache.unwrap_or(true) {
+        req.cache_path
+            .as_deref()
+            .map(PathBuf::from)
+            .or_else(|| default_cache_path(root, mode.as_str(), &walk_options))
+    } else {
+        None
+    };
+    let mut cache = cache_path
+        .as_deref()
+        .map(|path| ScanCache::load(path, &root.to_string_lossy(), mode.as_str(), &walk_options));
+
+    let mut result = scan_with_cache(root, mode, max_files, max_seconds, &walk_options, cache.as_mut(), None);
+
+    if let (Some(path), Some(cache)) = (&cache_path, &cache) {
+        if let Err(e) = cache.save(path) {
+            result.diagnostics.errors.push(e);
+        }
+    }
+
+    if req.action == "deps" {
+        let target = req.target.unwrap_or_default();
+        let resp = match deps(&result, target.trim(), req.transitive.unwrap_or(false)) {
+            Ok(deps) => DepsResponse { ok: true, result: Some(deps), error: None },
+            Err(e) => DepsResponse { ok: false, result: None, error: Some(e) },
+        };
+        println!("{}", serde_json::to_string(&resp).unwrap());
+        return;
+    }
 
     let resp = ScanResponse {
         ok: true,
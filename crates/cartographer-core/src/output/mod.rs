@@ -22,12 +22,49 @@ pub struct SymbolRecord {
     pub body_fragment: Option<String>,
 }
 
+/// An import or module reference found in a source file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportRecord {
+    /// The specifier as written: `crate::scanner::lang`, `./util`, `..pkg.mod`.
+    pub module: String,
+    /// `use`, `mod`, `import`, `require`, `export_from` or `from`.
+    pub kind: String,
+    pub line: u32,
+    /// Names imported from `module` (Python `from x import a, b`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+    /// Workspace files the import resolves to; empty for external packages
+    /// and anything that could not be resolved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileResult {
     pub path: String,
     pub language: String,
     pub size_bytes: u64,
     pub symbols: Vec<SymbolRecord>,
+    #[serde(default)]
+    pub imports: Vec<ImportRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DependencyEdge {
+    /// The importing file.
+    pub from: String,
+    /// The imported file.
+    pub to: String,
+}
+
+/// File-level dependency graph built from resolved imports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    /// Sorted and de-duplicated.
+    pub edges: Vec<DependencyEdge>,
+    /// Each import cycle as the sorted files of one strongly connected
+    /// component.
+    pub cycles: Vec<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +74,29 @@ pub struct DiagnosticsBlock {
     pub symbol_count: usize,
     pub budget_hit: bool,
     pub errors: Vec<String>,
+    /// Files and directories left out by exclude globs, ignore files and the
+    /// built-in exclusions; an ignored directory counts once.
+    #[serde(default)]
+    pub skipped_ignored: usize,
+    /// Files over the `max_file_bytes` cutoff.
+    #[serde(default)]
+    pub skipped_too_large: usize,
+    /// Files with a source extension but binary content.
+    #[serde(default)]
+    pub skipped_binary: usize,
+}
+
+/// How this scan differs from the previous cached scan of the same root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanChanges {
+    /// Whether a previous scan was available to compare against.  Without
+    /// one every file is reported as added.
+    pub baseline: bool,
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    /// Files served from the cache without re-parsing.
+    pub reused: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,9 +104,31 @@ pub struct ScanResult {
     pub root: String,
     pub scan_mode: String,
     pub files: Vec<FileResult>,
+    #[serde(default)]
+    pub dependency_graph: DependencyGraph,
+    /// Present when the scan ran against a cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<ScanChanges>,
     pub diagnostics: DiagnosticsBlock,
 }
 
+/// Answer to an `action: "deps"` request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepsResult {
+    pub root: String,
+    /// The queried file, as it appears in the scan.
+    pub target: String,
+    /// Files the target imports.
+    pub dependencies: Vec<String>,
+    /// Files that import the target directly.
+    pub dependents: Vec<String>,
+    /// Files that reach the target through any chain of imports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitive_dependents: Option<Vec<String>>,
+    /// Cycles the target takes part in.
+    pub cycles: Vec<Vec<String>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResponse {
     pub ok: bool,
@@ -56,6 +138,33 @@ pub struct ScanResponse {
     pub error: Option<String>,
 }
 
+/// A `find_symbol` hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolMatch {
+    pub path: String,
+    pub symbol: SymbolRecord,
+}
+
+/// One NDJSON response line in `--serve` mode.  `id` echoes the request's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    pub id: serde_json::Value,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepsResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<DepsResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,12 +177,17 @@ mod tests {
                 root: "/tmp".to_string(),
                 scan_mode: "summary".to_string(),
                 files: vec![],
+                dependency_graph: DependencyGraph::default(),
+                changes: None,
                 diagnostics: DiagnosticsBlock {
                     elapsed_seconds: 0.1,
                     file_count: 0,
                     symbol_count: 0,
                     budget_hit: false,
                     errors: vec![],
+                    skipped_ignored: 0,
+                    skipped_too_large: 0,
+                    skipped_binary: 0,
                 },
             }),
             error: None,
@@ -0,0 +1,442 @@
+//! Line scanner for brace-delimited languages without a bundled grammar.
+//!
+//! [`clean`] blanks comments and string literals so braces and keywords can
+//! be matched safely; [`scan`] walks the cleaned lines tracking brace depth
+//! and asks a per-language classifier about each line.  Declarations that
+//! open a `{` body (on the same line or a following one) get their end line
+//! from the matching `}`, and members are qualified by their enclosing
+//! containers, e.g. `Outer.Inner.method`.
+
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::lang::ScanMode;
+
+/// Lines a declaration may wait for its opening `{` before it is treated as
+/// bodiless.
+const PENDING_LINES: usize = 8;
+
+pub struct Syntax {
+    pub line_comment: &'static str,
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// Quotes of string and character literals that end with the line.
+    pub quotes: &'static [char],
+    pub escape: char,
+    /// `(open, close)` delimiters of literals that may span lines; checked
+    /// before `quotes`.
+    pub multiline: &'static [(&'static str, &'static str)],
+    /// `(open, verbatim)` prefixes of interpolated strings whose `{ ... }`
+    /// holes hold code, e.g. C#'s `$"`; checked before `multiline`.
+    /// Verbatim ones span lines and escape their quote by doubling it.
+    pub interpolated: &'static [(&'static str, bool)],
+}
+
+enum State {
+    Code,
+    Quote(char),
+    Until(&'static str),
+    Interpolated(Vec<Frame>),
+}
+
+/// Nesting inside an interpolated string: the strings themselves and the
+/// code holes between their `{` and `}`.
+enum Frame {
+    Str { quote: char, verbatim: bool, interpolated: bool },
+    Hole { depth: usize },
+}
+
+/// `source` line by line with comments removed and literal contents
+/// replaced by spaces.  Single-line string quotes are kept.
+pub fn clean(source: &str, syntax: &Syntax) -> Vec<String> {
+    let mut state = State::Code;
+    let mut out = Vec::new();
+    for line in source.lines() {
+        let mut cleaned = String::with_capacity(line.len());
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap_or(' ');
+            if let State::Interpolated(frames) = &mut state {
+                let n = skip_interpolated(frames, rest, syntax);
+                i += n;
+                if frames.is_empty() {
+                    cleaned.push(c);
+                    state = State::Code;
+                } else {
+                    pad(&mut cleaned, n);
+                }
+                continue;
+            }
+            match state {
+                State::Until(close) => match rest.find(close) {
+                    Some(pos) => {
+                        pad(&mut cleaned, pos + close.len());
+                        i += pos + close.len();
+                        state = State::Code;
+                    }
+                    None => {
+                        pad(&mut cleaned, rest.len());
+                        i = line.len();
+                    }
+                },
+                State::Quote(quote) => {
+                    if c == syntax.escape {
+                        let escaped = rest[c.len_utf8()..].chars().next().map_or(0, char::len_utf8);
+                        pad(&mut cleaned, c.len_utf8() + escaped);
+                        i += c.len_utf8() + escaped;
+                    } else if c == quote {
+                        cleaned.push(quote);
+                        i += c.len_utf8();
+                        state = State::Code;
+                    } else {
+                        cleaned.push(' ');
+                        i += c.len_utf8();
+                    }
+                }
+                State::Code => {
+                    if rest.starts_with(syntax.line_comment) {
+                        break;
+                    }
+                    if let Some(&(open, verbatim)) = syntax.interpolated.iter().find(|(open, _)| rest.starts_with(open)) {
+                        let quote = open.chars().last().unwrap_or('"');
+                        cleaned.push_str(open);
+                        i += open.len();
+                        state = State::Interpolated(vec![Frame::Str { quote, verbatim, interpolated: true }]);
+                        continue;
+                    }
+                    let delimited = syntax
+                        .block_comment
+                        .iter()
+                        .chain(syntax.multiline)
+                        .find(|(open, _)| rest.starts_with(open));
+                    if let Some((open, close)) = delimited {
+                        pad(&mut cleaned, open.len());
+                        i += open.len();
+                        state = State::Until(close);
+                    } else {
+                        if syntax.quotes.contains(&c) {
+                            state = State::Quote(c);
+                        }
+                        cleaned.push(c);
+                        i += c.len_utf8();
+                    }
+                }
+                State::Interpolated(_) => unreachable!(),
+            }
+        }
+        let ends_with_line = match &state {
+            State::Quote(_) => true,
+            State::Interpolated(frames) => matches!(frames.first(), Some(Frame::Str { verbatim: false, .. })),
+            _ => false,
+        };
+        if ends_with_line {
+            state = State::Code;
+        }
+        out.push(cleaned);
+    }
+    out
+}
+
+/// Steps over one token of an interpolated string at the start of `rest`,
+/// returning its length in bytes.  `{{` and `}}` are literal braces; inside
+/// a hole, nested strings are skipped and braces counted until the `}`
+/// that closes it.
+fn skip_interpolated(frames: &mut Vec<Frame>, rest: &str, syntax: &Syntax) -> usize {
+    let c = rest.chars().next().unwrap_or(' ');
+    let n = c.len_utf8();
+    match frames.last_mut() {
+        Some(&mut Frame::Str { quote, verbatim, interpolated }) => {
+            if verbatim && c == quote && rest[n..].starts_with(quote) {
+                return 2 * n;
+            }
+            if !verbatim && c == syntax.escape {
+                return n + rest[n..].chars().next().map_or(0, char::len_utf8);
+            }
+            if interpolated && (rest.starts_with("{{") || rest.starts_with("}}")) {
+                return 2;
+            }
+            if interpolated && c == '{' {
+                frames.push(Frame::Hole { depth: 0 });
+            } else if c == quote {
+                frames.pop();
+            }
+            n
+        }
+        Some(Frame::Hole { depth }) => {
+            if let Some(&(open, verbatim)) = syntax.interpolated.iter().find(|(open, _)| rest.starts_with(open)) {
+                let quote = open.chars().last().unwrap_or('"');
+                frames.push(Frame::Str { quote, verbatim, interpolated: true });
+                return open.len();
+            }
+            match c {
+                '{' => *depth += 1,
+                '}' if *depth == 0 => {
+                    frames.pop();
+                }
+                '}' => *depth -= 1,
+                _ if syntax.quotes.contains(&c) => frames.push(Frame::Str { quote: c, verbatim: false, interpolated: false }),
+                _ => {}
+            }
+            n
+        }
+        None => n,
+    }
+}
+
+fn pad(out: &mut String, n: usize) {
+    out.extend(std::iter::repeat_n(' ', n));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Body {
+    /// No body: the declaration is a single line.
+    None,
+    /// The next `{` opens the body.
+    Braces,
+    /// Everything to the end of the file, e.g. a file-scoped namespace.
+    RestOfFile,
+}
+
+#[derive(Debug, Clone)]
+pub struct Decl {
+    pub name: String,
+    pub kind: &'static str,
+    pub body: Body,
+    /// Members declared in the body are qualified by this symbol's name.
+    pub container: bool,
+    /// Left out of the result unless a later [`Action::Name`] names it.
+    pub anonymous: bool,
+    pub exported: Option<bool>,
+    pub async_fn: Option<bool>,
+    pub return_type: Option<String>,
+    /// Overrides the name derived from enclosing containers.
+    pub qualified_name: Option<String>,
+}
+
+impl Decl {
+    pub fn new(name: impl Into<String>, kind: &'static str, body: Body) -> Self {
+        Decl {
+            name: name.into(),
+            kind,
+            body,
+            container: false,
+            anonymous: false,
+            exported: None,
+            async_fn: None,
+            return_type: None,
+            qualified_name: None,
+        }
+    }
+
+    pub fn container(self) -> Self {
+        Decl { container: true, ..self }
+    }
+}
+
+pub enum Action {
+    Declare(Decl),
+    /// Name the innermost open symbol, e.g. a QML object's `id`.
+    Name(String),
+}
+
+/// A declaration whose body is open at the current line.
+#[derive(Debug, Clone)]
+pub struct Open {
+    pub name: String,
+    pub kind: &'static str,
+    pub container: bool,
+    index: usize,
+    body_depth: usize,
+}
+
+/// Where a line sits: the innermost open declaration, and whether the line
+/// is directly in its body (or at the top level when there is none) rather
+/// than in a nested block.
+pub struct Context<'a> {
+    pub parent: Option<&'a Open>,
+    pub direct: bool,
+}
+
+/// Walk `source` and collect the declarations `classify` reports.  The
+/// classifier sees each cleaned, trimmed line, except lines between a
+/// declaration and its opening `{`.
+pub fn scan<F>(source: &str, syntax: &Syntax, mode: &ScanMode, mut classify: F) -> Vec<SymbolRecord>
+where
+    F: FnMut(&str, &Context<'_>) -> Option<Action>,
+{
+    if *mode == ScanMode::Summary {
+        return vec![];
+    }
+
+    let raw_lines: Vec<&str> = source.lines().collect();
+    let cleaned = clean(source, syntax);
+    let mut symbols: Vec<SymbolRecord> = Vec::new();
+    let mut anonymous: Vec<bool> = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+    let mut pending: Option<(Open, usize)> = None;
+    let mut depth = 0usize;
+
+    for (row, line) in cleaned.iter().enumerate() {
+        let trimmed = line.trim();
+        if pending.as_ref().is_some_and(|(_, since)| row - since > PENDING_LINES) {
+            pending = None;
+        }
+        if pending.is_none() && !trimmed.is_empty() {
+            let parent = open.last();
+            let direct = parent.map_or(depth == 0, |p| depth == p.body_depth);
+            match classify(trimmed, &Context { parent, direct }) {
+                Some(Action::Declare(decl)) => {
+                    let index = symbols.len();
+                    let qualified_name = decl.qualified_name.clone().or_else(|| {
+                        let scope: Vec<&str> = open.iter().filter(|o| o.container).map(|o| o.name.as_str()).collect();
+                        (!scope.is_empty()).then(|| format!("{}.{}", scope.join("."), decl.name))
+                    });
+                    symbols.push(SymbolRecord {
+                        name: decl.name.clone(),
+                        kind: decl.kind.to_string(),
+                        line_start: row as u32,
+                        line_end: row as u32,
+                        qualified_name,
+                        exported: decl.exported,
+                        async_fn: decl.async_fn,
+                        params: None,
+                        return_type: decl.return_type.clone(),
+                        docstring: None,
+                        body_fragment: body_fragment(&raw_lines, row, mode),
+                    });
+                    anonymous.push(decl.anonymous);
+                    let opened = Open {
+                        name: decl.name,
+                        kind: decl.kind,
+                        container: decl.container,
+                        index,
+                        body_depth: depth,
+                    };
+                    match decl.body {
+                        Body::None => {}
+                        Body::Braces => pending = Some((opened, row)),
+                        Body::RestOfFile => open.push(opened),
+                    }
+                }
+                Some(Action::Name(name)) => {
+                    if let Some(top) = open.last_mut() {
+                        let symbol = &mut symbols[top.index];
+                        if let Some((scope, _)) = symbol.qualified_name.as_deref().and_then(|q| q.rsplit_once('.')) {
+                            symbol.qualified_name = Some(format!("{}.{}", scope, name));
+                        }
+                        symbol.name = name.clone();
+                        anonymous[top.index] = false;
+                        top.name = name;
+                    }
+                }
+                None => {}
+            }
+        }
+
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    if let Some((opened, _)) = pending.take() {
+                        open.push(Open { body_depth: depth, ..opened });
+                    }
+                }
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    pending = None;
+                    while open.last().is_some_and(|top| depth < top.body_depth) {
+                        if let Some(top) = open.pop() {
+                            symbols[top.index].line_end = row as u32;
+                        }
+                    }
+                }
+                ';' => pending = None,
+                _ => {}
+            }
+        }
+    }
+
+    let last_row = cleaned.len().saturating_sub(1) as u32;
+    for top in open {
+        symbols[top.index].line_end = last_row;
+    }
+    symbols
+        .into_iter()
+        .zip(anonymous)
+        .filter(|(_, anonymous)| !anonymous)
+        .map(|(symbol, _)| symbol)
+        .collect()
+}
+
+fn body_fragment(lines: &[&str], start: usize, mode: &ScanMode) -> Option<String> {
+    if *mode != ScanMode::Full || start >= lines.len() {
+        return None;
+    }
+    let end = std::cmp::min(start + 5, lines.len());
+    Some(lines[start..end].join("\n"))
+}
+
+pub fn import_at(module: impl Into<String>, kind: &str, row: usize) -> ImportRecord {
+    ImportRecord {
+        module: module.into(),
+        kind: kind.to_string(),
+        line: row as u32,
+        names: vec![],
+        resolved: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const C_LIKE: Syntax = Syntax {
+        line_comment: "//",
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        escape: '\\',
+        multiline: &[("`", "`")],
+        interpolated: &[("$\"", false)],
+    };
+
+    #[test]
+    fn test_clean_blanks_comments_and_literals() {
+        let lines = clean("a { // }\ns = \"}\\\"{\"; c = '{'\n/* {\n } */ b `{\n}` }", &C_LIKE);
+        let braces: Vec<String> = lines.iter().map(|l| l.chars().filter(|c| "{}".contains(*c)).collect()).collect();
+        assert_eq!(braces, vec!["{", "", "", "", "}"]);
+        assert!(lines[1].starts_with("s = \""));
+    }
+
+    #[test]
+    fn test_clean_skips_holes_of_interpolated_strings() {
+        let lines = clean("a { s = $\"{(x ? \"}\" : \"{\")}{{\"; }\nb { $\"{ {\n} }", &C_LIKE);
+        assert_eq!(lines[0].chars().filter(|c| "{}".contains(*c)).collect::<String>(), "{}");
+        assert_eq!(lines[1].chars().filter(|c| "{}".contains(*c)).collect::<String>(), "{");
+        assert_eq!(lines[2], "} }");
+    }
+
+    #[test]
+    fn test_scan_tracks_bodies_and_qualifies_members() {
+        let source = "type A\n{\n  fn one {\n    fn nested { }\n  }\n  fn two;\n}\nfn three {\n}\n";
+        let symbols = scan(source, &C_LIKE, &ScanMode::FileContext, |line, ctx| {
+            let (kind, rest) = line.split_once(' ')?;
+            let name = rest.trim_end_matches([' ', '{', ';']);
+            match kind {
+                "type" => Some(Action::Declare(Decl::new(name, "type", Body::Braces).container())),
+                "fn" if ctx.direct && ctx.parent.is_none_or(|p| p.container) => Some(Action::Declare(Decl::new(name, "function", Body::Braces))),
+                _ => None,
+            }
+        });
+        let found: Vec<(&str, Option<&str>, u32, u32)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.qualified_name.as_deref(), s.line_start, s.line_end))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("A", None, 0, 6),
+                ("one", Some("A.one"), 2, 4),
+                ("two", Some("A.two"), 5, 5),
+                ("three", None, 7, 8),
+            ]
+        );
+    }
+}
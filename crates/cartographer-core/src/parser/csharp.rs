@@ -0,0 +1,201 @@
+use super::braces::{clean, import_at, scan, Action, Body, Decl, Syntax};
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::lang::ScanMode;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const SYNTAX: Syntax = Syntax {
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    escape: '\\',
+    multiline: &[("\"\"\"", "\"\"\""), ("@\"", "\"")],
+    interpolated: &[("$@\"", true), ("@$\"", true), ("$\"", false)],
+};
+
+const MODIFIERS: &str = r"((?:(?:public|private|protected|internal|static|abstract|sealed|partial|readonly|virtual|override|extern|async|unsafe|new|const|volatile|required|file|ref|fixed|implicit|explicit)\s+)*)";
+const TYPE: &str = r"(?:\([^()]*\)|[\w.:]+(?:<[^;{}=]*>)?)\??(?:\[[,\s]*\])*\??";
+
+fn has_modifier(modifiers: &str, wanted: &str) -> bool {
+    modifiers.split_whitespace().any(|m| m == wanted)
+}
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\[[^\]]*\]\s*").unwrap());
+static NAMESPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^namespace\s+([\w.]+)\s*(;)?").unwrap());
+static TYPE_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}(class|struct|interface|enum|record)(?:\s+(?:class|struct))?\s+(\w+)")).unwrap());
+static DELEGATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}delegate\s+{TYPE}\s+(\w+)")).unwrap());
+static CONSTRUCTOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}(\w+)\s*\(")).unwrap());
+static METHOD: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}({TYPE})\s+(\w+)\s*(?:<[^>]*>)?\s*\(")).unwrap());
+static PROPERTY: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}({TYPE})\s+(\w+)\s*(\{{|=>|$)")).unwrap());
+static EVENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}event\s+{TYPE}\s+(\w+)")).unwrap());
+static FIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}({TYPE})\s+(\w+)\s*(?:=|;|,)")).unwrap());
+
+pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
+    scan(source, &SYNTAX, mode, |line, ctx| {
+        let mut line = line;
+        while line.starts_with('[') {
+            let end = ATTRIBUTE.find(line)?.end();
+            line = &line[end..];
+        }
+        if line.is_empty() || !ctx.direct {
+            return None;
+        }
+        let parent_kind = ctx.parent.map(|p| p.kind);
+        if ctx.parent.is_some_and(|p| !p.container) {
+            return None;
+        }
+
+        if let Some(caps) = NAMESPACE.captures(line) {
+            let body = if caps.get(2).is_some() { Body::RestOfFile } else { Body::Braces };
+            return Some(Action::Declare(Decl::new(&caps[1], "namespace", body).container()));
+        }
+        if let Some(caps) = TYPE_DECL.captures(line) {
+            let kind = match &caps[2] {
+                "struct" => "struct",
+                "interface" => "interface",
+                "enum" => "enum",
+                "record" => "record",
+                _ => "class",
+            };
+            let decl = Decl { exported: Some(has_modifier(&caps[1], "public")), ..Decl::new(&caps[3], kind, Body::Braces).container() };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = DELEGATE.captures(line) {
+            let decl = Decl { exported: Some(has_modifier(&caps[1], "public")), ..Decl::new(&caps[2], "delegate", Body::None) };
+            return Some(Action::Declare(decl));
+        }
+        let parent = ctx.parent.filter(|_| parent_kind != Some("namespace"))?;
+
+        if let Some(caps) = CONSTRUCTOR.captures(line).filter(|c| c[2] == parent.name) {
+            let decl = Decl { exported: Some(has_modifier(&caps[1], "public")), ..Decl::new(&caps[2], "constructor", Body::Braces) };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = METHOD.captures(line) {
+            let decl = Decl {
+                exported: Some(has_modifier(&caps[1], "public")),
+                async_fn: Some(has_modifier(&caps[1], "async")),
+                return_type: Some(caps[2].to_string()),
+                ..Decl::new(&caps[3], "method", Body::Braces)
+            };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = EVENT.captures(line) {
+            let decl = Decl { exported: Some(has_modifier(&caps[1], "public")), ..Decl::new(&caps[2], "event", Body::None) };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = PROPERTY.captures(line) {
+            let body = if &caps[4] == "=>" { Body::None } else { Body::Braces };
+            let decl = Decl {
+                exported: Some(has_modifier(&caps[1], "public")),
+                return_type: Some(caps[2].to_string()),
+                ..Decl::new(&caps[3], "property", body)
+            };
+            return Some(Action::Declare(decl));
+        }
+        let caps = FIELD.captures(line)?;
+        let kind = if has_modifier(&caps[1], "const") { "const" } else { "field" };
+        Some(Action::Declare(Decl { exported: Some(has_modifier(&caps[1], "public")), ..Decl::new(&caps[3], kind, Body::None) }))
+    })
+}
+
+static USING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:global\s+)?using\s+(?:static\s+)?(?:\w+\s*=\s*)?([\w.]+(?:<[^;]*>)?)\s*;").unwrap());
+
+/// `using` directives, including `global using`, `using static` and aliases.
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    clean(source, &SYNTAX)
+        .iter()
+        .enumerate()
+        .filter_map(|(row, line)| USING.captures(line.trim()).map(|caps| import_at(&caps[1], "using", row)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"using System;
+using static System.Math;
+using Json = System.Text.Json.JsonSerializer;
+
+namespace Acme.Billing
+{
+    /// <summary>Invoices. {</summary>
+    [Serializable]
+    public sealed class Invoice : IDisposable
+    {
+        public const int MaxLines = 100;
+        private readonly List<Line> _lines = new();
+        public event EventHandler? Changed;
+
+        public Invoice(string id) { Id = id; }
+
+        public string Id { get; }
+
+        public decimal Total => _lines.Sum(l => l.Amount);
+
+        public async Task<bool> SaveAsync(CancellationToken token)
+        {
+            using var scope = Begin();
+            var path = @"C:\{tmp}\";
+            return true;
+        }
+
+        public void Dispose() { }
+    }
+
+    internal record struct Line(decimal Amount);
+}
+"#;
+
+    #[test]
+    fn test_parse_csharp_declarations() {
+        let symbols = parse(SOURCE, &ScanMode::FileContext);
+        let found: Vec<(&str, &str, Option<&str>)> = symbols
+            .iter()
+            .map(|s| (s.kind.as_str(), s.name.as_str(), s.qualified_name.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("namespace", "Acme.Billing", None),
+                ("class", "Invoice", Some("Acme.Billing.Invoice")),
+                ("const", "MaxLines", Some("Acme.Billing.Invoice.MaxLines")),
+                ("field", "_lines", Some("Acme.Billing.Invoice._lines")),
+                ("event", "Changed", Some("Acme.Billing.Invoice.Changed")),
+                ("constructor", "Invoice", Some("Acme.Billing.Invoice.Invoice")),
+                ("property", "Id", Some("Acme.Billing.Invoice.Id")),
+                ("property", "Total", Some("Acme.Billing.Invoice.Total")),
+                ("method", "SaveAsync", Some("Acme.Billing.Invoice.SaveAsync")),
+                ("method", "Dispose", Some("Acme.Billing.Invoice.Dispose")),
+                ("record", "Line", Some("Acme.Billing.Line")),
+            ]
+        );
+        let save = symbols.iter().find(|s| s.name == "SaveAsync").unwrap();
+        assert_eq!((save.line_start, save.line_end, save.async_fn), (20, 25, Some(true)));
+        assert_eq!(symbols[1].line_end, 28);
+        assert_eq!(symbols.last().unwrap().exported, Some(false));
+    }
+
+    #[test]
+    fn test_file_scoped_namespace_runs_to_end_of_file() {
+        let source = "namespace Acme;\n\npublic interface IClock\n{\n    DateTime Now();\n}\n";
+        let symbols = parse(source, &ScanMode::FileContext);
+        let names: Vec<&str> = symbols.iter().filter_map(|s| s.qualified_name.as_deref()).collect();
+        assert_eq!(names, vec!["Acme.IClock", "Acme.IClock.Now"]);
+        assert_eq!(symbols[0].line_end, 5);
+    }
+
+    #[test]
+    fn test_braces_in_interpolated_strings_do_not_end_the_method() {
+        let source = "class Labels\n{\n    string Pick(bool x)\n    {\n        var s = $\"{(x ? \"}\" : \"{\")}\";\n        return s;\n    }\n\n    void Next() { }\n}\n";
+        let symbols = parse(source, &ScanMode::FileContext);
+        let found: Vec<(&str, u32, u32)> = symbols.iter().map(|s| (s.name.as_str(), s.line_start, s.line_end)).collect();
+        assert_eq!(found, vec![("Labels", 0, 9), ("Pick", 2, 6), ("Next", 8, 8)]);
+    }
+
+    #[test]
+    fn test_csharp_usings() {
+        let modules: Vec<String> = imports(SOURCE).into_iter().map(|i| i.module).collect();
+        assert_eq!(modules, vec!["System", "System.Math", "System.Text.Json.JsonSerializer"]);
+    }
+}
@@ -0,0 +1,182 @@
+use super::braces::{clean, import_at, scan, Action, Body, Decl, Syntax};
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::lang::ScanMode;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const SYNTAX: Syntax = Syntax {
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    escape: '\\',
+    multiline: &[("`", "`")],
+    interpolated: &[],
+};
+
+fn exported(name: &str) -> Option<bool> {
+    Some(name.starts_with(|c: char| c.is_uppercase()))
+}
+
+static FUNC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^func\s+(\w+)\s*[\[(]").unwrap());
+static METHOD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^func\s*\(\s*(?:\w+\s+)?\*?\s*(\w+)(?:\[[^\]]*\])?\s*\)\s*(\w+)\s*[\[(]").unwrap());
+static TYPE_SPEC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\w+)(?:\[[^\]]*\])?\s+(?:=\s*)?(struct\b|interface\b)?").unwrap());
+static VALUE_SPEC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\w+)").unwrap());
+
+pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
+    // Inside a parenthesised `type (`, `const (` or `var (` group.
+    let mut group: Option<&'static str> = None;
+
+    scan(source, &SYNTAX, mode, |line, ctx| {
+        if ctx.parent.is_some() || !ctx.direct {
+            return None;
+        }
+        if let Some(kind) = group {
+            if line.starts_with(')') {
+                group = None;
+                return None;
+            }
+            return declare_spec(kind, line);
+        }
+        for (keyword, kind) in [("type", "type"), ("const", "const"), ("var", "variable")] {
+            if let Some(rest) = line.strip_prefix(keyword).filter(|r| r.starts_with(char::is_whitespace) || r.starts_with('(')) {
+                let rest = rest.trim_start();
+                if rest.starts_with('(') {
+                    group = Some(kind);
+                    return None;
+                }
+                return declare_spec(kind, rest);
+            }
+        }
+        if let Some(caps) = METHOD.captures(line) {
+            let (receiver, name) = (&caps[1], &caps[2]);
+            let decl = Decl {
+                exported: exported(name),
+                qualified_name: Some(format!("{}.{}", receiver, name)),
+                ..Decl::new(name, "method", Body::Braces)
+            };
+            return Some(Action::Declare(decl));
+        }
+        let name = &FUNC.captures(line)?[1];
+        Some(Action::Declare(Decl { exported: exported(name), ..Decl::new(name, "function", Body::Braces) }))
+    })
+}
+
+/// One spec of a `type`, `const` or `var` declaration.
+fn declare_spec(kind: &'static str, spec: &str) -> Option<Action> {
+    let decl = if kind == "type" {
+        let caps = TYPE_SPEC.captures(spec)?;
+        match caps.get(2).map(|m| m.as_str()) {
+            Some("struct") => Decl::new(&caps[1], "struct", Body::Braces),
+            Some("interface") => Decl::new(&caps[1], "interface", Body::Braces),
+            _ => Decl::new(&caps[1], "type_alias", Body::None),
+        }
+    } else {
+        Decl::new(&VALUE_SPEC.captures(spec)?[1], kind, Body::None)
+    };
+    if decl.name == "_" {
+        return None;
+    }
+    Some(Action::Declare(Decl { exported: exported(&decl.name), ..decl }))
+}
+
+static SPEC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"^(?:[\w.]+\s+)?"([^"]+)""#).unwrap());
+
+/// Import paths, from single `import` lines and `import ( ... )` blocks.
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    let raw: Vec<&str> = source.lines().collect();
+    let mut in_block = false;
+    let mut imports = Vec::new();
+    for (row, line) in clean(source, &SYNTAX).iter().enumerate() {
+        let line = line.trim();
+        let rest = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            raw[row].trim()
+        } else if let Some(rest) = line.strip_prefix("import") {
+            if rest.trim_start().starts_with('(') {
+                in_block = true;
+                continue;
+            }
+            raw[row].trim().trim_start_matches("import").trim_start()
+        } else {
+            continue;
+        };
+        if let Some(caps) = SPEC.captures(rest) {
+            imports.push(import_at(&caps[1], "import", row));
+        }
+    }
+    imports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"package server
+
+import "fmt"
+
+import (
+    "net/http"
+    log "github.com/sirupsen/logrus"
+)
+
+type Server struct {
+    addr string
+}
+
+type (
+    Handler interface {
+        Serve() error
+    }
+    id = string
+)
+
+const Version = "1.0"
+
+func New(addr string) *Server {
+    return &Server{addr: addr}
+}
+
+func (s *Server) Start() error {
+    fmt.Println("{")
+    return nil
+}
+"#;
+
+    #[test]
+    fn test_parse_go_declarations() {
+        let symbols = parse(SOURCE, &ScanMode::FileContext);
+        let found: Vec<(&str, &str, Option<&str>)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind.as_str(), s.qualified_name.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Server", "struct", None),
+                ("Handler", "interface", None),
+                ("id", "type_alias", None),
+                ("Version", "const", None),
+                ("New", "function", None),
+                ("Start", "method", Some("Server.Start")),
+            ]
+        );
+        let start = symbols.iter().find(|s| s.name == "Start").unwrap();
+        assert_eq!((start.line_start, start.line_end), (26, 29));
+        assert_eq!(symbols.iter().find(|s| s.name == "id").unwrap().exported, Some(false));
+    }
+
+    #[test]
+    fn test_go_imports() {
+        let modules: Vec<String> = imports(SOURCE).into_iter().map(|i| i.module).collect();
+        assert_eq!(modules, vec!["fmt", "net/http", "github.com/sirupsen/logrus"]);
+    }
+
+    #[test]
+    fn test_summary_mode_empty() {
+        assert!(parse(SOURCE, &ScanMode::Summary).is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use super::braces::{clean, import_at, scan, Action, Body, Decl, Syntax};
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::lang::ScanMode;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const SYNTAX: Syntax = Syntax {
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    escape: '\\',
+    multiline: &[("\"\"\"", "\"\"\"")],
+    interpolated: &[],
+};
+
+const MODIFIERS: &str = r"((?:(?:public|protected|private|static|final|abstract|sealed|non-sealed|strictfp|synchronized|native|transient|volatile|default)\s+)*)";
+const TYPE: &str = r"[\w.$]+(?:<[^;{}=]*>)?(?:\[\])*";
+
+fn is_public(modifiers: &str) -> Option<bool> {
+    Some(modifiers.split_whitespace().any(|m| m == "public"))
+}
+
+static ANNOTATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^@[\w.]+(?:\([^)]*\))?\s*").unwrap());
+static TYPE_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}(class|interface|enum|record|@interface)\s+(\w+)")).unwrap());
+static CONSTRUCTOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}(?:<[^>]*>\s*)?(\w+)\s*\(")).unwrap());
+static METHOD: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}(?:<[^>]*>\s*)?({TYPE})\s+(\w+)\s*\(")).unwrap());
+static FIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^{MODIFIERS}({TYPE})\s+(\w+)\s*(?:=|;|,|$)")).unwrap());
+
+pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
+    scan(source, &SYNTAX, mode, |line, ctx| {
+        let mut line = line;
+        while line.starts_with('@') && !line.starts_with("@interface") {
+            let end = ANNOTATION.find(line)?.end();
+            line = &line[end..];
+        }
+        if line.is_empty() || !ctx.direct {
+            return None;
+        }
+        let in_type = ctx.parent.is_some_and(|p| p.container);
+        if ctx.parent.is_some() && !in_type {
+            return None;
+        }
+
+        if let Some(caps) = TYPE_DECL.captures(line) {
+            let kind = match &caps[2] {
+                "@interface" => "annotation",
+                "interface" => "interface",
+                "enum" => "enum",
+                "record" => "record",
+                _ => "class",
+            };
+            let decl = Decl { exported: is_public(&caps[1]), ..Decl::new(&caps[3], kind, Body::Braces).container() };
+            return Some(Action::Declare(decl));
+        }
+        let parent = ctx.parent.filter(|_| in_type)?;
+
+        if let Some(caps) = CONSTRUCTOR.captures(line).filter(|c| c[2] == parent.name) {
+            let decl = Decl { exported: is_public(&caps[1]), ..Decl::new(&caps[2], "constructor", Body::Braces) };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = METHOD.captures(line) {
+            let decl = Decl {
+                exported: is_public(&caps[1]),
+                return_type: Some(caps[2].to_string()),
+                ..Decl::new(&caps[3], "method", Body::Braces)
+            };
+            return Some(Action::Declare(decl));
+        }
+        let caps = FIELD.captures(line)?;
+        if matches!(&caps[2], "return" | "throw" | "new" | "else") {
+            return None;
+        }
+        Some(Action::Declare(Decl { exported: is_public(&caps[1]), ..Decl::new(&caps[3], "field", Body::None) }))
+    })
+}
+
+static IMPORT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^import\s+(?:static\s+)?([\w.]+(?:\.\*)?)\s*;").unwrap());
+
+/// `import` and `import static` declarations.
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    clean(source, &SYNTAX)
+        .iter()
+        .enumerate()
+        .filter_map(|(row, line)| IMPORT.captures(line.trim()).map(|caps| import_at(&caps[1], "import", row)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"package com.example;
+
+import java.util.List;
+import static java.util.Objects.requireNonNull;
+
+/** Greets. { */
+@Service
+public class Greeter<T> implements Runnable {
+    private static final String PREFIX = "{";
+    private final List<T> names;
+
+    public Greeter(List<T> names) {
+        this.names = names;
+    }
+
+    @Override
+    public void run() {
+        for (T name : names) {
+            String line = format(name);
+        }
+    }
+
+    <R> Map<String, List<R>> group(List<R> items)
+    {
+        return null;
+    }
+
+    interface Listener {
+        void changed(String value);
+    }
+
+    enum Mode { ON, OFF }
+}
+"#;
+
+    #[test]
+    fn test_parse_java_declarations() {
+        let symbols = parse(SOURCE, &ScanMode::FileContext);
+        let found: Vec<(&str, &str, Option<&str>)> = symbols
+            .iter()
+            .map(|s| (s.kind.as_str(), s.name.as_str(), s.qualified_name.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("class", "Greeter", None),
+                ("field", "PREFIX", Some("Greeter.PREFIX")),
+                ("field", "names", Some("Greeter.names")),
+                ("constructor", "Greeter", Some("Greeter.Greeter")),
+                ("method", "run", Some("Greeter.run")),
+                ("method", "group", Some("Greeter.group")),
+                ("interface", "Listener", Some("Greeter.Listener")),
+                ("method", "changed", Some("Greeter.Listener.changed")),
+                ("enum", "Mode", Some("Greeter.Mode")),
+            ]
+        );
+        let class = &symbols[0];
+        assert_eq!((class.line_start, class.line_end, class.exported), (7, 32, Some(true)));
+        let group = symbols.iter().find(|s| s.name == "group").unwrap();
+        assert_eq!((group.line_start, group.line_end), (22, 25));
+        assert_eq!(group.return_type.as_deref(), Some("Map<String, List<R>>"));
+    }
+
+    #[test]
+    fn test_java_imports() {
+        let modules: Vec<String> = imports(SOURCE).into_iter().map(|i| i.module).collect();
+        assert_eq!(modules, vec!["java.util.List", "java.util.Objects.requireNonNull"]);
+    }
+}
@@ -1,19 +1,171 @@
+pub mod braces;
+pub mod csharp;
+pub mod go;
+pub mod java;
+pub mod powershell;
 pub mod python;
+pub mod qml;
 pub mod rust_lang;
 pub mod sql;
 pub mod typescript;
 
-use crate::output::SymbolRecord;
+use crate::output::{ImportRecord, SymbolRecord};
 use crate::scanner::lang::{Language, ScanMode};
 use std::path::Path;
+use typescript::Dialect;
+
+/// A language the scanner understands: the file extensions it claims and
+/// its extractors.  Supporting a new language takes a [`Language`] variant,
+/// a parser module and an entry in [`LANGUAGES`]; detection, naming and
+/// dispatch all read this table.
+pub struct LanguageSpec {
+    pub language: Language,
+    /// Reported as `FileResult::language`.
+    pub id: &'static str,
+    /// Lowercase, without the dot.
+    pub extensions: &'static [&'static str],
+    pub symbols: fn(&str, &ScanMode) -> Vec<SymbolRecord>,
+    pub imports: fn(&str) -> Vec<ImportRecord>,
+}
+
+pub static LANGUAGES: &[LanguageSpec] = &[
+    LanguageSpec {
+        language: Language::TypeScript,
+        id: "typescript",
+        extensions: &["ts", "mts", "cts"],
+        symbols: typescript::parse,
+        imports: typescript::imports,
+    },
+    LanguageSpec {
+        language: Language::TypeScript,
+        id: "typescript",
+        extensions: &["tsx"],
+        symbols: |source, mode| typescript::parse_as(source, mode, Dialect::Tsx),
+        imports: |source| typescript::imports_as(source, Dialect::Tsx),
+    },
+    LanguageSpec {
+        language: Language::JavaScript,
+        id: "javascript",
+        extensions: &["js", "jsx", "mjs", "cjs"],
+        symbols: |source, mode| typescript::parse_as(source, mode, Dialect::JavaScript),
+        imports: |source| typescript::imports_as(source, Dialect::JavaScript),
+    },
+    LanguageSpec {
+        language: Language::Python,
+        id: "python",
+        extensions: &["py", "pyw"],
+        symbols: python::parse,
+        imports: python::imports,
+    },
+    LanguageSpec {
+        language: Language::Rust,
+        id: "rust",
+        extensions: &["rs"],
+        symbols: rust_lang::parse,
+        imports: rust_lang::imports,
+    },
+    LanguageSpec {
+        language: Language::Sql,
+        id: "sql",
+        extensions: &["sql"],
+        symbols: sql::parse,
+        imports: |_| vec![],
+    },
+    LanguageSpec {
+        language: Language::Go,
+        id: "go",
+        extensions: &["go"],
+        symbols: go::parse,
+        imports: go::imports,
+    },
+    LanguageSpec {
+        language: Language::CSharp,
+        id: "csharp",
+        extensions: &["cs"],
+        symbols: csharp::parse,
+        imports: csharp::imports,
+    },
+    LanguageSpec {
+        language: Language::Java,
+        id: "java",
+        extensions: &["java"],
+        symbols: java::parse,
+        imports: java::imports,
+    },
+    LanguageSpec {
+        language: Language::Qml,
+        id: "qml",
+        extensions: &["qml"],
+        symbols: qml::parse,
+        imports: qml::imports,
+    },
+    LanguageSpec {
+        language: Language::PowerShell,
+        id: "powershell",
+        extensions: &["ps1", "psm1"],
+        symbols: powershell::parse,
+        imports: powershell::imports,
+    },
+];
+
+pub fn spec_for_path(path: &Path) -> Option<&'static LanguageSpec> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    LANGUAGES.iter().find(|spec| spec.extensions.contains(&ext.as_str()))
+}
+
+/// The entry for `path` if it belongs to `language`, else the first entry
+/// for `language`.
+fn spec_for(path: &Path, language: &Language) -> Option<&'static LanguageSpec> {
+    spec_for_path(path)
+        .filter(|spec| spec.language == *language)
+        .or_else(|| LANGUAGES.iter().find(|spec| spec.language == *language))
+}
 
 pub fn parse_file(path: &Path, source: &str, language: &Language, mode: &ScanMode) -> Vec<SymbolRecord> {
-    let _ = path;
-    match language {
-        Language::TypeScript | Language::JavaScript => typescript::parse(source, mode),
-        Language::Rust => rust_lang::parse(source, mode),
-        Language::Python => python::parse(source, mode),
-        Language::Sql => sql::parse(source, mode),
-        Language::Unknown => vec![],
+    spec_for(path, language).map_or_else(Vec::new, |spec| (spec.symbols)(source, mode))
+}
+
+/// Imports and module references in `source`, unresolved.
+///
+/// Extracted in every scan mode: the dependency graph is file-level and does
+/// not depend on symbol detail.
+pub fn parse_imports(path: &Path, source: &str, language: &Language) -> Vec<ImportRecord> {
+    spec_for(path, language).map_or_else(Vec::new, |spec| (spec.imports)(source))
+}
+
+fn import_record(module: String, kind: &str, node: &tree_sitter::Node<'_>) -> ImportRecord {
+    ImportRecord {
+        module,
+        kind: kind.to_string(),
+        line: node.start_position().row as u32,
+        names: vec![],
+        resolved: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_each_extension_has_one_entry() {
+        let mut seen = HashSet::new();
+        for spec in LANGUAGES {
+            for ext in spec.extensions {
+                assert!(seen.insert(*ext), "extension {} registered twice", ext);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dispatch_uses_the_entry_for_the_extension() {
+        let source = "function Footer() { return <footer />; }\n";
+        let tsx = parse_file(Path::new("a.tsx"), source, &Language::TypeScript, &ScanMode::FileContext);
+        let ts = parse_file(Path::new("a.ts"), source, &Language::TypeScript, &ScanMode::FileContext);
+        assert!(tsx.iter().any(|s| s.name == "Footer"));
+        assert!(!ts.iter().any(|s| s.name == "Footer"));
+        let go = parse_imports(Path::new("main.go"), "import \"fmt\"\n", &Language::Go);
+        assert_eq!(go[0].module, "fmt");
     }
 }
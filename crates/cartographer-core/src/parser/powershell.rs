@@ -0,0 +1,141 @@
+use super::braces::{clean, import_at, scan, Action, Body, Decl, Syntax};
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::lang::ScanMode;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const SYNTAX: Syntax = Syntax {
+    line_comment: "#",
+    block_comment: Some(("<#", "#>")),
+    quotes: &['"', '\''],
+    escape: '`',
+    multiline: &[("@\"", "\"@"), ("@'", "'@")],
+    interpolated: &[],
+};
+
+static FUNCTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^(?:function|filter|workflow)\s+(?:(?:global|script|private|local):)?([\w-]+)").unwrap());
+static TYPE_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^(class|enum)\s+(\w+)").unwrap());
+static METHOD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^(?:(?:hidden|static)\s+)*(?:\[[^\]]+\]\s*)?(\w+)\s*\(").unwrap());
+static PROPERTY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^(?:(?:hidden|static)\s+)*(?:\[[^\]]+\]\s*)*\$(\w+)").unwrap());
+
+pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
+    scan(source, &SYNTAX, mode, |line, ctx| {
+        if !ctx.direct {
+            return None;
+        }
+        let parent = ctx.parent;
+        if let Some(caps) = FUNCTION.captures(line).filter(|_| parent.is_none_or(|p| !p.container)) {
+            return Some(Action::Declare(Decl::new(&caps[1], "function", Body::Braces)));
+        }
+        if let Some(caps) = TYPE_DECL.captures(line).filter(|_| parent.is_none()) {
+            let decl = if caps[1].eq_ignore_ascii_case("class") {
+                Decl::new(&caps[2], "class", Body::Braces).container()
+            } else {
+                Decl::new(&caps[2], "enum", Body::Braces)
+            };
+            return Some(Action::Declare(decl));
+        }
+        let class = parent.filter(|p| p.kind == "class")?;
+        if let Some(caps) = METHOD.captures(line) {
+            let kind = if caps[1].eq_ignore_ascii_case(&class.name) { "constructor" } else { "method" };
+            return Some(Action::Declare(Decl::new(&caps[1], kind, Body::Braces)));
+        }
+        let caps = PROPERTY.captures(line)?;
+        Some(Action::Declare(Decl::new(&caps[1], "property", Body::None)))
+    })
+}
+
+static IMPORT_PATTERNS: LazyLock<[(Regex, &str); 4]> = LazyLock::new(|| {
+    [
+        (Regex::new(r#"(?i)^Import-Module\s+(?:-Name\s+)?["']?([^"'\s;]+)"#).unwrap(), "import"),
+        (Regex::new(r#"(?i)^using\s+module\s+["']?([^"'\s;]+)"#).unwrap(), "import"),
+        (Regex::new(r"(?i)^using\s+namespace\s+([\w.]+)").unwrap(), "using"),
+        (Regex::new(r#"^\.\s+["']?([^"'\s;]+)"#).unwrap(), "dot_source"),
+    ]
+});
+
+/// `Import-Module`, `using module` and `using namespace` statements, and
+/// dot-sourced scripts (`. $PSScriptRoot\helpers.ps1`).
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    let raw: Vec<&str> = source.lines().collect();
+    let mut imports = Vec::new();
+    for (row, line) in clean(source, &SYNTAX).iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = raw[row].trim();
+        if let Some((caps, kind)) = IMPORT_PATTERNS.iter().find_map(|(re, kind)| re.captures(line).map(|c| (c, *kind))) {
+            imports.push(import_at(&caps[1], kind, row));
+        }
+    }
+    imports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"using namespace System.Collections.Generic
+Import-Module -Name PSReadLine
+. $PSScriptRoot\helpers.ps1
+
+<#
+.SYNOPSIS
+    Not code: function Fake { }
+#>
+function Get-Greeting
+{
+    param([string]$Name)
+    $banner = @"
+}
+"@
+    "Hello, $Name"
+}
+
+class Counter {
+    [int]$Value
+    hidden static [string]$Label = "{"
+
+    Counter([int]$start) { $this.Value = $start }
+
+    [void] Increment() {
+        $this.Value++
+    }
+}
+
+enum Color { Red; Green }
+"#;
+
+    #[test]
+    fn test_parse_powershell_declarations() {
+        let symbols = parse(SOURCE, &ScanMode::FileContext);
+        let found: Vec<(&str, &str, Option<&str>)> = symbols
+            .iter()
+            .map(|s| (s.kind.as_str(), s.name.as_str(), s.qualified_name.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("function", "Get-Greeting", None),
+                ("class", "Counter", None),
+                ("property", "Value", Some("Counter.Value")),
+                ("property", "Label", Some("Counter.Label")),
+                ("constructor", "Counter", Some("Counter.Counter")),
+                ("method", "Increment", Some("Counter.Increment")),
+                ("enum", "Color", None),
+            ]
+        );
+        assert_eq!((symbols[0].line_start, symbols[0].line_end), (8, 15));
+    }
+
+    #[test]
+    fn test_powershell_imports() {
+        let found: Vec<(String, String)> = imports(SOURCE).into_iter().map(|i| (i.kind, i.module)).collect();
+        let expected = [
+            ("using", "System.Collections.Generic"),
+            ("import", "PSReadLine"),
+            ("dot_source", "$PSScriptRoot\\helpers.ps1"),
+        ];
+        assert_eq!(found, expected.iter().map(|(k, m)| (k.to_string(), m.to_string())).collect::<Vec<_>>());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::output::SymbolRecord;
+use super::import_record;
+use crate::output::{ImportRecord, SymbolRecord};
 use crate::scanner::lang::ScanMode;
 use tree_sitter::Parser;
 
@@ -93,6 +94,62 @@ pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
     symbols
 }
 
+/// `import a.b` (one record per module) and `from .a import b, c` (module
+/// `.a`, names `b`, `c`).
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_python::language();
+    parser.set_language(&language).expect("Error loading Python grammar");
+
+    let tree = match parser.parse(source, None) {
+        Some(t) => t,
+        None => return vec![],
+    };
+
+    fn dotted(node: &tree_sitter::Node<'_>, source: &[u8]) -> String {
+        let target = if node.kind() == "aliased_import" {
+            node.child_by_field_name("name").unwrap_or(*node)
+        } else {
+            *node
+        };
+        target.utf8_text(source).unwrap_or("").to_string()
+    }
+
+    fn visit(node: &tree_sitter::Node<'_>, source: &[u8], imports: &mut Vec<ImportRecord>) {
+        match node.kind() {
+            "import_statement" => {
+                let mut cursor = node.walk();
+                for name in node.children_by_field_name("name", &mut cursor) {
+                    imports.push(import_record(dotted(&name, source), "import", node));
+                }
+                return;
+            }
+            "import_from_statement" => {
+                let Some(module) = node.child_by_field_name("module_name") else {
+                    return;
+                };
+                let mut record = import_record(dotted(&module, source), "from", node);
+                let mut cursor = node.walk();
+                record.names = node
+                    .children_by_field_name("name", &mut cursor)
+                    .map(|name| dotted(&name, source))
+                    .collect();
+                imports.push(record);
+                return;
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            visit(&child, source, imports);
+        }
+    }
+
+    let mut imports = Vec::new();
+    visit(&tree.root_node(), source.as_bytes(), &mut imports);
+    imports
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,4 +169,16 @@ mod tests {
         let source = "def hello(): pass";
         assert!(parse(source, &ScanMode::Summary).is_empty());
     }
+
+    #[test]
+    fn test_imports_plain_and_from() {
+        let source = "import os, pkg.util as u\nfrom ..models import User, Group\nfrom . import sibling\n";
+        let imports = imports(source);
+        let modules: Vec<&str> = imports.iter().map(|i| i.module.as_str()).collect();
+        assert_eq!(modules, vec!["os", "pkg.util", "..models", "."]);
+        assert_eq!(imports[2].kind, "from");
+        assert_eq!(imports[2].names, vec!["User", "Group"]);
+        assert_eq!(imports[3].names, vec!["sibling"]);
+        assert_eq!(imports[3].line, 2);
+    }
 }
@@ -0,0 +1,150 @@
+use super::braces::{clean, import_at, scan, Action, Body, Decl, Syntax};
+use crate::output::{ImportRecord, SymbolRecord};
+use crate::scanner::lang::ScanMode;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const SYNTAX: Syntax = Syntax {
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    escape: '\\',
+    multiline: &[("`", "`")],
+    interpolated: &[],
+};
+
+static OBJECT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:[\w.]+\s*:\s*)?([A-Z]\w*(?:\.[A-Z]\w*)*)\s*(?:on\s+[\w.]+\s*)?\{").unwrap());
+static ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|[{;]\s*)id\s*:\s*(\w+)").unwrap());
+static COMPONENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^component\s+(\w+)\s*:").unwrap());
+static PROPERTY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:(?:default|readonly|required|final)\s+)*property\s+([\w.]+(?:<[\w.]+>)?)\s+(\w+)").unwrap());
+static SIGNAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^signal\s+(\w+)").unwrap());
+static FUNCTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:async\s+)?function\s+(\w+)\s*\(").unwrap());
+static HANDLER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(on[A-Z]\w*)\s*:").unwrap());
+static ENUMERATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^enum\s+(\w+)").unwrap());
+
+/// Object declarations are reported under their `id`; objects without one
+/// are left out, except the root object, which is reported under its type.
+pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
+    scan(source, &SYNTAX, mode, |line, ctx| {
+        if !ctx.direct || ctx.parent.is_some_and(|p| !p.container) {
+            return None;
+        }
+        if line.starts_with("import ") || line.starts_with("pragma ") {
+            return None;
+        }
+        if let Some(caps) = COMPONENT.captures(line) {
+            return Some(Action::Declare(Decl::new(&caps[1], "component", Body::Braces).container()));
+        }
+        if let Some(caps) = PROPERTY.captures(line) {
+            let decl = Decl { return_type: Some(caps[1].to_string()), ..Decl::new(&caps[2], "property", Body::None) };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = SIGNAL.captures(line) {
+            return Some(Action::Declare(Decl::new(&caps[1], "signal", Body::None)));
+        }
+        if let Some(caps) = FUNCTION.captures(line) {
+            let decl = Decl { async_fn: Some(line.starts_with("async")), ..Decl::new(&caps[1], "function", Body::Braces) };
+            return Some(Action::Declare(decl));
+        }
+        if let Some(caps) = ENUMERATION.captures(line) {
+            return Some(Action::Declare(Decl::new(&caps[1], "enum", Body::Braces)));
+        }
+        if let Some(caps) = OBJECT.captures(line) {
+            let inline_id = ID.captures(&line[caps.get(0).map_or(0, |m| m.end() - 1)..]);
+            let decl = match inline_id {
+                Some(named) => Decl::new(&named[1], "object", Body::Braces),
+                None => Decl { anonymous: ctx.parent.is_some(), ..Decl::new(&caps[1], "object", Body::Braces) },
+            };
+            return Some(Action::Declare(decl.container()));
+        }
+        if let Some(caps) = HANDLER.captures(line) {
+            let body = if line.contains('{') { Body::Braces } else { Body::None };
+            return Some(Action::Declare(Decl::new(&caps[1], "handler", body)));
+        }
+        let caps = ID.captures(line).filter(|_| ctx.parent.is_some_and(|p| p.kind == "object"))?;
+        Some(Action::Name(caps[1].to_string()))
+    })
+}
+
+static IMPORT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"^import\s+(?:"([^"]+)"|([\w.]+))"#).unwrap());
+
+/// Module imports (`import QtQuick.Controls 2.15`) and directory or script
+/// imports (`import "components"`, `import "logic.js" as Logic`).
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    let raw: Vec<&str> = source.lines().collect();
+    clean(source, &SYNTAX)
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with("import "))
+        .filter_map(|(row, _)| {
+            let caps = IMPORT.captures(raw[row].trim())?;
+            let module = caps.get(1).or(caps.get(2))?.as_str();
+            Some(import_at(module, "import", row))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"import QtQuick 2.15
+import QtQuick.Controls
+import "components" as Parts
+
+// Root { of the window
+ApplicationWindow {
+    id: root
+    property int count: 0
+    readonly property list<Item> pages: []
+    signal refreshed(string reason)
+
+    function refresh() {
+        var text = "}";
+        refreshed("manual")
+    }
+
+    Timer { id: poller; interval: 500 }
+
+    Rectangle {
+        anchors { fill: parent }
+        MouseArea {
+            id: clickArea
+            onClicked: {
+                root.refresh()
+            }
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_parse_qml_declarations() {
+        let symbols = parse(SOURCE, &ScanMode::FileContext);
+        let found: Vec<(&str, &str, Option<&str>)> = symbols
+            .iter()
+            .map(|s| (s.kind.as_str(), s.name.as_str(), s.qualified_name.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("object", "root", None),
+                ("property", "count", Some("root.count")),
+                ("property", "pages", Some("root.pages")),
+                ("signal", "refreshed", Some("root.refreshed")),
+                ("function", "refresh", Some("root.refresh")),
+                ("object", "poller", Some("root.poller")),
+                ("object", "clickArea", Some("root.Rectangle.clickArea")),
+                ("handler", "onClicked", Some("root.Rectangle.clickArea.onClicked")),
+            ]
+        );
+        assert_eq!((symbols[0].line_start, symbols[0].line_end), (5, 27));
+        assert_eq!(symbols[2].return_type.as_deref(), Some("list<Item>"));
+    }
+
+    #[test]
+    fn test_qml_imports() {
+        let modules: Vec<String> = imports(SOURCE).into_iter().map(|i| i.module).collect();
+        assert_eq!(modules, vec!["QtQuick", "QtQuick.Controls", "components"]);
+    }
+}
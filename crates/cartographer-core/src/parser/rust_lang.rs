@@ -1,4 +1,5 @@
-use crate::output::SymbolRecord;
+use super::import_record;
+use crate::output::{ImportRecord, SymbolRecord};
 use crate::scanner::lang::ScanMode;
 use tree_sitter::Parser;
 
@@ -105,6 +106,117 @@ pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
     symbols
 }
 
+/// `use` paths (one per leaf of a use tree) and out-of-line `mod` items.
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_rust::language();
+    parser.set_language(&language).expect("Error loading Rust grammar");
+
+    let tree = match parser.parse(source, None) {
+        Some(t) => t,
+        None => return vec![],
+    };
+
+    fn use_paths(node: &tree_sitter::Node<'_>, prefix: &str, source: &[u8], out: &mut Vec<String>) {
+        let text = |n: &tree_sitter::Node<'_>| n.utf8_text(source).unwrap_or("").to_string();
+        match node.kind() {
+            "use_as_clause" => {
+                if let Some(path) = node.child_by_field_name("path") {
+                    use_paths(&path, prefix, source, out);
+                }
+            }
+            "use_wildcard" => {
+                let mut cursor = node.walk();
+                let path = node.named_children(&mut cursor).next();
+                match path {
+                    Some(path) => out.push(format!("{prefix}{}", text(&path))),
+                    None => out.push(prefix.trim_end_matches("::").to_string()),
+                }
+            }
+            "scoped_use_list" => {
+                let nested = match node.child_by_field_name("path") {
+                    Some(path) => format!("{prefix}{}::", text(&path)),
+                    None => prefix.to_string(),
+                };
+                if let Some(list) = node.child_by_field_name("list") {
+                    use_paths(&list, &nested, source, out);
+                }
+            }
+            "use_list" => {
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    use_paths(&child, prefix, source, out);
+                }
+            }
+            // `{self, ..}` names the prefix itself.
+            "self" if !prefix.is_empty() => out.push(prefix.trim_end_matches("::").to_string()),
+            _ => out.push(format!("{prefix}{}", text(node))),
+        }
+    }
+
+    /// Rewrite a use path written inside inline modules `inline` so it is
+    /// relative to the file's own module.
+    fn rebase(path: String, inline: &[String]) -> String {
+        if inline.is_empty() {
+            return path;
+        }
+        let segments: Vec<&str> = path.split("::").collect();
+        let supers = segments.iter().take_while(|s| **s == "super").count();
+        match segments[0] {
+            "self" => [&["self"], &inline.iter().map(String::as_str).collect::<Vec<_>>()[..], &segments[1..]].concat().join("::"),
+            "super" if supers <= inline.len() => {
+                let kept = &inline[..inline.len() - supers];
+                let kept: Vec<&str> = kept.iter().map(String::as_str).collect();
+                [&["self"], &kept[..], &segments[supers..]].concat().join("::")
+            }
+            "super" => [&segments[inline.len()..supers], &segments[supers..]].concat().join("::"),
+            _ => path,
+        }
+    }
+
+    fn visit(node: &tree_sitter::Node<'_>, source: &[u8], inline: &mut Vec<String>, imports: &mut Vec<ImportRecord>) {
+        match node.kind() {
+            "use_declaration" => {
+                if let Some(argument) = node.child_by_field_name("argument") {
+                    let mut paths = Vec::new();
+                    use_paths(&argument, "", source, &mut paths);
+                    for path in paths {
+                        imports.push(import_record(rebase(path, inline), "use", node));
+                    }
+                }
+                return;
+            }
+            "mod_item" => {
+                let name = node
+                    .child_by_field_name("name")
+                    .and_then(|n| n.utf8_text(source).ok())
+                    .unwrap_or("")
+                    .to_string();
+                let Some(body) = node.child_by_field_name("body") else {
+                    // Out-of-line modules nested in inline ones live in
+                    // subdirectories: `mod a { mod b; }` is `a/b.rs`.
+                    let module = inline.iter().cloned().chain([name]).collect::<Vec<_>>().join("::");
+                    imports.push(import_record(module, "mod", node));
+                    return;
+                };
+                inline.push(name);
+                visit(&body, source, inline, imports);
+                inline.pop();
+                return;
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            visit(&child, source, inline, imports);
+        }
+    }
+
+    let mut imports = Vec::new();
+    visit(&tree.root_node(), source.as_bytes(), &mut Vec::new(), &mut imports);
+    imports
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,4 +237,26 @@ mod tests {
         let source = "pub fn hello() {}";
         assert!(parse(source, &ScanMode::Summary).is_empty());
     }
+
+    #[test]
+    fn test_imports_expand_use_trees_and_out_of_line_mods() {
+        let source = "mod parser;\nmod inline { use super::x; mod nested; }\nuse crate::scanner::{self, lang::{Language, ScanMode as M}};\nuse std::io::*;\n#[cfg(test)]\nmod tests { use super::*; use super::super::sibling::Item; }\n";
+        let imports = imports(source);
+        let found: Vec<(&str, &str)> = imports.iter().map(|i| (i.kind.as_str(), i.module.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("mod", "parser"),
+                ("use", "self::x"),
+                ("mod", "inline::nested"),
+                ("use", "crate::scanner"),
+                ("use", "crate::scanner::lang::Language"),
+                ("use", "crate::scanner::lang::ScanMode"),
+                ("use", "std::io"),
+                ("use", "self"),
+                ("use", "super::sibling::Item"),
+            ]
+        );
+        assert_eq!(imports[3].line, 2);
+    }
 }
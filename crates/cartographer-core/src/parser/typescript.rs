@@ -1,15 +1,40 @@
-use crate::output::SymbolRecord;
+use super::import_record;
+use crate::output::{ImportRecord, SymbolRecord};
 use crate::scanner::lang::ScanMode;
 use tree_sitter::Parser;
 
+/// The grammar a file is parsed with.  The extraction below only relies on
+/// node kinds the three grammars share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    TypeScript,
+    /// TypeScript with JSX (`.tsx`).
+    Tsx,
+    /// JavaScript, including JSX.
+    JavaScript,
+}
+
+impl Dialect {
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Dialect::TypeScript => tree_sitter_typescript::language_typescript(),
+            Dialect::Tsx => tree_sitter_typescript::language_tsx(),
+            Dialect::JavaScript => tree_sitter_javascript::language(),
+        }
+    }
+}
+
 pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
+    parse_as(source, mode, Dialect::TypeScript)
+}
+
+pub fn parse_as(source: &str, mode: &ScanMode, dialect: Dialect) -> Vec<SymbolRecord> {
     if *mode == ScanMode::Summary {
         return vec![];
     }
 
     let mut parser = Parser::new();
-    let language = tree_sitter_typescript::language_typescript();
-    parser.set_language(&language).expect("Error loading TypeScript grammar");
+    parser.set_language(&dialect.grammar()).expect("Error loading TypeScript grammar");
 
     let tree = match parser.parse(source, None) {
         Some(t) => t,
@@ -47,14 +72,14 @@ pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
         }
         let kind = node.kind();
         let symbol_kind = match kind {
-            "function_declaration" | "function" => Some("function"),
+            "function_declaration" | "generator_function_declaration" | "function" => Some("function"),
             "arrow_function" => Some("arrow_function"),
             "class_declaration" | "class" => Some("class"),
             "interface_declaration" => Some("interface"),
             "type_alias_declaration" => Some("type_alias"),
             "enum_declaration" => Some("enum"),
             "method_definition" => Some("method"),
-            "public_field_definition" => Some("property"),
+            "public_field_definition" | "field_definition" => Some("property"),
             "variable_declarator" => Some("variable"),
             "import_statement" => Some("import"),
             "export_statement" => Some("export"),
@@ -103,6 +128,92 @@ pub fn parse(source: &str, mode: &ScanMode) -> Vec<SymbolRecord> {
     symbols
 }
 
+/// `import ... from`, `import x = require()`, `export ... from`, `require()`
+/// and dynamic `import()` specifiers.
+pub fn imports(source: &str) -> Vec<ImportRecord> {
+    imports_as(source, Dialect::TypeScript)
+}
+
+pub fn imports_as(source: &str, dialect: Dialect) -> Vec<ImportRecord> {
+    let mut parser = Parser::new();
+    parser.set_language(&dialect.grammar()).expect("Error loading TypeScript grammar");
+
+    let tree = match parser.parse(source, None) {
+        Some(t) => t,
+        None => return vec![],
+    };
+
+    fn string_value(node: &tree_sitter::Node<'_>, source: &[u8]) -> Option<String> {
+        if node.kind() != "string" {
+            return None;
+        }
+        let raw = node.utf8_text(source).ok()?;
+        let value = raw.trim_matches(|c| c == '"' || c == '\'' || c == '`');
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    }
+
+    fn visit(node: &tree_sitter::Node<'_>, source: &[u8], imports: &mut Vec<ImportRecord>) {
+        match node.kind() {
+            "import_statement" => {
+                let source_node = node.child_by_field_name("source").or_else(|| {
+                    let mut cursor = node.walk();
+                    let clause = node
+                        .named_children(&mut cursor)
+                        .find(|c| c.kind() == "import_require_clause");
+                    clause.and_then(|c| c.child_by_field_name("source"))
+                });
+                let kind = if source_node.is_some_and(|n| {
+                    n.parent().is_some_and(|p| p.kind() == "import_require_clause")
+                }) {
+                    "require"
+                } else {
+                    "import"
+                };
+                if let Some(module) = source_node.and_then(|n| string_value(&n, source)) {
+                    imports.push(import_record(module, kind, node));
+                }
+                return;
+            }
+            "export_statement" => {
+                if let Some(module) = node
+                    .child_by_field_name("source")
+                    .and_then(|n| string_value(&n, source))
+                {
+                    imports.push(import_record(module, "export_from", node));
+                    return;
+                }
+            }
+            "call_expression" => {
+                let callee = node.child_by_field_name("function");
+                let kind = match callee.map(|c| (c.kind(), c.utf8_text(source).unwrap_or(""))) {
+                    Some(("import", _)) => Some("import"),
+                    Some(("identifier", "require")) => Some("require"),
+                    _ => None,
+                };
+                let first_arg = node.child_by_field_name("arguments").and_then(|args| {
+                    let mut cursor = args.walk();
+                    let first = args.named_children(&mut cursor).next();
+                    first
+                });
+                if let (Some(kind), Some(module)) =
+                    (kind, first_arg.and_then(|a| string_value(&a, source)))
+                {
+                    imports.push(import_record(module, kind, node));
+                }
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            visit(&child, source, imports);
+        }
+    }
+
+    let mut imports = Vec::new();
+    visit(&tree.root_node(), source.as_bytes(), &mut imports);
+    imports
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,4 +252,51 @@ class Greeter {
             assert!(f.body_fragment.is_some());
         }
     }
+
+    #[test]
+    fn test_imports_cover_static_dynamic_and_reexports() {
+        let source = r#"
+import { a } from "./a";
+import type { B } from '../types/b';
+import fs = require("fs");
+export { c } from "./c.js";
+export const d = 1;
+const e = require('./e');
+async function load() { return import("./lazy"); }
+"#;
+        let found: Vec<(String, String)> = imports(source)
+            .into_iter()
+            .map(|i| (i.kind, i.module))
+            .collect();
+        let expected = [
+            ("import", "./a"),
+            ("import", "../types/b"),
+            ("require", "fs"),
+            ("export_from", "./c.js"),
+            ("require", "./e"),
+            ("import", "./lazy"),
+        ];
+        assert_eq!(
+            found,
+            expected
+                .iter()
+                .map(|(k, m)| (k.to_string(), m.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_jsx_needs_the_jsx_aware_grammars() {
+        let source = "export const App = () => <div className=\"x\">{items.map((i) => <Item key={i} />)}</div>;\nfunction Footer() { return <footer />; }\n";
+        let names = |dialect| -> Vec<String> {
+            parse_as(source, &ScanMode::FileContext, dialect)
+                .into_iter()
+                .filter(|s| s.name != "<anonymous>")
+                .map(|s| s.name)
+                .collect()
+        };
+        assert_eq!(names(Dialect::Tsx), vec!["App", "Footer"]);
+        assert_eq!(names(Dialect::JavaScript), vec!["App", "Footer"]);
+        assert!(!names(Dialect::TypeScript).contains(&"Footer".to_string()));
+    }
 }
@@ -1,9 +1,13 @@
-use crate::output::{DiagnosticsBlock, FileResult, ScanResult};
-use crate::parser::parse_file;
+use crate::cache::{content_hash, mtime_ns, CacheEntry, ScanCache};
+use crate::graph;
+use crate::output::{DiagnosticsBlock, FileResult, ScanChanges, ScanResult};
+use crate::parser::{parse_file, parse_imports};
 use crate::scanner::lang::{detect_language, Language, ScanMode};
-use crate::scanner::walker::walk;
+use crate::scanner::walker::{looks_binary, walk, WalkOptions};
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Instant;
 
 pub fn scan(
@@ -13,64 +17,281 @@ pub fn scan(
     max_seconds: f64,
     include_extensions: Option<Vec<String>>,
     extra_excludes: Option<Vec<String>>,
+) -> ScanResult {
+    let walk_options = WalkOptions {
+        include_extensions,
+        exclude_globs: extra_excludes,
+        ..WalkOptions::default()
+    };
+    scan_with_cache(root, mode, max_files, max_seconds, &walk_options, None, None)
+}
+
+enum FileStatus {
+    Reused,
+    Parsed,
+}
+
+/// Like [`scan`], but files unchanged since the scan recorded in `cache` are
+/// reused instead of re-parsed, and `cache` is updated to this scan.
+///
+/// An entry is reused without reading the file when mtime and size match, or
+/// after reading it when only the mtime moved but the content hash did not.
+/// `max_seconds` only limits re-parsing, so once a workspace is cached the
+/// result is complete; files left unparsed when the budget runs out are
+/// picked up by the next scan.
+///
+/// Setting `cancel` stops further parsing the same way; the caller decides
+/// whether to use the partial result.
+pub fn scan_with_cache(
+    root: &Path,
+    mode: ScanMode,
+    max_files: usize,
+    max_seconds: f64,
+    walk_options: &WalkOptions,
+    mut cache: Option<&mut ScanCache>,
+    cancel: Option<&AtomicBool>,
 ) -> ScanResult {
     let started = Instant::now();
+    let root_str = root.to_string_lossy().to_string();
 
-    let all_files = walk(
-        root,
-        include_extensions.as_deref(),
-        extra_excludes.as_deref(),
-    );
+    let walked_output = walk(root, walk_options);
+    let all_files = walked_output.files;
+    let skipped_binary = AtomicUsize::new(0);
 
+    let walked: HashSet<String> = all_files.iter().map(|p| p.to_string_lossy().to_string()).collect();
     let files_to_scan: Vec<_> = all_files.into_iter().take(max_files).collect();
-    let budget_hit = files_to_scan.len() == max_files;
+    let time_budget_hit = AtomicBool::new(false);
 
-    let file_results: Vec<FileResult> = files_to_scan
+    let caching = cache.is_some();
+    let (baseline, mut entries) = match cache.as_deref_mut() {
+        Some(c) if c.matches(&root_str, mode.as_str(), walk_options) => (true, std::mem::take(&mut c.entries)),
+        _ => (false, HashMap::new()),
+    };
+    let previous: Option<&HashMap<String, CacheEntry>> = baseline.then_some(&entries);
+
+    let outcomes: Vec<(FileResult, Option<CacheEntry>, FileStatus)> = files_to_scan
         .par_iter()
         .filter_map(|path| {
-            if started.elapsed().as_secs_f64() > max_seconds {
-                return None;
-            }
-
             let lang = detect_language(path);
             if lang == Language::Unknown {
                 return None;
             }
 
-            let source = match std::fs::read_to_string(path) {
-                Ok(s) => s,
-                Err(_) => return None,
+            let key = path.to_string_lossy().to_string();
+            let prior = previous.and_then(|entries| entries.get(&key));
+            let reuse = |entry: CacheEntry| {
+                let result = FileResult {
+                    path: key.clone(),
+                    language: entry.language.clone(),
+                    size_bytes: entry.size_bytes,
+                    symbols: entry.symbols.clone(),
+                    imports: entry.imports.clone(),
+                };
+                Some((result, Some(entry), FileStatus::Reused))
             };
 
+            let mtime = if caching {
+                std::fs::metadata(path).map(|m| mtime_ns(&m)).unwrap_or(0)
+            } else {
+                0
+            };
+            if let Some(prior) = prior {
+                let size = std::fs::metadata(path).map(|m| m.len()).ok();
+                if mtime != 0 && prior.mtime_ns == mtime && Some(prior.size_bytes) == size {
+                    return reuse(prior.clone());
+                }
+            }
+
+            let bytes = std::fs::read(path).ok()?;
+            if looks_binary(&bytes) {
+                skipped_binary.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            let source = String::from_utf8(bytes).ok()?;
+            let hash = if caching { content_hash(source.as_bytes()) } else { String::new() };
+            if let Some(prior) = prior.filter(|p| p.content_hash == hash) {
+                return reuse(CacheEntry { mtime_ns: mtime, ..prior.clone() });
+            }
+
+            if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+                return None;
+            }
+            if started.elapsed().as_secs_f64() > max_seconds {
+                time_budget_hit.store(true, Ordering::Relaxed);
+                return None;
+            }
+
             let size_bytes = source.len() as u64;
             let symbols = parse_file(path, &source, &lang, &mode);
+            let imports = parse_imports(path, &source, &lang);
 
-            Some(FileResult {
-                path: path.to_string_lossy().to_string(),
-                language: lang.as_str().to_string(),
+            let entry = caching.then(|| CacheEntry {
+                mtime_ns: mtime,
                 size_bytes,
-                symbols,
-            })
+                content_hash: hash,
+                language: lang.as_str().to_string(),
+                symbols: symbols.clone(),
+                imports: imports.clone(),
+            });
+            Some((
+                FileResult {
+                    path: key,
+                    language: lang.as_str().to_string(),
+                    size_bytes,
+                    symbols,
+                    imports,
+                },
+                entry,
+                FileStatus::Parsed,
+            ))
         })
         .collect();
 
+    let changes = cache.map(|cache| {
+        let mut changes = ScanChanges {
+            baseline,
+            ..ScanChanges::default()
+        };
+        // Entries for walked files this scan did not reach (max_files or time
+        // budget) are kept; they are revalidated by hash before any reuse.
+        for (result, _, status) in &outcomes {
+            match status {
+                FileStatus::Reused => changes.reused += 1,
+                FileStatus::Parsed if entries.contains_key(&result.path) => changes.changed.push(result.path.clone()),
+                FileStatus::Parsed => changes.added.push(result.path.clone()),
+            }
+        }
+        let mut removed: Vec<String> = entries.keys().filter(|path| !walked.contains(*path)).cloned().collect();
+        removed.sort();
+        for path in &removed {
+            entries.remove(path);
+        }
+        changes.removed = removed;
+        for (result, entry, _) in &outcomes {
+            if let Some(entry) = entry {
+                entries.insert(result.path.clone(), entry.clone());
+            }
+        }
+        *cache = ScanCache {
+            entries: std::mem::take(&mut entries),
+            ..ScanCache::new(&root_str, mode.as_str(), walk_options)
+        };
+        changes
+    });
+
+    let mut file_results: Vec<FileResult> = outcomes.into_iter().map(|(result, _, _)| result).collect();
+    graph::resolve_imports(root, &mut file_results);
+    let dependency_graph = graph::build(&file_results);
+
     let symbol_count: usize = file_results.iter().map(|f| f.symbols.len()).sum();
     let elapsed_seconds = started.elapsed().as_secs_f64();
+    let budget_hit = files_to_scan.len() == max_files || time_budget_hit.load(Ordering::Relaxed);
 
     ScanResult {
-        root: root.to_string_lossy().to_string(),
-        scan_mode: match mode {
-            ScanMode::Summary => "summary".to_string(),
-            ScanMode::FileContext => "file_context".to_string(),
-            ScanMode::Full => "full".to_string(),
-        },
+        root: root_str,
+        scan_mode: mode.as_str().to_string(),
         files: file_results,
+        dependency_graph,
+        changes,
         diagnostics: DiagnosticsBlock {
             elapsed_seconds,
             file_count: files_to_scan.len(),
             symbol_count,
             budget_hit,
             errors: vec![],
+            skipped_ignored: walked_output.skipped_ignored,
+            skipped_too_large: walked_output.skipped_too_large,
+            skipped_binary: skipped_binary.into_inner(),
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn file_names(paths: &[String]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_cached_scan_reports_added_changed_and_removed() {
+        let tmp = std::env::temp_dir().join(format!("cart_engine_cache_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("a.py"), "def a(): pass\n").unwrap();
+        fs::write(tmp.join("b.py"), "def b(): pass\n").unwrap();
+        fs::write(tmp.join("c.py"), "def c(): pass\n").unwrap();
+
+        let run = |cache: &mut ScanCache| scan_with_cache(&tmp, ScanMode::FileContext, 100, 10.0, &WalkOptions::default(), Some(cache), None);
+
+        let mut cache = ScanCache::default();
+        let first = run(&mut cache);
+        let changes = first.changes.unwrap();
+        assert!(!changes.baseline);
+        assert_eq!(changes.added.len(), 3);
+        assert_eq!(cache.entries.len(), 3);
+
+        fs::write(tmp.join("b.py"), "def b(): pass\ndef b2(): pass\n").unwrap();
+        fs::remove_file(tmp.join("c.py")).unwrap();
+        fs::write(tmp.join("d.py"), "import b\n").unwrap();
+
+        let second = run(&mut cache);
+        let changes = second.changes.clone().unwrap();
+        assert!(changes.baseline);
+        assert_eq!(file_names(&changes.added), vec!["d.py"]);
+        assert_eq!(file_names(&changes.changed), vec!["b.py"]);
+        assert_eq!(file_names(&changes.removed), vec!["c.py"]);
+        assert_eq!(changes.reused, 1);
+
+        let b = second.files.iter().find(|f| f.path.ends_with("b.py")).unwrap();
+        assert_eq!(b.symbols.iter().filter(|s| s.kind == "function").count(), 2);
+        let d = second.files.iter().find(|f| f.path.ends_with("d.py")).unwrap();
+        assert_eq!(file_names(&d.imports[0].resolved), vec!["b.py"]);
+
+        let third = run(&mut cache).changes.unwrap();
+        assert!(third.added.is_empty() && third.changed.is_empty() && third.removed.is_empty());
+        assert_eq!(third.reused, 3);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_touched_but_identical_file_is_reused() {
+        let tmp = std::env::temp_dir().join(format!("cart_engine_touch_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("a.rs"), "fn a() {}\n").unwrap();
+
+        let mut cache = ScanCache::default();
+        scan_with_cache(&tmp, ScanMode::Summary, 100, 10.0, &WalkOptions::default(), Some(&mut cache), None);
+        let key = tmp.join("a.rs").to_string_lossy().to_string();
+        cache.entries.get_mut(&key).unwrap().mtime_ns = 1;
+
+        let result = scan_with_cache(&tmp, ScanMode::Summary, 100, 10.0, &WalkOptions::default(), Some(&mut cache), None);
+        assert_eq!(result.changes.unwrap().reused, 1);
+        assert_ne!(cache.entries[&key].mtime_ns, 1);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_binary_source_files_are_skipped() {
+        let tmp = std::env::temp_dir().join(format!("cart_engine_binary_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("real.ts"), "export const a = 1;\n").unwrap();
+        // An MPEG transport stream sharing the TypeScript extension.
+        fs::write(tmp.join("clip.ts"), [0x47u8, 0x40, 0x00, 0x10, 0x00, 0x00]).unwrap();
+
+        let result = scan(&tmp, ScanMode::Summary, 100, 10.0, None, None);
+        assert_eq!(file_names(&result.files.iter().map(|f| f.path.clone()).collect::<Vec<_>>()), vec!["real.ts"]);
+        assert_eq!(result.diagnostics.skipped_binary, 1);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+}
@@ -1,6 +1,8 @@
+use crate::parser::{spec_for_path, LANGUAGES};
 use std::path::Path;
 use std::str::FromStr;
 
+/// Extensions, ids and parsers live in [`crate::parser::LANGUAGES`].
 #[derive(Debug, Clone, PartialEq)]
 pub enum Language {
     TypeScript,
@@ -8,31 +10,25 @@ pub enum Language {
     Python,
     Rust,
     Sql,
+    Go,
+    CSharp,
+    Java,
+    Qml,
+    PowerShell,
     Unknown,
 }
 
 impl Language {
     pub fn as_str(&self) -> &'static str {
-        match self {
-            Language::TypeScript => "typescript",
-            Language::JavaScript => "javascript",
-            Language::Python => "python",
-            Language::Rust => "rust",
-            Language::Sql => "sql",
-            Language::Unknown => "unknown",
-        }
+        LANGUAGES
+            .iter()
+            .find(|spec| spec.language == *self)
+            .map_or("unknown", |spec| spec.id)
     }
 }
 
 pub fn detect_language(path: &Path) -> Language {
-    match path.extension().and_then(|e| e.to_str()) {
-        Some("ts") | Some("tsx") => Language::TypeScript,
-        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => Language::JavaScript,
-        Some("py") | Some("pyw") => Language::Python,
-        Some("rs") => Language::Rust,
-        Some("sql") => Language::Sql,
-        _ => Language::Unknown,
-    }
+    spec_for_path(path).map_or(Language::Unknown, |spec| spec.language.clone())
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -42,6 +38,16 @@ pub enum ScanMode {
     Full,
 }
 
+impl ScanMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanMode::Summary => "summary",
+            ScanMode::FileContext => "file_context",
+            ScanMode::Full => "full",
+        }
+    }
+}
+
 impl FromStr for ScanMode {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -67,6 +73,13 @@ mod tests {
         assert_eq!(detect_language(Path::new("foo.py")), Language::Python);
         assert_eq!(detect_language(Path::new("foo.rs")), Language::Rust);
         assert_eq!(detect_language(Path::new("foo.sql")), Language::Sql);
+        assert_eq!(detect_language(Path::new("foo.go")), Language::Go);
+        assert_eq!(detect_language(Path::new("Foo.cs")), Language::CSharp);
+        assert_eq!(detect_language(Path::new("Foo.java")), Language::Java);
+        assert_eq!(detect_language(Path::new("Main.qml")), Language::Qml);
+        assert_eq!(detect_language(Path::new("build.PS1")), Language::PowerShell);
+        assert_eq!(Language::CSharp.as_str(), "csharp");
+        assert_eq!(Language::Unknown.as_str(), "unknown");
         assert_eq!(detect_language(Path::new("foo.txt")), Language::Unknown);
     }
 
@@ -1,3 +1,4 @@
 pub mod engine;
 pub mod lang;
+pub mod patterns;
 pub mod walker;
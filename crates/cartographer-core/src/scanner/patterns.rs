@@ -0,0 +1,182 @@
+//! Gitignore-style path patterns.
+//!
+//! Supports the `.gitignore` syntax: `#` comments, `!` negation, a trailing
+//! `/` for directories only, a leading or middle `/` to anchor the pattern
+//! to its base directory (otherwise it matches at any depth), `*`, `?`,
+//! `[...]` classes and `**` across directories.  Paths are matched relative
+//! to the base directory with `/` separators.
+
+use regex::Regex;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+}
+
+impl PatternSet {
+    /// Parse the contents of an ignore file.
+    pub fn parse(text: &str) -> Self {
+        let mut set = PatternSet::default();
+        for line in text.lines() {
+            set.add(line);
+        }
+        set
+    }
+
+    pub fn from_patterns<S: AsRef<str>>(patterns: &[S]) -> Self {
+        let mut set = PatternSet::default();
+        for pattern in patterns {
+            set.add(pattern.as_ref());
+        }
+        set
+    }
+
+    /// Add one pattern line; blank lines and comments are skipped, as are
+    /// patterns that do not compile.
+    pub fn add(&mut self, line: &str) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let line = if line.ends_with("\\ ") { line } else { line.trim_end() };
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        if line.is_empty() {
+            return;
+        }
+        let anchored = line.contains('/');
+        let body = glob_to_regex(line.strip_prefix('/').unwrap_or(line));
+        let source = if anchored { format!("^{}$", body) } else { format!("^(?:.*/)?{}$", body) };
+        if let Ok(regex) = Regex::new(&source) {
+            self.patterns.push(Pattern { regex, negated, dir_only });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// `Some(true)` when the last pattern matching `rel` ignores it,
+    /// `Some(false)` when it is a negation, `None` when nothing matches.
+    pub fn matched(&self, rel: &str, is_dir: bool) -> Option<bool> {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|p| (is_dir || !p.dir_only) && p.regex.is_match(rel))
+            .map(|p| !p.negated)
+    }
+
+    /// Whether any pattern matches the file `rel` or one of its parent
+    /// directories, for include lists.  Negations are not consulted.
+    pub fn includes(&self, rel: &str) -> bool {
+        let matches = |candidate: &str, is_dir: bool| {
+            self.patterns
+                .iter()
+                .any(|p| !p.negated && (is_dir || !p.dir_only) && p.regex.is_match(candidate))
+        };
+        matches(rel, false) || rel.match_indices('/').any(|(i, _)| matches(&rel[..i], true))
+    }
+}
+
+/// `path` relative to `base` with `/` separators, or `None` when `path` is
+/// not under `base`.
+pub fn relative(path: &Path, base: &Path) -> Option<String> {
+    let rel = path.strip_prefix(base).ok()?;
+    let parts: Vec<_> = rel.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    Some(parts.join("/"))
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') && (i == 0 || chars[i - 1] == '/') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    out.push_str("(?:.*/)?");
+                    i += 3;
+                } else if i + 2 == chars.len() {
+                    out.push_str(".*");
+                    i += 2;
+                } else {
+                    out.push_str("[^/]*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().skip(1).position(|&c| c == ']') {
+                Some(offset) => {
+                    let end = i + 2 + offset;
+                    out.push('[');
+                    let mut class = &chars[i + 1..end];
+                    if let Some(('!' | '^', rest)) = class.split_first() {
+                        out.push('^');
+                        class = rest;
+                    }
+                    for &c in class {
+                        if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                            out.push('\\');
+                        }
+                        out.push(c);
+                    }
+                    out.push(']');
+                    i = end;
+                }
+                None => out.push_str("\\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                out.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_semantics() {
+        let set = PatternSet::parse("# comment\n*.log\n!keep.log\n/build/\ndocs/**/*.md\ncache/\n");
+        assert_eq!(set.matched("a/b/debug.log", false), Some(true));
+        assert_eq!(set.matched("a/keep.log", false), Some(false));
+        assert_eq!(set.matched("build", true), Some(true));
+        assert_eq!(set.matched("src/build", true), None, "anchored to the base");
+        assert_eq!(set.matched("cache", false), None, "directory-only");
+        assert_eq!(set.matched("src/cache", true), Some(true));
+        assert_eq!(set.matched("docs/a.md", false), Some(true));
+        assert_eq!(set.matched("docs/x/y/a.md", false), Some(true));
+        assert_eq!(set.matched("docs/a.txt", false), None);
+        assert_eq!(set.matched("main.rs", false), None);
+    }
+
+    #[test]
+    fn test_classes_and_includes() {
+        let set = PatternSet::from_patterns(&["gen_[!a]?.ts", "src/"]);
+        assert_eq!(set.matched("gen_b1.ts", false), Some(true));
+        assert_eq!(set.matched("gen_a1.ts", false), None);
+        assert!(set.includes("src/deep/file.rs"));
+        assert!(!set.includes("lib/file.rs"));
+    }
+}
@@ -1,40 +1,184 @@
+//! File discovery for a scan.
+//!
+//! Walks the root honouring, from highest precedence to lowest: the
+//! request's exclude globs, then `.cartographerignore`, `.ignore` and
+//! `.gitignore` files (deeper files override shallower ones, and those above
+//! the root count up to the enclosing git repository), then
+//! [`DEFAULT_EXCLUDES`].
+
+use crate::scanner::patterns::{relative, PatternSet};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-const EXCLUDE_DIRS: &[&str] = &[
-    ".git", "node_modules", ".venv", "venv", "env", ".env",
-    "__pycache__", ".mypy_cache", ".pytest_cache", ".ruff_cache",
-    "dist", "build", ".next", ".nuxt", "coverage", ".coverage",
-    ".turbo", "tmp", "temp", "logs", ".tox", ".nox",
-    "target", ".cargo", ".rustup",
-];
+/// Ignore files read in every directory, lowest precedence first.
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".cartographerignore"];
 
-const EXCLUDE_FILES: &[&str] = &[
+/// Built-in exclusions, in ignore-file syntax, so an ignore file can
+/// re-include any of them with `!`.  Build output folders only match at the
+/// root: a `build/` inside a source tree is usually source.
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    ".git", "node_modules", ".venv", "venv", "__pycache__", ".mypy_cache",
+    ".pytest_cache", ".ruff_cache", ".next", ".nuxt", ".turbo", ".tox", ".nox",
+    ".cargo", ".rustup",
+    "/dist/", "/build/", "/coverage/", "/.coverage", "/tmp/", "/temp/", "/logs/",
+    "/target/", "/env/", "/.env/",
     "package-lock.json", "yarn.lock", "pnpm-lock.yaml",
     "poetry.lock", "Cargo.lock", "bun.lockb",
 ];
 
-pub fn walk(
-    root: &Path,
-    include_extensions: Option<&[String]>,
-    extra_excludes: Option<&[String]>,
-) -> Vec<PathBuf> {
-    let mut results = Vec::new();
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Bytes inspected by [`looks_binary`], the same window git uses.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    /// Extensions to keep, with or without the leading dot.
+    pub include_extensions: Option<Vec<String>>,
+    /// Gitignore-style patterns relative to the root; when non-empty a file
+    /// is kept only if it or one of its directories matches.
+    pub include_globs: Option<Vec<String>>,
+    /// Gitignore-style patterns relative to the root, excluded regardless of
+    /// ignore files.
+    pub exclude_globs: Option<Vec<String>>,
+    /// Larger files are skipped; `None` means no limit.
+    pub max_file_bytes: Option<u64>,
+    pub use_ignore_files: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions {
+            include_extensions: None,
+            include_globs: None,
+            exclude_globs: None,
+            max_file_bytes: Some(DEFAULT_MAX_FILE_BYTES),
+            use_ignore_files: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WalkOutput {
+    pub files: Vec<PathBuf>,
+    /// Files and directories dropped by exclude globs, ignore files or the
+    /// defaults.  An ignored directory counts once.
+    pub skipped_ignored: usize,
+    pub skipped_too_large: usize,
+}
+
+/// Ignore rules for a walk.
+struct Rules {
+    root: PathBuf,
+    excludes: PatternSet,
+    defaults: PatternSet,
+    use_ignore_files: bool,
+    /// Ignore files above the root, nearest first, with the root's path
+    /// relative to their directory.
+    inherited: Vec<(String, Vec<PatternSet>)>,
+    /// Ignore files per directory inside the root, loaded on first use.
+    loaded: HashMap<PathBuf, Vec<PatternSet>>,
+}
+
+impl Rules {
+    fn new(root: &Path, options: &WalkOptions) -> Self {
+        let inherited = if options.use_ignore_files { inherited_ignore_files(root) } else { Vec::new() };
+        Rules {
+            root: root.to_path_buf(),
+            excludes: PatternSet::from_patterns(options.exclude_globs.as_deref().unwrap_or_default()),
+            defaults: PatternSet::from_patterns(DEFAULT_EXCLUDES),
+            use_ignore_files: options.use_ignore_files,
+            inherited,
+            loaded: HashMap::new(),
+        }
+    }
+
+    fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Some(rel) = relative(path, &self.root) else {
+            return false;
+        };
+        if self.excludes.matched(&rel, is_dir) == Some(true) {
+            return true;
+        }
+        if self.use_ignore_files {
+            for dir in path.ancestors().skip(1) {
+                let sets = self.loaded.entry(dir.to_path_buf()).or_insert_with(|| read_ignore_files(dir));
+                let dir_rel = relative(path, dir).unwrap_or_default();
+                if let Some(ignored) = sets.iter().rev().find_map(|set| set.matched(&dir_rel, is_dir)) {
+                    return ignored;
+                }
+                if dir == self.root {
+                    break;
+                }
+            }
+            for (prefix, sets) in &self.inherited {
+                let outer_rel = format!("{}/{}", prefix, rel);
+                if let Some(ignored) = sets.iter().rev().find_map(|set| set.matched(&outer_rel, is_dir)) {
+                    return ignored;
+                }
+            }
+        }
+        self.defaults.matched(&rel, is_dir) == Some(true)
+    }
+}
+
+fn read_ignore_files(dir: &Path) -> Vec<PatternSet> {
+    IGNORE_FILES
+        .iter()
+        .filter_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+        .map(|text| PatternSet::parse(&text))
+        .filter(|set| !set.is_empty())
+        .collect()
+}
+
+/// Ignore files between `root` and the root of the git repository that
+/// contains it.  Empty when `root` is not inside a repository.
+fn inherited_ignore_files(root: &Path) -> Vec<(String, Vec<PatternSet>)> {
+    let Ok(root) = root.canonicalize() else {
+        return Vec::new();
+    };
+    if root.join(".git").exists() {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    for dir in root.ancestors().skip(1) {
+        let sets = read_ignore_files(dir);
+        if !sets.is_empty() {
+            found.push((relative(&root, dir).unwrap_or_default(), sets));
+        }
+        if dir.join(".git").exists() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
+/// Whether `bytes` (the start of a file) look binary: git's heuristic of a
+/// NUL byte within the first 8000 bytes.
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+pub fn walk(root: &Path, options: &WalkOptions) -> WalkOutput {
+    let mut rules = Rules::new(root, options);
+    let includes = PatternSet::from_patterns(options.include_globs.as_deref().unwrap_or_default());
+    let mut skipped_ignored = 0;
+    let mut skipped_too_large = 0;
+    let mut files = Vec::new();
 
     let walker = WalkDir::new(root)
         .follow_links(false)
         .into_iter()
         .filter_entry(|e| {
-            let fname = e.file_name().to_string_lossy();
-            if e.file_type().is_dir() {
-                let excluded = EXCLUDE_DIRS.iter().any(|d| *d == fname.as_ref());
-                let extra_excluded = extra_excludes
-                    .map(|xs| xs.iter().any(|x| x == fname.as_ref()))
-                    .unwrap_or(false);
-                !excluded && !extra_excluded
-            } else {
-                true
+            if e.depth() == 0 {
+                return true;
+            }
+            let ignored = rules.is_ignored(e.path(), e.file_type().is_dir());
+            if ignored {
+                skipped_ignored += 1;
             }
+            !ignored
         });
 
     for entry in walker.flatten() {
@@ -42,25 +186,30 @@ pub fn walk(
             continue;
         }
         let path = entry.path();
-        let fname = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
-
-        // Skip excluded file names
-        if EXCLUDE_FILES.iter().any(|f| *f == fname.as_str()) {
-            continue;
-        }
 
         // Apply extension filter if given
-        if let Some(exts) = include_extensions {
+        if let Some(exts) = &options.include_extensions {
             let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
             if !exts.iter().any(|e| e.trim_start_matches('.') == ext) {
                 continue;
             }
         }
 
-        results.push(path.to_path_buf());
+        if !includes.is_empty() && !relative(path, root).is_some_and(|rel| includes.includes(&rel)) {
+            continue;
+        }
+
+        if let Some(limit) = options.max_file_bytes {
+            if entry.metadata().map(|m| m.len() > limit).unwrap_or(false) {
+                skipped_too_large += 1;
+                continue;
+            }
+        }
+
+        files.push(path.to_path_buf());
     }
 
-    results
+    WalkOutput { files, skipped_ignored, skipped_too_large }
 }
 
 #[cfg(test)]
@@ -76,10 +225,71 @@ mod tests {
         fs::write(tmp.join("node_modules").join("foo.ts"), "x").unwrap();
         fs::write(tmp.join("index.ts"), "x").unwrap();
 
-        let files = walk(&tmp, None, None);
+        let files = walk(&tmp, &WalkOptions::default()).files;
         assert!(!files.iter().any(|p| p.to_string_lossy().contains("node_modules")));
         assert!(files.iter().any(|p| p.ends_with("index.ts")));
 
         let _ = fs::remove_dir_all(&tmp);
     }
+
+    fn names(root: &Path, files: &[PathBuf]) -> Vec<String> {
+        let mut names: Vec<String> = files.iter().filter_map(|p| relative(p, root)).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_honours_ignore_files_and_nested_build_dirs() {
+        let tmp = std::env::temp_dir().join("cart_walker_ignore_test");
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join(".git")).unwrap();
+        fs::create_dir_all(tmp.join("build")).unwrap();
+        fs::create_dir_all(tmp.join("src/build")).unwrap();
+        fs::create_dir_all(tmp.join("src/gen")).unwrap();
+        fs::write(tmp.join(".gitignore"), "*.log\nsrc/gen/\n").unwrap();
+        fs::write(tmp.join("src/.ignore"), "!keep.log\n").unwrap();
+        fs::write(tmp.join(".cartographerignore"), "fixtures/**\n").unwrap();
+        fs::write(tmp.join("build/out.ts"), "x").unwrap();
+        fs::write(tmp.join("src/build/plugin.ts"), "x").unwrap();
+        fs::write(tmp.join("src/gen/api.ts"), "x").unwrap();
+        fs::write(tmp.join("src/debug.log"), "x").unwrap();
+        fs::write(tmp.join("src/keep.log"), "x").unwrap();
+        fs::create_dir_all(tmp.join("fixtures/a")).unwrap();
+        fs::write(tmp.join("fixtures/a/f.ts"), "x").unwrap();
+
+        let output = walk(&tmp, &WalkOptions::default());
+        assert_eq!(names(&tmp, &output.files), vec![".cartographerignore", ".gitignore", "src/.ignore", "src/build/plugin.ts", "src/keep.log"]);
+        // .git, build/, src/gen/, src/debug.log and fixtures/a/f.ts
+        assert_eq!(output.skipped_ignored, 5);
+
+        let unfiltered = walk(&tmp, &WalkOptions { use_ignore_files: false, ..WalkOptions::default() });
+        assert!(names(&tmp, &unfiltered.files).contains(&"src/gen/api.ts".to_string()));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_globs_and_size_limit() {
+        let tmp = std::env::temp_dir().join("cart_walker_globs_test");
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("src/nested")).unwrap();
+        fs::write(tmp.join("src/a.rs"), "x").unwrap();
+        fs::write(tmp.join("src/nested/b.rs"), "x").unwrap();
+        fs::write(tmp.join("src/nested/b_generated.rs"), "x").unwrap();
+        fs::write(tmp.join("src/big.rs"), "x".repeat(64)).unwrap();
+        fs::write(tmp.join("top.rs"), "x").unwrap();
+
+        let options = WalkOptions {
+            include_globs: Some(vec!["src/".to_string()]),
+            exclude_globs: Some(vec!["*_generated.rs".to_string()]),
+            max_file_bytes: Some(16),
+            ..WalkOptions::default()
+        };
+        let output = walk(&tmp, &options);
+        assert_eq!(names(&tmp, &output.files), vec!["src/a.rs", "src/nested/b.rs"]);
+        assert_eq!(output.skipped_ignored, 1);
+        assert_eq!(output.skipped_too_large, 1);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
 }
@@ -17,20 +17,13 @@ fn binary_path() -> std::path::PathBuf {
     }
 }
 
-fn run_scan(root: &str, scan_mode: &str) -> Value {
+/// Send one request line to the binary and parse its response.
+fn run_cartographer(input: Value) -> Value {
     let bin = binary_path();
     if !bin.exists() {
         panic!("Binary not found at {:?}. Run cargo build first.", bin);
     }
 
-    let input = serde_json::json!({
-        "action": "scan",
-        "root": root,
-        "scan_mode": scan_mode,
-        "max_files": 100,
-        "max_seconds": 10.0
-    });
-
     let mut child = Command::new(&bin)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -48,6 +41,17 @@ fn run_scan(root: &str, scan_mode: &str) -> Value {
     serde_json::from_str(stdout.trim()).expect("Invalid JSON from binary")
 }
 
+fn run_scan(root: &str, scan_mode: &str) -> Value {
+    run_cartographer(serde_json::json!({
+        "action": "scan",
+        "root": root,
+        "scan_mode": scan_mode,
+        "max_files": 100,
+        "max_seconds": 10.0,
+        "cache": false
+    }))
+}
+
 #[test]
 fn test_schema_contract_ok_and_required_fields() {
     let root = env!("CARGO_MANIFEST_DIR");
@@ -93,3 +97,108 @@ fn test_node_modules_excluded() {
 
     let _ = fs::remove_dir_all(&tmp);
 }
+
+#[test]
+fn test_deps_reports_reverse_dependencies_and_cycles() {
+    use std::fs;
+    let tmp = std::env::temp_dir().join("cart_integ_deps_test");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(tmp.join("src")).unwrap();
+    fs::write(tmp.join("src").join("config.ts"), "import { b } from './b';\nexport const c = 1;").unwrap();
+    fs::write(tmp.join("src").join("a.ts"), "import { c } from './config';").unwrap();
+    fs::write(tmp.join("src").join("b.ts"), "import { c } from './config.js';\nexport const b = 2;").unwrap();
+
+    let result = run_cartographer(serde_json::json!({
+        "action": "deps",
+        "root": tmp.to_string_lossy(),
+        "target": "src/config.ts",
+        "transitive": true,
+        "cache": false
+    }));
+
+    assert_eq!(result["ok"], true, "deps must succeed: {}", result);
+    let names = |key: &str| -> Vec<String> {
+        result["result"][key].as_array().unwrap().iter()
+            .map(|p| Path::new(p.as_str().unwrap()).file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    };
+    assert_eq!(names("dependents"), vec!["a.ts", "b.ts"]);
+    assert_eq!(names("dependencies"), vec!["b.ts"]);
+    assert_eq!(result["result"]["cycles"].as_array().unwrap().len(), 1);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_second_scan_reuses_cache_and_reports_changes() {
+    use std::fs;
+    let tmp = std::env::temp_dir().join("cart_integ_cache_test");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(tmp.join("ws")).unwrap();
+    fs::write(tmp.join("ws").join("a.ts"), "export const a = 1;").unwrap();
+    fs::write(tmp.join("ws").join("b.ts"), "export const b = 2;").unwrap();
+
+    let scan = || {
+        run_cartographer(serde_json::json!({
+            "action": "scan",
+            "root": tmp.join("ws").to_string_lossy(),
+            "scan_mode": "file_context",
+            "cache_path": tmp.join("cache.json").to_string_lossy()
+        }))
+    };
+
+    let first = scan();
+    assert_eq!(first["result"]["changes"]["baseline"], false);
+    assert!(tmp.join("cache.json").exists(), "cache file must be written");
+
+    fs::write(tmp.join("ws").join("b.ts"), "export const b = 3; export const c = 4;").unwrap();
+    let second = scan();
+    let changes = &second["result"]["changes"];
+    assert_eq!(changes["baseline"], true);
+    assert_eq!(changes["reused"], 1);
+    assert_eq!(changes["changed"].as_array().unwrap().len(), 1);
+    assert_eq!(second["result"]["files"].as_array().unwrap().len(), 2);
+
+    let _ = fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_serve_answers_requests_until_stdin_closes() {
+    use std::fs;
+    use std::io::{BufRead, BufReader};
+    let tmp = std::env::temp_dir().join("cart_integ_serve_test");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(&tmp).unwrap();
+    fs::write(tmp.join("a.py"), "def alpha():\n    pass\n").unwrap();
+
+    let mut child = Command::new(binary_path())
+        .arg("--serve")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn binary");
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    // The daemon answers while stdin is still open.
+    let request = serde_json::json!({
+        "id": 7,
+        "action": "find_symbol",
+        "root": tmp.to_string_lossy(),
+        "name": "alpha",
+        "cache": false
+    });
+    writeln!(stdin, "{}", request).unwrap();
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let response: Value = serde_json::from_str(line.trim()).expect("Invalid JSON from daemon");
+    assert_eq!(response["id"], 7);
+    assert_eq!(response["ok"], true);
+    assert_eq!(response["result"][0]["symbol"]["name"], "alpha");
+
+    drop(stdin);
+    let status = child.wait().expect("Failed to wait for daemon");
+    assert!(status.success(), "daemon must exit cleanly on stdin EOF");
+
+    let _ = fs::remove_dir_all(&tmp);
+}
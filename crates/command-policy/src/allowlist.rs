@@ -0,0 +1,112 @@
+//! The `terminal-allowlist.json` file the terminal front ends and the
+//! server-side `terminal-auth.ts` share.
+//!
+//! File location: `{data_root}/default/terminal-allowlist.json`
+//! File format  : `{ "patterns": ["...", ...], "rules": [...], "updated_at": "..." }`
+
+use crate::{Policy, Rule};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const ALLOWLIST_FILENAME: &str = "terminal-allowlist.json";
+pub const DEFAULT_WORKSPACE_ID: &str = "default";
+
+/// Legacy allowlist patterns plus structured policy rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowlistFile {
+    pub patterns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+impl AllowlistFile {
+    pub fn path(data_root: &Path) -> PathBuf {
+        data_root
+            .join(DEFAULT_WORKSPACE_ID)
+            .join(ALLOWLIST_FILENAME)
+    }
+
+    /// Read the file under `data_root` with its patterns trimmed and
+    /// de-duplicated; `None` when it is missing, unreadable or empty.
+    pub fn load(data_root: &Path) -> Option<Self> {
+        let raw = std::fs::read_to_string(Self::path(data_root)).ok()?;
+        let mut file: AllowlistFile = serde_json::from_str(&raw).ok()?;
+        file.patterns = sanitize_patterns(file.patterns);
+        if file.patterns.is_empty() && file.rules.is_empty() {
+            None
+        } else {
+            Some(file)
+        }
+    }
+
+    /// Write the file under `data_root`, stamping `updated_at`.
+    pub fn save(&self, data_root: &Path) -> Result<(), String> {
+        let path = Self::path(data_root);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create allowlist directory: {e}"))?;
+        }
+        let secs_since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let file = AllowlistFile {
+            updated_at: Some(secs_since_epoch.to_string()),
+            ..self.clone()
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("failed to serialize allowlist: {e}"))?;
+        std::fs::write(&path, json).map_err(|e| format!("failed to write allowlist file: {e}"))
+    }
+
+    /// The built-in rules, this file's rules and its patterns as one policy.
+    pub fn policy(&self) -> Policy {
+        Policy::from_allowlist(&self.patterns, self.rules.clone())
+    }
+}
+
+/// Trim patterns and drop empty and case-insensitively repeated ones.
+pub fn sanitize_patterns(raw: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut result = Vec::new();
+    for value in raw {
+        let trimmed = value.trim().to_string();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if seen.insert(trimmed.to_ascii_lowercase()) {
+            result.push(trimmed);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+
+    #[test]
+    fn save_and_load_keep_rules() {
+        let root =
+            std::env::temp_dir().join(format!("command-policy-allowlist-{}", std::process::id()));
+        let file = AllowlistFile {
+            patterns: vec![" git status ".into(), "GIT STATUS".into(), "".into()],
+            rules: vec![Rule::new("team:no-curl", Action::Deny, "curl")],
+            updated_at: None,
+        };
+        file.save(&root).unwrap();
+        let loaded = AllowlistFile::load(&root).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        assert_eq!(loaded.patterns, vec!["git status"]);
+        assert_eq!(loaded.rules, file.rules);
+        assert!(loaded.updated_at.is_some());
+        assert_eq!(
+            loaded.policy().rules().last().unwrap().id,
+            "allowlist:git status"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Command policy shared by the interactive-terminal front ends.
+//!
+//! Instead of matching a command line against string prefixes, the line is
+//! tokenized for the shell it will run in ([`tokenize`]), split into the simple
+//! commands it would execute, and each one is checked against structured
+//! allow / ask / deny [`Rule`]s on program, subcommand, flags and path
+//! arguments.  [`Policy::evaluate`] returns a [`Decision`] naming the rule that
+//! decided, so callers can show *why* a command was auto-approved, held for
+//! approval or refused.
+
+pub mod allowlist;
+mod policy;
+mod rule;
+mod tokenize;
+
+pub use allowlist::AllowlistFile;
+pub use policy::{
+    builtin_rules, Decision, Policy, RULE_EXPANSION, RULE_NO_MATCH, RULE_REDIRECT,
+    RULE_SUBSTITUTION, RULE_UNPARSEABLE,
+};
+pub use rule::{Action, EvalContext, PathScope, Rule};
+pub use tokenize::{tokenize, ParsedCommand, Redirect, Segment, TokenizeError};
+
+/// Shell dialect a command line is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shell {
+    /// `sh`, `bash` and compatible shells.
+    Posix,
+    /// Windows PowerShell and `pwsh`.
+    PowerShell,
+    /// `cmd.exe`.
+    Cmd,
+}
+
+impl Shell {
+    /// The dialect of the platform's default terminal profile.
+    pub fn host_default() -> Self {
+        if cfg!(windows) {
+            Shell::PowerShell
+        } else {
+            Shell::Posix
+        }
+    }
+}
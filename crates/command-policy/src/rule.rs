@@ -0,0 +1,581 @@
+//! Policy rules and how a single rule is matched against a simple command.
+
+use crate::tokenize::Segment;
+use crate::Shell;
+use serde::{Deserialize, Serialize};
+
+/// What a matching rule decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Run without asking.
+    Allow,
+    /// Show the approval dialog.
+    Ask,
+    /// Refuse outright.
+    Deny,
+}
+
+impl Action {
+    /// Higher wins when several segments of one command line disagree.
+    pub(crate) fn severity(self) -> u8 {
+        match self {
+            Action::Allow => 0,
+            Action::Ask => 1,
+            Action::Deny => 2,
+        }
+    }
+}
+
+/// Restriction on the path arguments of a command.
+///
+/// Roots may use the placeholders `${workspace}` and `${cwd}`; relative paths
+/// are resolved against the request's working directory.  An argument that
+/// expands a variable (`$HOME`, `${HOME}/x`, `$env:USERPROFILE`,
+/// `%USERPROFILE%`) could name any path, so it never lies within a root; it
+/// counts as outside for ask rules but is not enough for a deny rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathScope {
+    /// Every path argument lies under one of the roots.
+    Within(Vec<String>),
+    /// At least one path argument lies outside all of the roots.
+    Outside(Vec<String>),
+}
+
+/// One allow / ask / deny rule.
+///
+/// A rule matches a simple command when every condition it sets holds:
+///
+/// * `program` — the program name, compared case-insensitively without
+///   directory or `.exe`/`.cmd`/`.bat`/`.com`/`.ps1` suffix; `*` matches any.
+///   Allow rules only match a bare program name (not `./git` or
+///   `/tmp/x/git`) with no leading `NAME=value` assignments, since either can
+///   change what actually runs.
+/// * `subcommand` — for allow rules, the first arguments must be exactly
+///   these words; for ask/deny rules they must appear, in order, among the
+///   non-flag arguments (so `git -C dir push` still matches `push`).
+/// * `flags` — at least one of these flags is present.
+/// * `all_flags` — every entry is present; an entry may list alternatives
+///   separated by `|`, e.g. `"-r|-R|--recursive"`.
+/// * `arg_prefixes` — some non-flag argument other than the subcommand
+///   starts with one of these, e.g. `+` for a forced git refspec.
+/// * `paths` — see [`PathScope`].
+///
+/// Flags are recognised per shell: POSIX short clusters (`-rf` contains `-r`)
+/// and `--long=value`; PowerShell case-insensitive parameter prefixes
+/// (`-Rec` is `-Recurse`) and `-Param:value`; cmd `/s`, `/s:value` and
+/// run-together switches like `/s/q`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    pub action: Action,
+    pub program: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subcommand: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub all_flags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arg_prefixes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paths: Option<PathScope>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Request details that rules may refer to.
+#[derive(Debug, Clone, Copy)]
+pub struct EvalContext<'a> {
+    pub shell: Shell,
+    pub working_directory: &'a str,
+    pub workspace: &'a str,
+}
+
+impl Rule {
+    pub fn new(id: impl Into<String>, action: Action, program: impl Into<String>) -> Self {
+        Rule {
+            id: id.into(),
+            action,
+            program: program.into(),
+            subcommand: Vec::new(),
+            flags: Vec::new(),
+            all_flags: Vec::new(),
+            arg_prefixes: Vec::new(),
+            paths: None,
+            reason: String::new(),
+        }
+    }
+
+    pub fn subcommand(mut self, words: &[&str]) -> Self {
+        self.subcommand = words.iter().map(|w| w.to_string()).collect();
+        self
+    }
+
+    pub fn flags(mut self, flags: &[&str]) -> Self {
+        self.flags = flags.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    pub fn all_flags(mut self, flags: &[&str]) -> Self {
+        self.all_flags = flags.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    pub fn arg_prefixes(mut self, prefixes: &[&str]) -> Self {
+        self.arg_prefixes = prefixes.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    pub fn paths(mut self, scope: PathScope) -> Self {
+        self.paths = Some(scope);
+        self
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = reason.into();
+        self
+    }
+
+    /// Convert a legacy allowlist pattern (`"git diff"`, `"npm run *"`) into
+    /// an allow rule: the first word is the program and the rest, minus any
+    /// trailing `*`, the required leading arguments.
+    pub fn from_pattern(pattern: &str) -> Option<Self> {
+        let mut words: Vec<&str> = pattern.split_whitespace().collect();
+        if let Some(last) = words.last_mut() {
+            *last = last.trim_end_matches('*');
+            if last.is_empty() {
+                words.pop();
+            }
+        }
+        let (program, rest) = words.split_first()?;
+        Some(
+            Rule::new(
+                format!("allowlist:{}", pattern.trim()),
+                Action::Allow,
+                *program,
+            )
+            .subcommand(rest)
+            .reason(format!("matches allowlist pattern `{}`", pattern.trim())),
+        )
+    }
+
+    pub(crate) fn matches(&self, segment: &Segment, ctx: &EvalContext<'_>) -> bool {
+        let Some(program) = segment.program() else {
+            return false;
+        };
+        let strict = self.action == Action::Allow;
+        if strict && (!segment.assignments.is_empty() || program.contains(['/', '\\'])) {
+            return false;
+        }
+        if self.program != "*" && normalize_program(program) != normalize_program(&self.program) {
+            return false;
+        }
+
+        let args = segment.args();
+        let flag_end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+        let is_flag = |i: usize, arg: &str| i < flag_end && looks_like_flag(arg, ctx.shell);
+
+        if !self.subcommand.is_empty() {
+            let ok = if strict {
+                args.len() >= self.subcommand.len()
+                    && args
+                        .iter()
+                        .zip(&self.subcommand)
+                        .all(|(a, s)| words_equal(a, s, ctx.shell))
+            } else {
+                let mut wanted = self.subcommand.iter().peekable();
+                for (i, arg) in args.iter().enumerate() {
+                    if is_flag(i, arg) {
+                        continue;
+                    }
+                    if wanted
+                        .peek()
+                        .is_some_and(|w| words_equal(arg, w, ctx.shell))
+                    {
+                        wanted.next();
+                    }
+                }
+                wanted.peek().is_none()
+            };
+            if !ok {
+                return false;
+            }
+        }
+
+        let present: Vec<&str> = args[..flag_end]
+            .iter()
+            .filter(|a| looks_like_flag(a, ctx.shell))
+            .map(String::as_str)
+            .collect();
+        let has = |wanted: &str| {
+            wanted
+                .split('|')
+                .any(|alt| present.iter().any(|p| flag_matches(p, alt, ctx.shell)))
+        };
+        if !self.flags.is_empty() && !self.flags.iter().any(|f| has(f)) {
+            return false;
+        }
+        if !self.all_flags.iter().all(|f| has(f)) {
+            return false;
+        }
+
+        if !self.arg_prefixes.is_empty() {
+            let skip = if strict { self.subcommand.len() } else { 0 };
+            let found = args.iter().enumerate().skip(skip).any(|(i, a)| {
+                !is_flag(i, a)
+                    && (strict || !self.is_subcommand_word(a, ctx.shell))
+                    && self.arg_prefixes.iter().any(|p| a.starts_with(p.as_str()))
+            });
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some(scope) = &self.paths {
+            let skip = if strict { self.subcommand.len() } else { 0 };
+            let paths: Vec<Option<String>> = args
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .filter(|(i, a)| {
+                    !is_flag(*i, a.as_str()) && (strict || !self.is_subcommand_word(a, ctx.shell))
+                })
+                .map(|(_, a)| {
+                    (!has_expansion(a, ctx.shell))
+                        .then(|| resolve(ctx.working_directory, a, ctx.shell))
+                })
+                .collect();
+            let within = |path: &str, roots: &[String]| {
+                roots
+                    .iter()
+                    .filter_map(|r| expand_root(r, ctx))
+                    .any(|root| is_under(path, &root))
+            };
+            let ok = match scope {
+                PathScope::Within(roots) => paths
+                    .iter()
+                    .all(|p| p.as_deref().is_some_and(|p| within(p, roots))),
+                PathScope::Outside(roots) => paths.iter().any(|p| match p {
+                    Some(p) => !within(p, roots),
+                    None => self.action == Action::Ask,
+                }),
+            };
+            if !ok {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn is_subcommand_word(&self, arg: &str, shell: Shell) -> bool {
+        self.subcommand.iter().any(|s| words_equal(arg, s, shell))
+    }
+}
+
+fn words_equal(a: &str, b: &str, shell: Shell) -> bool {
+    match shell {
+        Shell::Posix => a == b,
+        Shell::PowerShell | Shell::Cmd => a.eq_ignore_ascii_case(b),
+    }
+}
+
+/// Basename, lower-cased, without a Windows executable suffix.
+pub(crate) fn normalize_program(program: &str) -> String {
+    let base = program
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(program)
+        .to_ascii_lowercase();
+    for ext in [".exe", ".cmd", ".bat", ".com", ".ps1"] {
+        if let Some(stem) = base.strip_suffix(ext) {
+            if !stem.is_empty() {
+                return stem.to_string();
+            }
+        }
+    }
+    base
+}
+
+/// Whether `arg` expands a variable when the shell runs it, so its value is
+/// not known from the command line.
+pub(crate) fn has_expansion(arg: &str, shell: Shell) -> bool {
+    match shell {
+        Shell::Posix => arg.contains('$'),
+        Shell::PowerShell => arg.contains('$') || arg.starts_with('@'),
+        Shell::Cmd => arg.contains('%') || arg.matches('!').count() >= 2,
+    }
+}
+
+fn looks_like_flag(arg: &str, shell: Shell) -> bool {
+    match shell {
+        Shell::Posix | Shell::PowerShell => arg.len() > 1 && arg.starts_with('-'),
+        Shell::Cmd => {
+            arg.len() > 1
+                && (arg.starts_with('/') && !arg[1..].contains(['\\', '.']) || arg.starts_with('-'))
+        }
+    }
+}
+
+/// Whether the argument `present` sets the rule flag `wanted`.
+fn flag_matches(present: &str, wanted: &str, shell: Shell) -> bool {
+    match shell {
+        Shell::Posix => {
+            if let Some(long) = wanted.strip_prefix("--") {
+                present
+                    .strip_prefix("--")
+                    .is_some_and(|p| p == long || p.starts_with(&format!("{long}=")))
+            } else if let Some(short) = wanted.strip_prefix('-') {
+                present == wanted
+                    || (short.chars().count() == 1
+                        && !present.starts_with("--")
+                        && present[1..].contains(short))
+            } else {
+                present == wanted
+            }
+        }
+        Shell::PowerShell => {
+            let name = present.split(':').next().unwrap_or(present);
+            name.len() > 1
+                && wanted
+                    .get(..name.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(name))
+        }
+        Shell::Cmd => {
+            let wanted = wanted.to_ascii_lowercase();
+            let wanted = wanted.trim_start_matches(['/', '-']);
+            present
+                .to_ascii_lowercase()
+                .split(['/', '-'])
+                .filter(|s| !s.is_empty())
+                .any(|s| s == wanted || s.split(':').next() == Some(wanted))
+        }
+    }
+}
+
+fn expand_root(root: &str, ctx: &EvalContext<'_>) -> Option<String> {
+    let expanded = root
+        .replace("${workspace}", ctx.workspace)
+        .replace("${cwd}", ctx.working_directory);
+    if expanded.trim().is_empty() || (root.contains("${") && !is_absolute(&expanded)) {
+        return None;
+    }
+    Some(resolve(ctx.working_directory, &expanded, ctx.shell))
+}
+
+fn is_absolute(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with('~')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// Lexically resolve `path` against `base`, collapsing `.` and `..`.
+/// Windows shells compare paths case-insensitively, so their paths are
+/// lower-cased here.
+fn resolve(base: &str, path: &str, shell: Shell) -> String {
+    let path = path.replace('\\', "/");
+    let joined = if is_absolute(&path) || base.trim().is_empty() {
+        path
+    } else {
+        format!("{}/{}", base.replace('\\', "/"), path)
+    };
+    let (prefix, rest) = match joined.as_bytes() {
+        [d, b':', ..] if d.is_ascii_alphabetic() => (joined[..2].to_string(), &joined[2..]),
+        _ if joined.starts_with('/') => (String::new(), joined.as_str()),
+        _ => (String::new(), joined.as_str()),
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            p => parts.push(p),
+        }
+    }
+    let leading = if joined.starts_with('~') { "" } else { "/" };
+    let resolved = format!("{prefix}{leading}{}", parts.join("/"));
+    match shell {
+        Shell::Posix => resolved,
+        Shell::PowerShell | Shell::Cmd => resolved.to_ascii_lowercase(),
+    }
+}
+
+fn is_under(path: &str, root: &str) -> bool {
+    let root = root.trim_end_matches('/');
+    path == root
+        || path
+            .strip_prefix(root)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenize::tokenize;
+
+    fn ctx(shell: Shell) -> EvalContext<'static> {
+        EvalContext {
+            shell,
+            working_directory: "/repo/src",
+            workspace: "/repo",
+        }
+    }
+
+    fn matches(rule: &Rule, cmd: &str, shell: Shell) -> bool {
+        let parsed = tokenize(cmd, shell).unwrap();
+        rule.matches(&parsed.segments[0], &ctx(shell))
+    }
+
+    #[test]
+    fn from_pattern_strips_trailing_wildcard() {
+        let rule = Rule::from_pattern("npm run *").unwrap();
+        assert_eq!(rule.program, "npm");
+        assert_eq!(rule.subcommand, vec!["run"]);
+        assert_eq!(rule.id, "allowlist:npm run *");
+        assert_eq!(
+            Rule::from_pattern("ls*").unwrap().subcommand,
+            Vec::<String>::new()
+        );
+        assert!(Rule::from_pattern("  ").is_none());
+    }
+
+    #[test]
+    fn allow_rules_need_leading_subcommand_and_bare_program() {
+        let rule = Rule::from_pattern("git diff").unwrap();
+        assert!(matches(&rule, "git diff --stat", Shell::Posix));
+        assert!(matches(&rule, "git.exe diff", Shell::PowerShell));
+        assert!(!matches(&rule, "git -C x diff", Shell::Posix));
+        assert!(!matches(&rule, "./git diff", Shell::Posix));
+        assert!(!matches(&rule, "GIT_PAGER=x git diff", Shell::Posix));
+    }
+
+    #[test]
+    fn deny_rules_find_subcommand_among_positionals() {
+        let rule = Rule::new("t", Action::Deny, "git")
+            .subcommand(&["push"])
+            .flags(&["--force", "-f"]);
+        assert!(matches(
+            &rule,
+            "git -C /x push origin main --force",
+            Shell::Posix
+        ));
+        assert!(matches(&rule, "/usr/bin/git push -f", Shell::Posix));
+        assert!(matches(&rule, "git push --force=yes", Shell::Posix));
+        assert!(!matches(&rule, "git push origin", Shell::Posix));
+        assert!(!matches(&rule, "git push -- --force", Shell::Posix));
+    }
+
+    #[test]
+    fn argument_prefixes() {
+        let rule = Rule::new("t", Action::Deny, "git")
+            .subcommand(&["push"])
+            .arg_prefixes(&["+"]);
+        assert!(matches(&rule, "git push origin +main", Shell::Posix));
+        assert!(matches(&rule, "git -C x push origin +a:b", Shell::Posix));
+        assert!(!matches(&rule, "git push origin main", Shell::Posix));
+    }
+
+    #[test]
+    fn posix_short_flag_clusters() {
+        let rule =
+            Rule::new("t", Action::Deny, "rm").all_flags(&["-r|-R|--recursive", "-f|--force"]);
+        assert!(matches(&rule, "rm -rf x", Shell::Posix));
+        assert!(matches(&rule, "rm -f -R x", Shell::Posix));
+        assert!(matches(&rule, "rm --recursive --force x", Shell::Posix));
+        assert!(!matches(&rule, "rm -r x", Shell::Posix));
+    }
+
+    #[test]
+    fn powershell_parameter_prefixes() {
+        let rule = Rule::new("t", Action::Deny, "Remove-Item").all_flags(&["-Recurse", "-Force"]);
+        assert!(matches(&rule, "remove-item -rec -fo x", Shell::PowerShell));
+        assert!(matches(
+            &rule,
+            "Remove-Item x -Recurse:$true -Force",
+            Shell::PowerShell
+        ));
+        assert!(!matches(&rule, "Remove-Item x -Recurse", Shell::PowerShell));
+    }
+
+    #[test]
+    fn cmd_switches() {
+        let rule = Rule::new("t", Action::Deny, "rd").flags(&["/s"]);
+        assert!(matches(&rule, "RD /S /Q build", Shell::Cmd));
+        assert!(matches(&rule, "rd /q/s build", Shell::Cmd));
+        assert!(!matches(&rule, "rd build", Shell::Cmd));
+    }
+
+    #[test]
+    fn path_scopes() {
+        let outside = Rule::new("t", Action::Ask, "cat")
+            .paths(PathScope::Outside(vec!["${workspace}".to_string()]));
+        assert!(!matches(
+            &outside,
+            "cat main.rs ../Cargo.toml",
+            Shell::Posix
+        ));
+        assert!(matches(&outside, "cat ../../etc/passwd", Shell::Posix));
+        assert!(matches(&outside, "cat /etc/passwd", Shell::Posix));
+        assert!(matches(&outside, "cat ~/.ssh/id_rsa", Shell::Posix));
+        assert!(!matches(&outside, "cat /repo2/../repo/a", Shell::Posix));
+
+        let within = Rule::new("t", Action::Allow, "type")
+            .paths(PathScope::Within(vec!["${workspace}".to_string()]));
+        let win = EvalContext {
+            shell: Shell::Cmd,
+            working_directory: "C:\\Repo\\src",
+            workspace: "C:\\Repo",
+        };
+        let seg = |cmd: &str| tokenize(cmd, Shell::Cmd).unwrap().segments.remove(0);
+        assert!(within.matches(&seg("type main.rs ..\\README.md"), &win));
+        assert!(within.matches(&seg("type c:\\repo\\x.txt"), &win));
+        assert!(!within.matches(&seg("type C:\\Windows\\win.ini"), &win));
+    }
+
+    #[test]
+    fn variable_arguments_are_unknown_paths() {
+        let within = Rule::new("t", Action::Allow, "cat")
+            .paths(PathScope::Within(vec!["${workspace}".to_string()]));
+        let outside_ask = Rule::new("t", Action::Ask, "cat")
+            .paths(PathScope::Outside(vec!["${workspace}".to_string()]));
+        let outside_deny = Rule::new("t", Action::Deny, "rm")
+            .paths(PathScope::Outside(vec!["${workspace}".to_string()]));
+        for cmd in ["cat $HOME", "cat ${HOME}/x", "cat $HOME/.ssh/id_rsa"] {
+            assert!(!matches(&within, cmd, Shell::Posix), "{cmd}");
+            assert!(matches(&outside_ask, cmd, Shell::Posix), "{cmd}");
+        }
+        assert!(!matches(&outside_deny, "rm -r $HOME", Shell::Posix));
+        assert!(!matches(
+            &within,
+            "cat $env:USERPROFILE\\x",
+            Shell::PowerShell
+        ));
+        assert!(matches(&outside_ask, "cat $HOME", Shell::PowerShell));
+        assert!(!matches(&within, "cat %USERPROFILE%\\x", Shell::Cmd));
+        assert!(matches(&outside_ask, "cat %USERPROFILE%", Shell::Cmd));
+        assert!(matches(&within, "cat main.rs", Shell::Posix));
+    }
+
+    #[test]
+    fn rules_roundtrip_through_json() {
+        let rule = Rule::new("no-force-push", Action::Deny, "git")
+            .subcommand(&["push"])
+            .flags(&["--force"])
+            .paths(PathScope::Within(vec!["${workspace}".to_string()]))
+            .reason("rewrites remote history");
+        let json = serde_json::to_string(&rule).unwrap();
+        assert!(json.contains("\"action\":\"deny\""));
+        assert!(json.contains("\"within\""));
+        assert_eq!(serde_json::from_str::<Rule>(&json).unwrap(), rule);
+
+        let minimal: Rule =
+            serde_json::from_str(r#"{"id":"x","action":"ask","program":"curl"}"#).unwrap();
+        assert_eq!(minimal, Rule::new("x", Action::Ask, "curl"));
+    }
+}
@@ -0,0 +1,790 @@
+//! Shell-aware splitting of a command line into simple commands.
+//!
+//! This is not a full shell parser.  It understands enough of each dialect —
+//! quoting, escapes, command separators, redirections and substitutions — to
+//! tell which programs a line would run and with which arguments.  Anything it
+//! cannot follow is reported as an error so the caller can fall back to asking.
+
+use crate::Shell;
+use std::fmt;
+
+/// A command line split into the simple commands it would execute.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedCommand {
+    /// Every simple command, in source order, regardless of whether it was
+    /// joined by `;`, `&&`, `||`, `|` or `&`.
+    pub segments: Vec<Segment>,
+    /// The line contains a command or process substitution (`$(..)`, backticks,
+    /// `<(..)`, PowerShell sub-expressions and script blocks).  Whatever runs
+    /// inside cannot be judged from the outer words.
+    pub has_substitution: bool,
+}
+
+/// One simple command: its words (program first) and its redirections.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Segment {
+    /// Leading `NAME=value` environment assignments (POSIX only).
+    pub assignments: Vec<String>,
+    pub words: Vec<String>,
+    pub redirects: Vec<Redirect>,
+}
+
+impl Segment {
+    /// The program being run, if any.
+    pub fn program(&self) -> Option<&str> {
+        self.words.first().map(String::as_str)
+    }
+
+    /// Arguments after the program.
+    pub fn args(&self) -> &[String] {
+        self.words.get(1..).unwrap_or(&[])
+    }
+}
+
+/// A redirection such as `> out.txt` or `2>&1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// The operator including any fd prefix, e.g. `>`, `2>>`, `&>`, `>&`.
+    pub op: String,
+    pub target: String,
+}
+
+impl Redirect {
+    /// Whether this redirection can create or modify a file.
+    pub fn writes_file(&self) -> bool {
+        if !self.op.contains('>') {
+            return false;
+        }
+        if self.op.ends_with('&') && (self.target == "-" || is_all_digits(&self.target)) {
+            return false;
+        }
+        !is_null_device(&self.target)
+    }
+}
+
+fn is_null_device(target: &str) -> bool {
+    target == "/dev/null"
+        || target.eq_ignore_ascii_case("nul")
+        || target.eq_ignore_ascii_case("$null")
+}
+
+fn is_all_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Why a command line could not be tokenized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenizeError {
+    UnterminatedQuote(char),
+    UnterminatedSubstitution,
+    MissingRedirectTarget(String),
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenizeError::UnterminatedQuote(q) => write!(f, "unterminated {q} quote"),
+            TokenizeError::UnterminatedSubstitution => write!(f, "unterminated substitution"),
+            TokenizeError::MissingRedirectTarget(op) => {
+                write!(f, "redirection `{op}` has no target")
+            }
+            TokenizeError::Unsupported(what) => write!(f, "{what} are not supported"),
+        }
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+/// Words that introduce or close shell syntax rather than name a program.
+const POSIX_RESERVED: &[&str] = &[
+    "!", "{", "}", "if", "then", "else", "elif", "fi", "do", "done", "while", "until",
+];
+
+/// Split `command` into simple commands using the rules of `shell`.
+pub fn tokenize(command: &str, shell: Shell) -> Result<ParsedCommand, TokenizeError> {
+    let mut lexer = Lexer {
+        chars: command.chars().collect(),
+        pos: 0,
+        shell,
+        out: ParsedCommand::default(),
+        segment: Segment::default(),
+        word: String::new(),
+        in_word: false,
+        pending_redirect: None,
+    };
+    lexer.run()?;
+    Ok(lexer.out)
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    shell: Shell,
+    out: ParsedCommand,
+    segment: Segment,
+    word: String,
+    /// Distinguishes an empty quoted word (`""`) from no word at all.
+    in_word: bool,
+    pending_redirect: Option<String>,
+}
+
+impl Lexer {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn at_segment_start(&self) -> bool {
+        !self.in_word && self.segment.words.is_empty() && self.pending_redirect.is_none()
+    }
+
+    fn run(&mut self) -> Result<(), TokenizeError> {
+        while let Some(c) = self.peek() {
+            match self.shell {
+                Shell::Posix => self.posix_char(c)?,
+                Shell::PowerShell => self.powershell_char(c)?,
+                Shell::Cmd => self.cmd_char(c)?,
+            }
+        }
+        self.finish_segment()
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.word.push(c);
+        self.in_word = true;
+    }
+
+    fn finish_word(&mut self) {
+        if !self.in_word {
+            return;
+        }
+        let word = std::mem::take(&mut self.word);
+        self.in_word = false;
+        if let Some(op) = self.pending_redirect.take() {
+            self.segment.redirects.push(Redirect { op, target: word });
+        } else if self.shell == Shell::Posix
+            && self.segment.words.is_empty()
+            && is_assignment(&word)
+        {
+            self.segment.assignments.push(word);
+        } else {
+            self.segment.words.push(word);
+        }
+    }
+
+    fn finish_segment(&mut self) -> Result<(), TokenizeError> {
+        self.finish_word();
+        if let Some(op) = self.pending_redirect.take() {
+            return Err(TokenizeError::MissingRedirectTarget(op));
+        }
+        let mut segment = std::mem::take(&mut self.segment);
+        match self.shell {
+            Shell::Posix => {
+                let reserved = segment
+                    .words
+                    .iter()
+                    .take_while(|w| POSIX_RESERVED.contains(&w.as_str()))
+                    .count();
+                segment.words.drain(..reserved);
+            }
+            Shell::PowerShell => {
+                // `& $cmd` / `$cmd args`: the program is whatever a variable holds.
+                if segment.program().is_some_and(|p| p.starts_with('$')) {
+                    self.out.has_substitution = true;
+                }
+            }
+            Shell::Cmd => {
+                if let Some(first) = segment.words.first_mut() {
+                    let trimmed = first.trim_start_matches('@').to_string();
+                    if trimmed.is_empty() {
+                        segment.words.remove(0);
+                    } else {
+                        *first = trimmed;
+                    }
+                }
+            }
+        }
+        if !segment.words.is_empty()
+            || !segment.redirects.is_empty()
+            || !segment.assignments.is_empty()
+        {
+            self.out.segments.push(segment);
+        }
+        Ok(())
+    }
+
+    /// Start a redirection whose first operator character is at the cursor.
+    /// A word made only of digits immediately before it is the fd prefix.
+    fn start_redirect(&mut self, mut op: String) -> Result<(), TokenizeError> {
+        if self.in_word && is_all_digits(&self.word) && self.pending_redirect.is_none() {
+            op.insert_str(0, &std::mem::take(&mut self.word));
+            self.in_word = false;
+        } else {
+            self.finish_word();
+        }
+        if let Some(prev) = &self.pending_redirect {
+            return Err(TokenizeError::MissingRedirectTarget(prev.clone()));
+        }
+        let first = self.bump().unwrap_or('>');
+        op.push(first);
+        if self.peek() == Some(first) {
+            op.push(first);
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some('&') => {
+                op.push('&');
+                self.pos += 1;
+            }
+            Some('|') if self.shell == Shell::Posix && op.ends_with('>') => {
+                op.push('|');
+                self.pos += 1;
+            }
+            _ => {}
+        }
+        self.pending_redirect = Some(op);
+        Ok(())
+    }
+
+    /// Consume a balanced `( .. )` or `{ .. }` group starting at the cursor
+    /// into the current word and flag a substitution.
+    fn consume_group(&mut self, open: char, close: char) -> Result<(), TokenizeError> {
+        self.out.has_substitution = true;
+        let mut depth = 0usize;
+        let mut quote: Option<char> = None;
+        while let Some(c) = self.bump() {
+            self.push_char(c);
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c == open => depth += 1,
+                None if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                None => {}
+            }
+        }
+        Err(TokenizeError::UnterminatedSubstitution)
+    }
+
+    fn consume_backticks(&mut self) -> Result<(), TokenizeError> {
+        self.out.has_substitution = true;
+        self.pos += 1;
+        self.push_char('`');
+        while let Some(c) = self.bump() {
+            self.push_char(c);
+            match c {
+                '\\' => {
+                    if let Some(next) = self.bump() {
+                        self.push_char(next);
+                    }
+                }
+                '`' => return Ok(()),
+                _ => {}
+            }
+        }
+        Err(TokenizeError::UnterminatedSubstitution)
+    }
+
+    fn skip_comment(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    // ── POSIX sh / bash ──────────────────────────────────────────────────
+
+    fn posix_char(&mut self, c: char) -> Result<(), TokenizeError> {
+        match c {
+            ' ' | '\t' | '\r' => {
+                self.pos += 1;
+                self.finish_word();
+            }
+            '\n' | ';' | '(' | ')' => {
+                self.pos += 1;
+                self.finish_segment()?;
+            }
+            '#' if !self.in_word => self.skip_comment(),
+            '\\' => {
+                self.pos += 1;
+                match self.bump() {
+                    Some('\n') | None => {}
+                    Some(next) => self.push_char(next),
+                }
+            }
+            '\'' => {
+                self.pos += 1;
+                self.in_word = true;
+                loop {
+                    match self.bump() {
+                        Some('\'') => break,
+                        Some(ch) => self.word.push(ch),
+                        None => return Err(TokenizeError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => self.posix_double_quoted()?,
+            '`' => self.consume_backticks()?,
+            '$' if self.peek_at(1) == Some('(') => {
+                self.pos += 1;
+                self.push_char('$');
+                self.consume_group('(', ')')?;
+            }
+            '<' | '>' if self.peek_at(1) == Some('(') => {
+                self.push_char(c);
+                self.pos += 1;
+                self.consume_group('(', ')')?;
+            }
+            '<' if self.peek_at(1) == Some('<') && self.peek_at(2) != Some('<') => {
+                return Err(TokenizeError::Unsupported("here-documents"));
+            }
+            '<' | '>' => self.start_redirect(String::new())?,
+            '&' => match self.peek_at(1) {
+                Some('>') => {
+                    self.finish_word();
+                    self.pos += 1;
+                    self.start_redirect("&".to_string())?;
+                }
+                Some('&') => {
+                    self.pos += 2;
+                    self.finish_segment()?;
+                }
+                _ => {
+                    self.pos += 1;
+                    self.finish_segment()?;
+                }
+            },
+            '|' => {
+                self.pos += 1;
+                if matches!(self.peek(), Some('|') | Some('&')) {
+                    self.pos += 1;
+                }
+                self.finish_segment()?;
+            }
+            _ => {
+                self.pos += 1;
+                self.push_char(c);
+            }
+        }
+        Ok(())
+    }
+
+    fn posix_double_quoted(&mut self) -> Result<(), TokenizeError> {
+        self.pos += 1;
+        self.in_word = true;
+        loop {
+            match self.peek() {
+                None => return Err(TokenizeError::UnterminatedQuote('"')),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.bump() {
+                        Some(next @ ('"' | '\\' | '$' | '`')) => self.word.push(next),
+                        Some('\n') => {}
+                        Some(next) => {
+                            self.word.push('\\');
+                            self.word.push(next);
+                        }
+                        None => return Err(TokenizeError::UnterminatedQuote('"')),
+                    }
+                }
+                Some('`') => self.consume_backticks()?,
+                Some('$') if self.peek_at(1) == Some('(') => {
+                    self.pos += 1;
+                    self.word.push('$');
+                    self.consume_group('(', ')')?;
+                }
+                Some(ch) => {
+                    self.pos += 1;
+                    self.word.push(ch);
+                }
+            }
+        }
+    }
+
+    // ── PowerShell ───────────────────────────────────────────────────────
+
+    fn powershell_char(&mut self, c: char) -> Result<(), TokenizeError> {
+        match c {
+            ' ' | '\t' | '\r' => {
+                self.pos += 1;
+                self.finish_word();
+            }
+            '\n' | ';' | '|' => {
+                self.pos += 1;
+                if c == '|' && self.peek() == Some('|') {
+                    self.pos += 1;
+                }
+                self.finish_segment()?;
+            }
+            '#' if !self.in_word => self.skip_comment(),
+            '`' => {
+                self.pos += 1;
+                match self.bump() {
+                    Some('\n') | None => {}
+                    Some(next) => self.push_char(next),
+                }
+            }
+            '\'' => {
+                self.pos += 1;
+                self.in_word = true;
+                loop {
+                    match self.bump() {
+                        Some('\'') if self.peek() == Some('\'') => {
+                            self.pos += 1;
+                            self.word.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(ch) => self.word.push(ch),
+                        None => return Err(TokenizeError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => self.powershell_double_quoted()?,
+            '$' | '@' if self.peek_at(1) == Some('(') => {
+                self.pos += 1;
+                self.push_char(c);
+                self.consume_group('(', ')')?;
+            }
+            '(' => self.consume_group('(', ')')?,
+            '{' => self.consume_group('{', '}')?,
+            '*' if self.peek_at(1) == Some('>') && !self.in_word => {
+                self.pos += 1;
+                self.start_redirect("*".to_string())?;
+            }
+            '<' | '>' => self.start_redirect(String::new())?,
+            '&' => {
+                if self.peek_at(1) == Some('&') {
+                    self.pos += 2;
+                    self.finish_segment()?;
+                } else if self.at_segment_start() {
+                    // Call operator: `& 'C:\tools\x.exe' args`.
+                    self.pos += 1;
+                } else {
+                    self.pos += 1;
+                    self.finish_segment()?;
+                }
+            }
+            _ => {
+                self.pos += 1;
+                self.push_char(c);
+            }
+        }
+        Ok(())
+    }
+
+    fn powershell_double_quoted(&mut self) -> Result<(), TokenizeError> {
+        self.pos += 1;
+        self.in_word = true;
+        loop {
+            match self.peek() {
+                None => return Err(TokenizeError::UnterminatedQuote('"')),
+                Some('"') if self.peek_at(1) == Some('"') => {
+                    self.pos += 2;
+                    self.word.push('"');
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('`') => {
+                    self.pos += 1;
+                    if let Some(next) = self.bump() {
+                        self.word.push(next);
+                    }
+                }
+                Some('$') if self.peek_at(1) == Some('(') => {
+                    self.pos += 1;
+                    self.word.push('$');
+                    self.consume_group('(', ')')?;
+                }
+                Some(ch) => {
+                    self.pos += 1;
+                    self.word.push(ch);
+                }
+            }
+        }
+    }
+
+    // ── cmd.exe ──────────────────────────────────────────────────────────
+
+    fn cmd_char(&mut self, c: char) -> Result<(), TokenizeError> {
+        match c {
+            ' ' | '\t' | '\r' => {
+                self.pos += 1;
+                self.finish_word();
+            }
+            '\n' | '(' | ')' => {
+                self.pos += 1;
+                self.finish_segment()?;
+            }
+            '^' => {
+                self.pos += 1;
+                match self.bump() {
+                    Some('\n') | None => {}
+                    Some(next) => self.push_char(next),
+                }
+            }
+            '"' => {
+                self.pos += 1;
+                self.in_word = true;
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some(ch) => self.word.push(ch),
+                        None => return Err(TokenizeError::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '<' | '>' => self.start_redirect(String::new())?,
+            '&' | '|' => {
+                self.pos += 1;
+                if self.peek() == Some(c) {
+                    self.pos += 1;
+                }
+                self.finish_segment()?;
+            }
+            _ => {
+                self.pos += 1;
+                self.push_char(c);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_assignment(word: &str) -> bool {
+    let Some((name, _)) = word.split_once('=') else {
+        return false;
+    };
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(parsed: &ParsedCommand) -> Vec<Vec<&str>> {
+        parsed
+            .segments
+            .iter()
+            .map(|s| s.words.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn posix_splits_on_every_connector() {
+        let parsed = tokenize(
+            "git status && rm -rf x; ls | wc -l || echo a & sleep 1",
+            Shell::Posix,
+        )
+        .unwrap();
+        assert_eq!(
+            words(&parsed),
+            vec![
+                vec!["git", "status"],
+                vec!["rm", "-rf", "x"],
+                vec!["ls"],
+                vec!["wc", "-l"],
+                vec!["echo", "a"],
+                vec!["sleep", "1"],
+            ]
+        );
+        assert!(!parsed.has_substitution);
+    }
+
+    #[test]
+    fn posix_quotes_and_escapes_are_removed() {
+        let parsed = tokenize(r#"echo 'a b' "c \"d\"" e\ f ''"#, Shell::Posix).unwrap();
+        assert_eq!(
+            words(&parsed),
+            vec![vec!["echo", "a b", "c \"d\"", "e f", ""]]
+        );
+    }
+
+    #[test]
+    fn posix_separators_inside_quotes_do_not_split() {
+        let parsed = tokenize("echo 'a; rm -rf /' \"b && c\"", Shell::Posix).unwrap();
+        assert_eq!(words(&parsed), vec![vec!["echo", "a; rm -rf /", "b && c"]]);
+    }
+
+    #[test]
+    fn posix_redirects_are_separated_from_words() {
+        let parsed = tokenize("cat a >out.txt 2>&1 2>> err.log < in", Shell::Posix).unwrap();
+        let seg = &parsed.segments[0];
+        assert_eq!(seg.words, vec!["cat", "a"]);
+        let ops: Vec<(&str, &str)> = seg
+            .redirects
+            .iter()
+            .map(|r| (r.op.as_str(), r.target.as_str()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (">", "out.txt"),
+                ("2>&", "1"),
+                ("2>>", "err.log"),
+                ("<", "in")
+            ]
+        );
+        let writes: Vec<bool> = seg.redirects.iter().map(Redirect::writes_file).collect();
+        assert_eq!(writes, vec![true, false, true, false]);
+    }
+
+    #[test]
+    fn posix_null_redirect_does_not_write() {
+        let parsed = tokenize("ls &> /dev/null", Shell::Posix).unwrap();
+        let redirect = &parsed.segments[0].redirects[0];
+        assert_eq!(redirect.op, "&>");
+        assert!(!redirect.writes_file());
+    }
+
+    #[test]
+    fn posix_substitutions_are_flagged() {
+        for cmd in [
+            "echo $(rm -rf /)",
+            "echo `id`",
+            "echo \"$(id)\"",
+            "diff <(ls a) <(ls b)",
+        ] {
+            let parsed = tokenize(cmd, Shell::Posix).unwrap();
+            assert!(parsed.has_substitution, "{cmd}");
+        }
+        assert!(
+            !tokenize("echo '$(id)' $HOME", Shell::Posix)
+                .unwrap()
+                .has_substitution
+        );
+    }
+
+    #[test]
+    fn posix_assignments_and_reserved_words_are_not_programs() {
+        let parsed = tokenize("FOO=1 BAR=2 git log; if true; then ls; fi", Shell::Posix).unwrap();
+        assert_eq!(parsed.segments[0].assignments, vec!["FOO=1", "BAR=2"]);
+        assert_eq!(parsed.segments[0].program(), Some("git"));
+        assert_eq!(words(&parsed)[1..], [vec!["true"], vec!["ls"]]);
+    }
+
+    #[test]
+    fn posix_comments_are_ignored() {
+        let parsed = tokenize("ls # && rm -rf /\npwd", Shell::Posix).unwrap();
+        assert_eq!(words(&parsed), vec![vec!["ls"], vec!["pwd"]]);
+    }
+
+    #[test]
+    fn posix_errors() {
+        assert_eq!(
+            tokenize("echo 'x", Shell::Posix),
+            Err(TokenizeError::UnterminatedQuote('\''))
+        );
+        assert_eq!(
+            tokenize("echo $(x", Shell::Posix),
+            Err(TokenizeError::UnterminatedSubstitution)
+        );
+        assert_eq!(
+            tokenize("echo x >", Shell::Posix),
+            Err(TokenizeError::MissingRedirectTarget(">".to_string()))
+        );
+        assert!(matches!(
+            tokenize("cat <<EOF", Shell::Posix),
+            Err(TokenizeError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn powershell_quotes_escapes_and_connectors() {
+        let parsed = tokenize(
+            "Get-Content 'it''s.txt' \"a`\"b\"; git status | Select-String x && ls",
+            Shell::PowerShell,
+        )
+        .unwrap();
+        assert_eq!(
+            words(&parsed),
+            vec![
+                vec!["Get-Content", "it's.txt", "a\"b"],
+                vec!["git", "status"],
+                vec!["Select-String", "x"],
+                vec!["ls"],
+            ]
+        );
+    }
+
+    #[test]
+    fn powershell_call_operator_and_substitutions() {
+        let parsed = tokenize("& 'C:\\Tools\\x.exe' -v", Shell::PowerShell).unwrap();
+        assert_eq!(words(&parsed), vec![vec!["C:\\Tools\\x.exe", "-v"]]);
+        assert!(!parsed.has_substitution);
+
+        for cmd in [
+            "echo $(Remove-Item x)",
+            "echo @(1,2)",
+            "& { rm x }",
+            "& $cmd",
+            "Write-Output (Get-Date)",
+        ] {
+            assert!(
+                tokenize(cmd, Shell::PowerShell).unwrap().has_substitution,
+                "{cmd}"
+            );
+        }
+    }
+
+    #[test]
+    fn powershell_redirects() {
+        let parsed = tokenize("Get-ChildItem *> $null 2>&1 > out.txt", Shell::PowerShell).unwrap();
+        let writes: Vec<(String, bool)> = parsed.segments[0]
+            .redirects
+            .iter()
+            .map(|r| (r.op.clone(), r.writes_file()))
+            .collect();
+        assert_eq!(
+            writes,
+            vec![
+                ("*>".to_string(), false),
+                ("2>&".to_string(), false),
+                (">".to_string(), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn cmd_quotes_carets_and_connectors() {
+        let parsed = tokenize(
+            "@dir \"C:\\Program Files\" & del /s /q x || type a^&b",
+            Shell::Cmd,
+        )
+        .unwrap();
+        assert_eq!(
+            words(&parsed),
+            vec![
+                vec!["dir", "C:\\Program Files"],
+                vec!["del", "/s", "/q", "x"],
+                vec!["type", "a&b"],
+            ]
+        );
+        let parsed = tokenize("echo hi > NUL", Shell::Cmd).unwrap();
+        assert!(!parsed.segments[0].redirects[0].writes_file());
+    }
+}
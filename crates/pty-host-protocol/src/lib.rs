@@ -1,18 +1,37 @@
-#[cfg(any(test, feature = "pty-host"))]
+//! Wire protocol shared by the interactive-terminal UI process and the
+//! out-of-process `pty-host` binary.
+//!
+//! Every connection starts with a handshake: the client's first frame must be
+//! [`Hello`] carrying [`PROTOCOL_VERSION`] and the secret the launcher handed
+//! to `pty-host` through [`SECRET_ENV`].  The host answers [`HelloAck`] or
+//! [`HelloRejected`] (and closes the connection) before any other traffic.
+
 use serde::{Deserialize, Serialize};
-#[cfg(any(test, feature = "pty-host"))]
 use std::collections::HashMap;
 
-#[cfg(any(test, feature = "pty-host"))]
+/// Version of the message set below.  Bump on any incompatible change; the
+/// host rejects clients speaking a different version.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Environment variable through which the launcher passes the connection
+/// secret to `pty-host`.
+pub const SECRET_ENV: &str = "PTY_HOST_SECRET";
+
 /// IPC message protocol between the interactive-terminal UI process
 /// and the out-of-process `pty-host` binary.
 ///
 /// Wire format: newline-delimited JSON (NDJSON) over a local TCP socket.
-/// This enum is intentionally isolated from `crate::protocol::Message`
+/// This enum is intentionally isolated from the MCP `Message` enum
 /// to ensure zero changes to the MCP wire format.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum PtyHostMessage {
+    /// UI → host: mandatory first frame of every connection.
+    Hello(Hello),
+    /// Host → UI: handshake accepted.
+    HelloAck(HelloAck),
+    /// Host → UI: handshake refused; the host closes the connection.
+    HelloRejected(HelloRejected),
     /// UI → host: spawn a new PTY session.
     SessionCreate(SessionCreate),
     /// Host → UI: session was spawned successfully.
@@ -29,11 +48,52 @@ pub enum PtyHostMessage {
     SessionExited(SessionExited),
     /// UI → host: kill a session.
     SessionKill(SessionKill),
+    /// UI → host: subscribe to an existing session's output.
+    SessionAttach(SessionAttach),
+    /// Host → UI: attach succeeded; carries the scrollback to replay.
+    SessionAttached(SessionAttached),
+    /// Host → UI: attach failed (unknown session).
+    SessionAttachFailed(SessionAttachFailed),
+    /// UI → host: stop receiving a session's output without killing it.
+    SessionDetach(SessionDetach),
+    /// UI → host: request the list of sessions the host is keeping.
+    SessionList(SessionList),
+    /// Host → UI: reply to `SessionList`.
+    SessionListed(SessionListed),
     /// Host → UI: periodic liveness signal.
     Heartbeat(Heartbeat),
 }
 
-#[cfg(any(test, feature = "pty-host"))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Hello {
+    pub protocol_version: u32,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HelloAck {
+    pub protocol_version: u32,
+    /// `pty-host` package version, for diagnostics.
+    pub host_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HelloRejected {
+    /// The version the host speaks, so a mismatched client can say which
+    /// side is out of date.
+    pub protocol_version: u32,
+    pub error: String,
+}
+
+impl Hello {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Hello {
+            protocol_version: PROTOCOL_VERSION,
+            secret: secret.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionCreate {
     pub session_id: String,
@@ -47,20 +107,17 @@ pub struct SessionCreate {
     pub rows: u16,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionCreated {
     pub session_id: String,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionCreateFailed {
     pub session_id: String,
     pub error: String,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionInput {
     pub session_id: String,
@@ -68,7 +125,6 @@ pub struct SessionInput {
     pub data: String,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionResize {
     pub session_id: String,
@@ -76,7 +132,6 @@ pub struct SessionResize {
     pub rows: u16,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionOutput {
     pub session_id: String,
@@ -84,20 +139,63 @@ pub struct SessionOutput {
     pub data: String,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionExited {
     pub session_id: String,
     pub exit_code: Option<i32>,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SessionKill {
     pub session_id: String,
 }
 
-#[cfg(any(test, feature = "pty-host"))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionAttach {
+    pub session_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionAttached {
+    pub session_id: String,
+    pub cols: u16,
+    pub rows: u16,
+    /// Most recent PTY output, oldest first, encoded as UTF-8 (lossy) string.
+    pub scrollback: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionAttachFailed {
+    pub session_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionDetach {
+    pub session_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionList {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionListed {
+    pub sessions: Vec<SessionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub program: String,
+    pub cwd: String,
+    pub cols: u16,
+    pub rows: u16,
+    /// `false` once the process has exited; such sessions are kept until a
+    /// client attaches and sees how they ended.
+    pub running: bool,
+    pub exit_code: Option<i32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Heartbeat {
     pub ts: u64,
@@ -112,6 +210,31 @@ mod tests {
         serde_json::from_str(&json).expect("deserialize failed")
     }
 
+    #[test]
+    fn hello_roundtrip() {
+        let msg = PtyHostMessage::Hello(Hello::new("s3cret"));
+        assert_eq!(roundtrip(&msg), msg);
+
+        let msg = PtyHostMessage::HelloAck(HelloAck {
+            protocol_version: PROTOCOL_VERSION,
+            host_version: "0.1.0".to_string(),
+        });
+        assert_eq!(roundtrip(&msg), msg);
+
+        let msg = PtyHostMessage::HelloRejected(HelloRejected {
+            protocol_version: PROTOCOL_VERSION,
+            error: "authentication failed".to_string(),
+        });
+        assert_eq!(roundtrip(&msg), msg);
+    }
+
+    #[test]
+    fn hello_carries_current_version() {
+        let json = serde_json::to_value(PtyHostMessage::Hello(Hello::new("x"))).unwrap();
+        assert_eq!(json["type"], "hello");
+        assert_eq!(json["protocol_version"], PROTOCOL_VERSION);
+    }
+
     #[test]
     fn session_create_roundtrip() {
         let msg = PtyHostMessage::SessionCreate(SessionCreate {
@@ -197,6 +320,43 @@ mod tests {
         assert_eq!(roundtrip(&msg), msg);
     }
 
+    #[test]
+    fn session_attach_roundtrip() {
+        let msg = PtyHostMessage::SessionAttach(SessionAttach {
+            session_id: "sess-1".to_string(),
+        });
+        assert_eq!(roundtrip(&msg), msg);
+
+        let msg = PtyHostMessage::SessionAttached(SessionAttached {
+            session_id: "sess-1".to_string(),
+            cols: 120,
+            rows: 40,
+            scrollback: "$ cargo build\r\n".to_string(),
+        });
+        assert_eq!(roundtrip(&msg), msg);
+    }
+
+    #[test]
+    fn session_list_roundtrip() {
+        let msg = PtyHostMessage::SessionList(SessionList {});
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(json, r#"{"type":"session_list"}"#);
+        assert_eq!(roundtrip(&msg), msg);
+
+        let msg = PtyHostMessage::SessionListed(SessionListed {
+            sessions: vec![SessionInfo {
+                session_id: "sess-1".to_string(),
+                program: "sh".to_string(),
+                cwd: "/tmp".to_string(),
+                cols: 80,
+                rows: 24,
+                running: false,
+                exit_code: Some(3),
+            }],
+        });
+        assert_eq!(roundtrip(&msg), msg);
+    }
+
     #[test]
     fn heartbeat_roundtrip() {
         let msg = PtyHostMessage::Heartbeat(Heartbeat { ts: 1_234_567_890 });
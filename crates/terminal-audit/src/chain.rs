@@ -0,0 +1,496 @@
+//! Append-only, hash-chained JSONL storage.
+//!
+//! Each line is an [`AuditEntry`] with three extra fields: `seq` (0, 1, 2, ..),
+//! `prev_hash` (the previous record's `hash`, or [`GENESIS_HASH`] for the
+//! first) and `hash`, the HMAC-SHA256 under an [`AuditKey`] of the record's
+//! other fields serialized as canonical JSON (object keys sorted, no
+//! whitespace).  Editing, inserting, reordering or deleting a line breaks the
+//! chain at that point, which [`verify`] reports, and without the key a
+//! rewritten chain cannot be made to verify again.  Dropping lines from the
+//! *end* cannot be detected from the file alone; callers that care can keep
+//! the `head_hash` from [`VerifySummary`] elsewhere and compare.
+//!
+//! Lines written before chaining was introduced may precede the first record;
+//! once the chain has started every line must be a record.
+
+use crate::entry::AuditEntry;
+use crate::key::AuditKey;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// `prev_hash` of the first record in a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainedRecord {
+    /// Position in the chain.  Lines written before chaining was introduced
+    /// have no `seq`, `prev_hash` or `hash` and read back with defaults.
+    #[serde(default)]
+    pub seq: u64,
+    #[serde(default)]
+    pub prev_hash: String,
+    #[serde(flatten)]
+    pub entry: AuditEntry,
+    #[serde(default)]
+    pub hash: String,
+}
+
+impl ChainedRecord {
+    fn is_chained(&self) -> bool {
+        !self.hash.is_empty()
+    }
+}
+
+/// Append `entry` to the log at `path`, chaining it onto the last record and
+/// authenticating it with `key`.
+///
+/// The file is held under an exclusive OS lock from reading the last record
+/// until the new line is written, so writers in different processes (both
+/// front ends can log to the same workspace) never chain onto the same
+/// predecessor.
+pub fn append(path: &Path, key: &AuditKey, entry: AuditEntry) -> Result<ChainedRecord, String> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("failed to open audit log {}: {e}", path.display()))?;
+    // Released when `file` is dropped.
+    file.lock()
+        .map_err(|e| format!("failed to lock audit log {}: {e}", path.display()))?;
+
+    let (seq, prev_hash) = match last_line(&mut file)? {
+        Some(line) => match serde_json::from_str::<ChainedRecord>(&line) {
+            Ok(prev) if prev.is_chained() => (prev.seq + 1, prev.hash),
+            // A legacy line after the chain started would otherwise make this
+            // record start a second chain at seq 0.
+            Ok(_) if has_chained_record(&mut file)? => {
+                return Err(format!(
+                    "refusing to append: {} has an unchained line after its chained records",
+                    path.display()
+                ))
+            }
+            // Only legacy lines so far: this record starts the chain.
+            Ok(_) => (0, GENESIS_HASH.to_string()),
+            Err(e) => {
+                return Err(format!(
+                    "refusing to append: last line of {} is not a valid record: {e}",
+                    path.display()
+                ))
+            }
+        },
+        None => (0, GENESIS_HASH.to_string()),
+    };
+
+    let mut record = ChainedRecord {
+        seq,
+        prev_hash,
+        entry,
+        hash: String::new(),
+    };
+    let value = serde_json::to_value(&record)
+        .map_err(|e| format!("failed to serialize audit entry: {e}"))?;
+    record.hash = record_mac(key, &value);
+
+    let line = serde_json::to_string(&record)
+        .map_err(|e| format!("failed to serialize audit entry: {e}"))?;
+    writeln!(file, "{line}").map_err(|e| format!("failed to write audit entry: {e}"))?;
+    Ok(record)
+}
+
+/// Read the last non-empty line without scanning the whole file.
+fn last_line(file: &mut std::fs::File) -> Result<Option<String>, String> {
+    const CHUNK: u64 = 8 * 1024;
+    let len = file
+        .seek(SeekFrom::End(0))
+        .map_err(|e| format!("failed to seek audit log: {e}"))?;
+    let mut start = len;
+    let mut tail: Vec<u8> = Vec::new();
+    loop {
+        let content_end = tail.iter().rposition(|b| !b.is_ascii_whitespace());
+        if let Some(end) = content_end {
+            if let Some(nl) = tail[..end].iter().rposition(|b| *b == b'\n') {
+                return Ok(Some(
+                    String::from_utf8_lossy(&tail[nl + 1..=end]).into_owned(),
+                ));
+            }
+            if start == 0 {
+                return Ok(Some(String::from_utf8_lossy(&tail[..=end]).into_owned()));
+            }
+        } else if start == 0 {
+            return Ok(None);
+        }
+        let read_from = start.saturating_sub(CHUNK);
+        let mut chunk = vec![0u8; (start - read_from) as usize];
+        file.seek(SeekFrom::Start(read_from))
+            .and_then(|_| file.read_exact(&mut chunk))
+            .map_err(|e| format!("failed to read audit log: {e}"))?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        start = read_from;
+    }
+}
+
+/// Whether any line of `file` is a chained record.
+fn has_chained_record(file: &mut std::fs::File) -> Result<bool, String> {
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| format!("failed to seek audit log: {e}"))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("failed to read audit log: {e}"))?;
+        if serde_json::from_str::<ChainedRecord>(&line).is_ok_and(|r| r.is_chained()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// HMAC-SHA256 (hex) under `key` of a record value with its `hash` field
+/// removed.
+fn record_mac(key: &AuditKey, value: &Value) -> String {
+    let mut value = value.clone();
+    if let Value::Object(map) = &mut value {
+        map.remove("hash");
+    }
+    let mut canonical = String::new();
+    write_canonical(&value, &mut canonical);
+    let mac = hmac_sha256(key.as_bytes(), canonical.as_bytes());
+    mac.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// HMAC (RFC 2104) over SHA-256.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// JSON with object keys in sorted order, independent of how the map type
+/// orders them.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Result of a successful [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifySummary {
+    /// Chained records checked.
+    pub records: u64,
+    /// Unchained lines written before the chain started.
+    pub legacy_lines: u64,
+    /// `hash` of the last record, or [`GENESIS_HASH`] for an empty chain.
+    pub head_hash: String,
+}
+
+/// Where and how the chain is broken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyError {
+    /// 1-based line number of the first bad line.
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Check every record's hash under `key`, its sequence number and its link
+/// to its predecessor.
+///
+/// A missing file is an empty, valid chain.
+pub fn verify(path: &Path, key: &AuditKey) -> Result<VerifySummary, VerifyError> {
+    let mut summary = VerifySummary {
+        records: 0,
+        legacy_lines: 0,
+        head_hash: GENESIS_HASH.to_string(),
+    };
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(summary),
+        Err(e) => {
+            return Err(VerifyError {
+                line: 0,
+                message: format!("failed to open {}: {e}", path.display()),
+            })
+        }
+    };
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line_no = index as u64 + 1;
+        let fail = |message: String| VerifyError {
+            line: line_no,
+            message,
+        };
+        let line = line.map_err(|e| fail(format!("read error: {e}")))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value =
+            serde_json::from_str(&line).map_err(|e| fail(format!("invalid JSON: {e}")))?;
+        let Some(hash) = value.get("hash").and_then(Value::as_str) else {
+            if summary.records == 0 {
+                summary.legacy_lines += 1;
+                continue;
+            }
+            return Err(fail("record has no hash".to_string()));
+        };
+        let seq = value.get("seq").and_then(Value::as_u64);
+        if seq != Some(summary.records) {
+            return Err(fail(format!(
+                "expected seq {}, found {}",
+                summary.records,
+                seq.map_or("none".to_string(), |s| s.to_string())
+            )));
+        }
+        let prev = value.get("prev_hash").and_then(Value::as_str).unwrap_or("");
+        if prev != summary.head_hash {
+            return Err(fail(
+                "prev_hash does not match the previous record".to_string(),
+            ));
+        }
+        if record_mac(key, &value) != hash {
+            return Err(fail("hash does not match record contents".to_string()));
+        }
+        summary.head_hash = hash.to_string();
+        summary.records += 1;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::AuditEvent;
+    use std::path::PathBuf;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "terminal-audit-{name}-{}-{:?}",
+            std::process::id(),
+            std::time::SystemTime::now()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("audit.jsonl")
+    }
+
+    fn key() -> AuditKey {
+        AuditKey::new("test key")
+    }
+
+    fn entry(request_id: &str) -> AuditEntry {
+        AuditEntry {
+            request_id: Some(request_id.to_string()),
+            ..AuditEntry::new(AuditEvent::CommandApproved)
+        }
+    }
+
+    fn rewrite(path: &Path, f: impl FnOnce(&mut Vec<String>)) {
+        let mut lines: Vec<String> = std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        f(&mut lines);
+        std::fs::write(path, lines.join("\n") + "\n").unwrap();
+    }
+
+    #[test]
+    fn appended_records_form_a_verifiable_chain() {
+        let path = temp_log("chain");
+        let first = append(&path, &key(), entry("a")).unwrap();
+        let second = append(&path, &key(), entry("b")).unwrap();
+        let third = append(&path, &key(), entry("c")).unwrap();
+
+        assert_eq!((first.seq, first.prev_hash.as_str()), (0, GENESIS_HASH));
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(third.seq, 2);
+
+        let summary = verify(&path, &key()).unwrap();
+        assert_eq!(summary.records, 3);
+        assert_eq!(summary.head_hash, third.hash);
+    }
+
+    #[test]
+    fn editing_a_record_is_detected() {
+        let path = temp_log("edit");
+        for id in ["a", "b", "c"] {
+            append(&path, &key(), entry(id)).unwrap();
+        }
+        rewrite(&path, |lines| lines[1] = lines[1].replace("\"b\"", "\"x\""));
+        let err = verify(&path, &key()).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("hash does not match"), "{err}");
+    }
+
+    #[test]
+    fn deleting_or_reordering_records_is_detected() {
+        let path = temp_log("delete");
+        for id in ["a", "b", "c"] {
+            append(&path, &key(), entry(id)).unwrap();
+        }
+        rewrite(&path, |lines| {
+            lines.remove(1);
+        });
+        assert_eq!(verify(&path, &key()).unwrap_err().line, 2);
+
+        let path = temp_log("reorder");
+        for id in ["a", "b", "c"] {
+            append(&path, &key(), entry(id)).unwrap();
+        }
+        rewrite(&path, |lines| lines.swap(1, 2));
+        assert!(verify(&path, &key()).is_err());
+    }
+
+    #[test]
+    fn recomputing_one_hash_still_breaks_the_next_link() {
+        let path = temp_log("rehash");
+        for id in ["a", "b", "c"] {
+            append(&path, &key(), entry(id)).unwrap();
+        }
+        rewrite(&path, |lines| {
+            let mut value: Value = serde_json::from_str(&lines[1]).unwrap();
+            value["request_id"] = Value::String("x".to_string());
+            let hash = record_mac(&key(), &value);
+            value["hash"] = Value::String(hash);
+            lines[1] = value.to_string();
+        });
+        let err = verify(&path, &key()).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(err.message.contains("prev_hash"), "{err}");
+    }
+
+    #[test]
+    fn legacy_lines_before_the_chain_are_tolerated() {
+        let path = temp_log("legacy");
+        std::fs::write(
+            &path,
+            "{\"timestamp\":\"2025-01-01T00:00:00Z\",\"event\":\"launch_requested\"}\n",
+        )
+        .unwrap();
+        let first = append(&path, &key(), entry("a")).unwrap();
+        assert_eq!(first.seq, 0);
+        append(&path, &key(), entry("b")).unwrap();
+
+        let summary = verify(&path, &key()).unwrap();
+        assert_eq!((summary.records, summary.legacy_lines), (2, 1));
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        writeln!(
+            file,
+            "{{\"timestamp\":\"x\",\"event\":\"launch_requested\"}}"
+        )
+        .unwrap();
+        assert_eq!(verify(&path, &key()).unwrap_err().line, 4);
+        let err = append(&path, &key(), entry("c")).unwrap_err();
+        assert!(err.contains("unchained line"), "{err}");
+    }
+
+    #[test]
+    fn a_chain_rewritten_without_the_key_does_not_verify() {
+        let path = temp_log("forge");
+        for id in ["a", "b"] {
+            append(&path, &key(), entry(id)).unwrap();
+        }
+        // Rebuild the whole file as a consistent chain under another key.
+        let forged = temp_log("forged");
+        let attacker = AuditKey::new("guessed key");
+        append(&forged, &attacker, entry("x")).unwrap();
+        append(&forged, &attacker, entry("b")).unwrap();
+        std::fs::copy(&forged, &path).unwrap();
+
+        assert!(verify(&path, &attacker).is_ok());
+        let err = verify(&path, &key()).unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("hash does not match"), "{err}");
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        let hex: String = mac.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(
+            hex,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let long_key = [0xaa; 131];
+        let mac = hmac_sha256(
+            &long_key,
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+        );
+        let hex: String = mac.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(
+            hex,
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn last_line_spans_chunks() {
+        let path = temp_log("long");
+        let long = AuditEntry {
+            command: Some("x".repeat(20_000)),
+            ..entry("long")
+        };
+        append(&path, &key(), long).unwrap();
+        let next = append(&path, &key(), entry("after")).unwrap();
+        assert_eq!(next.seq, 1);
+        assert_eq!(verify(&path, &key()).unwrap().records, 2);
+    }
+
+    #[test]
+    fn missing_file_is_an_empty_chain() {
+        let summary = verify(Path::new("/nonexistent/terminal-audit.jsonl"), &key()).unwrap();
+        assert_eq!(summary.records, 0);
+        assert_eq!(summary.head_hash, GENESIS_HASH);
+    }
+}
@@ -0,0 +1,528 @@
+//! Best-effort emitters used by both interactive-terminal front ends.
+//!
+//! Every `emit_*` function builds an [`AuditEntry`] and appends it to the
+//! workspace's audit log under the key installed with
+//! [`crate::install_key_file`] at startup.  Errors are printed to stderr and
+//! swallowed so a broken log never blocks a launch or a command.
+
+use crate::chain::{append, verify, ChainedRecord, VerifyError, VerifySummary};
+use crate::entry::{AuditEntry, AuditEvent, ContextPackSummary};
+use crate::key::installed_key;
+use crate::query::{query, AuditQuery};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The fields of a front end's command request that command events record.
+pub trait AuditedCommand {
+    /// Workspace root whose audit log receives the entry.
+    fn workspace_path(&self) -> &str;
+    /// Raw `context` JSON, which may embed a `context_pack`.
+    fn context_json(&self) -> &str;
+    fn terminal_session_id(&self) -> &str;
+    fn request_id(&self) -> &str;
+    fn command(&self) -> &str;
+    fn workspace_id(&self) -> &str;
+}
+
+impl<T: AuditedCommand + ?Sized> AuditedCommand for &T {
+    fn workspace_path(&self) -> &str {
+        (**self).workspace_path()
+    }
+    fn context_json(&self) -> &str {
+        (**self).context_json()
+    }
+    fn terminal_session_id(&self) -> &str {
+        (**self).terminal_session_id()
+    }
+    fn request_id(&self) -> &str {
+        (**self).request_id()
+    }
+    fn command(&self) -> &str {
+        (**self).command()
+    }
+    fn workspace_id(&self) -> &str {
+        (**self).workspace_id()
+    }
+}
+
+/// The parts of a request's `context_pack` that audit entries record.
+#[derive(Debug, Default, Deserialize)]
+struct LaunchContext {
+    #[serde(default)]
+    step_notes: Option<String>,
+    #[serde(default)]
+    relevant_files: Vec<serde_json::Value>,
+    #[serde(default)]
+    custom_instructions: Option<String>,
+    #[serde(default)]
+    requesting_agent: Option<String>,
+    #[serde(default)]
+    plan_id: Option<String>,
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+impl LaunchContext {
+    /// Parse `context_json["context_pack"]`; `None` when absent or invalid.
+    fn parse(context_json: &str) -> Option<Self> {
+        if context_json.is_empty() {
+            return None;
+        }
+        let value: serde_json::Value = serde_json::from_str(context_json).ok()?;
+        serde_json::from_value(value.get("context_pack")?.clone()).ok()
+    }
+
+    fn summary(&self) -> ContextPackSummary {
+        ContextPackSummary {
+            has_step_notes: self
+                .step_notes
+                .as_deref()
+                .is_some_and(|s| !s.trim().is_empty()),
+            file_count: self.relevant_files.len(),
+            has_custom_instructions: self
+                .custom_instructions
+                .as_deref()
+                .is_some_and(|s| !s.trim().is_empty()),
+        }
+    }
+
+    /// `entry` with the requesting agent, plan and session filled in from
+    /// `pack`, when there is one.
+    fn annotate(pack: Option<&Self>, entry: AuditEntry) -> AuditEntry {
+        let Some(pack) = pack else {
+            return entry;
+        };
+        AuditEntry {
+            requesting_agent: pack.requesting_agent.clone(),
+            plan_id: pack.plan_id.clone(),
+            session_id: pack.session_id.clone(),
+            ..entry
+        }
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.to_string()).filter(|s| !s.is_empty())
+}
+
+// ─── Write helpers ────────────────────────────────────────────────────────────
+
+/// Return the audit log path for a given workspace root.
+///
+/// Creates the `logs/` directory if it does not yet exist.
+pub fn audit_log_path(workspace_path: &str) -> Option<PathBuf> {
+    let path = crate::log_path(workspace_path)?;
+    let logs_dir = path.parent()?;
+    if let Err(err) = std::fs::create_dir_all(logs_dir) {
+        eprintln!("[audit_log] failed to create logs directory {logs_dir:?}: {err}");
+        return None;
+    }
+    Some(path)
+}
+
+/// Append a single `AuditEntry` to the chained JSONL file.
+///
+/// Best-effort: errors are logged to stderr and silently swallowed.
+pub fn write_entry(workspace_path: &str, entry: AuditEntry) {
+    let Some(key) = installed_key() else {
+        eprintln!(
+            "[audit_log] no audit key installed; dropping {:?} entry",
+            entry.event
+        );
+        return;
+    };
+    let Some(path) = audit_log_path(workspace_path) else {
+        return;
+    };
+    if let Err(err) = append(&path, key, entry) {
+        eprintln!("[audit_log] {err}");
+    }
+}
+
+// ─── Launch events ───────────────────────────────────────────────────────────
+
+/// Emit a `launch_requested` event when a super-subagent command is enqueued
+/// for GUI approval.
+pub fn emit_launch_requested(workspace_path: &str, context_json: &str, provider: &str) {
+    let pack = LaunchContext::parse(context_json);
+    write_entry(
+        workspace_path,
+        LaunchContext::annotate(
+            pack.as_ref(),
+            AuditEntry {
+                provider: non_empty(provider),
+                context_pack_summary: pack.as_ref().map(LaunchContext::summary),
+                ..AuditEntry::new(AuditEvent::LaunchRequested)
+            },
+        ),
+    );
+}
+
+/// Emit a `launch_approved` event immediately before the CLI launch command
+/// is dispatched.
+pub fn emit_launch_approved(
+    workspace_path: &str,
+    context_json: &str,
+    provider: &str,
+    autonomy_mode: &str,
+    terminal_session_id: &str,
+) {
+    let pack = LaunchContext::parse(context_json);
+    write_entry(
+        workspace_path,
+        LaunchContext::annotate(
+            pack.as_ref(),
+            AuditEntry {
+                provider: non_empty(provider),
+                autonomy_mode: non_empty(autonomy_mode),
+                context_pack_summary: pack.as_ref().map(LaunchContext::summary),
+                terminal_session_id: non_empty(terminal_session_id),
+                ..AuditEntry::new(AuditEvent::LaunchApproved)
+            },
+        ),
+    );
+}
+
+/// Emit a `launch_started` event once the CLI process is dispatched to the pty.
+pub fn emit_launch_started(
+    workspace_path: &str,
+    provider: &str,
+    terminal_session_id: &str,
+    requesting_agent: Option<&str>,
+    plan_id: Option<&str>,
+) {
+    write_entry(
+        workspace_path,
+        AuditEntry {
+            provider: non_empty(provider),
+            requesting_agent: requesting_agent.map(|s| s.to_string()),
+            plan_id: plan_id.map(|s| s.to_string()),
+            terminal_session_id: non_empty(terminal_session_id),
+            ..AuditEntry::new(AuditEvent::LaunchStarted)
+        },
+    );
+}
+
+/// Emit a `launch_denied` event when the user explicitly declines a launch.
+pub fn emit_launch_denied(
+    workspace_path: &str,
+    context_json: &str,
+    provider: &str,
+    reason: Option<&str>,
+) {
+    let pack = LaunchContext::parse(context_json);
+    write_entry(
+        workspace_path,
+        LaunchContext::annotate(
+            pack.as_ref(),
+            AuditEntry {
+                provider: non_empty(provider),
+                reason: reason.and_then(non_empty),
+                ..AuditEntry::new(AuditEvent::LaunchDenied)
+            },
+        ),
+    );
+}
+
+/// Emit a `launch_cancelled` event when the approval dialog is dismissed
+/// without a decision (i.e., the reason is empty or indicates a cancel).
+pub fn emit_launch_cancelled(workspace_path: &str, context_json: &str, provider: &str) {
+    let pack = LaunchContext::parse(context_json);
+    write_entry(
+        workspace_path,
+        LaunchContext::annotate(
+            pack.as_ref(),
+            AuditEntry {
+                provider: non_empty(provider),
+                ..AuditEntry::new(AuditEvent::LaunchCancelled)
+            },
+        ),
+    );
+}
+
+/// Emit a `session_completed` or `session_exited` event when a hosted agent CLI session ends.
+///
+/// Appends to the same `agent_launch_audit.jsonl` as the launch events, giving a
+/// full lifecycle record for every agent session in one file.
+///
+/// - `session_completed` — exit code 0 (clean exit)
+/// - `session_exited`    — any other outcome (non-zero, killed, or `None`)
+pub fn emit_session_exited(workspace_path: &str, session_id: &str, exit_code: Option<i32>) {
+    let event = if exit_code == Some(0) {
+        AuditEvent::SessionCompleted
+    } else {
+        AuditEvent::SessionExited
+    };
+    if exit_code != Some(0) {
+        eprintln!("[agent_session] session {session_id} exited — code: {exit_code:?}");
+    }
+    write_entry(
+        workspace_path,
+        AuditEntry {
+            session_id: Some(session_id.to_string()),
+            terminal_session_id: Some(session_id.to_string()),
+            exit_code,
+            ..AuditEntry::new(event)
+        },
+    );
+}
+
+// ─── Command events ──────────────────────────────────────────────────────────
+
+/// Entry for `event` carrying the identifying fields of a command request.
+fn command_entry(req: &impl AuditedCommand, event: AuditEvent) -> AuditEntry {
+    let pack = LaunchContext::parse(req.context_json());
+    LaunchContext::annotate(
+        pack.as_ref(),
+        AuditEntry {
+            terminal_session_id: non_empty(req.terminal_session_id()),
+            request_id: non_empty(req.request_id()),
+            command: non_empty(req.command()),
+            workspace_id: non_empty(req.workspace_id()),
+            ..AuditEntry::new(event)
+        },
+    )
+}
+
+/// Emit a `command_approved` event when the user approves a command.
+pub fn emit_command_approved(req: &impl AuditedCommand) {
+    write_entry(
+        req.workspace_path(),
+        command_entry(req, AuditEvent::CommandApproved),
+    );
+}
+
+/// Emit a `command_auto_approved` event when the command policy lets a
+/// command run without asking.
+pub fn emit_command_auto_approved(req: &impl AuditedCommand, policy_rule: Option<&str>) {
+    write_entry(
+        req.workspace_path(),
+        AuditEntry {
+            policy_rule: policy_rule.and_then(non_empty),
+            ..command_entry(req, AuditEvent::CommandAutoApproved)
+        },
+    );
+}
+
+/// Emit a `command_declined` event for a user decline or a policy deny
+/// (`policy_rule` set).
+pub fn emit_command_declined(req: &impl AuditedCommand, reason: &str, policy_rule: Option<&str>) {
+    write_entry(
+        req.workspace_path(),
+        AuditEntry {
+            reason: non_empty(reason),
+            policy_rule: policy_rule.and_then(non_empty),
+            ..command_entry(req, AuditEvent::CommandDeclined)
+        },
+    );
+}
+
+/// Emit the end of an approved command's execution.
+///
+/// - `command_timed_out` — `timed_out` is set
+/// - `command_completed` — exit code 0
+/// - `command_failed`    — any other exit code, or none
+pub fn emit_command_finished(req: &impl AuditedCommand, exit_code: Option<i32>, timed_out: bool) {
+    let event = if timed_out {
+        AuditEvent::CommandTimedOut
+    } else if exit_code == Some(0) {
+        AuditEvent::CommandCompleted
+    } else {
+        AuditEvent::CommandFailed
+    };
+    write_entry(
+        req.workspace_path(),
+        AuditEntry {
+            exit_code,
+            ..command_entry(req, event)
+        },
+    );
+}
+
+/// Emit a `command_killed` event when a running command is killed on request.
+pub fn emit_command_killed(req: &impl AuditedCommand) {
+    write_entry(
+        req.workspace_path(),
+        AuditEntry {
+            exit_code: Some(-1),
+            ..command_entry(req, AuditEvent::CommandKilled)
+        },
+    );
+}
+
+// ─── Queries ─────────────────────────────────────────────────────────────────
+
+/// MCP server → GUI: read (and optionally verify) a workspace's audit log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditQueryRequest {
+    pub id: String,
+    /// Workspace root whose `logs/agent_launch_audit.jsonl` is read.
+    pub workspace_path: String,
+    #[serde(flatten)]
+    pub filter: AuditQuery,
+    /// Also walk the whole hash chain and report whether it is intact.
+    #[serde(default)]
+    pub verify: bool,
+}
+
+/// Outcome of walking the audit log's hash chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditVerification {
+    pub intact: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<VerifySummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<VerifyError>,
+}
+
+/// GUI → MCP server: matching audit records, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditQueryResponse {
+    pub id: String,
+    pub success: bool,
+    #[serde(default)]
+    pub records: Vec<ChainedRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<AuditVerification>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Answer an `audit_query_request` from the MCP server.
+///
+/// Blocking file I/O; call from a blocking-capable context.
+pub fn answer_query(req: &AuditQueryRequest) -> AuditQueryResponse {
+    let Some(path) = crate::log_path(&req.workspace_path) else {
+        return AuditQueryResponse {
+            id: req.id.clone(),
+            success: false,
+            records: Vec::new(),
+            verification: None,
+            error: Some("workspace_path is required".to_string()),
+        };
+    };
+
+    let verification = req
+        .verify
+        .then(|| match installed_key().map(|key| verify(&path, key)) {
+            Some(Ok(summary)) => AuditVerification {
+                intact: true,
+                summary: Some(summary),
+                error: None,
+            },
+            Some(Err(error)) => AuditVerification {
+                intact: false,
+                summary: None,
+                error: Some(error),
+            },
+            None => AuditVerification {
+                intact: false,
+                summary: None,
+                error: Some(VerifyError {
+                    line: 0,
+                    message: "no audit key installed".to_string(),
+                }),
+            },
+        });
+
+    match query(&path, &req.filter) {
+        Ok(records) => AuditQueryResponse {
+            id: req.id.clone(),
+            success: true,
+            records,
+            verification,
+            error: None,
+        },
+        Err(error) => AuditQueryResponse {
+            id: req.id.clone(),
+            success: false,
+            records: Vec::new(),
+            verification,
+            error: Some(error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Request {
+        workspace_path: String,
+        context: String,
+    }
+
+    impl AuditedCommand for Request {
+        fn workspace_path(&self) -> &str {
+            &self.workspace_path
+        }
+        fn context_json(&self) -> &str {
+            &self.context
+        }
+        fn terminal_session_id(&self) -> &str {
+            "default"
+        }
+        fn request_id(&self) -> &str {
+            "req-1"
+        }
+        fn command(&self) -> &str {
+            "cargo test"
+        }
+        fn workspace_id(&self) -> &str {
+            "ws-1"
+        }
+    }
+
+    #[test]
+    fn command_and_launch_events_share_one_chain() {
+        let dir = std::env::temp_dir().join(format!(
+            "terminal-audit-emit-{}-{:?}",
+            std::process::id(),
+            std::time::SystemTime::now()
+        ));
+        let workspace_path = dir.to_string_lossy().to_string();
+        crate::install_key(crate::AuditKey::new("emit test key"));
+        let req = Request {
+            workspace_path: workspace_path.clone(),
+            context: r#"{"context_pack":{"requesting_agent":"Tester","relevant_files":[{"path":"a.rs"}]}}"#
+                .to_string(),
+        };
+
+        emit_launch_requested(&workspace_path, &req.context, "gemini");
+        emit_command_auto_approved(&req, Some("builtin:read-only"));
+        emit_command_finished(&req, Some(-1), true);
+
+        let response = answer_query(&AuditQueryRequest {
+            id: "q-1".to_string(),
+            workspace_path,
+            filter: AuditQuery {
+                agent: Some("tester".to_string()),
+                ..Default::default()
+            },
+            verify: true,
+        });
+        assert!(response.success);
+        assert!(
+            response
+                .verification
+                .expect("verification requested")
+                .intact
+        );
+        let events: Vec<_> = response.records.iter().map(|r| r.entry.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                AuditEvent::LaunchRequested,
+                AuditEvent::CommandAutoApproved,
+                AuditEvent::CommandTimedOut
+            ]
+        );
+        let launch = &response.records[0].entry;
+        assert_eq!(launch.context_pack_summary.as_ref().unwrap().file_count, 1);
+        let approved = &response.records[1].entry;
+        assert_eq!(approved.policy_rule.as_deref(), Some("builtin:read-only"));
+        assert_eq!(approved.workspace_id.as_deref(), Some("ws-1"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
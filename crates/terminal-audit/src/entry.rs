@@ -0,0 +1,183 @@
+//! Audit event kinds and the entry written for each.
+
+use serde::{Deserialize, Serialize};
+
+/// Summary of a context pack included in an audit entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextPackSummary {
+    pub has_step_notes: bool,
+    pub file_count: usize,
+    pub has_custom_instructions: bool,
+}
+
+/// All audited event kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    LaunchRequested,
+    LaunchApproved,
+    LaunchDenied,
+    LaunchCancelled,
+    LaunchStarted,
+    /// Agent CLI session exited cleanly (exit_code == Some(0)).
+    SessionCompleted,
+    /// Agent CLI session exited with a non-zero code, was killed, or returned no exit code.
+    SessionExited,
+    /// A user approved a command from the approval dialog.
+    CommandApproved,
+    /// The command policy allowed a command without asking.
+    CommandAutoApproved,
+    /// A user or the command policy declined a command.
+    CommandDeclined,
+    /// An approved command exited with code 0.
+    CommandCompleted,
+    /// An approved command exited non-zero or failed to run.
+    CommandFailed,
+    /// An approved command was killed on request.
+    CommandKilled,
+    /// An approved command ran past its `timeout_seconds` and was stopped.
+    CommandTimedOut,
+}
+
+/// A single structured audit entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// ISO-8601 UTC timestamp.
+    pub timestamp: String,
+    /// Event kind.
+    pub event: AuditEvent,
+    /// Normalised provider token, e.g. `"gemini"` or `"copilot"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Autonomy mode selected by the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autonomy_mode: Option<String>,
+    /// Requesting agent type, e.g. `"Executor"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requesting_agent: Option<String>,
+    /// Plan ID from the context pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_id: Option<String>,
+    /// Session ID from the context pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Summary of the context pack, if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_pack_summary: Option<ContextPackSummary>,
+    /// Terminal session the command or agent ran in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_session_id: Option<String>,
+    /// Risk tier inferred from the request context.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_tier: Option<String>,
+    /// Human-readable denial reason, if applicable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Process exit code — only set for session and command end events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// `CommandRequest.id` of the command concerned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The command line as submitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Project Memory workspace ID of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Command policy rule that decided an automatic approval or decline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_rule: Option<String>,
+}
+
+impl AuditEntry {
+    /// An entry for `event` stamped with the current time and no other fields.
+    pub fn new(event: AuditEvent) -> Self {
+        AuditEntry {
+            timestamp: iso_now(),
+            event,
+            provider: None,
+            autonomy_mode: None,
+            requesting_agent: None,
+            plan_id: None,
+            session_id: None,
+            context_pack_summary: None,
+            terminal_session_id: None,
+            risk_tier: None,
+            reason: None,
+            exit_code: None,
+            request_id: None,
+            command: None,
+            workspace_id: None,
+            policy_rule: None,
+        }
+    }
+}
+
+// ─── ISO-8601 timestamp helper ────────────────────────────────────────────────
+
+/// Current UTC time as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn iso_now() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    // Format as YYYY-MM-DDTHH:MM:SSZ using integer arithmetic (no chrono dep).
+    let s = secs % 60;
+    let m = (secs / 60) % 60;
+    let h = (secs / 3600) % 24;
+    let days = secs / 86400;
+
+    // Approximate calendar date from days-since-epoch
+    let (year, month, day) = days_to_ymd(days);
+    format!("{year:04}-{month:02}-{day:02}T{h:02}:{m:02}:{s:02}Z")
+}
+
+fn days_to_ymd(mut days: u64) -> (u32, u32, u32) {
+    // Simple Gregorian calendar computation
+    let year_400_days: u64 = 365 * 400 + 97;
+    let year_100_days: u64 = 365 * 100 + 24;
+    let year_4_days: u64 = 365 * 4 + 1;
+
+    days += 719468; // offset from Unix epoch to proleptic Gregorian epoch
+
+    let era = days / year_400_days;
+    let doe = days % year_400_days;
+    let yoe =
+        (doe - doe / (year_4_days - 1) + doe / year_100_days - doe / (year_400_days - 1)) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = if m <= 2 { y + 1 } else { y };
+
+    (y as u32, m as u32, d as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_to_ymd_known_dates() {
+        assert_eq!(days_to_ymd(0), (1970, 1, 1));
+        assert_eq!(days_to_ymd(11_016), (2000, 2, 29));
+        assert_eq!(days_to_ymd(20_743), (2026, 10, 17));
+    }
+
+    #[test]
+    fn entry_omits_unset_fields() {
+        let mut entry = AuditEntry::new(AuditEvent::CommandTimedOut);
+        entry.timestamp = "2026-01-01T00:00:00Z".to_string();
+        entry.exit_code = Some(-1);
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(
+            json,
+            r#"{"timestamp":"2026-01-01T00:00:00Z","event":"command_timed_out","exit_code":-1}"#
+        );
+        assert_eq!(serde_json::from_str::<AuditEntry>(&json).unwrap(), entry);
+    }
+}
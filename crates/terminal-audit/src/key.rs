@@ -0,0 +1,139 @@
+//! The secret that keys each record's `hash`.
+//!
+//! A plain hash chain only shows that the file is self-consistent: anyone who
+//! can write the log can rewrite it and recompute every hash.  Records are
+//! therefore authenticated with HMAC-SHA256 under a per-user key kept outside
+//! the workspace (the front ends store it in their app data directory, owner
+//! readable only), so a rewritten log no longer verifies without the key.
+
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// File name of the key inside a front end's app data directory.
+pub const KEY_FILE: &str = "audit.key";
+
+static INSTALLED: OnceLock<AuditKey> = OnceLock::new();
+
+/// Secret bytes the audit chain is keyed with.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuditKey(Vec<u8>);
+
+impl AuditKey {
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// 244 random bits from two v4 UUIDs, as 64 hex characters.
+    pub fn generate() -> Self {
+        Self::new(format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        ))
+    }
+
+    /// Read the key at `path`, creating it (mode 0600 on Unix; on Windows the
+    /// per-user app data directory restricts access) when it does not exist.
+    ///
+    /// An existing key is never replaced: records written under it would no
+    /// longer verify.
+    pub fn load_or_create(path: &Path) -> Result<Self, String> {
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let key = bytes.trim_ascii();
+                if key.is_empty() {
+                    return Err(format!("audit key {} is empty", path.display()));
+                }
+                return Ok(Self::new(key));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("failed to read audit key {}: {e}", path.display())),
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+        }
+        let key = Self::generate();
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        match options.open(path) {
+            Ok(mut file) => {
+                file.write_all(&key.0)
+                    .map_err(|e| format!("failed to write audit key {}: {e}", path.display()))?;
+                Ok(key)
+            }
+            // Another process created it first; use theirs.
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Self::load_or_create(path),
+            Err(e) => Err(format!(
+                "failed to create audit key {}: {e}",
+                path.display()
+            )),
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for AuditKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditKey(..)")
+    }
+}
+
+/// Make `key` the one the [`crate::emit`] helpers sign and verify with.
+///
+/// Returns `false` if a key was already installed; the first one stays.
+pub fn install_key(key: AuditKey) -> bool {
+    INSTALLED.set(key).is_ok()
+}
+
+/// Load or create the key at `path` and install it.
+pub fn install_key_file(path: &Path) -> Result<(), String> {
+    install_key(AuditKey::load_or_create(path)?);
+    Ok(())
+}
+
+/// The key installed by [`install_key`], if any.
+pub fn installed_key() -> Option<&'static AuditKey> {
+    INSTALLED.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_file_is_created_once_and_reused() {
+        let dir = std::env::temp_dir().join(format!(
+            "terminal-audit-key-{}-{:?}",
+            std::process::id(),
+            std::time::SystemTime::now()
+        ));
+        let path = dir.join("nested").join(KEY_FILE);
+
+        let created = AuditKey::load_or_create(&path).unwrap();
+        assert_eq!(created.as_bytes().len(), 64);
+        assert_eq!(AuditKey::load_or_create(&path).unwrap(), created);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(AuditKey::load_or_create(&path).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
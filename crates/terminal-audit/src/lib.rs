@@ -0,0 +1,37 @@
+//! Tamper-evident audit trail shared by the interactive-terminal front ends.
+//!
+//! Approval decisions, automatic approvals, command outcomes (exit code, kill,
+//! timeout) and agent launch lifecycle events are appended to
+//! `{workspace_path}/logs/agent_launch_audit.jsonl` as a chain of records
+//! keyed with a per-user [`AuditKey`] ([`append`]), can be checked for tampering ([`verify`]) and filtered
+//! ([`query`]).  The file keeps its historical name so existing readers find
+//! it; entries written before chaining are reported as legacy lines.
+//!
+//! [`emit`] holds the best-effort `emit_*` helpers both front ends call and
+//! the handler for the MCP server's audit queries.
+
+mod chain;
+pub mod emit;
+mod entry;
+mod key;
+mod query;
+
+use std::path::{Path, PathBuf};
+
+pub use chain::{append, verify, ChainedRecord, VerifyError, VerifySummary, GENESIS_HASH};
+pub use emit::{AuditQueryRequest, AuditQueryResponse, AuditVerification, AuditedCommand};
+pub use entry::{iso_now, AuditEntry, AuditEvent, ContextPackSummary};
+pub use key::{install_key, install_key_file, installed_key, AuditKey, KEY_FILE};
+pub use query::{query, AuditQuery};
+
+/// File name of the audit log inside `{workspace_path}/logs/`.
+pub const AUDIT_LOG_FILE: &str = "agent_launch_audit.jsonl";
+
+/// Audit log path for a workspace root, or `None` for an empty path.
+pub fn log_path(workspace_path: &str) -> Option<PathBuf> {
+    let trimmed = workspace_path.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(Path::new(trimmed).join("logs").join(AUDIT_LOG_FILE))
+}
@@ -0,0 +1,240 @@
+//! Filtering the audit log.
+
+use crate::chain::ChainedRecord;
+use crate::entry::AuditEvent;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Filters for [`query`]; unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// Matches `workspace_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Matches either the context-pack `session_id` or the
+    /// `terminal_session_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Matches `requesting_agent`, case-insensitively.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Inclusive lower bound, `YYYY-MM-DDTHH:MM:SSZ`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Exclusive upper bound, `YYYY-MM-DDTHH:MM:SSZ`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Event kinds to include.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status: Vec<AuditEvent>,
+    /// Keep only the most recent `limit` matches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &ChainedRecord) -> bool {
+        let entry = &record.entry;
+        if self.workspace_id.is_some() && entry.workspace_id != self.workspace_id {
+            return false;
+        }
+        if let Some(session) = &self.session_id {
+            let hit = entry.session_id.as_deref() == Some(session.as_str())
+                || entry.terminal_session_id.as_deref() == Some(session.as_str());
+            if !hit {
+                return false;
+            }
+        }
+        if let Some(agent) = &self.agent {
+            if !entry
+                .requesting_agent
+                .as_deref()
+                .is_some_and(|a| a.eq_ignore_ascii_case(agent))
+            {
+                return false;
+            }
+        }
+        // Timestamps share one fixed-width UTC format, so string order is
+        // time order.
+        if self
+            .since
+            .as_deref()
+            .is_some_and(|since| entry.timestamp.as_str() < since)
+        {
+            return false;
+        }
+        if self
+            .until
+            .as_deref()
+            .is_some_and(|until| entry.timestamp.as_str() >= until)
+        {
+            return false;
+        }
+        self.status.is_empty() || self.status.contains(&entry.event)
+    }
+}
+
+/// Records in the log at `path` that match `filter`, oldest first.
+///
+/// Lines that do not parse are skipped; use [`crate::verify`] to find out
+/// whether the log has been tampered with.  A missing file yields no records.
+pub fn query(path: &Path, filter: &AuditQuery) -> Result<Vec<ChainedRecord>, String> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("failed to open audit log {}: {e}", path.display())),
+    };
+    let mut matches = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("failed to read audit log: {e}"))?;
+        let Ok(record) = serde_json::from_str::<ChainedRecord>(&line) else {
+            continue;
+        };
+        if filter.matches(&record) {
+            matches.push(record);
+        }
+    }
+    if let Some(limit) = filter.limit {
+        let excess = matches.len().saturating_sub(limit);
+        matches.drain(..excess);
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::append;
+    use crate::entry::AuditEntry;
+    use crate::key::AuditKey;
+
+    fn record(event: AuditEvent, timestamp: &str, agent: &str, terminal: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: timestamp.to_string(),
+            requesting_agent: Some(agent.to_string()).filter(|a| !a.is_empty()),
+            terminal_session_id: Some(terminal.to_string()),
+            workspace_id: Some("ws-1".to_string()),
+            ..AuditEntry::new(event)
+        }
+    }
+
+    fn sample_log() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "terminal-audit-query-{}-{:?}",
+            std::process::id(),
+            std::time::SystemTime::now()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        for entry in [
+            record(
+                AuditEvent::CommandAutoApproved,
+                "2026-03-01T10:00:00Z",
+                "Executor",
+                "default",
+            ),
+            record(
+                AuditEvent::CommandCompleted,
+                "2026-03-01T10:00:05Z",
+                "Executor",
+                "default",
+            ),
+            record(
+                AuditEvent::CommandDeclined,
+                "2026-03-02T09:00:00Z",
+                "Reviewer",
+                "tab-2",
+            ),
+            record(
+                AuditEvent::CommandApproved,
+                "2026-03-03T12:00:00Z",
+                "",
+                "tab-2",
+            ),
+            record(
+                AuditEvent::CommandKilled,
+                "2026-03-03T12:10:00Z",
+                "",
+                "tab-2",
+            ),
+        ] {
+            append(&path, &AuditKey::new("test key"), entry).unwrap();
+        }
+        path
+    }
+
+    fn events(records: &[ChainedRecord]) -> Vec<AuditEvent> {
+        records.iter().map(|r| r.entry.event).collect()
+    }
+
+    #[test]
+    fn filters_combine() {
+        let path = sample_log();
+        let all = query(&path, &AuditQuery::default()).unwrap();
+        assert_eq!(all.len(), 5);
+
+        let other_workspace = AuditQuery {
+            workspace_id: Some("ws-2".to_string()),
+            ..Default::default()
+        };
+        assert!(query(&path, &other_workspace).unwrap().is_empty());
+
+        let by_session = AuditQuery {
+            session_id: Some("tab-2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query(&path, &by_session).unwrap().len(), 3);
+
+        let by_agent = AuditQuery {
+            agent: Some("executor".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            events(&query(&path, &by_agent).unwrap()),
+            vec![
+                AuditEvent::CommandAutoApproved,
+                AuditEvent::CommandCompleted
+            ]
+        );
+
+        let by_time_and_status = AuditQuery {
+            since: Some("2026-03-02T00:00:00Z".to_string()),
+            until: Some("2026-03-03T12:10:00Z".to_string()),
+            status: vec![AuditEvent::CommandDeclined, AuditEvent::CommandApproved],
+            ..Default::default()
+        };
+        assert_eq!(
+            events(&query(&path, &by_time_and_status).unwrap()),
+            vec![AuditEvent::CommandDeclined, AuditEvent::CommandApproved]
+        );
+    }
+
+    #[test]
+    fn limit_keeps_the_most_recent() {
+        let path = sample_log();
+        let last_two = AuditQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let records = query(&path, &last_two).unwrap();
+        assert_eq!(
+            events(&records),
+            vec![AuditEvent::CommandApproved, AuditEvent::CommandKilled]
+        );
+        assert_eq!(records[1].seq, 4);
+    }
+
+    #[test]
+    fn query_deserializes_from_wire_json() {
+        let q: AuditQuery = serde_json::from_str(
+            r#"{"session_id":"s","status":["command_killed","command_timed_out"],"limit":10}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            q.status,
+            vec![AuditEvent::CommandKilled, AuditEvent::CommandTimedOut]
+        );
+        assert_eq!(q.limit, Some(10));
+    }
+}
@@ -32,8 +32,15 @@ pub enum Message {
     // ── Backend events (TCP / protocol) ──────────────────────────────────────
     /// A new pending command has arrived from the MCP client
     CommandReceived(PendingCommand),
-    /// A pending command was resolved (approved or declined)
-    CommandCompleted { request_id: String, success: bool },
+    /// A command request ended: resolved elsewhere while still pending, or
+    /// finished running after approval.  `exit_code` and `timed_out` are
+    /// taken from the backend when it reports them.
+    CommandCompleted {
+        request_id: String,
+        success: bool,
+        exit_code: Option<i32>,
+        timed_out: bool,
+    },
     /// The TCP connection state changed
     ConnectionChanged(ConnectionState),
     /// An agent session was launched and assigned a dedicated tab
@@ -250,6 +257,10 @@ pub struct AppState {
     // ── Pending commands ─────────────────────────────────────────────────────
     pub pending_commands: Vec<PendingCommand>,
     pub current_request_id: String,
+    /// Approved commands still running, keyed by request id, as the audit
+    /// log records them; their end is logged on `CommandCompleted` or
+    /// `KillSessionDone`.
+    pub running_commands: HashMap<String, crate::protocol::CommandRequest>,
 
     // ── Current session ───────────────────────────────────────────────────────
     pub current_session_id: String,
@@ -291,6 +302,9 @@ pub struct AppState {
 
     // ── Allowlist ─────────────────────────────────────────────────────────────
     pub allowlist_patterns: Vec<AllowlistPattern>,
+    /// Structured `rules` from `terminal-allowlist.json`, evaluated with the
+    /// patterns by `evaluate_command_policy`.
+    pub policy_rules: Vec<command_policy::Rule>,
     pub allowlist_filter: String,
     pub allowlist_input: String,
     pub allowlist_last_op: String,
@@ -341,6 +355,7 @@ impl Default for AppState {
             // Pending commands
             pending_commands: Vec::new(),
             current_request_id: String::new(),
+            running_commands: HashMap::new(),
 
             // Current session
             current_session_id: String::new(),
@@ -381,6 +396,7 @@ impl Default for AppState {
 
             // Allowlist
             allowlist_patterns: Vec::new(),
+            policy_rules: Vec::new(),
             allowlist_filter: String::new(),
             allowlist_input: String::new(),
             allowlist_last_op: String::new(),
@@ -419,6 +435,8 @@ impl Default for AppState {
 pub fn init(port: u16, host: String) -> (AppState, iced::Task<Message>) {
     let mut state = AppState::default();
     state.terminal_ws_port = port;
+    load_allowlist_file(&mut state);
+    crate::audit_log::install_audit_key();
 
     // Start the TCP backend bridge — returns the sender for outgoing messages.
     let outgoing_tx = crate::backend_bridge::start(host, port);
@@ -460,14 +478,66 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
             iced::Task::none()
         }
 
-        Message::CommandReceived(cmd) => {
+        Message::CommandReceived(mut cmd) => {
+            let decision =
+                evaluate_command_policy(&state.allowlist_patterns, &state.policy_rules, &cmd);
+            tracing::info!(
+                "command policy for {}: {:?} by `{}` ({})",
+                cmd.request_id,
+                decision.action,
+                decision.rule_id,
+                decision.reason
+            );
+            if decision.action == command_policy::Action::Deny {
+                let reason = format!(
+                    "blocked by command policy rule `{}`: {}",
+                    decision.rule_id, decision.reason
+                );
+                crate::audit_log::emit_command_declined(
+                    &audit_request(&cmd, &state.current_session_id),
+                    &reason,
+                    Some(decision.rule_id.as_str()),
+                );
+                if let Some(tx) = &state.outgoing_tx {
+                    let _ = tx.send(crate::backend_bridge::OutgoingMessage::Decline {
+                        request_id: cmd.request_id.clone(),
+                        reason,
+                    });
+                }
+                return iced::Task::none();
+            }
+            cmd.is_allowlisted = decision.is_allowed();
+            cmd.policy_rule = decision.rule_id;
+            if cmd.is_allowlisted {
+                // Allowlisted: run with the dialog's current settings, without
+                // asking.
+                let req = audit_request(&cmd, &state.current_session_id);
+                crate::audit_log::emit_command_auto_approved(
+                    &req,
+                    Some(cmd.policy_rule.as_str()),
+                );
+                if let Some(tx) = &state.outgoing_tx {
+                    let _ = tx.send(approve_message(&cmd.request_id, &state.approval_dialog));
+                }
+                state.running_commands.insert(cmd.request_id.clone(), req);
+                return iced::Task::none();
+            }
             state.current_request_id = cmd.request_id.clone();
             state.pending_commands.push(cmd);
             state.active_overlay = ActiveOverlay::ApprovalDialog;
             iced::Task::none()
         }
 
-        Message::CommandCompleted { request_id, .. } => {
+        Message::CommandCompleted {
+            request_id,
+            success,
+            exit_code,
+            timed_out,
+        } => {
+            if let Some(req) = state.running_commands.remove(&request_id) {
+                let exit_code = exit_code.or(success.then_some(0));
+                crate::audit_log::emit_command_finished(&req, exit_code, timed_out);
+            }
             state
                 .pending_commands
                 .retain(|c| c.request_id != request_id);
@@ -524,6 +594,13 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
         }
 
         Message::KillSessionDone(session_id) => {
+            state.running_commands.retain(|_, req| {
+                if req.session_id != session_id {
+                    return true;
+                }
+                crate::audit_log::emit_command_killed(&*req);
+                false
+            });
             state.session_tabs.retain(|t| t.session_id != session_id);
             state.terminal_output.remove(&session_id);
             iced::Task::none()
@@ -605,25 +682,13 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
             iced::Task::none()
         }
         Message::ApproveCommand { request_id } => {
+            if let Some(cmd) = state.pending_commands.iter().find(|c| c.request_id == request_id) {
+                let req = audit_request(cmd, &state.current_session_id);
+                crate::audit_log::emit_command_approved(&req);
+                state.running_commands.insert(request_id.clone(), req);
+            }
             if let Some(tx) = &state.outgoing_tx {
-                let d = &state.approval_dialog;
-                let _ = tx.send(crate::backend_bridge::OutgoingMessage::Approve {
-                    request_id: request_id.clone(),
-                    autonomy_mode: d.autonomy_mode.as_str().to_string(),
-                    provider: d.provider.as_str().to_string(),
-                    session_mode: d.session_mode.as_str().to_string(),
-                    resume_session_id: if d.resume_session_id.is_empty() {
-                        None
-                    } else {
-                        Some(d.resume_session_id.clone())
-                    },
-                    output_format: d.output_format.as_str().to_string(),
-                    budget_max_commands: d.budget_max_commands,
-                    budget_max_duration_secs: d.budget_max_duration_secs,
-                    budget_max_files: d.budget_max_files,
-                    gemini_screen_reader: d.gemini_screen_reader,
-                    copilot_minimal_ui: d.copilot_minimal_ui,
-                });
+                let _ = tx.send(approve_message(&request_id, &state.approval_dialog));
             }
             state.pending_commands.retain(|c| c.request_id != request_id);
             if state.pending_commands.is_empty() {
@@ -634,6 +699,13 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
             iced::Task::none()
         }
         Message::DeclineCommand { request_id } => {
+            if let Some(cmd) = state.pending_commands.iter().find(|c| c.request_id == request_id) {
+                crate::audit_log::emit_command_declined(
+                    &audit_request(cmd, &state.current_session_id),
+                    &state.approval_dialog.decline_reason,
+                    None,
+                );
+            }
             if let Some(tx) = &state.outgoing_tx {
                 let reason = state.approval_dialog.decline_reason.clone();
                 let _ = tx.send(crate::backend_bridge::OutgoingMessage::Decline {
@@ -873,9 +945,18 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
                                     let request_id =
                                         val["requestId"].as_str().unwrap_or("").to_string();
                                     let success = val["success"].as_bool().unwrap_or(true);
+                                    let exit_code = val["exitCode"]
+                                        .as_i64()
+                                        .and_then(|code| i32::try_from(code).ok());
+                                    let timed_out = val["timedOut"].as_bool().unwrap_or(false);
                                     let t = update(
                                         state,
-                                        Message::CommandCompleted { request_id, success },
+                                        Message::CommandCompleted {
+                                            request_id,
+                                            success,
+                                            exit_code,
+                                            timed_out,
+                                        },
                                     );
                                     extra_tasks.push(t);
                                 }
@@ -896,6 +977,23 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
                                     );
                                     extra_tasks.push(t);
                                 }
+                                "AuditQueryRequest" => {
+                                    if let Ok(req) = serde_json::from_value::<
+                                        crate::protocol::AuditQueryRequest,
+                                    >(val)
+                                    {
+                                        let response = crate::protocol::Message::AuditQueryResponse(
+                                            crate::audit_log::answer_query(&req),
+                                        );
+                                        if let (Some(tx), Ok(json)) =
+                                            (&state.outgoing_tx, serde_json::to_string(&response))
+                                        {
+                                            let _ = tx.send(
+                                                crate::backend_bridge::OutgoingMessage::Raw(json),
+                                            );
+                                        }
+                                    }
+                                }
                                 "OutputChunk" => {
                                     let session_id =
                                         val["sessionId"].as_str().unwrap_or("").to_string();
@@ -1039,6 +1137,99 @@ pub fn update(state: &mut AppState, msg: Message) -> iced::Task<Message> {
     }
 }
 
+/// Run the shared command policy over an incoming request, with the
+/// configured allowlist patterns as its allow rules.
+fn evaluate_command_policy(
+    patterns: &[AllowlistPattern],
+    rules: &[command_policy::Rule],
+    cmd: &PendingCommand,
+) -> command_policy::Decision {
+    let patterns: Vec<String> = patterns.iter().map(|p| p.pattern.clone()).collect();
+    let policy = command_policy::Policy::from_allowlist(&patterns, rules.to_vec());
+    policy.evaluate(
+        &cmd.command_text,
+        &command_policy::EvalContext {
+            shell: policy_shell(&cmd.terminal_profile),
+            working_directory: &cmd.working_directory,
+            workspace: &cmd.workspace_path,
+        },
+    )
+}
+
+/// Load the patterns and structured rules of `terminal-allowlist.json` from
+/// the data root, the same file the Qt terminal and the server read.
+fn load_allowlist_file(state: &mut AppState) {
+    let repo = crate::saved_commands_repository::SavedCommandsRepository::from_env_or_default();
+    if let Some(file) = command_policy::AllowlistFile::load(repo.data_root()) {
+        state.allowlist_patterns = file
+            .patterns
+            .into_iter()
+            .map(|pattern| AllowlistPattern {
+                pattern,
+                is_builtin: false,
+            })
+            .collect();
+        state.policy_rules = file.rules;
+    }
+}
+
+/// The `CommandResponse` approving `request_id` with the approval dialog's
+/// current settings.
+fn approve_message(
+    request_id: &str,
+    d: &ApprovalDialogState,
+) -> crate::backend_bridge::OutgoingMessage {
+    crate::backend_bridge::OutgoingMessage::Approve {
+        request_id: request_id.to_string(),
+        autonomy_mode: d.autonomy_mode.as_str().to_string(),
+        provider: d.provider.as_str().to_string(),
+        session_mode: d.session_mode.as_str().to_string(),
+        resume_session_id: if d.resume_session_id.is_empty() {
+            None
+        } else {
+            Some(d.resume_session_id.clone())
+        },
+        output_format: d.output_format.as_str().to_string(),
+        budget_max_commands: d.budget_max_commands,
+        budget_max_duration_secs: d.budget_max_duration_secs,
+        budget_max_files: d.budget_max_files,
+        gemini_screen_reader: d.gemini_screen_reader,
+        copilot_minimal_ui: d.copilot_minimal_ui,
+    }
+}
+
+/// The fields of a pending command that the audit log records, as a
+/// `CommandRequest`.
+fn audit_request(cmd: &PendingCommand, session_id: &str) -> crate::protocol::CommandRequest {
+    crate::protocol::CommandRequest {
+        id: cmd.request_id.clone(),
+        command: cmd.command_text.clone(),
+        working_directory: cmd.working_directory.clone(),
+        context: cmd.context_info.clone(),
+        session_id: session_id.to_string(),
+        terminal_profile: Default::default(),
+        workspace_path: cmd.workspace_path.clone(),
+        venv_path: String::new(),
+        activate_venv: false,
+        timeout_seconds: 0,
+        args: Vec::new(),
+        env: HashMap::new(),
+        workspace_id: String::new(),
+        allowlisted: cmd.is_allowlisted,
+    }
+}
+
+/// Map a `TerminalProfile` key (`"bash"`, `"power_shell"`, `"pwsh"`, `"cmd"`,
+/// `"system"`) to the dialect the policy tokenizes in.
+fn policy_shell(profile: &str) -> command_policy::Shell {
+    match profile.to_ascii_lowercase().as_str() {
+        "power_shell" | "powershell" | "pwsh" => command_policy::Shell::PowerShell,
+        "cmd" => command_policy::Shell::Cmd,
+        "bash" => command_policy::Shell::Posix,
+        _ => command_policy::Shell::host_default(),
+    }
+}
+
 pub fn view(state: &AppState, _window: window::Id) -> iced::Element<'_, Message> {
     use iced::widget::{column, container, Stack};
     use iced::Length;
@@ -35,6 +35,8 @@ pub struct ExecutionResult {
     pub exit_code: Option<i32>,
     /// All accumulated stdout + stderr output.
     pub output: String,
+    /// The command ran past `timeout_seconds` and was stopped.
+    pub timed_out: bool,
 }
 
 pub struct PersistentShellManager {
@@ -166,6 +168,7 @@ pub async fn execute_command(
         request_id: request.id.clone(),
         exit_code: status.code(),
         output,
+        timed_out: false,
     })
 }
 
@@ -216,6 +219,7 @@ pub async fn execute_command_with_timeout(
                 request_id: request.id.clone(),
                 exit_code: Some(-1),
                 output,
+                timed_out: true,
             })
         }
     }
@@ -250,6 +254,7 @@ impl PersistentShellManager {
                     request_id: request.id.clone(),
                     exit_code: Some(-1),
                     output: timeout_msg,
+                    timed_out: true,
                 })
             }
         }
@@ -311,6 +316,7 @@ impl PersistentShellManager {
             request_id: request.id.clone(),
             exit_code,
             output,
+            timed_out: false,
         })
     }
 
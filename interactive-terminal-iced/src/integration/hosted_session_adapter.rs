@@ -7,6 +7,7 @@ use crate::launch_builder::{build_launch_command, LaunchOptions};
 use crate::protocol::{CommandRequest, ContextPack, Message, TerminalProfile};
 use serde_json::json;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 struct StartContractError {
@@ -15,15 +16,39 @@ struct StartContractError {
     fallback_reason: &'static str,
 }
 
+/// Temp files written for a hosted session's launch (context pack, MCP
+/// config).  They are deleted when this is dropped, so the adapter keeps it
+/// with the session and drops it when the session ends.
+#[derive(Debug, Default)]
+pub struct LaunchTempFiles(Vec<PathBuf>);
+
+impl Drop for LaunchTempFiles {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            if let Err(e) = std::fs::remove_file(path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!(
+                        "[PM][hosted_session_adapter] failed to remove {}: {e}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+}
+
 pub trait HostedSessionAdapter {
     fn hydrate_request_with_session_context(&mut self, request: &mut CommandRequest);
     fn queue_command(&mut self, request: CommandRequest) -> Result<(), String>;
     fn register_running_output(&mut self, request_id: &str);
+    /// `temp_files` must live as long as the session; dropping it deletes
+    /// the launch's temp files.
     fn start_hosted_session(
         &mut self,
         response_id: &str,
         session_id: &str,
         request_id: &str,
+        temp_files: LaunchTempFiles,
     ) -> Message;
     fn fail_hosted_session_start(
         &mut self,
@@ -134,7 +159,9 @@ fn validate_specialized_start_request(
     Ok(runtime_session_id)
 }
 
-pub fn start_request_to_command(req: &StartAgentSessionRequest) -> CommandRequest {
+pub fn start_request_to_command(
+    req: &StartAgentSessionRequest,
+) -> (CommandRequest, LaunchTempFiles) {
     let runtime_session_id = effective_runtime_session_id(req);
 
     // Build a ContextPack from the prompt payload so the launch builder can
@@ -170,7 +197,7 @@ pub fn start_request_to_command(req: &StartAgentSessionRequest) -> CommandReques
         use_cli_mcp: true,
         ..LaunchOptions::default()
     };
-    let (command, args, mut launch_env) = match build_launch_command(
+    let (command, args, mut launch_env, temp_files) = match build_launch_command(
         &req.command,
         Some(&context_pack),
         "guided",
@@ -178,13 +205,24 @@ pub fn start_request_to_command(req: &StartAgentSessionRequest) -> CommandReques
         Some(&req.plan_id),
         &options,
     ) {
-        Ok(launch) => (launch.program, launch.args, launch.env),
+        Ok(launch) => {
+            let temp_files = [launch.context_pack_path, launch.mcp_config_path]
+                .into_iter()
+                .flatten()
+                .collect();
+            (launch.program, launch.args, launch.env, LaunchTempFiles(temp_files))
+        }
         Err(e) => {
             eprintln!(
                 "[PM][hosted_session_adapter] build_launch_command failed: {e}; \
                  falling back to raw command"
             );
-            (req.command.clone(), req.args.clone(), HashMap::new())
+            (
+                req.command.clone(),
+                req.args.clone(),
+                HashMap::new(),
+                LaunchTempFiles::default(),
+            )
         }
     };
 
@@ -194,7 +232,7 @@ pub fn start_request_to_command(req: &StartAgentSessionRequest) -> CommandReques
         launch_env.insert(k.clone(), v.clone());
     }
 
-    CommandRequest {
+    let command_request = CommandRequest {
         id: runtime_session_id,
         command,
         working_directory: req.working_directory.clone(),
@@ -213,7 +251,8 @@ pub fn start_request_to_command(req: &StartAgentSessionRequest) -> CommandReques
         env: launch_env,
         workspace_id: req.workspace_id.clone(),
         allowlisted: true,
-    }
+    };
+    (command_request, temp_files)
 }
 
 pub fn handle_start_session(
@@ -234,7 +273,8 @@ pub fn handle_start_session(
         }
     };
 
-    let mut command_request = start_request_to_command(req);
+    // On any failure below `temp_files` is dropped here, removing the files.
+    let (mut command_request, temp_files) = start_request_to_command(req);
     command_request.context = append_metadata_projection(req, &command_request.context);
     adapter.hydrate_request_with_session_context(&mut command_request);
     let request_id = command_request.id.clone();
@@ -242,7 +282,12 @@ pub fn handle_start_session(
     match adapter.queue_command(command_request) {
         Ok(()) => {
             adapter.register_running_output(&request_id);
-            adapter.start_hosted_session(&req.id, &req.session_id, &runtime_session_id)
+            adapter.start_hosted_session(
+                &req.id,
+                &req.session_id,
+                &runtime_session_id,
+                temp_files,
+            )
         }
         Err(error) => adapter.fail_hosted_session_start(
             &req.id,
@@ -279,3 +324,29 @@ pub fn handle_list_sessions(
 pub fn handle_get_session(adapter: &impl HostedSessionAdapter, req: &GetAgentSessionRequest) -> Message {
     adapter.get_hosted_session(&req.id, &req.session_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launch_temp_files_are_removed_on_drop() {
+        let dir = std::env::temp_dir();
+        let paths: Vec<PathBuf> = ["context", "mcp"]
+            .iter()
+            .map(|name| dir.join(format!("pm_launch_temp_{name}_{}.json", std::process::id())))
+            .collect();
+        for path in &paths {
+            std::fs::write(path, "{}").unwrap();
+        }
+        let missing = dir.join(format!("pm_launch_temp_missing_{}.json", std::process::id()));
+
+        let mut files = paths.clone();
+        files.push(missing);
+        drop(LaunchTempFiles(files));
+
+        for path in &paths {
+            assert!(!path.exists(), "{} was not removed", path.display());
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Claude Code CLI launch builder.
+//!
+//! Unlike Gemini and Copilot, Claude Code takes everything the launch needs as
+//! native flags: `--resume <id>`, `--permission-mode <mode>` and
+//! `--mcp-config <file>`.  The startup prompt is the positional argument,
+//! placed after a `--` separator because `--mcp-config` takes a variadic list
+//! and would otherwise read the prompt as a second config file.
+
+use super::{
+    attach_context_pack, inject_budget_env, inject_project_memory_mcp_env_defaults,
+    pack_with_resume, requested_resume_session_id, startup_prompt_from_context_pack,
+    write_json_tempfile, LaunchCommand, LaunchOptions,
+};
+use crate::protocol::ContextPack;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Permission modes accepted by `claude --permission-mode`.
+pub const CLAUDE_PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+/// Build a [`LaunchCommand`] for an interactive Claude Code CLI session.
+///
+/// - The permission mode comes from `options.permission_mode`, or is derived
+///   from `autonomy_mode` (`acceptEdits` when autonomous, else `default`).
+///   `bypassPermissions` requires `options.trusted_scope_confirmed`.
+/// - The Project Memory MCP server is written to a temporary MCP config file
+///   passed via `--mcp-config` (see [`LaunchCommand::mcp_config_path`]).
+/// - A resume session ID is passed as `--resume <id>` and also embedded in the
+///   context pack; without an ID the session falls back to `"new"`.
+/// - Structured output is not available interactively: `"json"` and
+///   `"stream-json"` fall back to text and set `PM_REQUESTED_OUTPUT_FORMAT`.
+///
+/// # Errors
+/// Returns `Err` for an unknown or unconfirmed permission mode, or when a
+/// temp-file write fails.
+pub fn build_claude_launch(
+    context_pack: Option<&ContextPack>,
+    autonomy_mode: &str,
+    requesting_agent: Option<&str>,
+    plan_short_id: Option<&str>,
+    options: &LaunchOptions,
+) -> Result<LaunchCommand, String> {
+    #[cfg(target_os = "windows")]
+    let program = "claude.cmd".to_string();
+    #[cfg(not(target_os = "windows"))]
+    let program = "claude".to_string();
+
+    let permission_mode = resolve_permission_mode(autonomy_mode, options)?;
+
+    let mut args: Vec<String> = Vec::new();
+    let mut env: HashMap<String, String> = HashMap::new();
+
+    env.insert("NPM_CONFIG_UPDATE_NOTIFIER".to_string(), "false".to_string());
+    if !autonomy_mode.is_empty() {
+        env.insert(
+            "PM_AGENT_AUTONOMY_MODE".to_string(),
+            autonomy_mode.to_string(),
+        );
+    }
+
+    // ── Session mode ─────────────────────────────────────────────────────────
+    let resume_session_id = requested_resume_session_id(context_pack, options);
+    let owned_pack_with_resume = pack_with_resume(context_pack, resume_session_id.as_deref());
+    let resolved_pack: Option<&ContextPack> = owned_pack_with_resume.as_ref().or(context_pack);
+    if let Some(session_id) = &resume_session_id {
+        args.push("--resume".to_string());
+        args.push(session_id.clone());
+    }
+
+    args.push("--permission-mode".to_string());
+    args.push(permission_mode.to_string());
+
+    // ── MCP config ───────────────────────────────────────────────────────────
+    inject_project_memory_mcp_env_defaults(&mut env, "claude", resolved_pack, options);
+    let mcp_config_path = write_mcp_config(&env)
+        .map_err(|err| format!("Failed to write Claude MCP config temp file: {err}"))?;
+    args.push("--mcp-config".to_string());
+    args.push(mcp_config_path.to_string_lossy().to_string());
+
+    // ── Output format ────────────────────────────────────────────────────────
+    let requested_output_format = options.output_format.trim();
+    if matches!(requested_output_format, "json" | "stream-json") {
+        eprintln!(
+            "[PM][launch_builder] output_format '{requested_output_format}' not supported by \
+             interactive claude sessions, falling back to text"
+        );
+        env.insert(
+            "PM_REQUESTED_OUTPUT_FORMAT".to_string(),
+            requested_output_format.to_string(),
+        );
+    }
+
+    let context_pack_path = attach_context_pack(&mut env, resolved_pack, None)
+        .map_err(|err| format!("Failed to write Claude context-pack temp file: {err}"))?;
+
+    inject_budget_env(&mut env, options.autonomy_budget.as_ref());
+
+    // The prompt goes last, behind `--` so the variadic `--mcp-config` stops
+    // before it.  Newlines are stripped for the same shell-quoting reason as
+    // Gemini.
+    if let Some(startup_prompt) = startup_prompt_from_context_pack(resolved_pack) {
+        args.push("--".to_string());
+        args.push(startup_prompt.replace('\r', "").replace('\n', " "));
+    }
+
+    let agent_tag = requesting_agent.unwrap_or("agent");
+    let plan_tag = plan_short_id.unwrap_or("");
+    let session_label = if plan_tag.is_empty() {
+        format!("Claude \u{2014} {agent_tag}")
+    } else {
+        format!("Claude \u{2014} {agent_tag} \u{2014} {plan_tag}")
+    };
+
+    Ok(LaunchCommand {
+        program,
+        args,
+        env,
+        context_pack_path,
+        mcp_config_path: Some(mcp_config_path),
+        session_label,
+        provider: "claude".to_string(),
+    })
+}
+
+fn resolve_permission_mode(
+    autonomy_mode: &str,
+    options: &LaunchOptions,
+) -> Result<&'static str, String> {
+    let requested = options.permission_mode.trim();
+    let mode = if requested.is_empty() {
+        if autonomy_mode.trim().eq_ignore_ascii_case("autonomous") {
+            "acceptEdits"
+        } else {
+            "default"
+        }
+    } else {
+        CLAUDE_PERMISSION_MODES
+            .iter()
+            .copied()
+            .find(|mode| mode.eq_ignore_ascii_case(requested))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown Claude permission mode \"{requested}\" (expected one of: {})",
+                    CLAUDE_PERMISSION_MODES.join(", ")
+                )
+            })?
+    };
+    if mode == "bypassPermissions" && !options.trusted_scope_confirmed {
+        return Err(
+            "Claude permission mode \"bypassPermissions\" requires trusted-scope confirmation"
+                .to_string(),
+        );
+    }
+    Ok(mode)
+}
+
+/// Write an MCP config pointing Claude at the Project Memory server chosen by
+/// [`inject_project_memory_mcp_env_defaults`].
+fn write_mcp_config(env: &HashMap<String, String>) -> Result<PathBuf, String> {
+    let url = env.get("PM_MCP_SERVER_URL").cloned().unwrap_or_default();
+    let transport = match env.get("PM_MCP_TRANSPORT").map(|t| t.as_str()) {
+        Some("sse") => "sse",
+        _ => "http",
+    };
+    let config = serde_json::json!({
+        "mcpServers": {
+            "project-memory": {
+                "type": transport,
+                "url": url,
+            }
+        }
+    });
+    let json =
+        serde_json::to_string_pretty(&config).map_err(|e| format!("JSON serialise error: {e}"))?;
+    write_json_tempfile("pm-claude-mcp", &json)
+}
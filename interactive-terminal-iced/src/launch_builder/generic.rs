@@ -0,0 +1,580 @@
+//! Config-driven launch providers for CLIs without a built-in builder.
+//!
+//! Providers are declared in `agent-providers.toml`, read from
+//! `PM_AGENT_PROVIDERS_FILE` or
+//! `{app data}/ProjectMemory/interactive-terminal/agent-providers.toml`:
+//!
+//! ```toml
+//! [[provider]]
+//! name = "acme-agent"
+//! label = "Acme"
+//! binary = "acme"
+//! args = ["--interactive", ["--plan", "{plan_id}"], "{startup_prompt}"]
+//! resume_args = [["--resume", "{resume_session_id}"]]
+//! context_file_env = "ACME_CONTEXT_FILE"
+//!
+//! [provider.env]
+//! ACME_MCP_URL = "{mcp_server_url}"
+//!
+//! [provider.budget_env]
+//! max_commands = "ACME_MAX_STEPS"
+//! ```
+//!
+//! Argument and env templates may use the placeholders in [`PLACEHOLDERS`];
+//! `{{` and `}}` produce literal braces.  An argument, or a group of arguments
+//! written as an inner array, is dropped when any placeholder it uses is
+//! empty, so optional flags disappear together with their value.
+
+use super::{
+    attach_context_pack, inject_budget_env, inject_project_memory_mcp_env_defaults,
+    is_builtin_provider, normalize_provider_token, pack_with_resume,
+    requested_resume_session_id, startup_prompt_from_context_pack, LaunchCommand,
+    LaunchProvider, LaunchRequest,
+};
+use crate::saved_commands_repository::SavedCommandsRepository;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// File name of the generic provider config.
+pub const PROVIDERS_FILE_NAME: &str = "agent-providers.toml";
+
+/// Placeholders available to argument and env templates.
+pub const PLACEHOLDERS: &[&str] = &[
+    "startup_prompt",
+    "resume_session_id",
+    "autonomy_mode",
+    "requesting_agent",
+    "plan_id",
+    "session_id",
+    "context_file",
+    "mcp_server_url",
+    "output_format",
+];
+
+/// One argument, or a group of arguments kept or dropped together.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ArgTemplate {
+    Single(String),
+    Group(Vec<String>),
+}
+
+impl ArgTemplate {
+    fn parts(&self) -> &[String] {
+        match self {
+            ArgTemplate::Single(arg) => std::slice::from_ref(arg),
+            ArgTemplate::Group(args) => args,
+        }
+    }
+}
+
+/// Extra env var names that receive the [`super::AutonomyBudget`] caps, in
+/// addition to the standard `PM_BUDGET_*` variables.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BudgetEnvNames {
+    #[serde(default)]
+    pub max_commands: Option<String>,
+    #[serde(default)]
+    pub max_duration_secs: Option<String>,
+    #[serde(default)]
+    pub max_files: Option<String>,
+}
+
+/// A `[[provider]]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenericProviderConfig {
+    /// Provider token used in launch requests.
+    pub name: String,
+    /// Session tab label prefix; defaults to `name`.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Program to run.
+    pub binary: String,
+    /// Arguments for every launch.
+    #[serde(default)]
+    pub args: Vec<ArgTemplate>,
+    /// Arguments placed before `args` when resuming.  Without them, resume
+    /// requests are rejected.
+    #[serde(default)]
+    pub resume_args: Option<Vec<ArgTemplate>>,
+    /// Extra env var that receives the context-pack file path.
+    #[serde(default)]
+    pub context_file_env: Option<String>,
+    /// Env vars set after the standard `PM_*` ones; values are templates.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub budget_env: BudgetEnvNames,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProvidersFile {
+    #[serde(default)]
+    provider: Vec<GenericProviderConfig>,
+}
+
+/// A validated generic provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericProvider {
+    token: String,
+    config: GenericProviderConfig,
+}
+
+impl GenericProvider {
+    pub fn config(&self) -> &GenericProviderConfig {
+        &self.config
+    }
+
+    fn label(&self) -> &str {
+        self.config
+            .label
+            .as_deref()
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .unwrap_or(&self.config.name)
+    }
+}
+
+impl LaunchProvider for GenericProvider {
+    fn token(&self) -> &str {
+        &self.token
+    }
+
+    fn build(&self, request: &LaunchRequest<'_>) -> Result<LaunchCommand, String> {
+        let options = request.options;
+        let config = &self.config;
+        if options.session_mode.trim().eq_ignore_ascii_case("resume")
+            && config.resume_args.is_none()
+        {
+            return Err(format!("{} does not support session resume", self.label()));
+        }
+
+        let resume_session_id = requested_resume_session_id(request.context_pack, options);
+        let owned_pack_with_resume =
+            pack_with_resume(request.context_pack, resume_session_id.as_deref());
+        let resolved_pack = owned_pack_with_resume.as_ref().or(request.context_pack);
+
+        let mut env: HashMap<String, String> = HashMap::new();
+        if !request.autonomy_mode.is_empty() {
+            env.insert(
+                "PM_AGENT_AUTONOMY_MODE".to_string(),
+                request.autonomy_mode.to_string(),
+            );
+        }
+        inject_project_memory_mcp_env_defaults(&mut env, &self.token, resolved_pack, options);
+        let context_pack_path =
+            attach_context_pack(&mut env, resolved_pack, config.context_file_env.as_deref())
+                .map_err(|err| {
+                    format!("Failed to write {} context-pack temp file: {err}", self.label())
+                })?;
+        inject_budget_env(&mut env, options.autonomy_budget.as_ref());
+        if let Some(budget) = &options.autonomy_budget {
+            let names = &config.budget_env;
+            for (name, value) in [
+                (&names.max_commands, budget.max_commands.map(u64::from)),
+                (&names.max_duration_secs, budget.max_duration_secs),
+                (&names.max_files, budget.max_files.map(u64::from)),
+            ] {
+                if let (Some(name), Some(value)) = (name, value) {
+                    env.insert(name.clone(), value.to_string());
+                }
+            }
+        }
+
+        let output_format = match options.output_format.trim() {
+            "json" | "stream-json" => options.output_format.trim().to_string(),
+            _ => String::new(),
+        };
+        let vars: HashMap<&str, String> = HashMap::from([
+            (
+                "startup_prompt",
+                startup_prompt_from_context_pack(resolved_pack)
+                    .map(|prompt| prompt.replace('\r', "").replace('\n', " "))
+                    .unwrap_or_default(),
+            ),
+            ("resume_session_id", resume_session_id.clone().unwrap_or_default()),
+            ("autonomy_mode", request.autonomy_mode.to_string()),
+            (
+                "requesting_agent",
+                request.requesting_agent.unwrap_or_default().to_string(),
+            ),
+            (
+                "plan_id",
+                env.get("PM_PLAN_ID")
+                    .cloned()
+                    .or_else(|| request.plan_short_id.map(str::to_string))
+                    .unwrap_or_default(),
+            ),
+            (
+                "session_id",
+                env.get("PM_AGENT_SESSION_ID").cloned().unwrap_or_default(),
+            ),
+            (
+                "context_file",
+                context_pack_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "mcp_server_url",
+                env.get("PM_MCP_SERVER_URL").cloned().unwrap_or_default(),
+            ),
+            ("output_format", output_format),
+        ]);
+
+        let mut args = Vec::new();
+        if resume_session_id.is_some() {
+            if let Some(resume_args) = &config.resume_args {
+                expand_args(resume_args, &vars, &mut args)?;
+            }
+        }
+        expand_args(&config.args, &vars, &mut args)?;
+
+        for (key, template) in &config.env {
+            match expand(template, &vars)? {
+                Some(value) => {
+                    env.insert(key.clone(), value);
+                }
+                None => {
+                    env.remove(key);
+                }
+            }
+        }
+
+        let agent_tag = request.requesting_agent.unwrap_or("agent");
+        let plan_tag = request.plan_short_id.unwrap_or("");
+        let session_label = if plan_tag.is_empty() {
+            format!("{} \u{2014} {agent_tag}", self.label())
+        } else {
+            format!("{} \u{2014} {agent_tag} \u{2014} {plan_tag}", self.label())
+        };
+
+        Ok(LaunchCommand {
+            program: config.binary.trim().to_string(),
+            args,
+            env,
+            context_pack_path,
+            mcp_config_path: None,
+            session_label,
+            provider: self.token.clone(),
+        })
+    }
+}
+
+// ─── Loading ─────────────────────────────────────────────────────────────────
+
+/// Where `agent-providers.toml` is read from.
+pub fn providers_config_path() -> Option<PathBuf> {
+    if let Ok(explicit) = std::env::var("PM_AGENT_PROVIDERS_FILE") {
+        if !explicit.trim().is_empty() {
+            return Some(PathBuf::from(explicit.trim()));
+        }
+    }
+    SavedCommandsRepository::platform_app_data_root()
+        .map(|root| root.join("interactive-terminal").join(PROVIDERS_FILE_NAME))
+}
+
+/// Providers from the configured file.  A missing file yields none; an
+/// invalid one is logged and ignored.
+pub fn configured_providers() -> Vec<GenericProvider> {
+    let Some(path) = providers_config_path() else {
+        return Vec::new();
+    };
+    load_generic_providers(&path).unwrap_or_else(|err| {
+        eprintln!("[PM][launch_builder] ignoring {}: {err}", path.display());
+        Vec::new()
+    })
+}
+
+/// Load and validate the providers in `path`; a missing file yields none.
+pub fn load_generic_providers(path: &Path) -> Result<Vec<GenericProvider>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => parse_generic_providers(&text),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(format!("cannot read provider config: {err}")),
+    }
+}
+
+/// Parse and validate provider config TOML.
+pub fn parse_generic_providers(text: &str) -> Result<Vec<GenericProvider>, String> {
+    let file: ProvidersFile =
+        toml::from_str(text).map_err(|err| format!("invalid provider config: {err}"))?;
+    let mut seen = HashSet::new();
+    let mut providers = Vec::with_capacity(file.provider.len());
+    for config in file.provider {
+        let token = normalize_provider_token(&config.name);
+        if token.is_empty() {
+            return Err("provider name must not be empty".to_string());
+        }
+        if config.binary.trim().is_empty() {
+            return Err(format!("provider \"{token}\": binary must not be empty"));
+        }
+        if is_builtin_provider(&token) {
+            return Err(format!(
+                "provider \"{token}\" clashes with a built-in provider"
+            ));
+        }
+        if !seen.insert(token.clone()) {
+            return Err(format!("provider \"{token}\" is defined more than once"));
+        }
+        if config
+            .context_file_env
+            .as_deref()
+            .is_some_and(|var| var.trim().is_empty())
+        {
+            return Err(format!(
+                "provider \"{token}\": context_file_env must not be empty"
+            ));
+        }
+        let templates = config
+            .args
+            .iter()
+            .chain(config.resume_args.iter().flatten())
+            .flat_map(ArgTemplate::parts)
+            .chain(config.env.values());
+        for template in templates {
+            validate_template(template)
+                .map_err(|err| format!("provider \"{token}\": {err}"))?;
+        }
+        providers.push(GenericProvider { token, config });
+    }
+    Ok(providers)
+}
+
+// ─── Templates ───────────────────────────────────────────────────────────────
+
+fn validate_template(template: &str) -> Result<(), String> {
+    let all_set: HashMap<&str, String> = PLACEHOLDERS
+        .iter()
+        .map(|name| (*name, "x".to_string()))
+        .collect();
+    expand(template, &all_set).map(|_| ())
+}
+
+fn expand_args(
+    templates: &[ArgTemplate],
+    vars: &HashMap<&str, String>,
+    out: &mut Vec<String>,
+) -> Result<(), String> {
+    'templates: for template in templates {
+        let mut expanded = Vec::new();
+        for part in template.parts() {
+            match expand(part, vars)? {
+                Some(value) => expanded.push(value),
+                None => continue 'templates,
+            }
+        }
+        out.extend(expanded);
+    }
+    Ok(())
+}
+
+/// Substitute placeholders in `template`; `None` when one of them is empty.
+fn expand(template: &str, vars: &HashMap<&str, String>) -> Result<Option<String>, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    let mut missing = false;
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err(format!("unmatched '}}' in \"{template}\""));
+        }
+        let Some(end) = tail.find('}') else {
+            return Err(format!("unclosed placeholder in \"{template}\""));
+        };
+        let name = &tail[1..end];
+        let Some(value) = vars.get(name) else {
+            return Err(format!("unknown placeholder {{{name}}} in \"{template}\""));
+        };
+        if value.is_empty() {
+            missing = true;
+        }
+        out.push_str(value);
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    Ok((!missing).then_some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launch_builder::{AutonomyBudget, LaunchOptions};
+    use crate::protocol::ContextPack;
+
+    const SAMPLE: &str = r#"
+[[provider]]
+name = "Acme-Agent"
+label = "Acme"
+binary = "acme"
+args = ["--interactive", ["--plan", "{plan_id}"], ["--format", "{output_format}"], "{startup_prompt}"]
+resume_args = [["--resume", "{resume_session_id}"]]
+context_file_env = "ACME_CONTEXT_FILE"
+
+[provider.env]
+ACME_MCP_URL = "{mcp_server_url}"
+ACME_AGENT = "agent:{requesting_agent}"
+
+[provider.budget_env]
+max_commands = "ACME_MAX_STEPS"
+
+[[provider]]
+name = "plain"
+binary = "plain-cli"
+"#;
+
+    fn sample(name: &str) -> GenericProvider {
+        parse_generic_providers(SAMPLE)
+            .unwrap()
+            .into_iter()
+            .find(|p| p.token() == name)
+            .unwrap()
+    }
+
+    fn build(
+        provider: &GenericProvider,
+        pack: Option<&ContextPack>,
+        options: &LaunchOptions,
+    ) -> Result<LaunchCommand, String> {
+        provider.build(&LaunchRequest {
+            context_pack: pack,
+            autonomy_mode: "guided",
+            requesting_agent: Some("Executor"),
+            plan_short_id: None,
+            options,
+        })
+    }
+
+    #[test]
+    fn parses_and_normalizes_names() {
+        let providers = parse_generic_providers(SAMPLE).unwrap();
+        let tokens: Vec<&str> = providers.iter().map(|p| p.token()).collect();
+        assert_eq!(tokens, vec!["acme-agent", "plain"]);
+        assert_eq!(
+            providers[0].config().args[1],
+            ArgTemplate::Group(vec!["--plan".to_string(), "{plan_id}".to_string()])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_configs() {
+        for (toml, needle) in [
+            ("[[provider]]\nname = \"claude\"\nbinary = \"x\"", "built-in"),
+            ("[[provider]]\nname = \"\"\nbinary = \"x\"", "name must not be empty"),
+            ("[[provider]]\nname = \"a\"\nbinary = \" \"", "binary must not be empty"),
+            (
+                "[[provider]]\nname = \"a\"\nbinary = \"x\"\n[[provider]]\nname = \"A\"\nbinary = \"y\"",
+                "more than once",
+            ),
+            (
+                "[[provider]]\nname = \"a\"\nbinary = \"x\"\nargs = [\"{bogus}\"]",
+                "unknown placeholder",
+            ),
+            (
+                "[[provider]]\nname = \"a\"\nbinary = \"x\"\nargs = [\"{plan_id\"]",
+                "unclosed",
+            ),
+        ] {
+            let err = parse_generic_providers(toml).unwrap_err();
+            assert!(err.contains(needle), "{toml:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn missing_file_yields_no_providers() {
+        let path = std::env::temp_dir().join("pm-no-such-agent-providers.toml");
+        assert!(load_generic_providers(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn expand_handles_escapes_and_empty_values() {
+        let vars: HashMap<&str, String> =
+            HashMap::from([("plan_id", "p1".to_string()), ("session_id", String::new())]);
+        assert_eq!(
+            expand("{{literal}} {plan_id}", &vars).unwrap().as_deref(),
+            Some("{literal} p1")
+        );
+        assert_eq!(expand("--s={session_id}", &vars).unwrap(), None);
+    }
+
+    #[test]
+    fn build_expands_templates_and_drops_empty_groups() {
+        let pack = ContextPack {
+            startup_prompt: Some("Line one\nline two".to_string()),
+            plan_id: Some("plan_1".to_string()),
+            ..ContextPack::default()
+        };
+        let cmd = build(&sample("acme-agent"), Some(&pack), &LaunchOptions::default()).unwrap();
+
+        assert_eq!(cmd.program, "acme");
+        assert_eq!(cmd.provider, "acme-agent");
+        assert_eq!(
+            cmd.args,
+            vec!["--interactive", "--plan", "plan_1", "Line one line two"]
+        );
+        assert_eq!(cmd.session_label, "Acme \u{2014} Executor");
+        assert_eq!(cmd.env.get("ACME_AGENT").map(String::as_str), Some("agent:Executor"));
+        assert_eq!(cmd.env.get("ACME_MCP_URL"), cmd.env.get("PM_MCP_SERVER_URL"));
+        let path = cmd.context_pack_path.clone().expect("context pack written");
+        let path_str = path.to_string_lossy().to_string();
+        assert_eq!(cmd.env.get("ACME_CONTEXT_FILE"), Some(&path_str));
+        assert_eq!(cmd.env.get("PM_CONTEXT_PACK_FILE"), Some(&path_str));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn build_resumes_with_resume_args() {
+        let opts = LaunchOptions {
+            session_mode: "resume".to_string(),
+            resume_session_id: Some("sess_9".to_string()),
+            ..LaunchOptions::default()
+        };
+        let cmd = build(&sample("acme-agent"), None, &opts).unwrap();
+        assert_eq!(&cmd.args[..3], ["--resume", "sess_9", "--interactive"]);
+        if let Some(path) = &cmd.context_pack_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn build_without_resume_args_rejects_resume() {
+        let opts = LaunchOptions {
+            session_mode: "resume".to_string(),
+            resume_session_id: Some("sess_9".to_string()),
+            ..LaunchOptions::default()
+        };
+        let err = build(&sample("plain"), None, &opts).unwrap_err();
+        assert!(err.contains("does not support session resume"), "{err}");
+    }
+
+    #[test]
+    fn build_maps_budget_onto_standard_and_custom_env() {
+        let opts = LaunchOptions {
+            autonomy_budget: Some(AutonomyBudget {
+                max_commands: Some(7),
+                max_duration_secs: Some(60),
+                max_files: None,
+            }),
+            ..LaunchOptions::default()
+        };
+        let cmd = build(&sample("acme-agent"), None, &opts).unwrap();
+        assert_eq!(cmd.env.get("PM_BUDGET_MAX_COMMANDS").map(String::as_str), Some("7"));
+        assert_eq!(cmd.env.get("ACME_MAX_STEPS").map(String::as_str), Some("7"));
+        assert_eq!(
+            cmd.env.get("PM_BUDGET_MAX_DURATION_SECS").map(String::as_str),
+            Some("60")
+        );
+        assert!(!cmd.env.contains_key("PM_BUDGET_MAX_FILES"));
+    }
+}
@@ -2,17 +2,34 @@
 //!
 //! Each builder produces a [`LaunchCommand`] that describes the program,
 //! arguments, and environment variables needed to start an interactive AI
-//! provider session.  The caller in `approve_command` converts a
+//! provider session.  Gemini, Copilot and Claude Code are built in (see
+//! [`claude`] for the latter); further CLIs are described in TOML and handled
+//! by [`generic`].  [`build_launch_command`] resolves a provider token to a
+//! [`LaunchProvider`] and dispatches to it.
+//!
+//! Every provider maps the launch inputs the same way:
+//! - the [`ContextPack`] is written to a temp file exposed as
+//!   `PM_CONTEXT_PACK_FILE` (plus any provider-specific variable);
+//! - a resume request is embedded in that pack as `session_resume` and passed
+//!   natively where the CLI supports it;
+//! - [`AutonomyBudget`] caps become `PM_BUDGET_*` variables.
+//!
+//! The caller in `approve_command` converts a
 //! `LaunchCommand` into a `CommandRequest` that is dispatched through the
 //! existing ConPTY / pty-host abstraction via `command_tx` — matching the same
 //! execution path as every other terminal command.  **No process is spawned
 //! directly here.**
 //!
 //! ## Temp-file lifecycle
-//! When a context pack is serialised to disk (`context_pack_path` is `Some`),
-//! the caller is responsible for cleaning up the file after the launched
-//! process has started.  A reasonable approach is to schedule deletion via a
-//! background task after a short delay.
+//! When a context pack is serialised to disk (`context_pack_path` is `Some`,
+//! likewise `mcp_config_path`), the caller is responsible for cleaning up the file.  Hosted
+//! agent sessions hand both paths to a `LaunchTempFiles` guard that the
+//! session holds until it ends (see `integration::hosted_session_adapter`).
+
+pub mod claude;
+pub mod generic;
+
+pub use claude::build_claude_launch;
 
 use crate::protocol::ContextPack;
 use std::collections::HashMap;
@@ -80,8 +97,7 @@ pub fn approval_trusted_scope_text_for_tier(tier: u8) -> String {
 
 /// Additional options controlling session lifecycle and output format for an
 /// agent launch.  Passed alongside the standard context-pack and autonomy
-/// parameters to [`build_gemini_launch`], [`build_copilot_launch`], and
-/// [`build_launch_command`].
+/// parameters to every provider builder and [`build_launch_command`].
 #[derive(Debug, Clone, Default)]
 pub struct LaunchOptions {
     /// Session lifecycle mode.
@@ -122,13 +138,17 @@ pub struct LaunchOptions {
     ///
     /// Defaults to `false` (use the standard MCP server).
     pub use_cli_mcp: bool,
+    /// Claude Code `--permission-mode`: `"default"`, `"acceptEdits"`,
+    /// `"plan"` or `"bypassPermissions"`.  Empty derives it from the autonomy
+    /// mode.  Ignored by the other providers.
+    pub permission_mode: String,
 }
 
 // ─── LaunchCommand ────────────────────────────────────────────────────────────
 
 /// Fully resolved description of a CLI session to launch.
 ///
-/// Produced by [`build_gemini_launch`] and [`build_copilot_launch`].
+/// Produced by a [`LaunchProvider`].
 /// Converted to a `CommandRequest` (and dispatched via `command_tx`) by the
 /// `approve_command` handler in `cxxqt_bridge/invokables.rs`.
 #[derive(Debug, Clone)]
@@ -140,12 +160,16 @@ pub struct LaunchCommand {
     /// Extra environment variables to inject (merged with the process env).
     pub env: HashMap<String, String>,
     /// Path to a temporary context-pack JSON file, if written.
-    /// The caller should schedule deletion after the process has started.
+    /// The caller is responsible for deleting it (see the module docs).
     #[allow(dead_code)]
     pub context_pack_path: Option<PathBuf>,
+    /// Path to a temporary MCP server config file, if written (Claude).
+    /// Same lifecycle as `context_pack_path`.
+    #[allow(dead_code)]
+    pub mcp_config_path: Option<PathBuf>,
     /// Human-readable label suitable for use as the session tab title.
     pub session_label: String,
-    /// Normalised provider token (`"gemini"` or `"copilot"`).
+    /// Normalised provider token, e.g. `"gemini"` or `"claude"`.
     pub provider: String,
 }
 
@@ -188,7 +212,6 @@ pub fn build_gemini_launch(
 
     let mut args: Vec<String> = Vec::new();
     let mut env: HashMap<String, String> = HashMap::new();
-    let mut context_pack_path: Option<PathBuf> = None;
 
     // Inject stored API key if available.
     //
@@ -226,40 +249,9 @@ pub fn build_gemini_launch(
     // ── Session mode ─────────────────────────────────────────────────────────
     // For "resume": embed session_id in ContextPack JSON under session_resume.
     // There is no native Gemini CLI flag; the GEMINI_CONTEXT_FILE wrapper reads it.
-    let effective_session_mode =
-        if options.session_mode.trim().eq_ignore_ascii_case("resume") {
-            "resume"
-        } else {
-            "new"
-        };
-
-    // Build an owned ContextPack that may have session_resume injected, then
-    // resolve which pack reference to use (owned or original).
-    let owned_pack_with_resume: Option<ContextPack> = if effective_session_mode == "resume" {
-        match &options.resume_session_id {
-            Some(sid) if !sid.trim().is_empty() => {
-                let mut pack = context_pack.cloned().unwrap_or_default();
-                pack.session_resume = Some(crate::protocol::SessionResume {
-                    session_id: sid.clone(),
-                });
-                Some(pack)
-            }
-            _ => {
-                eprintln!(
-                    "[PM][launch_builder] session_mode=resume but resume_session_id is empty \
-                     or absent; falling back to new session"
-                );
-                None
-            }
-        }
-    } else {
-        None
-    };
-    let resolved_pack: Option<&ContextPack> = if owned_pack_with_resume.is_some() {
-        owned_pack_with_resume.as_ref()
-    } else {
-        context_pack
-    };
+    let resume_session_id = requested_resume_session_id(context_pack, options);
+    let owned_pack_with_resume = pack_with_resume(context_pack, resume_session_id.as_deref());
+    let resolved_pack: Option<&ContextPack> = owned_pack_with_resume.as_ref().or(context_pack);
 
     inject_project_memory_mcp_env_defaults(&mut env, "gemini", resolved_pack, options);
 
@@ -295,22 +287,8 @@ pub fn build_gemini_launch(
     }
 
     // Serialise context pack to a temp file if supplied
-    if let Some(pack) = resolved_pack {
-        match write_context_pack_to_tempfile(pack) {
-            Ok(path) => {
-                env.insert(
-                    "GEMINI_CONTEXT_FILE".to_string(),
-                    path.to_string_lossy().to_string(),
-                );
-                context_pack_path = Some(path);
-            }
-            Err(err) => {
-                return Err(format!(
-                    "Failed to write Gemini context-pack temp file: {err}"
-                ));
-            }
-        }
-    }
+    let context_pack_path = attach_context_pack(&mut env, resolved_pack, Some("GEMINI_CONTEXT_FILE"))
+        .map_err(|err| format!("Failed to write Gemini context-pack temp file: {err}"))?;
 
     let agent_tag = requesting_agent.unwrap_or("agent");
     let plan_tag = plan_short_id.unwrap_or("");
@@ -321,23 +299,14 @@ pub fn build_gemini_launch(
     };
 
     // Inject autonomy budget env vars when caps are set
-    if let Some(budget) = &options.autonomy_budget {
-        if let Some(n) = budget.max_commands {
-            env.insert("PM_BUDGET_MAX_COMMANDS".to_string(), n.to_string());
-        }
-        if let Some(n) = budget.max_duration_secs {
-            env.insert("PM_BUDGET_MAX_DURATION_SECS".to_string(), n.to_string());
-        }
-        if let Some(n) = budget.max_files {
-            env.insert("PM_BUDGET_MAX_FILES".to_string(), n.to_string());
-        }
-    }
+    inject_budget_env(&mut env, options.autonomy_budget.as_ref());
 
     Ok(LaunchCommand {
         program,
         args,
         env,
         context_pack_path,
+        mcp_config_path: None,
         session_label,
         provider: "gemini".to_string(),
     })
@@ -397,27 +366,12 @@ pub fn build_copilot_launch(
     #[cfg_attr(target_os = "windows", allow(unused_mut))]
     let mut args: Vec<String> = Vec::new();
     let mut env: HashMap<String, String> = HashMap::new();
-    let mut context_pack_path: Option<PathBuf> = None;
 
     // Keep context available via temp file. Prompt injection for spawn_cli_session
     // is handled through dedicated startup fields in later steps.
-    if let Some(pack) = context_pack {
-        // Write full context pack to a temp file for reference
-        match write_context_pack_to_tempfile(pack) {
-            Ok(path) => {
-                env.insert(
-                    "PM_COPILOT_CONTEXT_FILE".to_string(),
-                    path.to_string_lossy().to_string(),
-                );
-                context_pack_path = Some(path);
-            }
-            Err(err) => {
-                return Err(format!(
-                    "Failed to write Copilot context-pack temp file: {err}"
-                ));
-            }
-        }
-    }
+    let context_pack_path =
+        attach_context_pack(&mut env, context_pack, Some("PM_COPILOT_CONTEXT_FILE"))
+            .map_err(|err| format!("Failed to write Copilot context-pack temp file: {err}"))?;
 
     if let Some(prompt) = startup_prompt {
         #[cfg(target_os = "windows")]
@@ -469,23 +423,14 @@ pub fn build_copilot_launch(
     };
 
     // Inject autonomy budget env vars when caps are set
-    if let Some(budget) = &options.autonomy_budget {
-        if let Some(n) = budget.max_commands {
-            env.insert("PM_BUDGET_MAX_COMMANDS".to_string(), n.to_string());
-        }
-        if let Some(n) = budget.max_duration_secs {
-            env.insert("PM_BUDGET_MAX_DURATION_SECS".to_string(), n.to_string());
-        }
-        if let Some(n) = budget.max_files {
-            env.insert("PM_BUDGET_MAX_FILES".to_string(), n.to_string());
-        }
-    }
+    inject_budget_env(&mut env, options.autonomy_budget.as_ref());
 
     Ok(LaunchCommand {
         program,
         args,
         env,
         context_pack_path,
+        mcp_config_path: None,
         session_label,
         provider: "copilot".to_string(),
     })
@@ -505,10 +450,99 @@ fn copilot_interactive_launch_command() -> String {
 
 // ─── Provider dispatch ───────────────────────────────────────────────────────
 
+/// The launch inputs shared by every provider.
+#[derive(Debug, Clone, Copy)]
+pub struct LaunchRequest<'a> {
+    pub context_pack: Option<&'a ContextPack>,
+    pub autonomy_mode: &'a str,
+    pub requesting_agent: Option<&'a str>,
+    pub plan_short_id: Option<&'a str>,
+    pub options: &'a LaunchOptions,
+}
+
+/// A CLI agent that can be launched into a terminal session.
+pub trait LaunchProvider {
+    /// Normalised provider token, e.g. `"claude"`.
+    fn token(&self) -> &str;
+    /// Build the launch for `request`.  The risk gate has already passed.
+    fn build(&self, request: &LaunchRequest<'_>) -> Result<LaunchCommand, String>;
+}
+
+type BuilderFn = fn(
+    Option<&ContextPack>,
+    &str,
+    Option<&str>,
+    Option<&str>,
+    &LaunchOptions,
+) -> Result<LaunchCommand, String>;
+
+/// A provider implemented by one of the `build_*_launch` functions.
+struct BuiltinProvider {
+    token: &'static str,
+    build: BuilderFn,
+}
+
+impl LaunchProvider for BuiltinProvider {
+    fn token(&self) -> &str {
+        self.token
+    }
+
+    fn build(&self, request: &LaunchRequest<'_>) -> Result<LaunchCommand, String> {
+        (self.build)(
+            request.context_pack,
+            request.autonomy_mode,
+            request.requesting_agent,
+            request.plan_short_id,
+            request.options,
+        )
+    }
+}
+
+const BUILTIN_PROVIDERS: &[BuiltinProvider] = &[
+    BuiltinProvider { token: "gemini", build: build_gemini_launch },
+    BuiltinProvider { token: "copilot", build: build_copilot_launch },
+    BuiltinProvider { token: "claude", build: build_claude_launch },
+];
+
+/// Whether `token` (already normalised) names a built-in provider.
+pub fn is_builtin_provider(token: &str) -> bool {
+    BUILTIN_PROVIDERS.iter().any(|p| p.token == token)
+}
+
+/// Resolve a raw provider token to its provider: a built-in one, or a
+/// generic provider from the configured `agent-providers.toml`.
+pub fn resolve_provider(provider: &str) -> Option<Box<dyn LaunchProvider>> {
+    let normalized = normalize_provider_token(provider);
+    if let Some(builtin) = BUILTIN_PROVIDERS.iter().find(|p| p.token == normalized) {
+        return Some(Box::new(BuiltinProvider {
+            token: builtin.token,
+            build: builtin.build,
+        }));
+    }
+    generic::configured_providers()
+        .into_iter()
+        .find(|p| p.token() == normalized)
+        .map(|p| Box::new(p) as Box<dyn LaunchProvider>)
+}
+
+/// Tokens of every launchable provider, built-in first.
+pub fn known_provider_tokens() -> Vec<String> {
+    BUILTIN_PROVIDERS
+        .iter()
+        .map(|p| p.token.to_string())
+        .chain(
+            generic::configured_providers()
+                .iter()
+                .map(|p| p.token().to_string()),
+        )
+        .collect()
+}
+
 /// Dispatch to the appropriate builder based on the `provider` string.
 ///
 /// Accepted values (case-insensitive, `.cmd`/`.exe` suffix stripped):
-/// `"gemini"`, `"copilot"`.
+/// `"gemini"`, `"copilot"`, `"claude"`, or the `name` of a configured
+/// generic provider.
 pub fn build_launch_command(
     provider: &str,
     context_pack: Option<&ContextPack>,
@@ -536,11 +570,16 @@ pub fn build_launch_command(
         ));
     }
 
-    match normalized.as_str() {
-        "gemini" => build_gemini_launch(context_pack, autonomy_mode, requesting_agent, plan_short_id, options),
-        "copilot" => build_copilot_launch(context_pack, autonomy_mode, requesting_agent, plan_short_id, options),
-        other => Err(format!("Unknown provider: \"{other}\"")),
-    }
+    let Some(launch_provider) = resolve_provider(&normalized) else {
+        return Err(format!("Unknown provider: \"{normalized}\""));
+    };
+    launch_provider.build(&LaunchRequest {
+        context_pack,
+        autonomy_mode,
+        requesting_agent,
+        plan_short_id,
+        options,
+    })
 }
 
 /// Normalise a raw provider token (strip path and known suffixes, lower-case).
@@ -571,6 +610,85 @@ fn startup_prompt_from_context_pack(context_pack: Option<&ContextPack>) -> Optio
     })
 }
 
+/// Session ID to resume, when `options` asks for a resume.
+///
+/// Taken from `options.resume_session_id`, else from the pack's own
+/// `session_resume`.  Without either a warning is logged and the launch falls
+/// back to a new session.
+fn requested_resume_session_id(
+    context_pack: Option<&ContextPack>,
+    options: &LaunchOptions,
+) -> Option<String> {
+    if !options.session_mode.trim().eq_ignore_ascii_case("resume") {
+        return None;
+    }
+    let from_pack = context_pack
+        .and_then(|pack| pack.session_resume.as_ref())
+        .map(|resume| resume.session_id.clone());
+    let resolved = options
+        .resume_session_id
+        .clone()
+        .or(from_pack)
+        .map(|sid| sid.trim().to_string())
+        .filter(|sid| !sid.is_empty());
+    if resolved.is_none() {
+        eprintln!(
+            "[PM][launch_builder] session_mode=resume but resume_session_id is empty \
+             or absent; falling back to new session"
+        );
+    }
+    resolved
+}
+
+/// A copy of the pack (or an empty one) with `session_resume` set, or `None`
+/// when there is nothing to resume.
+fn pack_with_resume(
+    context_pack: Option<&ContextPack>,
+    resume_session_id: Option<&str>,
+) -> Option<ContextPack> {
+    let session_id = resume_session_id?;
+    let mut pack = context_pack.cloned().unwrap_or_default();
+    pack.session_resume = Some(crate::protocol::SessionResume {
+        session_id: session_id.to_string(),
+    });
+    Some(pack)
+}
+
+/// Write `context_pack` to a temp file and point `PM_CONTEXT_PACK_FILE` (and
+/// `provider_var`, if any) at it.
+fn attach_context_pack(
+    env: &mut HashMap<String, String>,
+    context_pack: Option<&ContextPack>,
+    provider_var: Option<&str>,
+) -> Result<Option<PathBuf>, String> {
+    let Some(pack) = context_pack else {
+        return Ok(None);
+    };
+    let path = write_context_pack_to_tempfile(pack)?;
+    let value = path.to_string_lossy().to_string();
+    if let Some(var) = provider_var {
+        env.insert(var.to_string(), value.clone());
+    }
+    env.insert("PM_CONTEXT_PACK_FILE".to_string(), value);
+    Ok(Some(path))
+}
+
+/// Inject `PM_BUDGET_*` for each cap that is set.
+fn inject_budget_env(env: &mut HashMap<String, String>, budget: Option<&AutonomyBudget>) {
+    let Some(budget) = budget else {
+        return;
+    };
+    if let Some(n) = budget.max_commands {
+        env.insert("PM_BUDGET_MAX_COMMANDS".to_string(), n.to_string());
+    }
+    if let Some(n) = budget.max_duration_secs {
+        env.insert("PM_BUDGET_MAX_DURATION_SECS".to_string(), n.to_string());
+    }
+    if let Some(n) = budget.max_files {
+        env.insert("PM_BUDGET_MAX_FILES".to_string(), n.to_string());
+    }
+}
+
 fn inject_project_memory_mcp_env_defaults(
     env: &mut HashMap<String, String>,
     provider: &str,
@@ -657,7 +775,11 @@ fn inject_project_memory_mcp_env_defaults(
 fn write_context_pack_to_tempfile(pack: &ContextPack) -> Result<PathBuf, String> {
     let json =
         serde_json::to_string_pretty(pack).map_err(|e| format!("JSON serialise error: {e}"))?;
+    write_json_tempfile("pm-ctx-pack", &json)
+}
 
+/// Write `json` to a uniquely named `{prefix}-*.json` file in the temp dir.
+fn write_json_tempfile(prefix: &str, json: &str) -> Result<PathBuf, String> {
     // Use create_new + a monotonic nonce so concurrent test threads cannot
     // overwrite each other's context-pack files in the same millisecond.
     let ts_nanos = std::time::SystemTime::now()
@@ -669,25 +791,25 @@ fn write_context_pack_to_tempfile(pack: &ContextPack) -> Result<PathBuf, String>
 
     for _attempt in 0..32 {
         let nonce = CONTEXT_PACK_FILE_NONCE.fetch_add(1, Ordering::Relaxed);
-        let file_name = format!("pm-ctx-pack-{ts_nanos}-{pid}-{nonce}.json");
+        let file_name = format!("{prefix}-{ts_nanos}-{pid}-{nonce}.json");
         let path = temp_dir.join(file_name);
 
         match OpenOptions::new().write(true).create_new(true).open(&path) {
             Ok(mut file) => {
                 file.write_all(json.as_bytes())
-                    .map_err(|e| format!("Cannot write context-pack temp file: {e}"))?;
+                    .map_err(|e| format!("Cannot write {prefix} temp file: {e}"))?;
                 return Ok(path);
             }
             Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
                 continue;
             }
             Err(err) => {
-                return Err(format!("Cannot create context-pack temp file: {err}"));
+                return Err(format!("Cannot create {prefix} temp file: {err}"));
             }
         }
     }
 
-    Err("Cannot create unique context-pack temp file after retries".to_string())
+    Err(format!("Cannot create unique {prefix} temp file after retries"))
 }
 
 // ─── Tests ───────────────────────────────────────────────────────────────────
@@ -1576,4 +1698,126 @@ mod tests {
 
         let _ = std::fs::remove_file(path);
     }
+
+    // ── Claude Code ──────────────────────────────────────────────────────────
+
+    fn cleanup(cmd: &LaunchCommand) {
+        for path in [&cmd.context_pack_path, &cmd.mcp_config_path].into_iter().flatten() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn dispatch_claude() {
+        let opts = LaunchOptions::default();
+        let cmd = build_launch_command("claude.cmd", None, "guided", Some("Tester"), None, &opts)
+            .expect("dispatch claude");
+        assert_eq!(cmd.provider, "claude");
+        assert!(cmd.program.starts_with("claude"));
+        cleanup(&cmd);
+    }
+
+    #[test]
+    fn claude_launch_writes_mcp_config_and_permission_mode() {
+        let opts = LaunchOptions::default();
+        let cmd = build_claude_launch(None, "guided", Some("Executor"), Some("plan_abc1"), &opts)
+            .expect("claude launch should succeed");
+
+        assert_eq!(cmd.session_label, "Claude \u{2014} Executor \u{2014} plan_abc1");
+        assert_eq!(&cmd.args[..2], ["--permission-mode", "default"]);
+        assert_pm_mcp_defaults(&cmd, "claude");
+
+        let mcp_path = cmd.mcp_config_path.as_ref().expect("MCP config must be written");
+        let pos = cmd.args.iter().position(|a| a == "--mcp-config").unwrap();
+        assert_eq!(cmd.args[pos + 1], mcp_path.to_string_lossy());
+        let config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(mcp_path).unwrap()).unwrap();
+        assert_eq!(
+            config["mcpServers"]["project-memory"]["url"].as_str(),
+            cmd.env.get("PM_MCP_SERVER_URL").map(String::as_str)
+        );
+        cleanup(&cmd);
+    }
+
+    #[test]
+    fn claude_permission_mode_follows_autonomy_and_options() {
+        let autonomous = LaunchOptions::default();
+        let cmd = build_claude_launch(None, "autonomous", None, None, &autonomous).unwrap();
+        assert!(cmd.args.windows(2).any(|w| w == ["--permission-mode", "acceptEdits"]));
+        cleanup(&cmd);
+
+        let plan = LaunchOptions {
+            permission_mode: "PLAN".to_string(),
+            ..LaunchOptions::default()
+        };
+        let cmd = build_claude_launch(None, "guided", None, None, &plan).unwrap();
+        assert!(cmd.args.windows(2).any(|w| w == ["--permission-mode", "plan"]));
+        cleanup(&cmd);
+
+        let bogus = LaunchOptions {
+            permission_mode: "yolo".to_string(),
+            ..LaunchOptions::default()
+        };
+        assert!(build_claude_launch(None, "guided", None, None, &bogus).is_err());
+
+        let bypass = LaunchOptions {
+            permission_mode: "bypassPermissions".to_string(),
+            ..LaunchOptions::default()
+        };
+        let err = build_claude_launch(None, "guided", None, None, &bypass).unwrap_err();
+        assert!(err.contains("trusted-scope"), "{err}");
+        let trusted = LaunchOptions {
+            trusted_scope_confirmed: true,
+            ..bypass
+        };
+        let cmd = build_claude_launch(None, "guided", None, None, &trusted).unwrap();
+        cleanup(&cmd);
+    }
+
+    #[test]
+    fn claude_resume_passes_flag_and_embeds_in_pack() {
+        let opts = LaunchOptions {
+            session_mode: "resume".to_string(),
+            resume_session_id: Some("sess_claude_1".to_string()),
+            ..LaunchOptions::default()
+        };
+        let cmd = build_claude_launch(None, "guided", None, None, &opts).unwrap();
+        assert_eq!(&cmd.args[..2], ["--resume", "sess_claude_1"]);
+
+        let path = cmd.context_pack_path.as_ref().expect("resume writes a context pack");
+        let parsed: ContextPack =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(
+            parsed.session_resume.map(|sr| sr.session_id).as_deref(),
+            Some("sess_claude_1")
+        );
+        assert_eq!(
+            cmd.env.get("PM_CONTEXT_PACK_FILE").map(String::as_str),
+            Some(path.to_string_lossy().as_ref())
+        );
+        cleanup(&cmd);
+    }
+
+    #[test]
+    fn claude_startup_prompt_follows_the_option_separator() {
+        let mut pack = sample_context_pack();
+        pack.startup_prompt = Some("Run the tests\nthen report".to_string());
+        let opts = LaunchOptions {
+            output_format: "json".to_string(),
+            ..LaunchOptions::default()
+        };
+        let cmd = build_claude_launch(Some(&pack), "guided", None, None, &opts).unwrap();
+        // `--mcp-config` is variadic: the prompt must not directly follow its
+        // path, or claude reads it as another config file.
+        let separator = cmd.args.iter().position(|a| a == "--").expect("`--` before the prompt");
+        let mcp_config = cmd.args.iter().position(|a| a == "--mcp-config").unwrap();
+        assert!(mcp_config < separator);
+        assert_eq!(&cmd.args[separator + 1..], ["Run the tests then report"]);
+        assert_eq!(
+            cmd.env.get("PM_REQUESTED_OUTPUT_FORMAT").map(String::as_str),
+            Some("json")
+        );
+        assert!(!cmd.args.iter().any(|a| a.contains("output")));
+        cleanup(&cmd);
+    }
 }
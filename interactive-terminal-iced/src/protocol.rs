@@ -36,6 +36,8 @@ pub enum Message {
     ReadOutputResponse(ReadOutputResponse),
     KillSessionRequest(KillSessionRequest),
     KillSessionResponse(KillSessionResponse),
+    AuditQueryRequest(AuditQueryRequest),
+    AuditQueryResponse(AuditQueryResponse),
     /// MCP server → GUI push: list of registered Project Memory workspaces.
     WorkspaceListPush(WorkspaceListPush),
 }
@@ -166,6 +168,7 @@ pub struct ContextPack {
 ///
 /// Returns `None` when the context is empty, not valid JSON, or does not
 /// contain a `"context_pack"` key.
+#[allow(dead_code)]
 pub fn context_pack_from_context_json(context: &str) -> Option<ContextPack> {
     if context.is_empty() {
         return None;
@@ -303,6 +306,10 @@ pub struct KillSessionResponse {
     pub error: Option<String>,
 }
 
+/// Audit-log query messages; the types live in `terminal-audit` so both
+/// front ends answer queries with the same code.
+pub use terminal_audit::{AuditQueryRequest, AuditQueryResponse, AuditVerification};
+
 // ---------------------------------------------------------------------------
 // Workspace list push (MCP server → GUI, fire-and-forget)
 // ---------------------------------------------------------------------------
@@ -642,6 +649,50 @@ mod tests {
         assert_eq!(val["type"], "read_output_response");
     }
 
+    #[test]
+    fn audit_query_request_filters_are_top_level() {
+        let json = r#"{"type":"audit_query_request","id":"audit-001","workspace_path":"/ws","agent":"Executor","status":["command_declined"],"limit":5,"verify":true}"#;
+        let msg: Message = serde_json::from_str(json).unwrap();
+        match &msg {
+            Message::AuditQueryRequest(req) => {
+                assert_eq!(req.workspace_path, "/ws");
+                assert_eq!(req.filter.agent.as_deref(), Some("Executor"));
+                assert_eq!(
+                    req.filter.status,
+                    vec![terminal_audit::AuditEvent::CommandDeclined]
+                );
+                assert_eq!(req.filter.limit, Some(5));
+                assert!(req.verify);
+            }
+            other => panic!("expected AuditQueryRequest, got {other:?}"),
+        }
+        let decoded: Message = serde_json::from_str(&serde_json::to_string(&msg).unwrap()).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn roundtrip_audit_query_response() {
+        let msg = Message::AuditQueryResponse(AuditQueryResponse {
+            id: "audit-001".into(),
+            success: true,
+            records: Vec::new(),
+            verification: Some(AuditVerification {
+                intact: false,
+                summary: None,
+                error: Some(terminal_audit::VerifyError {
+                    line: 3,
+                    message: "hash mismatch".into(),
+                }),
+            }),
+            error: None,
+        });
+        let val: serde_json::Value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(val["type"], "audit_query_response");
+        assert_eq!(val["verification"]["error"]["line"], 3);
+        let decoded: Message = serde_json::from_value(val).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
     #[test]
     fn json_has_correct_type_tag_kill_session_request() {
         let msg = Message::KillSessionRequest(sample_kill_session_request());
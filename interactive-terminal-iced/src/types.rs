@@ -36,7 +36,17 @@ pub struct PendingCommand {
     pub command_text: String,
     pub working_directory: String,
     pub context_info: String,
+    /// Set from the command policy decision in `CommandReceived`; the value
+    /// sent by the client is not trusted.
+    #[serde(default)]
     pub is_allowlisted: bool,
+    #[serde(default)]
+    pub terminal_profile: String,
+    #[serde(default)]
+    pub workspace_path: String,
+    /// Id of the policy rule that decided `is_allowlisted`.
+    #[serde(default)]
+    pub policy_rule: String,
 }
 
 // ─── Saved commands ──────────────────────────────────────────────────────────
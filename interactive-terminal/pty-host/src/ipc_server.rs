@@ -1,21 +1,204 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::TcpListener;
-use tokio::sync::mpsc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines, ReadHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Instant;
 
-use crate::pty_host_protocol::*;
+use pty_host_protocol::*;
 use crate::pty_manager::{HostEvent, PtyManager};
 
+/// How long a new connection has to send its `Hello`.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A connected UI client.
+struct Client {
+    out_tx: mpsc::UnboundedSender<PtyHostMessage>,
+    /// Sessions whose output is forwarded to this client: the ones it
+    /// created or attached to.
+    sessions: HashSet<String>,
+}
+
+/// State shared by every connection: the sessions and who is watching them.
+struct Hub {
+    manager: PtyManager,
+    clients: HashMap<u64, Client>,
+    next_client_id: u64,
+    /// Woken whenever the hub may have gone idle; see [`Hub::is_idle`].
+    idle: Arc<Notify>,
+}
+
+impl Hub {
+    /// Forward a session event to its subscribers and record it for
+    /// clients that attach later.
+    fn route(&mut self, event: HostEvent) {
+        match event {
+            HostEvent::Output { session_id, data } => {
+                self.manager.record_output(&session_id, &data);
+                let text = String::from_utf8_lossy(&data).to_string();
+                for client in self.subscribers(&session_id) {
+                    let _ = client
+                        .out_tx
+                        .send(PtyHostMessage::SessionOutput(SessionOutput {
+                            session_id: session_id.clone(),
+                            data: text.clone(),
+                        }));
+                }
+            }
+            HostEvent::Exited {
+                session_id,
+                exit_code,
+            } => {
+                let mut delivered = false;
+                for client in self.clients.values_mut() {
+                    if !client.sessions.remove(&session_id) {
+                        continue;
+                    }
+                    delivered |= client
+                        .out_tx
+                        .send(PtyHostMessage::SessionExited(SessionExited {
+                            session_id: session_id.clone(),
+                            exit_code,
+                        }))
+                        .is_ok();
+                }
+                // Nobody saw the exit: keep the session so a reconnecting
+                // client can replay its final output.
+                if delivered {
+                    self.manager.remove(&session_id);
+                } else {
+                    self.manager.record_exit(&session_id, exit_code);
+                    self.notify_if_idle();
+                }
+            }
+        }
+    }
+
+    fn subscribers<'a>(&'a self, session_id: &'a str) -> impl Iterator<Item = &'a Client> {
+        self.clients
+            .values()
+            .filter(move |c| c.sessions.contains(session_id))
+    }
+
+    /// Handle one message from client `client_id`.
+    fn handle(&mut self, client_id: u64, msg: PtyHostMessage) {
+        let Some(out_tx) = self.clients.get(&client_id).map(|c| c.out_tx.clone()) else {
+            return;
+        };
+
+        match msg {
+            PtyHostMessage::SessionCreate(req) => {
+                let session_id = req.session_id.clone();
+                let reply = match self.manager.spawn(&req) {
+                    Ok(()) => {
+                        self.subscribe(client_id, &session_id);
+                        PtyHostMessage::SessionCreated(SessionCreated {
+                            session_id: session_id.clone(),
+                        })
+                    }
+                    Err(err) => PtyHostMessage::SessionCreateFailed(SessionCreateFailed {
+                        session_id: session_id.clone(),
+                        error: err,
+                    }),
+                };
+                let _ = out_tx.send(reply);
+            }
+            PtyHostMessage::SessionInput(inp) => {
+                self.manager
+                    .write_input(&inp.session_id, inp.data.as_bytes());
+            }
+            PtyHostMessage::SessionResize(r) => {
+                self.manager.resize(&r.session_id, r.cols, r.rows);
+            }
+            PtyHostMessage::SessionKill(k) => {
+                self.manager.kill(&k.session_id);
+            }
+            PtyHostMessage::SessionAttach(a) => match self.manager.attach(&a.session_id) {
+                Some((attached, info)) => {
+                    let _ = out_tx.send(PtyHostMessage::SessionAttached(attached));
+                    if info.running {
+                        self.subscribe(client_id, &a.session_id);
+                    } else {
+                        // The exit has now been seen; the session can go.
+                        let _ = out_tx.send(PtyHostMessage::SessionExited(SessionExited {
+                            session_id: a.session_id.clone(),
+                            exit_code: info.exit_code,
+                        }));
+                        self.manager.remove(&a.session_id);
+                    }
+                }
+                None => {
+                    let _ = out_tx.send(PtyHostMessage::SessionAttachFailed(SessionAttachFailed {
+                        session_id: a.session_id.clone(),
+                        error: format!("unknown session {}", a.session_id),
+                    }));
+                }
+            },
+            PtyHostMessage::SessionDetach(d) => {
+                if let Some(client) = self.clients.get_mut(&client_id) {
+                    client.sessions.remove(&d.session_id);
+                }
+            }
+            PtyHostMessage::SessionList(_) => {
+                let _ = out_tx.send(PtyHostMessage::SessionListed(SessionListed {
+                    sessions: self.manager.list(),
+                }));
+            }
+            PtyHostMessage::SessionExited(e) => {
+                // Cleanup in manager when we receive our own Exited back (shouldn't happen normally)
+                self.manager.remove(&e.session_id);
+            }
+            _ => {
+                // Heartbeat and other host→UI messages: ignore
+            }
+        }
+    }
+
+    fn subscribe(&mut self, client_id: u64, session_id: &str) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.sessions.insert(session_id.to_string());
+        }
+    }
+
+    /// Nothing connected and nothing still running.  Exited sessions may
+    /// remain, waiting for a client to replay them.
+    fn is_idle(&self) -> bool {
+        self.clients.is_empty() && !self.manager.has_running()
+    }
+
+    fn notify_if_idle(&self) {
+        if self.is_idle() {
+            self.idle.notify_one();
+        }
+    }
+}
+
 pub(crate) struct IpcServer;
 
 impl IpcServer {
-    /// Bind the TCP listener and accept exactly one client (the UI process).
-    /// Runs the recv/send loops until either side disconnects or an error occurs.
+    /// Bind the TCP listener and serve UI clients until the host goes idle.
+    ///
+    /// Any number of clients may connect, concurrently or one after another.
+    /// Sessions outlive the client that created them: when the UI restarts
+    /// it reattaches with `SessionAttach` and gets the scrollback replayed.
+    /// The host exits once the last client has disconnected and no sessions
+    /// remain.  When the only sessions left have exited unseen, it waits
+    /// `idle_grace` for a client to replay them first.
+    ///
+    /// Every connection must open with a `Hello` carrying `secret`; anything
+    /// else is rejected before it can reach a session.
+    ///
+    /// When `shutdown` resolves the server stops the same way: every session
+    /// is killed, and `run` returns only once their processes are gone.
     pub(crate) async fn run(
-        mut manager: PtyManager,
+        manager: PtyManager,
         mut event_rx: mpsc::UnboundedReceiver<HostEvent>,
         ipc_port: u16,
         heartbeat_ms: u64,
+        idle_grace: Duration,
+        secret: String,
+        shutdown: impl std::future::Future<Output = ()>,
     ) -> Result<(), String> {
         let listener = TcpListener::bind(format!("127.0.0.1:{ipc_port}"))
             .await
@@ -23,68 +206,25 @@ impl IpcServer {
 
         eprintln!("[pty-host] IPC server listening on 127.0.0.1:{ipc_port}");
 
-        // Accept one client connection (the UI process).
-        let (stream, peer) = listener
-            .accept()
-            .await
-            .map_err(|e| format!("IPC accept failed: {e}"))?;
-
-        eprintln!("[pty-host] UI process connected from {peer}");
-
-        let (read_half, write_half) = tokio::io::split(stream);
-
-        // Channel to send outgoing frames from various sources to the write task.
-        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<PtyHostMessage>();
-
-        // ── Write task: serialize outgoing PtyHostMessage frames ──────────────
-        let write_task = tokio::spawn(async move {
-            let mut write_half = write_half;
-            while let Some(msg) = out_rx.recv().await {
-                match serde_json::to_string(&msg) {
-                    Ok(line) => {
-                        let payload = format!("{line}\n");
-                        if write_half.write_all(payload.as_bytes()).await.is_err() {
-                            break;
-                        }
-                        if write_half.flush().await.is_err() {
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("[pty-host] serialize error: {e}");
-                    }
-                }
-            }
-        });
+        let idle = Arc::new(Notify::new());
+        let hub = Arc::new(StdMutex::new(Hub {
+            manager,
+            clients: HashMap::new(),
+            next_client_id: 0,
+            idle: Arc::clone(&idle),
+        }));
+        let secret: Arc<str> = secret.into();
 
-        // ── Event forwarder: route HostEvents to out_tx ──────────────────────
-        let out_tx_for_events = out_tx.clone();
+        // ── Event router: fan HostEvents out to subscribed clients ───────────
+        let hub_for_events = Arc::clone(&hub);
         let event_task = tokio::spawn(async move {
             while let Some(event) = event_rx.recv().await {
-                let msg = match event {
-                    HostEvent::Output { session_id, data } => {
-                        let text = String::from_utf8_lossy(&data).to_string();
-                        PtyHostMessage::SessionOutput(SessionOutput {
-                            session_id,
-                            data: text,
-                        })
-                    }
-                    HostEvent::Exited {
-                        session_id,
-                        exit_code,
-                    } => PtyHostMessage::SessionExited(SessionExited {
-                        session_id,
-                        exit_code,
-                    }),
-                };
-                if out_tx_for_events.send(msg).is_err() {
-                    break;
-                }
+                hub_for_events.lock().unwrap().route(event);
             }
         });
 
         // ── Heartbeat task ────────────────────────────────────────────────────
-        let out_tx_for_hb = out_tx.clone();
+        let hub_for_hb = Arc::clone(&hub);
         let heartbeat_interval = std::time::Duration::from_millis(heartbeat_ms);
         let heartbeat_task = tokio::spawn(async move {
             loop {
@@ -93,79 +233,211 @@ impl IpcServer {
                     .duration_since(UNIX_EPOCH)
                     .unwrap_or_default()
                     .as_secs();
-                if out_tx_for_hb
-                    .send(PtyHostMessage::Heartbeat(Heartbeat { ts }))
-                    .is_err()
-                {
-                    break;
+                for client in hub_for_hb.lock().unwrap().clients.values() {
+                    let _ = client
+                        .out_tx
+                        .send(PtyHostMessage::Heartbeat(Heartbeat { ts }));
                 }
             }
         });
 
-        // ── Read task: deserialize incoming commands ──────────────────────────
-        let mut lines = BufReader::new(read_half).lines();
+        // ── Accept loop ───────────────────────────────────────────────────────
+        tokio::pin!(shutdown);
+        // Set while idle with exited sessions still waiting for replay.
+        let mut idle_deadline: Option<Instant> = None;
         loop {
-            match lines.next_line().await {
-                Ok(Some(line)) => {
-                    let msg = match serde_json::from_str::<PtyHostMessage>(&line) {
-                        Ok(m) => m,
-                        Err(e) => {
-                            eprintln!("[pty-host] deserialize error: {e} (line: {line})");
-                            continue;
-                        }
-                    };
-
-                    match msg {
-                        PtyHostMessage::SessionCreate(req) => {
-                            let session_id = req.session_id.clone();
-                            let reply = match manager.spawn(&req) {
-                                Ok(()) => PtyHostMessage::SessionCreated(SessionCreated {
-                                    session_id: session_id.clone(),
-                                }),
-                                Err(err) => {
-                                    PtyHostMessage::SessionCreateFailed(SessionCreateFailed {
-                                        session_id: session_id.clone(),
-                                        error: err,
-                                    })
-                                }
-                            };
-                            let _ = out_tx.send(reply);
-                        }
-                        PtyHostMessage::SessionInput(inp) => {
-                            manager.write_input(&inp.session_id, inp.data.as_bytes());
-                        }
-                        PtyHostMessage::SessionResize(r) => {
-                            manager.resize(&r.session_id, r.cols, r.rows);
-                        }
-                        PtyHostMessage::SessionKill(k) => {
-                            manager.kill(&k.session_id);
-                        }
-                        PtyHostMessage::SessionExited(e) => {
-                            // Cleanup in manager when we receive our own Exited back (shouldn't happen normally)
-                            manager.remove(&e.session_id);
-                        }
-                        _ => {
-                            // Heartbeat and other UI→host messages: ignore
-                        }
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, peer)) => {
+                        eprintln!("[pty-host] connection from {peer}");
+                        tokio::spawn(serve_client(
+                            Arc::clone(&hub),
+                            stream,
+                            Arc::clone(&secret),
+                        ));
+                    }
+                    Err(e) => eprintln!("[pty-host] IPC accept failed: {e}"),
+                },
+                _ = idle.notified() => {
+                    if hub.lock().unwrap().manager.is_empty() {
+                        eprintln!("[pty-host] no clients and no sessions left, exiting");
+                        break;
                     }
+                    eprintln!(
+                        "[pty-host] no clients and no running sessions, exiting in {}ms unless a client attaches",
+                        idle_grace.as_millis()
+                    );
+                    idle_deadline = Some(Instant::now() + idle_grace);
                 }
-                Ok(None) => {
-                    eprintln!("[pty-host] UI process disconnected");
-                    break;
+                _ = tokio::time::sleep_until(idle_deadline.unwrap_or_else(Instant::now)),
+                    if idle_deadline.is_some() =>
+                {
+                    idle_deadline = None;
+                    if hub.lock().unwrap().is_idle() {
+                        eprintln!("[pty-host] still idle after {}ms, exiting", idle_grace.as_millis());
+                        break;
+                    }
                 }
-                Err(e) => {
-                    eprintln!("[pty-host] IPC read error: {e}");
+                _ = &mut shutdown => {
+                    eprintln!("[pty-host] shutdown signal received, exiting");
                     break;
                 }
             }
         }
 
-        // Cleanup
-        manager.kill_all();
-        write_task.abort();
+        // Cleanup: kill_all blocks through the SIGHUP grace period.
         event_task.abort();
         heartbeat_task.abort();
+        let hub_for_cleanup = Arc::clone(&hub);
+        let _ = tokio::task::spawn_blocking(move || {
+            hub_for_cleanup.lock().unwrap().manager.kill_all();
+        })
+        .await;
 
         Ok(())
     }
 }
+
+/// Run one client connection until it disconnects.  Its sessions keep running.
+async fn serve_client(
+    hub: Arc<StdMutex<Hub>>,
+    stream: TcpStream,
+    secret: Arc<str>,
+) {
+    let (read_half, write_half) = tokio::io::split(stream);
+
+    // Channel to send outgoing frames from various sources to the write task.
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<PtyHostMessage>();
+
+    // ── Write task: serialize outgoing PtyHostMessage frames ──────────────
+    let write_task = tokio::spawn(async move {
+        let mut write_half = write_half;
+        while let Some(msg) = out_rx.recv().await {
+            match serde_json::to_string(&msg) {
+                Ok(line) => {
+                    let payload = format!("{line}\n");
+                    if write_half.write_all(payload.as_bytes()).await.is_err() {
+                        break;
+                    }
+                    if write_half.flush().await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[pty-host] serialize error: {e}");
+                }
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(read_half).lines();
+    match handshake(&mut lines, &secret).await {
+        Ok(ack) => {
+            let _ = out_tx.send(PtyHostMessage::HelloAck(ack));
+        }
+        Err(rejected) => {
+            eprintln!("[pty-host] rejected connection: {}", rejected.error);
+            let _ = out_tx.send(PtyHostMessage::HelloRejected(rejected));
+            // Let the rejection reach the peer before the socket closes.
+            drop(out_tx);
+            let _ = write_task.await;
+            return;
+        }
+    }
+
+    let client_id = {
+        let mut hub = hub.lock().unwrap();
+        let id = hub.next_client_id;
+        hub.next_client_id += 1;
+        hub.clients.insert(
+            id,
+            Client {
+                out_tx,
+                sessions: HashSet::new(),
+            },
+        );
+        id
+    };
+
+    eprintln!("[pty-host] UI client {client_id} authenticated");
+
+    // ── Read loop: deserialize incoming commands ──────────────────────────
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => match serde_json::from_str::<PtyHostMessage>(&line) {
+                Ok(msg) => hub.lock().unwrap().handle(client_id, msg),
+                Err(e) => eprintln!("[pty-host] deserialize error: {e} (line: {line})"),
+            },
+            Ok(None) => {
+                eprintln!("[pty-host] UI client {client_id} disconnected");
+                break;
+            }
+            Err(e) => {
+                eprintln!("[pty-host] IPC read error: {e}");
+                break;
+            }
+        }
+    }
+
+    {
+        let mut hub = hub.lock().unwrap();
+        hub.clients.remove(&client_id);
+        hub.notify_if_idle();
+    }
+    write_task.abort();
+}
+
+/// Read the connection's first frame and check that it is a `Hello` with
+/// our protocol version and secret.
+async fn handshake(
+    lines: &mut Lines<BufReader<ReadHalf<TcpStream>>>,
+    secret: &str,
+) -> Result<HelloAck, HelloRejected> {
+    let reject = |error: String| HelloRejected {
+        protocol_version: PROTOCOL_VERSION,
+        error,
+    };
+
+    let line = match tokio::time::timeout(HELLO_TIMEOUT, lines.next_line()).await {
+        Ok(Ok(Some(line))) => line,
+        Ok(Ok(None)) => return Err(reject("connection closed before hello".to_string())),
+        Ok(Err(e)) => return Err(reject(format!("read error before hello: {e}"))),
+        Err(_) => {
+            return Err(reject(format!(
+                "no hello within {}s",
+                HELLO_TIMEOUT.as_secs()
+            )))
+        }
+    };
+
+    let hello = match serde_json::from_str::<PtyHostMessage>(&line) {
+        Ok(PtyHostMessage::Hello(hello)) => hello,
+        Ok(_) => return Err(reject("expected hello as the first frame".to_string())),
+        Err(e) => return Err(reject(format!("malformed hello: {e}"))),
+    };
+
+    if hello.protocol_version != PROTOCOL_VERSION {
+        return Err(reject(format!(
+            "protocol version mismatch: client speaks v{}, pty-host speaks v{PROTOCOL_VERSION}",
+            hello.protocol_version
+        )));
+    }
+    if !secrets_match(hello.secret.as_bytes(), secret.as_bytes()) {
+        return Err(reject("authentication failed".to_string()));
+    }
+
+    Ok(HelloAck {
+        protocol_version: PROTOCOL_VERSION,
+        host_version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+/// Compare secrets without short-circuiting on the first differing byte.
+fn secrets_match(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
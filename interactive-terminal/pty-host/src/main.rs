@@ -2,11 +2,12 @@ use clap::Parser;
 
 mod ipc_server;
 mod pty_backend;
-mod pty_host_protocol;
 mod pty_manager;
+mod scrollback;
 
 const DEFAULT_IPC_PORT: u16 = 9102;
 const DEFAULT_HEARTBEAT_MS: u64 = 10_000;
+const DEFAULT_IDLE_GRACE_MS: u64 = 300_000;
 
 #[derive(Parser, Debug)]
 #[command(name = "pty-host")]
@@ -19,34 +20,60 @@ struct Args {
     /// Heartbeat interval in milliseconds
     #[arg(long, default_value_t = DEFAULT_HEARTBEAT_MS)]
     heartbeat_ms: u64,
+
+    /// Bytes of output kept per session and replayed to clients that attach
+    #[arg(long, default_value_t = scrollback::DEFAULT_SCROLLBACK_BYTES)]
+    scrollback_bytes: usize,
+
+    /// How long exited sessions are kept for replay once no client is
+    /// connected and nothing is running, before the host exits
+    #[arg(long, default_value_t = DEFAULT_IDLE_GRACE_MS)]
+    idle_grace_ms: u64,
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
 
+    // The launcher hands us the connection secret out of band; without it
+    // any local process could drive our sessions, so refuse to start.
+    let secret = match std::env::var(pty_host_protocol::SECRET_ENV) {
+        Ok(secret) if !secret.is_empty() => secret,
+        _ => {
+            eprintln!(
+                "[pty-host] {} is not set; refusing to start without a connection secret",
+                pty_host_protocol::SECRET_ENV
+            );
+            std::process::exit(2);
+        }
+    };
+
     eprintln!(
         "[pty-host] starting IPC server on port {} (heartbeat {}ms)",
         args.ipc_port, args.heartbeat_ms
     );
 
     let (event_tx, event_rx) = tokio::sync::mpsc::unbounded_channel();
-    let manager = pty_manager::PtyManager::new(event_tx);
+    let manager = pty_manager::PtyManager::new(event_tx, args.scrollback_bytes);
 
+    // Handled inside the server so sessions are torn down before we exit.
     let shutdown = async {
         tokio::signal::ctrl_c()
             .await
             .expect("failed to listen for Ctrl-C");
     };
 
-    tokio::select! {
-        result = ipc_server::IpcServer::run(manager, event_rx, args.ipc_port, args.heartbeat_ms) => {
-            if let Err(e) = result {
-                eprintln!("[pty-host] IPC server error: {e}");
-            }
-        }
-        _ = shutdown => {
-            eprintln!("[pty-host] shutdown signal received, exiting");
-        }
+    if let Err(e) = ipc_server::IpcServer::run(
+        manager,
+        event_rx,
+        args.ipc_port,
+        args.heartbeat_ms,
+        std::time::Duration::from_millis(args.idle_grace_ms),
+        secret,
+        shutdown,
+    )
+    .await
+    {
+        eprintln!("[pty-host] IPC server error: {e}");
     }
 }
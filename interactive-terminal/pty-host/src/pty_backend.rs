@@ -1,58 +1,52 @@
 // PTY backend for the pty-host binary.
 // Adapted from interactive-terminal/src/terminal_core/conpty_backend.rs —
-// includes only the ConptyRawSession (raw-bytes) path; the line-based
+// includes only the raw-bytes session path; the line-based
 // ConptyShellSession is not needed here.
+//
+// portable-pty provides ConPTY on Windows and openpty(3) on Linux/macOS.
+// On Unix the child is started with setsid(), so it leads its own session
+// and its pid is also its process-group id, recorded at spawn.  Killing a
+// session signals that group and every other group in the session, which
+// covers background jobs an interactive shell moved into their own groups.
 
-#[cfg(windows)]
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-#[cfg(windows)]
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
 use std::io::Read;
-#[cfg(windows)]
 use std::path::Path;
-#[cfg(windows)]
 use std::sync::{Arc, Mutex as StdMutex};
-#[cfg(windows)]
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+
+/// Grace period between SIGHUP and SIGKILL when tearing down a process group.
+#[cfg(unix)]
+const KILL_GRACE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// How often the grace period checks whether the groups have exited.
+#[cfg(unix)]
+const KILL_POLL: std::time::Duration = std::time::Duration::from_millis(20);
+
+pub(crate) type Killer = Box<dyn ChildKiller + Send + Sync>;
 
 // ─── Raw-bytes PTY session ─────────────────────────────────────────────────
 
-#[cfg(windows)]
-pub(crate) struct ConptyRawSession {
+pub(crate) struct RawPtySession {
     pub(crate) writer: Arc<StdMutex<Box<dyn std::io::Write + Send>>>,
-    #[allow(dead_code)]
-    pub(crate) child: Arc<StdMutex<Box<dyn portable_pty::Child + Send + Sync>>>,
-    pub(crate) pty_master: Arc<StdMutex<Box<dyn portable_pty::MasterPty + Send>>>,
+    pub(crate) pty_master: Arc<StdMutex<Box<dyn MasterPty + Send>>>,
+    pub(crate) killer: Killer,
+    /// The child's process-group id (Unix only).
+    pub(crate) pgid: Option<u32>,
     pub(crate) raw_output_rx: mpsc::Receiver<Vec<u8>>,
-}
-
-#[cfg(not(windows))]
-pub(crate) struct ConptyRawSession {}
-
-#[cfg(windows)]
-impl ConptyRawSession {
-    /// Split into `(output_rx, writer_arc, master_arc, child_arc)`.
-    pub(crate) fn into_task_parts(
-        self,
-    ) -> (
-        mpsc::Receiver<Vec<u8>>,
-        Arc<StdMutex<Box<dyn std::io::Write + Send>>>,
-        Arc<StdMutex<Box<dyn portable_pty::MasterPty + Send>>>,
-        Arc<StdMutex<Box<dyn portable_pty::Child + Send + Sync>>>,
-    ) {
-        (self.raw_output_rx, self.writer, self.pty_master, self.child)
-    }
+    /// Resolves with the exit code once the child has been reaped.
+    pub(crate) exit_rx: oneshot::Receiver<Option<i32>>,
 }
 
 /// Spawn a raw-bytes PTY session.
-#[cfg(windows)]
-pub(crate) fn spawn_conpty_raw_session(
+pub(crate) fn spawn_raw_session(
     program: &str,
     args: &[String],
     cwd: &Path,
     env_overrides: &[(String, String)],
     cols: u16,
     rows: u16,
-) -> Result<ConptyRawSession, String> {
+) -> Result<RawPtySession, String> {
     let pty = native_pty_system();
     let pair = pty
         .openpty(PtySize {
@@ -68,28 +62,38 @@ pub(crate) fn spawn_conpty_raw_session(
         cmd.arg(arg);
     }
     cmd.cwd(cwd);
+    // Sessions must not learn the secret that authorizes driving them.
+    cmd.env_remove(pty_host_protocol::SECRET_ENV);
+    #[cfg(unix)]
+    if std::env::var_os("TERM").is_none() {
+        cmd.env("TERM", "xterm-256color");
+    }
     for (key, value) in env_overrides {
         cmd.env(key, value);
     }
 
-    let child = pair
+    let mut child = pair
         .slave
         .spawn_command(cmd)
-        .map_err(|e| format!("ConPTY spawn failed: {e}"))?;
+        .map_err(|e| format!("PTY spawn failed: {e}"))?;
+    // Close our copy of the slave so the reader sees EOF once the child exits.
+    drop(pair.slave);
 
     let reader = pair
         .master
         .try_clone_reader()
-        .map_err(|e| format!("ConPTY reader init failed: {e}"))?;
+        .map_err(|e| format!("PTY reader init failed: {e}"))?;
     let writer = pair
         .master
         .take_writer()
-        .map_err(|e| format!("ConPTY writer init failed: {e}"))?;
+        .map_err(|e| format!("PTY writer init failed: {e}"))?;
 
-    let child = Arc::new(StdMutex::new(child));
+    let pgid = process_group(child.process_id());
+    let killer = child.clone_killer();
     let writer = Arc::new(StdMutex::new(writer));
     let pty_master = Arc::new(StdMutex::new(pair.master));
     let (raw_output_tx, raw_output_rx) = mpsc::channel::<Vec<u8>>(256);
+    let (exit_tx, exit_rx) = oneshot::channel();
 
     // Reader thread: forward raw PTY bytes to the async channel.
     std::thread::spawn(move || {
@@ -107,33 +111,24 @@ pub(crate) fn spawn_conpty_raw_session(
         }
     });
 
-    Ok(ConptyRawSession {
+    // Waiter thread: reap the child and report its exit code.
+    std::thread::spawn(move || {
+        let code = child.wait().ok().map(|status| status.exit_code() as i32);
+        let _ = exit_tx.send(code);
+    });
+
+    Ok(RawPtySession {
         writer,
-        child,
         pty_master,
+        killer,
+        pgid,
         raw_output_rx,
+        exit_rx,
     })
 }
 
-#[cfg(not(windows))]
-pub(crate) fn spawn_conpty_raw_session(
-    _program: &str,
-    _args: &[String],
-    _cwd: &std::path::Path,
-    _env_overrides: &[(String, String)],
-    _cols: u16,
-    _rows: u16,
-) -> Result<ConptyRawSession, String> {
-    Err("PTY spawning is only supported on Windows".to_string())
-}
-
-/// Resize the ConPTY to new dimensions.
-#[cfg(windows)]
-pub(crate) fn resize_conpty(
-    master: &Arc<StdMutex<Box<dyn portable_pty::MasterPty + Send>>>,
-    cols: u16,
-    rows: u16,
-) {
+/// Resize the PTY to new dimensions.
+pub(crate) fn resize_pty(master: &Arc<StdMutex<Box<dyn MasterPty + Send>>>, cols: u16, rows: u16) {
     if let Ok(m) = master.lock() {
         let _ = m.resize(PtySize {
             rows,
@@ -143,3 +138,137 @@ pub(crate) fn resize_conpty(
         });
     }
 }
+
+/// The process group `pid` leads, read once while the child is known to be
+/// alive so later signals never go to a recycled pid.
+#[cfg(unix)]
+fn process_group(pid: Option<u32>) -> Option<u32> {
+    let pid = libc::pid_t::try_from(pid?).ok()?;
+    // SAFETY: getpgid only reads the process table.
+    let pgid = unsafe { libc::getpgid(pid) };
+    u32::try_from(pgid).ok().filter(|&pgid| pgid > 0)
+}
+
+#[cfg(not(unix))]
+fn process_group(_pid: Option<u32>) -> Option<u32> {
+    None
+}
+
+/// Terminate a session's process tree without blocking the caller.
+///
+/// On Unix every process group in the child's session gets SIGHUP (what a
+/// closing terminal sends) and a helper thread sends SIGKILL to whatever is
+/// still alive after [`KILL_GRACE`].  Elsewhere the child is killed directly.
+pub(crate) fn kill_session(killer: Killer, pgid: Option<u32>) {
+    let groups = hang_up(vec![(killer, pgid)]);
+    if !groups.is_empty() {
+        std::thread::spawn(move || finish_kill(groups));
+    }
+}
+
+/// Terminate several sessions' process trees and return only once they have
+/// exited or been sent SIGKILL.
+///
+/// Used on shutdown, where an escalation left to a helper thread would die
+/// with the host before it fired.
+pub(crate) fn kill_sessions_now(sessions: Vec<(Killer, Option<u32>)>) {
+    finish_kill(hang_up(sessions));
+}
+
+/// Send the first, polite signal to each session and return the process
+/// groups that may need SIGKILL.
+fn hang_up(sessions: Vec<(Killer, Option<u32>)>) -> Vec<i32> {
+    let mut groups = Vec::new();
+    for (mut killer, pgid) in sessions {
+        #[cfg(unix)]
+        if let Some(pgid) = pgid.and_then(|p| libc::pid_t::try_from(p).ok()) {
+            for group in session_groups(pgid) {
+                signal_group(group, libc::SIGHUP);
+                groups.push(group);
+            }
+            continue;
+        }
+        let _ = pgid;
+        let _ = killer.kill();
+    }
+    groups
+}
+
+/// Wait up to [`KILL_GRACE`] for `groups` to exit, then SIGKILL the rest.
+fn finish_kill(groups: Vec<i32>) {
+    #[cfg(unix)]
+    {
+        let deadline = std::time::Instant::now() + KILL_GRACE;
+        let mut alive = groups;
+        loop {
+            alive.retain(|&group| group_alive(group));
+            if alive.is_empty() || std::time::Instant::now() >= deadline {
+                break;
+            }
+            std::thread::sleep(KILL_POLL);
+        }
+        for group in alive {
+            signal_group(group, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = groups;
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: libc::pid_t, signal: libc::c_int) {
+    // SAFETY: killpg only signals processes; a group that no longer exists
+    // yields ESRCH, which is ignored.
+    unsafe {
+        libc::killpg(pgid, signal);
+    }
+}
+
+#[cfg(unix)]
+fn group_alive(pgid: libc::pid_t) -> bool {
+    // SAFETY: signal 0 only checks that the group exists.
+    unsafe { libc::killpg(pgid, 0) == 0 }
+}
+
+/// The session led by `sid`: its own process group (`sid`, recorded at
+/// spawn) plus any group a job-control shell created inside it.  Finding
+/// those needs one pass over the process table; escalation then only uses
+/// the groups found here.
+#[cfg(unix)]
+fn session_groups(sid: libc::pid_t) -> Vec<libc::pid_t> {
+    let mut groups = vec![sid];
+    for pid in all_pids() {
+        // SAFETY: getsid/getpgid only read the process table; a pid that has
+        // gone away yields -1.
+        let group = unsafe {
+            if pid == sid || libc::getsid(pid) != sid {
+                continue;
+            }
+            libc::getpgid(pid)
+        };
+        if group > 0 && !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+/// Every pid on the system: from /proc where available, otherwise `ps`.
+#[cfg(unix)]
+fn all_pids() -> Vec<libc::pid_t> {
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        return entries
+            .filter_map(|e| e.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+    }
+    std::process::Command::new("ps")
+        .args(["-A", "-o", "pid="])
+        .output()
+        .map(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .split_whitespace()
+                .filter_map(|pid| pid.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
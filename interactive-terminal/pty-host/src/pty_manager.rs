@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 use tokio::sync::mpsc;
 
-use crate::pty_host_protocol::SessionCreate;
+use crate::scrollback::Scrollback;
+use pty_host_protocol::{SessionAttached, SessionCreate, SessionInfo};
 
 /// Events emitted by PTY sessions to the IPC send loop.
 #[derive(Debug)]
@@ -15,161 +16,240 @@ pub(crate) enum HostEvent {
     },
 }
 
-// ── Windows: live PTY sessions ────────────────────────────────────────────
+// ── Live PTY sessions ─────────────────────────────────────────────────────
 
-#[cfg(windows)]
 use std::sync::{Arc, Mutex as StdMutex};
-#[cfg(windows)]
-use crate::pty_backend::{resize_conpty, spawn_conpty_raw_session};
+use std::time::Duration;
+
+use crate::pty_backend::{kill_session, kill_sessions_now, resize_pty, spawn_raw_session, Killer};
+
+/// How long to keep draining output after the child has been reaped.  The
+/// reader may never see EOF (ConPTY, or a surviving background job holding
+/// the terminal open), so the drain is bounded by inactivity.
+const EXIT_DRAIN_IDLE: Duration = Duration::from_millis(200);
 
-#[cfg(windows)]
 struct ActiveSession {
     writer: Arc<StdMutex<Box<dyn std::io::Write + Send>>>,
     master: Arc<StdMutex<Box<dyn portable_pty::MasterPty + Send>>>,
-    child: Arc<StdMutex<Box<dyn portable_pty::Child + Send + Sync>>>,
+    killer: Killer,
+    pgid: Option<u32>,
+    program: String,
+    cwd: String,
+    cols: u16,
+    rows: u16,
+    scrollback: Scrollback,
+    running: bool,
+    exit_code: Option<i32>,
+}
+
+impl ActiveSession {
+    fn info(&self, session_id: &str) -> SessionInfo {
+        SessionInfo {
+            session_id: session_id.to_string(),
+            program: self.program.clone(),
+            cwd: self.cwd.clone(),
+            cols: self.cols,
+            rows: self.rows,
+            running: self.running,
+            exit_code: self.exit_code,
+        }
+    }
 }
 
+/// Owns every PTY session the host is keeping, independent of which (if
+/// any) UI clients are connected.
 pub(crate) struct PtyManager {
-    /// Only populated on Windows; empty on other platforms.
-    #[cfg(windows)]
     sessions: HashMap<String, ActiveSession>,
-    #[cfg(not(windows))]
-    _sessions: HashMap<String, ()>,
-
     event_tx: mpsc::UnboundedSender<HostEvent>,
+    scrollback_bytes: usize,
 }
 
 impl PtyManager {
-    pub(crate) fn new(event_tx: mpsc::UnboundedSender<HostEvent>) -> Self {
+    pub(crate) fn new(event_tx: mpsc::UnboundedSender<HostEvent>, scrollback_bytes: usize) -> Self {
         PtyManager {
-            #[cfg(windows)]
             sessions: HashMap::new(),
-            #[cfg(not(windows))]
-            _sessions: HashMap::new(),
             event_tx,
+            scrollback_bytes,
         }
     }
 
-    /// Spawn a new PTY session. The reader task forwards output via `event_tx`.
+    /// Spawn a new PTY session. The reader task forwards output via `event_tx`
+    /// and reports the exit code once the child has been reaped.
     pub(crate) fn spawn(&mut self, req: &SessionCreate) -> Result<(), String> {
-        #[cfg(windows)]
-        {
-            let cwd = std::path::Path::new(&req.cwd);
-            let session =
-                spawn_conpty_raw_session(&req.program, &req.args, cwd, &[], req.cols, req.rows)?;
-            let (mut raw_rx, writer, master, child) = session.into_task_parts();
-
-            let session_id = req.session_id.clone();
-            let event_tx = self.event_tx.clone();
-            tokio::spawn(async move {
-                while let Some(chunk) = raw_rx.recv().await {
-                    if event_tx
-                        .send(HostEvent::Output {
-                            session_id: session_id.clone(),
-                            data: chunk,
-                        })
-                        .is_err()
-                    {
-                        break;
+        if self.sessions.contains_key(&req.session_id) {
+            return Err(format!("session {} already exists", req.session_id));
+        }
+
+        let cwd = std::path::Path::new(&req.cwd);
+        let env: Vec<(String, String)> = req
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let session = spawn_raw_session(&req.program, &req.args, cwd, &env, req.cols, req.rows)?;
+        let mut raw_rx = session.raw_output_rx;
+        let mut exit_rx = session.exit_rx;
+
+        let session_id = req.session_id.clone();
+        let event_tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let send_output = |data: Vec<u8>| {
+                event_tx
+                    .send(HostEvent::Output {
+                        session_id: session_id.clone(),
+                        data,
+                    })
+                    .is_ok()
+            };
+
+            let exit_code = loop {
+                tokio::select! {
+                    chunk = raw_rx.recv() => match chunk {
+                        Some(data) => {
+                            if !send_output(data) {
+                                return;
+                            }
+                        }
+                        // Reader hit EOF; the child is gone or about to be.
+                        None => break (&mut exit_rx).await.ok().flatten(),
+                    },
+                    code = &mut exit_rx => {
+                        // Flush whatever the child wrote just before exiting.
+                        while let Ok(Some(data)) =
+                            tokio::time::timeout(EXIT_DRAIN_IDLE, raw_rx.recv()).await
+                        {
+                            if !send_output(data) {
+                                return;
+                            }
+                        }
+                        break code.ok().flatten();
                     }
                 }
-                // PTY reader loop ended → session exited.
-                let exit_code: Option<i32> = None; // portable-pty doesn't always surface exit code
-                let _ = event_tx.send(HostEvent::Exited {
-                    session_id: session_id.clone(),
-                    exit_code,
-                });
+            };
+
+            let _ = event_tx.send(HostEvent::Exited {
+                session_id: session_id.clone(),
+                exit_code,
             });
+        });
 
-            self.sessions.insert(
-                req.session_id.clone(),
-                ActiveSession { writer, master, child },
-            );
-            Ok(())
-        }
-        #[cfg(not(windows))]
-        {
-            let _ = req;
-            Err("PTY spawning is only supported on Windows".to_string())
-        }
+        self.sessions.insert(
+            req.session_id.clone(),
+            ActiveSession {
+                writer: session.writer,
+                master: session.pty_master,
+                killer: session.killer,
+                pgid: session.pgid,
+                program: req.program.clone(),
+                cwd: req.cwd.clone(),
+                cols: req.cols,
+                rows: req.rows,
+                scrollback: Scrollback::new(self.scrollback_bytes),
+                running: true,
+                exit_code: None,
+            },
+        );
+        Ok(())
     }
 
     /// Write raw input bytes to a session's PTY.
     pub(crate) fn write_input(&mut self, session_id: &str, data: &[u8]) {
-        #[cfg(windows)]
-        {
-            if let Some(session) = self.sessions.get(session_id) {
-                let writer = session.writer.clone();
-                let data_vec = data.to_vec();
-                // spawn_blocking because the writer lock is a std Mutex + blocking IO
-                let _ = tokio::task::spawn_blocking(move || {
-                    if let Ok(mut lock) = writer.lock() {
-                        let _ = std::io::Write::write_all(&mut *lock, &data_vec);
-                        let _ = std::io::Write::flush(&mut *lock);
-                    }
-                });
-            }
-        }
-        #[cfg(not(windows))]
-        {
-            let _ = (session_id, data);
+        if let Some(session) = self.sessions.get(session_id) {
+            let writer = session.writer.clone();
+            let data_vec = data.to_vec();
+            // spawn_blocking because the writer lock is a std Mutex + blocking IO
+            tokio::task::spawn_blocking(move || {
+                if let Ok(mut lock) = writer.lock() {
+                    let _ = std::io::Write::write_all(&mut *lock, &data_vec);
+                    let _ = std::io::Write::flush(&mut *lock);
+                }
+            });
         }
     }
 
     /// Resize a session's PTY.
-    pub(crate) fn resize(&self, session_id: &str, cols: u16, rows: u16) {
-        #[cfg(windows)]
-        {
-            if let Some(session) = self.sessions.get(session_id) {
-                resize_conpty(&session.master, cols, rows);
-            }
-        }
-        #[cfg(not(windows))]
-        {
-            let _ = (session_id, cols, rows);
+    pub(crate) fn resize(&mut self, session_id: &str, cols: u16, rows: u16) {
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            resize_pty(&session.master, cols, rows);
+            session.cols = cols;
+            session.rows = rows;
         }
     }
 
-    /// Kill a single session.
+    /// Kill a single session and every process it started.  An exited
+    /// session is simply dropped.
     pub(crate) fn kill(&mut self, session_id: &str) {
-        #[cfg(windows)]
-        {
-            if let Some(session) = self.sessions.remove(session_id) {
-                let child = session.child.clone();
-                let _ = tokio::task::spawn_blocking(move || {
-                    if let Ok(mut lock) = child.lock() {
-                        let _ = lock.kill();
-                    }
-                });
+        if let Some(session) = self.sessions.remove(session_id) {
+            if session.running {
+                kill_session(session.killer, session.pgid);
             }
         }
-        #[cfg(not(windows))]
-        {
-            let _ = session_id;
+    }
+
+    /// Append a session's output to its scrollback.
+    pub(crate) fn record_output(&mut self, session_id: &str, data: &[u8]) {
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            session.scrollback.push(data);
         }
     }
 
-    /// Kill all active sessions (called during graceful shutdown).
-    pub(crate) fn kill_all(&mut self) {
-        #[cfg(windows)]
-        {
-            let ids: Vec<String> = self.sessions.keys().cloned().collect();
-            for id in ids {
-                self.kill(&id);
-            }
+    /// Mark a session as exited; it stays listed so a client can still
+    /// attach and replay how it ended.
+    pub(crate) fn record_exit(&mut self, session_id: &str, exit_code: Option<i32>) {
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            session.running = false;
+            session.exit_code = exit_code;
         }
     }
 
+    /// Scrollback replay for a client attaching to `session_id`, plus
+    /// the session's state.
+    pub(crate) fn attach(&self, session_id: &str) -> Option<(SessionAttached, SessionInfo)> {
+        let session = self.sessions.get(session_id)?;
+        let attached = SessionAttached {
+            session_id: session_id.to_string(),
+            cols: session.cols,
+            rows: session.rows,
+            scrollback: session.scrollback.contents(),
+        };
+        Some((attached, session.info(session_id)))
+    }
+
+    /// Every session the host is keeping, ordered by id.
+    pub(crate) fn list(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self
+            .sessions
+            .iter()
+            .map(|(id, session)| session.info(id))
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// True while any session's process has not exited yet.
+    pub(crate) fn has_running(&self) -> bool {
+        self.sessions.values().any(|session| session.running)
+    }
+
+    /// Kill all active sessions and wait for their process trees to go
+    /// (called during shutdown, so nothing is left to finish the job later).
+    pub(crate) fn kill_all(&mut self) {
+        let running = self
+            .sessions
+            .drain()
+            .filter(|(_, session)| session.running)
+            .map(|(_, session)| (session.killer, session.pgid))
+            .collect();
+        kill_sessions_now(running);
+    }
+
     /// Remove a session from the map after it has exited (cleanup without killing).
     pub(crate) fn remove(&mut self, session_id: &str) {
-        #[cfg(windows)]
-        {
-            self.sessions.remove(session_id);
-        }
-        #[cfg(not(windows))]
-        {
-            let _ = session_id;
-        }
+        self.sessions.remove(session_id);
     }
 }
 
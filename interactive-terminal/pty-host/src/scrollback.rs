@@ -0,0 +1,45 @@
+use std::collections::VecDeque;
+
+/// Default scrollback kept per session, in bytes.
+pub(crate) const DEFAULT_SCROLLBACK_BYTES: usize = 256 * 1024;
+
+/// Fixed-capacity ring of a session's most recent raw PTY output, replayed to
+/// clients that attach after the output was produced.
+pub(crate) struct Scrollback {
+    buf: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl Scrollback {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Scrollback {
+            buf: VecDeque::with_capacity(capacity.min(64 * 1024)),
+            capacity,
+        }
+    }
+
+    /// Append output, discarding the oldest bytes beyond capacity.
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        let data = &data[data.len().saturating_sub(self.capacity)..];
+        let overflow = (self.buf.len() + data.len()).saturating_sub(self.capacity);
+        self.buf.drain(..overflow);
+        self.buf.extend(data);
+    }
+
+    /// The retained output as a (lossy) UTF-8 string.
+    ///
+    /// Trimming can cut a multi-byte character in half; leading continuation
+    /// bytes are skipped so the replay does not start with a replacement char.
+    pub(crate) fn contents(&self) -> String {
+        let (front, back) = self.buf.as_slices();
+        let mut bytes = Vec::with_capacity(self.buf.len());
+        bytes.extend_from_slice(front);
+        bytes.extend_from_slice(back);
+        let start = bytes
+            .iter()
+            .take(3)
+            .take_while(|b| (**b & 0b1100_0000) == 0b1000_0000)
+            .count();
+        String::from_utf8_lossy(&bytes[start..]).into_owned()
+    }
+}
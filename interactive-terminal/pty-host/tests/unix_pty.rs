@@ -0,0 +1,495 @@
+//! Integration tests for the Unix PTY backend.
+//!
+//! Each test starts the real `pty-host` binary on a free port, connects as
+//! the UI process would, and drives an `sh` session over the NDJSON IPC
+//! protocol:
+//!
+//! 1. **Spawn + input**: command output and env overrides reach the client
+//! 2. **Exit codes**: `exit 3` is reported in `session_exited`
+//! 3. **Resize**: `stty size` sees the new dimensions
+//! 4. **Kill**: background jobs in the session are cleaned up with the shell
+//! 5. **Reattach**: sessions survive a client disconnect and replay their
+//!    scrollback on `session_attach`; `session_list` reports them
+//! 6. **Observers**: every attached client receives a session's output
+//! 7. **Exit while detached**: the exit is replayed on attach, after which the
+//!    idle host shuts down; unreplayed, it shuts down after the idle grace
+//! 8. **Handshake**: connections without the right `hello` (secret, version,
+//!    first frame) are rejected explicitly and closed; the secret is never
+//!    visible to sessions
+//! 9. **Shutdown**: on SIGINT the host kills its sessions, escalating to
+//!    SIGKILL, before it exits
+
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use pty_host_protocol::{PROTOCOL_VERSION, SECRET_ENV};
+use serde_json::{json, Value};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const SECRET: &str = "test-secret-0123456789";
+
+// ─── Helpers ────────────────────────────────────────────────────────────────
+
+/// A running `pty-host` process.
+struct Host {
+    process: Child,
+    port: u16,
+}
+
+impl Host {
+    fn start() -> Self {
+        Self::start_with(&[])
+    }
+
+    fn start_with(args: &[&str]) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("no free port")
+            .port();
+        let process = Command::new(env!("CARGO_BIN_EXE_pty-host"))
+            .args(["--ipc-port", &port.to_string()])
+            .args(args)
+            .env(SECRET_ENV, SECRET)
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start pty-host");
+        Host { process, port }
+    }
+
+    /// Connect and complete the handshake.
+    fn connect(&self) -> Ui {
+        let mut ui = self.connect_raw();
+        ui.send(json!({ "type": "hello", "protocol_version": PROTOCOL_VERSION, "secret": SECRET }));
+        let reply = ui.next_matching(|_| true);
+        assert_eq!(reply["type"], "hello_ack", "unexpected reply: {reply}");
+        ui
+    }
+
+    /// Open a TCP connection without sending `hello`, retrying while the
+    /// host is still starting up.
+    fn connect_raw(&self) -> Ui {
+        let deadline = Instant::now() + TIMEOUT;
+        let stream = loop {
+            match TcpStream::connect(("127.0.0.1", self.port)) {
+                Ok(stream) => break stream,
+                Err(_) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(50))
+                }
+                Err(e) => panic!("could not connect to pty-host: {e}"),
+            }
+        };
+        stream
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+
+        Ui {
+            writer: stream.try_clone().unwrap(),
+            reader: BufReader::new(stream),
+            output: String::new(),
+        }
+    }
+
+    /// Wait for the host process to exit on its own.
+    fn expect_exit(&mut self) {
+        let deadline = Instant::now() + TIMEOUT;
+        while self.process.try_wait().unwrap().is_none() {
+            assert!(Instant::now() < deadline, "pty-host did not exit");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// One IPC connection, standing in for the UI process.
+struct Ui {
+    writer: TcpStream,
+    reader: BufReader<TcpStream>,
+    /// Output received so far for the session under test.
+    output: String,
+}
+
+impl Ui {
+    fn send(&mut self, message: Value) {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).unwrap();
+    }
+
+    fn create_sh(&mut self, env: Value) {
+        self.send(json!({
+            "type": "session_create",
+            "session_id": "s1",
+            "program": "sh",
+            "cwd": std::env::temp_dir().to_string_lossy(),
+            "env": env,
+            "cols": 80,
+            "rows": 24,
+        }));
+        let reply = self.next_matching(|m| m["type"] != "session_output");
+        assert_eq!(
+            reply["type"], "session_created",
+            "unexpected reply: {reply}"
+        );
+    }
+
+    /// Attach to `s1`; the scrollback it carries counts as received output.
+    fn attach(&mut self) -> Value {
+        self.send(json!({ "type": "session_attach", "session_id": "s1" }));
+        let reply = self.next_matching(|m| m["type"] != "session_output");
+        assert_eq!(
+            reply["type"], "session_attached",
+            "unexpected reply: {reply}"
+        );
+        self.output
+            .push_str(reply["scrollback"].as_str().unwrap_or(""));
+        reply
+    }
+
+    fn list(&mut self) -> Vec<Value> {
+        self.send(json!({ "type": "session_list" }));
+        let reply = self.next_matching(|m| m["type"] == "session_listed");
+        reply["sessions"].as_array().cloned().unwrap_or_default()
+    }
+
+    fn input(&mut self, data: &str) {
+        self.send(json!({ "type": "session_input", "session_id": "s1", "data": data }));
+    }
+
+    /// Read frames until one satisfies `pred`, collecting session output on
+    /// the way.  Heartbeats are skipped.
+    fn next_matching(&mut self, pred: impl Fn(&Value) -> bool) -> Value {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut line = String::new();
+        while Instant::now() < deadline {
+            // A read timeout keeps the partial line in `line`; resume it.
+            match self.reader.read_line(&mut line) {
+                Ok(0) => panic!("pty-host closed the connection"),
+                Ok(_) => {}
+                Err(_) => continue,
+            }
+            let message: Value = serde_json::from_str(&line).expect("invalid frame");
+            line.clear();
+            if message["type"] == "session_output" {
+                self.output.push_str(message["data"].as_str().unwrap_or(""));
+            }
+            if pred(&message) {
+                return message;
+            }
+        }
+        panic!("timed out; output so far:\n{}", self.output);
+    }
+
+    /// Wait until the session's accumulated output contains `needle`.
+    fn expect_output(&mut self, needle: &str) {
+        if self.output.contains(needle) {
+            return;
+        }
+        let needle = needle.to_string();
+        // Output may arrive split across frames, so re-check the whole buffer.
+        loop {
+            self.next_matching(|m| m["type"] == "session_output");
+            if self.output.contains(&needle) {
+                return;
+            }
+        }
+    }
+
+    /// Expect the host to close the connection.
+    fn expect_closed(&mut self) {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut line = String::new();
+        while Instant::now() < deadline {
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return,
+                Ok(_) => line.clear(),
+                Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => return,
+                Err(_) => {}
+            }
+        }
+        panic!("connection was not closed");
+    }
+
+    /// Wait for a `label=[pid]` line printed by the shell and return the pid.
+    /// The echoed command line reads `label=[$..]`, so only a number counts.
+    fn expect_pid(&mut self, label: &str) -> String {
+        let marker = format!("{label}=[");
+        loop {
+            let found = self.output.match_indices(&marker).find_map(|(at, _)| {
+                let rest = &self.output[at + marker.len()..];
+                let end = rest.find(']')?;
+                rest[..end].parse::<u32>().ok()
+            });
+            if let Some(pid) = found {
+                return pid.to_string();
+            }
+            self.next_matching(|m| m["type"] == "session_output");
+        }
+    }
+
+    fn expect_exit(&mut self) -> Value {
+        let exited = self.next_matching(|m| m["type"] == "session_exited");
+        assert_eq!(exited["session_id"], "s1");
+        exited["exit_code"].clone()
+    }
+}
+
+/// True while `pid` exists and is not a zombie.
+fn process_alive(pid: &str) -> bool {
+    Command::new("ps")
+        .args(["-o", "stat=", "-p", pid])
+        .output()
+        .map(|out| {
+            let stat = String::from_utf8_lossy(&out.stdout);
+            let stat = stat.trim();
+            !stat.is_empty() && !stat.starts_with('Z')
+        })
+        .unwrap_or(false)
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[test]
+fn spawn_runs_commands_and_applies_env_overrides() {
+    let host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({ "PTY_HOST_TEST": "from-env" }));
+
+    // Arithmetic so the echoed command line itself never matches.
+    ui.input("echo hello-$((40 + 2))\n");
+    ui.expect_output("hello-42");
+
+    ui.input("echo \"value=$PTY_HOST_TEST\"\n");
+    ui.expect_output("value=from-env");
+}
+
+#[test]
+fn exit_code_is_reported() {
+    let host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+
+    ui.input("exit 3\n");
+    assert_eq!(ui.expect_exit(), json!(3));
+}
+
+#[test]
+fn resize_reaches_the_terminal() {
+    let host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+
+    ui.send(json!({ "type": "session_resize", "session_id": "s1", "cols": 100, "rows": 40 }));
+    ui.input("stty size\n");
+    ui.expect_output("40 100");
+}
+
+#[test]
+fn kill_cleans_up_background_jobs() {
+    let host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+
+    ui.input("sleep 300 &\n");
+    ui.input("echo \"bg=[$!]\"\n");
+    let pid = ui.expect_pid("bg");
+    assert!(process_alive(&pid), "background job did not start");
+
+    ui.send(json!({ "type": "session_kill", "session_id": "s1" }));
+    ui.expect_exit();
+
+    let deadline = Instant::now() + TIMEOUT;
+    while process_alive(&pid) {
+        assert!(
+            Instant::now() < deadline,
+            "background job {pid} survived kill"
+        );
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn shutdown_kills_sessions_before_exiting() {
+    let mut host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+
+    // A job that ignores SIGHUP and never reads the terminal only goes away
+    // once the host escalates to SIGKILL.
+    ui.input("sh -c 'trap \"\" HUP; echo \"sleeper=[$$]\"; exec sleep 300'\n");
+    let pid = ui.expect_pid("sleeper");
+    assert!(process_alive(&pid), "job did not start");
+
+    let status = Command::new("kill")
+        .args(["-INT", &host.process.id().to_string()])
+        .status()
+        .expect("failed to run kill");
+    assert!(status.success());
+    host.expect_exit();
+
+    assert!(!process_alive(&pid), "session {pid} outlived the pty-host");
+}
+
+#[test]
+fn sessions_survive_disconnect_and_replay_scrollback() {
+    let host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+    ui.input("echo marker-$((20 + 1))\n");
+    ui.expect_output("marker-21");
+    drop(ui);
+
+    let mut ui = host.connect();
+    let sessions = ui.list();
+    assert_eq!(sessions.len(), 1, "sessions: {sessions:?}");
+    assert_eq!(sessions[0]["session_id"], "s1");
+    assert_eq!(sessions[0]["running"], true);
+
+    let attached = ui.attach();
+    assert_eq!(
+        (attached["cols"].as_u64(), attached["rows"].as_u64()),
+        (Some(80), Some(24))
+    );
+    assert!(ui.output.contains("marker-21"), "scrollback: {}", ui.output);
+
+    // The session is still live for the new client.
+    ui.input("echo again-$((20 + 2))\n");
+    ui.expect_output("again-22");
+}
+
+#[test]
+fn observers_receive_session_output() {
+    let host = Host::start();
+    let mut owner = host.connect();
+    owner.create_sh(json!({}));
+    let mut observer = host.connect();
+    observer.attach();
+
+    owner.input("echo shared-$((30 + 3))\n");
+    owner.expect_output("shared-33");
+    observer.expect_output("shared-33");
+}
+
+#[test]
+fn exit_while_detached_is_replayed_on_attach() {
+    let mut host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+    ui.input("echo last-$((2 + 2)); sleep 0.3; exit 5\n");
+    drop(ui);
+    std::thread::sleep(Duration::from_millis(1000));
+
+    let mut ui = host.connect();
+    let sessions = ui.list();
+    assert_eq!(sessions[0]["running"], false, "sessions: {sessions:?}");
+    assert_eq!(sessions[0]["exit_code"], 5);
+
+    ui.attach();
+    assert!(ui.output.contains("last-4"), "scrollback: {}", ui.output);
+    assert_eq!(ui.expect_exit(), json!(5));
+    assert!(ui.list().is_empty());
+
+    // Nothing left to reattach to: the host shuts down with its last client.
+    drop(ui);
+    host.expect_exit();
+}
+
+#[test]
+fn last_unattended_exit_starts_the_idle_shutdown() {
+    let mut host = Host::start_with(&["--idle-grace-ms", "300"]);
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+    ui.input("sleep 0.5; exit 0\n");
+    drop(ui);
+
+    // The session is still running, so the disconnect alone keeps the host.
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(host.process.try_wait().unwrap().is_none(), "pty-host exited early");
+
+    // Its exit, seen by nobody, leaves the host idle.
+    host.expect_exit();
+}
+
+/// Send `first` as the opening frame and return the host's rejection.
+fn rejected_handshake(host: &Host, first: Value) -> String {
+    let mut ui = host.connect_raw();
+    ui.send(first);
+    let reply = ui.next_matching(|_| true);
+    assert_eq!(reply["type"], "hello_rejected", "unexpected reply: {reply}");
+    assert_eq!(reply["protocol_version"], PROTOCOL_VERSION);
+    ui.expect_closed();
+    reply["error"].as_str().unwrap_or("").to_string()
+}
+
+#[test]
+fn wrong_secret_is_rejected() {
+    let host = Host::start();
+    let error = rejected_handshake(
+        &host,
+        json!({ "type": "hello", "protocol_version": PROTOCOL_VERSION, "secret": "guess" }),
+    );
+    assert_eq!(error, "authentication failed");
+}
+
+#[test]
+fn version_mismatch_is_explicit() {
+    let host = Host::start();
+    let error = rejected_handshake(
+        &host,
+        json!({ "type": "hello", "protocol_version": PROTOCOL_VERSION + 1, "secret": SECRET }),
+    );
+    assert!(error.contains("protocol version mismatch"), "{error}");
+    assert!(
+        error.contains(&format!("v{}", PROTOCOL_VERSION + 1)),
+        "{error}"
+    );
+}
+
+#[test]
+fn commands_before_hello_are_rejected() {
+    let host = Host::start();
+    let error = rejected_handshake(
+        &host,
+        json!({
+            "type": "session_create",
+            "session_id": "intruder",
+            "program": "sh",
+            "cwd": "/",
+            "cols": 80,
+            "rows": 24,
+        }),
+    );
+    assert!(error.contains("expected hello"), "{error}");
+
+    let mut ui = host.connect();
+    assert!(ui.list().is_empty());
+}
+
+#[test]
+fn secret_is_hidden_from_sessions() {
+    let host = Host::start();
+    let mut ui = host.connect();
+    ui.create_sh(json!({}));
+    ui.input(&format!("echo \"leak=[${{{SECRET_ENV}:-none}}]\"\n"));
+    ui.expect_output("leak=[none]");
+}
+
+#[test]
+fn host_refuses_to_start_without_secret() {
+    let status = Command::new(env!("CARGO_BIN_EXE_pty-host"))
+        .args(["--ipc-port", "0"])
+        .env_remove(SECRET_ENV)
+        .stderr(Stdio::null())
+        .status()
+        .expect("failed to run pty-host");
+    assert_eq!(status.code(), Some(2));
+}
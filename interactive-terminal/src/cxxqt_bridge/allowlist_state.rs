@@ -1,16 +1,18 @@
 //! Allowlist management for the Interactive Terminal GUI.
 //!
-//! Reads/writes `terminal-allowlist.json` in the same format used by the
-//! server-side `terminal-auth.ts`, so changes made here are visible to the
-//! MCP server and vice-versa.
+//! Reads/writes `terminal-allowlist.json` through `command_policy::allowlist`,
+//! in the same format used by the server-side `terminal-auth.ts`, so changes
+//! made here are visible to the MCP server and vice-versa.
 //!
-//! File location: `{data_root}/default/terminal-allowlist.json`
-//! File format  : `{ "patterns": ["...", ...], "updated_at": "ISO-8601" }`
+//! Patterns are not matched as string prefixes any more: each becomes an allow
+//! rule of the shared `command_policy` engine, alongside the built-in deny/ask
+//! rules and any structured `rules` in the file (see `command_policy::Rule`).
+//! `evaluate_command_policy` is what decides whether a request is allowlisted.
 
 use super::AppState;
-use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::protocol::{CommandRequest, TerminalProfile};
+use command_policy::{AllowlistFile, Decision, EvalContext, Policy, Shell};
+use std::path::PathBuf;
 
 /// Default built-in patterns that are always available even when no file exists.
 pub(crate) const DEFAULT_PATTERNS: &[&str] = &[
@@ -39,16 +41,6 @@ pub(crate) const DEFAULT_PATTERNS: &[&str] = &[
     "git --version",
 ];
 
-const ALLOWLIST_FILENAME: &str = "terminal-allowlist.json";
-const DEFAULT_WORKSPACE_ID: &str = "default";
-
-#[derive(Serialize, Deserialize, Default)]
-struct AllowlistFile {
-    patterns: Vec<String>,
-    #[serde(default)]
-    updated_at: Option<String>,
-}
-
 // ---------------------------------------------------------------------------
 // Path helpers
 // ---------------------------------------------------------------------------
@@ -80,65 +72,6 @@ fn discover_data_root() -> Option<PathBuf> {
     None
 }
 
-fn allowlist_file_path(data_root: &Path) -> PathBuf {
-    data_root
-        .join(DEFAULT_WORKSPACE_ID)
-        .join(ALLOWLIST_FILENAME)
-}
-
-// ---------------------------------------------------------------------------
-// File I/O (sync — called from GUI thread, patterns are small)
-// ---------------------------------------------------------------------------
-
-fn load_from_file(data_root: &Path) -> Option<Vec<String>> {
-    let path = allowlist_file_path(data_root);
-    let raw = std::fs::read_to_string(&path).ok()?;
-    let data: AllowlistFile = serde_json::from_str(&raw).ok()?;
-    let cleaned = sanitize_patterns(data.patterns);
-    if cleaned.is_empty() {
-        None
-    } else {
-        Some(cleaned)
-    }
-}
-
-fn save_to_file(data_root: &Path, patterns: &[String]) -> Result<(), String> {
-    let path = allowlist_file_path(data_root);
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("failed to create allowlist directory: {e}"))?;
-    }
-    let secs_since_epoch = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
-    // Use a minimal RFC 3339–like string to avoid pulling in chrono.
-    let updated_at = format!("{secs_since_epoch}");
-    let file = AllowlistFile {
-        patterns: patterns.to_vec(),
-        updated_at: Some(updated_at),
-    };
-    let json = serde_json::to_string_pretty(&file)
-        .map_err(|e| format!("failed to serialize allowlist: {e}"))?;
-    std::fs::write(&path, &json).map_err(|e| format!("failed to write allowlist file: {e}"))
-}
-
-fn sanitize_patterns(raw: Vec<String>) -> Vec<String> {
-    let mut seen = std::collections::HashSet::new();
-    let mut result = Vec::new();
-    for value in raw {
-        let trimmed = value.trim().to_string();
-        if trimmed.is_empty() {
-            continue;
-        }
-        let key = trimmed.to_ascii_lowercase();
-        if seen.insert(key) {
-            result.push(trimmed);
-        }
-    }
-    result
-}
-
 // ---------------------------------------------------------------------------
 // AppState impl
 // ---------------------------------------------------------------------------
@@ -151,8 +84,9 @@ impl AppState {
         }
 
         if let Some(root) = &self.allowlist_data_root.clone() {
-            if let Some(patterns) = load_from_file(root) {
-                self.allowlist_patterns = patterns;
+            if let Some(file) = AllowlistFile::load(root) {
+                self.allowlist_patterns = file.patterns;
+                self.policy_rules = file.rules;
                 return;
             }
         }
@@ -162,6 +96,27 @@ impl AppState {
             .iter()
             .map(|s| s.to_string())
             .collect();
+        self.policy_rules = Vec::new();
+    }
+
+    /// Decide whether `req` may run without approval.
+    ///
+    /// The request's own `allowlisted` flag is ignored; the command is
+    /// tokenized for its terminal profile and checked against the policy
+    /// built from the allowlist.  The allowlist is loaded on first use.
+    pub(crate) fn evaluate_command_policy(&mut self, req: &CommandRequest) -> Decision {
+        if self.allowlist_patterns.is_empty() && self.policy_rules.is_empty() {
+            self.refresh_allowlist();
+        }
+        let policy = Policy::from_allowlist(&self.allowlist_patterns, self.policy_rules.clone());
+        policy.evaluate(
+            &req.command,
+            &EvalContext {
+                shell: policy_shell(&req.terminal_profile),
+                working_directory: &req.working_directory,
+                workspace: &req.workspace_path,
+            },
+        )
     }
 
     /// Persist the current pattern list to disk.
@@ -170,7 +125,12 @@ impl AppState {
             .allowlist_data_root
             .as_ref()
             .ok_or_else(|| "data root not discovered; cannot persist allowlist".to_string())?;
-        save_to_file(root, &self.allowlist_patterns)
+        AllowlistFile {
+            patterns: self.allowlist_patterns.clone(),
+            rules: self.policy_rules.clone(),
+            updated_at: None,
+        }
+        .save(root)
     }
 
     /// Add a pattern. Returns an error string on validation failure or duplicate.
@@ -216,6 +176,16 @@ impl AppState {
     }
 }
 
+/// Shell dialect the policy tokenizes a request's command in.
+pub(crate) fn policy_shell(profile: &TerminalProfile) -> Shell {
+    match profile {
+        TerminalProfile::PowerShell | TerminalProfile::Pwsh => Shell::PowerShell,
+        TerminalProfile::Cmd => Shell::Cmd,
+        TerminalProfile::Bash => Shell::Posix,
+        TerminalProfile::System => Shell::host_default(),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Pattern derivation (step 23 + 24)
 // ---------------------------------------------------------------------------
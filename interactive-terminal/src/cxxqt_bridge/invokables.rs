@@ -673,6 +673,9 @@ impl ffi::TerminalApp {
             .set_status_text(QString::from("Executing command..."));
         self.as_mut().set_output_text(QString::default());
 
+        if let Some(cmd) = approved_cmd_for_echo.as_ref() {
+            crate::audit_log::emit_command_approved(cmd);
+        }
         if let (Some(cmd), Some(tx)) = (approved_cmd_for_echo.as_ref(), ws_tx.as_ref()) {
             let _ = tx.send(format!("{}\r\n", cmd.command).into_bytes());
         }
@@ -1015,7 +1018,7 @@ impl ffi::TerminalApp {
                 .pending_commands_by_session
                 .get(&selected_session)
                 .and_then(|q| q.iter().find(|c| c.id == id_str))
-                .map(|c| (c.command.clone(), c.context.clone(), c.workspace_path.clone()))
+                .cloned()
         };
 
         let (next_cmd, count, json, tabs_json, selected_session_id) = {
@@ -1035,33 +1038,34 @@ impl ffi::TerminalApp {
             (next, count, json, tabs_json, selected_session_id)
         };
 
-        // ── Audit: emit launch_denied or launch_cancelled ─────────────────
-        if let Some((command, context, workspace_path)) = declined_cmd_info {
-            let provider = crate::launch_builder::normalize_provider_token(&command);
-            if !provider.is_empty() {
-                let ctx_lower = context.to_ascii_lowercase();
-                let is_agent_ctx = ctx_lower.contains("agent_cli_launch")
-                    || ctx_lower.contains("super_subagent")
-                    || ctx_lower.contains("launch_kind")
-                    || ctx_lower.contains("launch_type")
-                    || ctx_lower.contains("\"intent\":\"agent")
-                    || ctx_lower.contains("\"intent\": \"agent");
-                if is_agent_ctx {
-                    let reason_lower = reason_str.trim().to_ascii_lowercase();
-                    if reason_lower.is_empty()
-                        || reason_lower.contains("cancel")
-                        || reason_lower.contains("dismiss")
-                    {
-                        crate::audit_log::emit_launch_cancelled(&workspace_path, &context, &provider);
-                    } else {
-                        crate::audit_log::emit_launch_denied(
-                            &workspace_path,
-                            &context,
-                            &provider,
-                            normalized_reason.as_deref(),
-                        );
-                    }
+        // ── Audit: emit launch_denied / launch_cancelled, or command_declined ─
+        if let Some(cmd) = declined_cmd_info {
+            let (command, context, workspace_path) = (&cmd.command, &cmd.context, &cmd.workspace_path);
+            let provider = crate::launch_builder::normalize_provider_token(command);
+            let ctx_lower = context.to_ascii_lowercase();
+            let is_agent_ctx = ctx_lower.contains("agent_cli_launch")
+                || ctx_lower.contains("super_subagent")
+                || ctx_lower.contains("launch_kind")
+                || ctx_lower.contains("launch_type")
+                || ctx_lower.contains("\"intent\":\"agent")
+                || ctx_lower.contains("\"intent\": \"agent");
+            if !provider.is_empty() && is_agent_ctx {
+                let reason_lower = reason_str.trim().to_ascii_lowercase();
+                if reason_lower.is_empty()
+                    || reason_lower.contains("cancel")
+                    || reason_lower.contains("dismiss")
+                {
+                    crate::audit_log::emit_launch_cancelled(workspace_path, context, &provider);
+                } else {
+                    crate::audit_log::emit_launch_denied(
+                        workspace_path,
+                        context,
+                        &provider,
+                        normalized_reason.as_deref(),
+                    );
                 }
+            } else {
+                crate::audit_log::emit_command_declined(&cmd, &reason_str, None);
             }
         }
 
@@ -9,13 +9,13 @@ pub mod inner {
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex as StdMutex, OnceLock};
 
-    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines, ReadHalf, WriteHalf};
     use tokio::net::TcpStream;
     use tokio::sync::{mpsc, oneshot};
     use tokio::time::{timeout, Duration};
 
     use crate::cxxqt_bridge::session_runtime::{AppState, SessionLifecycleState};
-    use crate::pty_host_protocol::*;
+    use pty_host_protocol::*;
 
     /// Module-level handle to the connected PtyHostClient.
     /// Initialised once from `runtime_tasks.rs` after the pty-host process has started.
@@ -76,11 +76,16 @@ pub mod inner {
     // ── PtyHostClient ────────────────────────────────────────────────────────
 
     type PendingCreates = Arc<StdMutex<HashMap<String, oneshot::Sender<Result<(), String>>>>>;
+    type PendingAttaches =
+        Arc<StdMutex<HashMap<String, oneshot::Sender<Result<SessionAttached, String>>>>>;
+    type PendingLists = Arc<StdMutex<Vec<oneshot::Sender<Vec<SessionInfo>>>>>;
 
     /// Manages the single TCP connection to the pty-host process.
     pub struct PtyHostClient {
         send_tx: mpsc::UnboundedSender<PtyHostMessage>,
         pending_creates: PendingCreates,
+        pending_attaches: PendingAttaches,
+        pending_lists: PendingLists,
     }
 
     impl PtyHostClient {
@@ -90,17 +95,25 @@ pub mod inner {
         ///   session output fan-out loop (unchanged from the non-pty-host path).
         ///
         /// `app_state` — used to mark sessions as `Closed` on `SessionExited`.
+        ///
+        /// The connection is authenticated with `secret` (the one the launcher
+        /// passed to pty-host) before any other traffic.
         pub async fn connect(
             port: u16,
+            secret: &str,
             session_output_tx: mpsc::Sender<(String, Vec<u8>)>,
             app_state: Arc<StdMutex<AppState>>,
             disconnect_tx: mpsc::UnboundedSender<String>,
         ) -> Result<Arc<Self>, String> {
             // Retry: the pty-host process may take a moment to bind its socket.
             let stream = Self::connect_with_retry(port).await?;
-            let (read_half, write_half) = tokio::io::split(stream);
+            let (read_half, mut write_half) = tokio::io::split(stream);
+            let mut lines = BufReader::new(read_half).lines();
+            Self::handshake(&mut lines, &mut write_half, secret).await?;
 
             let pending_creates: PendingCreates = Arc::new(StdMutex::new(HashMap::new()));
+            let pending_attaches: PendingAttaches = Arc::new(StdMutex::new(HashMap::new()));
+            let pending_lists: PendingLists = Arc::new(StdMutex::new(Vec::new()));
 
             // Channel: enqueues outgoing messages for the write task.
             let (send_tx, mut send_rx) = mpsc::unbounded_channel::<PtyHostMessage>();
@@ -127,9 +140,10 @@ pub mod inner {
 
             // ── Recv task ────────────────────────────────────────────────────
             let pending_for_recv = pending_creates.clone();
+            let attaches_for_recv = pending_attaches.clone();
+            let lists_for_recv = pending_lists.clone();
             let app_state_for_recv = app_state;
             tokio::spawn(async move {
-                let mut lines = BufReader::new(read_half).lines();
                 loop {
                     match lines.next_line().await {
                         Ok(Some(line)) => {
@@ -188,6 +202,31 @@ pub mod inner {
                                         e.session_id, e.exit_code
                                     );
                                 }
+                                PtyHostMessage::SessionAttached(a) => {
+                                    if let Some(tx) = attaches_for_recv
+                                        .lock()
+                                        .unwrap()
+                                        .remove(&a.session_id)
+                                    {
+                                        let _ = tx.send(Ok(a));
+                                    }
+                                }
+                                PtyHostMessage::SessionAttachFailed(f) => {
+                                    if let Some(tx) = attaches_for_recv
+                                        .lock()
+                                        .unwrap()
+                                        .remove(&f.session_id)
+                                    {
+                                        let _ = tx.send(Err(f.error));
+                                    }
+                                }
+                                PtyHostMessage::SessionListed(l) => {
+                                    // Replies arrive in request order.
+                                    let mut lists = lists_for_recv.lock().unwrap();
+                                    if !lists.is_empty() {
+                                        let _ = lists.remove(0).send(l.sessions);
+                                    }
+                                }
                                 PtyHostMessage::Heartbeat(_) => {
                                     // Liveness signal — no action required.
                                 }
@@ -215,9 +254,56 @@ pub mod inner {
             Ok(Arc::new(PtyHostClient {
                 send_tx,
                 pending_creates,
+                pending_attaches,
+                pending_lists,
             }))
         }
 
+        /// Sessions the pty-host is keeping, including ones started by a
+        /// previous UI process.
+        pub async fn list_sessions(&self) -> Result<Vec<SessionInfo>, String> {
+            let (tx, rx) = oneshot::channel();
+            self.pending_lists.lock().unwrap().push(tx);
+            self.send_tx
+                .send(PtyHostMessage::SessionList(SessionList {}))
+                .map_err(|_| "PtyHostClient send channel closed".to_string())?;
+            match timeout(Duration::from_secs(5), rx).await {
+                Ok(Ok(sessions)) => Ok(sessions),
+                Ok(Err(_)) => Err("pending list oneshot dropped".to_string()),
+                Err(_) => Err("timed out waiting for the session list".to_string()),
+            }
+        }
+
+        /// Subscribe to an existing session's output.
+        ///
+        /// Returns a handle for the session and the host's reply, whose
+        /// `scrollback` is the output produced before this attach.
+        pub async fn attach_session(
+            self: &Arc<Self>,
+            session_id: impl Into<String>,
+        ) -> Result<(PtyHostSessionHandle, SessionAttached), String> {
+            let session_id = session_id.into();
+            let (tx, rx) = oneshot::channel();
+            self.pending_attaches
+                .lock()
+                .unwrap()
+                .insert(session_id.clone(), tx);
+            self.send_tx
+                .send(PtyHostMessage::SessionAttach(SessionAttach {
+                    session_id: session_id.clone(),
+                }))
+                .map_err(|_| "PtyHostClient send channel closed".to_string())?;
+            match timeout(Duration::from_secs(10), rx).await {
+                Ok(Ok(Ok(attached))) => Ok((
+                    PtyHostSessionHandle::new(session_id, self.send_tx.clone()),
+                    attached,
+                )),
+                Ok(Ok(Err(e))) => Err(format!("pty-host failed to attach session: {e}")),
+                Ok(Err(_)) => Err("pending attach oneshot dropped".to_string()),
+                Err(_) => Err("timed out waiting for session attach".to_string()),
+            }
+        }
+
         /// Spawn a new PTY session in the pty-host process.
         ///
         /// Returns a `PtyHostSessionHandle` that can be used to send input/resize/kill.
@@ -261,6 +347,44 @@ pub mod inner {
 
         // ── Internal helpers ─────────────────────────────────────────────────
 
+        /// Send `Hello` and wait for the host to accept it.
+        async fn handshake(
+            lines: &mut Lines<BufReader<ReadHalf<TcpStream>>>,
+            write_half: &mut WriteHalf<TcpStream>,
+            secret: &str,
+        ) -> Result<(), String> {
+            let hello = serde_json::to_string(&PtyHostMessage::Hello(Hello::new(secret)))
+                .map_err(|e| format!("serialize hello: {e}"))?;
+            write_half
+                .write_all(format!("{hello}\n").as_bytes())
+                .await
+                .map_err(|e| format!("send hello: {e}"))?;
+
+            let reply = match timeout(Duration::from_secs(5), lines.next_line()).await {
+                Ok(Ok(Some(line))) => line,
+                Ok(Ok(None)) => return Err("pty-host closed the connection during handshake".to_string()),
+                Ok(Err(e)) => return Err(format!("pty-host handshake read error: {e}")),
+                Err(_) => return Err("timed out waiting for pty-host handshake".to_string()),
+            };
+            match serde_json::from_str::<PtyHostMessage>(&reply) {
+                Ok(PtyHostMessage::HelloAck(ack)) => {
+                    eprintln!(
+                        "[PtyHostClient] handshake ok (protocol v{}, pty-host {})",
+                        ack.protocol_version, ack.host_version
+                    );
+                    Ok(())
+                }
+                Ok(PtyHostMessage::HelloRejected(r)) if r.protocol_version != PROTOCOL_VERSION => Err(format!(
+                    "pty-host speaks protocol v{} but this terminal speaks v{PROTOCOL_VERSION}; \
+                     rebuild both from the same checkout ({})",
+                    r.protocol_version, r.error
+                )),
+                Ok(PtyHostMessage::HelloRejected(r)) => Err(format!("pty-host rejected handshake: {}", r.error)),
+                Ok(other) => Err(format!("unexpected handshake reply from pty-host: {other:?}")),
+                Err(e) => Err(format!("malformed handshake reply from pty-host: {e}")),
+            }
+        }
+
         async fn connect_with_retry(port: u16) -> Result<TcpStream, String> {
             const MAX_ATTEMPTS: u32 = 20;
             const BASE_DELAY_MS: u64 = 200;
@@ -6,7 +6,7 @@ use crate::cxxqt_bridge::terminal_profile_to_key;
 use crate::integration::hosted_session_adapter;
 use crate::perf_monitor::PerfMonitor;
 use crate::protocol::{
-    CommandRequest, CommandResponse, Message, OutputChunk, ResponseStatus, SavedCommandsAction,
+    AuditQueryResponse, CommandRequest, CommandResponse, Message, OutputChunk, ResponseStatus, SavedCommandsAction,
     SavedCommandsResponse,
 };
 use crate::tcp_server::{ConnectionEvent, TcpServer};
@@ -288,6 +288,60 @@ async fn ensure_ws_terminal_session(
     Ok(())
 }
 
+/// pty-host path: attach to every session the host kept across a UI restart,
+/// give each a tab and replay its scrollback through the output fan-out.
+#[cfg(feature = "pty-host")]
+async fn reattach_pty_host_sessions(
+    client: &Arc<crate::cxxqt_bridge::pty_host_client::PtyHostClient>,
+    state: &Arc<Mutex<AppState>>,
+    session_handles: &Arc<tokio::sync::Mutex<HashMap<String, WsTerminalSessionHandle>>>,
+    session_output_tx: &tokio::sync::mpsc::Sender<(String, Vec<u8>)>,
+) {
+    let sessions = match client.list_sessions().await {
+        Ok(sessions) => sessions,
+        Err(error) => {
+            eprintln!("[WsTerminal] failed to list pty-host sessions: {error}");
+            return;
+        }
+    };
+
+    for info in sessions {
+        if session_handles.lock().await.contains_key(&info.session_id) {
+            continue;
+        }
+        let (handle, attached) = match client.attach_session(info.session_id.clone()).await {
+            Ok(attached) => attached,
+            Err(error) => {
+                eprintln!("[WsTerminal] failed to reattach {}: {error}", info.session_id);
+                continue;
+            }
+        };
+        state
+            .lock()
+            .unwrap()
+            .register_reattached_session(&info.session_id, &info.cwd, info.running);
+        session_handles
+            .lock()
+            .await
+            .insert(info.session_id.clone(), handle);
+        eprintln!(
+            "[WsTerminal] reattached pty-host session {} ({} bytes of scrollback)",
+            info.session_id,
+            attached.scrollback.len()
+        );
+
+        if !attached.scrollback.is_empty() {
+            // The fan-out task may not be running yet; do not block on it.
+            let output_tx = session_output_tx.clone();
+            tokio::spawn(async move {
+                let _ = output_tx
+                    .send((attached.session_id, attached.scrollback.into_bytes()))
+                    .await;
+            });
+        }
+    }
+}
+
 #[cfg(all(windows, not(feature = "pty-host")))]
 async fn terminate_ws_terminal_session(handle: WsTerminalSessionHandle) {
     let child = handle.child.clone();
@@ -477,14 +531,30 @@ pub(crate) fn spawn_runtime_tasks(
                 #[cfg(feature = "pty-host")]
                 {
                     use crate::cxxqt_bridge::pty_host_client::{PTY_HOST_CLIENT, PtyHostClient};
+                    use crate::pty_host_launcher::PTY_HOST_SECRET;
                     // Must match PTY_HOST_IPC_PORT in pty_host_launcher.rs and pty-host/src/main.rs.
                     const PTY_HOST_IPC_PORT: u16 = 9102;
                     let app_state_for_client = state_for_msg.clone();
                     let session_output_for_client = session_output_tx.clone();
                     let (crash_tx, mut crash_rx) =
                         tokio::sync::mpsc::unbounded_channel::<String>();
-                    match PtyHostClient::connect(PTY_HOST_IPC_PORT, session_output_for_client, app_state_for_client, crash_tx).await {
+                    let secret = PTY_HOST_SECRET.get().map(String::as_str).unwrap_or_default();
+                    match PtyHostClient::connect(PTY_HOST_IPC_PORT, secret, session_output_for_client, app_state_for_client, crash_tx).await {
                         Ok(client) => {
+                            // Sessions started by a previous UI process are still
+                            // running in the host: take them over before creating
+                            // any new ones so their ids are not reused.
+                            reattach_pty_host_sessions(
+                                &client,
+                                &state_for_msg,
+                                &session_handles,
+                                &session_output_tx,
+                            )
+                            .await;
+                            let tabs_json = state_for_msg.lock().unwrap().session_tabs_to_json();
+                            let _ = qt_thread_terminal.queue(move |mut obj| {
+                                obj.as_mut().set_session_tabs_json(tabs_json);
+                            });
                             if PTY_HOST_CLIENT.set(client).is_err() {
                                 eprintln!("[WsTerminal] PtyHostClient already initialized");
                             }
@@ -878,8 +948,48 @@ pub(crate) fn spawn_runtime_tasks(
                     while let Some(msg) = rx.recv().await {
                         match msg {
                             Message::CommandRequest(req) => {
-                                let force_gui_approval = requires_gui_approval_hard_gate(&req);
+                                // The caller's `allowlisted` flag is only a hint; the command
+                                // policy decides, on the request as it will actually run.
+                                let (decision, probe) = {
+                                    let mut s = state.lock().unwrap();
+                                    let mut probe = req.clone();
+                                    s.hydrate_request_with_session_context(&mut probe);
+                                    (s.evaluate_command_policy(&probe), probe)
+                                };
+                                eprintln!(
+                                    "[command-policy] request {}: {:?} by `{}` ({})",
+                                    req.id, decision.action, decision.rule_id, decision.reason
+                                );
+
+                                if decision.action == command_policy::Action::Deny {
+                                    let reason = format!(
+                                        "blocked by command policy rule `{}`: {}",
+                                        decision.rule_id, decision.reason
+                                    );
+                                    crate::audit_log::emit_command_declined(
+                                        &probe,
+                                        &reason,
+                                        Some(decision.rule_id.as_str()),
+                                    );
+                                    state.lock().unwrap().send_response(Message::CommandResponse(
+                                        CommandResponse {
+                                            id: req.id.clone(),
+                                            status: ResponseStatus::Declined,
+                                            output: None,
+                                            exit_code: None,
+                                            reason: Some(reason.clone()),
+                                            output_file_path: None,
+                                        },
+                                    ));
+                                    let _ = qt.queue(move |mut obj| {
+                                        obj.as_mut().set_status_text(QString::from(&reason));
+                                    });
+                                    continue;
+                                }
+
                                 let mut effective_req = req.clone();
+                                effective_req.allowlisted = decision.is_allowed();
+                                let force_gui_approval = requires_gui_approval_hard_gate(&effective_req);
                                 if force_gui_approval {
                                     effective_req.allowlisted = false;
                                 }
@@ -901,6 +1011,12 @@ pub(crate) fn spawn_runtime_tasks(
                                         let selected_session_id = s.selected_session_id.clone();
                                         (send_result, tabs_json, selected_session_id, hydrated)
                                     };
+                                    if send_result.is_ok() {
+                                        crate::audit_log::emit_command_auto_approved(
+                                            &hydrated,
+                                            Some(decision.rule_id.as_str()),
+                                        );
+                                    }
 
                                     let req_clone = hydrated;
                                     let _ = qt.queue(move |mut obj| {
@@ -1197,6 +1313,22 @@ pub(crate) fn spawn_runtime_tasks(
                                 let s = state.lock().unwrap();
                                 s.send_response(response);
                             }
+                            Message::AuditQueryRequest(req) => {
+                                let request_id = req.id.clone();
+                                let response = tokio::task::spawn_blocking(move || {
+                                    crate::audit_log::answer_query(&req)
+                                })
+                                .await
+                                .unwrap_or_else(|error| AuditQueryResponse {
+                                    id: request_id,
+                                    success: false,
+                                    records: Vec::new(),
+                                    verification: None,
+                                    error: Some(format!("audit query failed: {error}")),
+                                });
+                                let s = state.lock().unwrap();
+                                s.send_response(Message::AuditQueryResponse(response));
+                            }
                             Message::Heartbeat(_) => {}
                             Message::WorkspaceListPush(push) => {
                                 // Store pushed workspace paths in state and
@@ -1410,9 +1542,9 @@ pub(crate) fn spawn_runtime_tasks(
 
                                         let (separated_stdout, separated_stderr) = separate_stdout_stderr(&captured_lines);
                                         {
-                                            let exit_code = match &result {
-                                                Ok(r) => r.exit_code,
-                                                Err(_) => Some(-1),
+                                            let (exit_code, timed_out) = match &result {
+                                                Ok(r) => (r.exit_code, r.timed_out),
+                                                Err(_) => (Some(-1), false),
                                             };
                                             let mut s = state_s.lock().unwrap();
                                             s.output_tracker.mark_completed(
@@ -1425,6 +1557,8 @@ pub(crate) fn spawn_runtime_tasks(
                                                     &req_s.session_id,
                                                     exit_code,
                                                 );
+                                            } else {
+                                                crate::audit_log::emit_command_finished(&req_s, exit_code, timed_out);
                                             }
                                         }
 
@@ -1527,6 +1661,8 @@ pub(crate) fn spawn_runtime_tasks(
                                                     &req_s.session_id,
                                                     Some(-1),
                                                 );
+                                            } else {
+                                                crate::audit_log::emit_command_killed(&req_s);
                                             }
                                         }
                                         let persisted_path = match crate::output_persistence::write_command_output_file(
@@ -1571,9 +1707,9 @@ pub(crate) fn spawn_runtime_tasks(
                                     // (C) Mark completed with separated stdout/stderr
                                     let (separated_stdout, separated_stderr) = separate_stdout_stderr(&captured_lines);
                                     {
-                                        let exit_code = match &result {
-                                            Ok(r) => r.exit_code,
-                                            Err(_) => Some(-1),
+                                        let (exit_code, timed_out) = match &result {
+                                            Ok(r) => (r.exit_code, r.timed_out),
+                                            Err(_) => (Some(-1), false),
                                         };
                                         let mut s = state.lock().unwrap();
                                         s.output_tracker.mark_completed(
@@ -1586,6 +1722,8 @@ pub(crate) fn spawn_runtime_tasks(
                                                 &req.session_id,
                                                 exit_code,
                                             );
+                                        } else {
+                                            crate::audit_log::emit_command_finished(&req, exit_code, timed_out);
                                         }
                                     }
 
@@ -1701,6 +1839,8 @@ pub(crate) fn spawn_runtime_tasks(
                                                 &req.session_id,
                                                 Some(-1),
                                             );
+                                        } else {
+                                            crate::audit_log::emit_command_killed(&req);
                                         }
                                     }
 
@@ -2096,6 +2236,7 @@ async fn execute_command_via_ws_terminal(
                 request_id: request.id.clone(),
                 exit_code: Some(-1),
                 output: captured_output,
+                timed_out: true,
             });
         }
 
@@ -2135,6 +2276,7 @@ async fn execute_command_via_ws_terminal(
                             request_id: request.id.clone(),
                             exit_code: Some(code),
                             output: captured_output,
+                            timed_out: false,
                         });
                     }
 
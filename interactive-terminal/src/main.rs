@@ -11,7 +11,6 @@ mod output_persistence;
 mod perf_monitor;
 mod protocol;
 mod pty_host_launcher;
-mod pty_host_protocol;
 mod saved_commands;
 mod saved_commands_repository;
 mod session;
@@ -219,6 +218,7 @@ fn main() {
         .unwrap_or_else(|error| panic!("Failed to prebind {host}:{port}: {error}"));
 
     eprintln!("Interactive Terminal listening on {host}:{port}");
+    audit_log::install_audit_key();
     host_bridge_listener::spawn(host_bridge_port, port);
 
     // Launch the out-of-process PTY host (pty-host feature only).
@@ -3,7 +3,14 @@
 /// The launcher is active only when the `pty-host` feature flag is enabled.
 /// It spawns the `pty-host` binary as a child process, passing the IPC port
 /// argument so the binary binds the expected socket before the UI process tries
-/// to connect.
+/// to connect, and a fresh per-launch secret (in the environment, so it does
+/// not show up in process listings) that the client must present in its
+/// `Hello`.
+///
+/// The secret is also written to an owner-only file in the app data
+/// directory.  A UI that restarts while the host keeps running finds the host
+/// already listening, reads the secret back and reattaches instead of
+/// spawning a second host.
 
 /// Default IPC port used by both the pty-host server and the client connector.
 ///
@@ -14,12 +21,72 @@ pub const PTY_HOST_IPC_PORT: u16 = 9102;
 #[cfg(feature = "pty-host")]
 mod launcher_impl {
     use super::PTY_HOST_IPC_PORT;
+    use crate::saved_commands_repository::SavedCommandsRepository;
+    use std::io::Write;
+    use std::net::{SocketAddr, TcpStream};
+    use std::path::PathBuf;
     use std::process::{Child, Command};
     use std::sync::{Mutex, OnceLock};
+    use std::time::Duration;
+
+    const SECRET_FILE_NAME: &str = "pty-host.secret";
 
     /// Handle to the spawned `pty-host` child process (Windows only).
     pub static PTY_HOST_CHILD: OnceLock<Mutex<Child>> = OnceLock::new();
 
+    /// Secret handed to the spawned `pty-host`; `PtyHostClient::connect`
+    /// presents it in the handshake.
+    pub static PTY_HOST_SECRET: OnceLock<String> = OnceLock::new();
+
+    /// 244 random bits from two v4 UUIDs.
+    fn generate_secret() -> String {
+        format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        )
+    }
+
+    /// `{app data}/interactive-terminal/pty-host.secret`.
+    fn secret_path() -> Option<PathBuf> {
+        SavedCommandsRepository::platform_app_data_root()
+            .map(|root| root.join("interactive-terminal").join(SECRET_FILE_NAME))
+    }
+
+    fn read_secret() -> Option<String> {
+        let secret = std::fs::read_to_string(secret_path()?).ok()?;
+        let secret = secret.trim();
+        (!secret.is_empty()).then(|| secret.to_string())
+    }
+
+    /// Write `secret` readable by the current user only (mode 0600 on Unix;
+    /// on Windows the per-user app data directory restricts access).
+    fn write_secret(secret: &str) -> Result<(), String> {
+        let path = secret_path().ok_or("no app data directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(secret.as_bytes()))
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))
+    }
+
+    /// Whether something already accepts connections on the IPC port,
+    /// normally a `pty-host` that outlived the previous UI process.
+    fn host_listening() -> bool {
+        let addr = SocketAddr::from(([127, 0, 0, 1], PTY_HOST_IPC_PORT));
+        TcpStream::connect_timeout(&addr, Duration::from_millis(300)).is_ok()
+    }
+
     /// Locate the `pty-host` executable next to the current binary.
     fn pty_host_exe_path() -> std::path::PathBuf {
         let mut path = std::env::current_exe()
@@ -32,13 +99,28 @@ mod launcher_impl {
         path
     }
 
-    /// Spawn the `pty-host` binary.
+    /// Spawn the `pty-host` binary, or reuse the one already running.
     ///
     /// The child process binds its IPC socket on `PTY_HOST_IPC_PORT` and waits
     /// for the UI process to connect.  This function returns as soon as the child
     /// process has been launched — the caller is responsible for connecting
-    /// (retried by `PtyHostClient::connect`).
+    /// (retried by `PtyHostClient::connect`).  When a host is already
+    /// listening, its secret is read from the secret file and nothing is
+    /// spawned.
     pub fn launch_pty_host() -> Result<(), String> {
+        if host_listening() {
+            let secret = read_secret().ok_or_else(|| {
+                format!("port {PTY_HOST_IPC_PORT} is in use but no pty-host secret file was found")
+            })?;
+            PTY_HOST_SECRET
+                .set(secret)
+                .map_err(|_| "PTY_HOST_SECRET already set".to_string())?;
+            eprintln!(
+                "[pty-host launcher] reattaching to the pty-host already listening on {PTY_HOST_IPC_PORT}"
+            );
+            return Ok(());
+        }
+
         let exe = pty_host_exe_path();
 
         if !exe.exists() {
@@ -48,9 +130,17 @@ mod launcher_impl {
             ));
         }
 
+        let secret = PTY_HOST_SECRET.get_or_init(generate_secret);
+        if let Err(e) = write_secret(secret) {
+            eprintln!(
+                "[pty-host launcher] WARNING: {e}; a restarted UI will not be able to reattach"
+            );
+        }
+
         let child = Command::new(&exe)
             .arg("--ipc-port")
             .arg(PTY_HOST_IPC_PORT.to_string())
+            .env(pty_host_protocol::SECRET_ENV, secret)
             .spawn()
             .map_err(|e| format!("Failed to spawn pty-host: {e}"))?;
 
@@ -69,4 +159,4 @@ mod launcher_impl {
 }
 
 #[cfg(feature = "pty-host")]
-pub use launcher_impl::{launch_pty_host, PTY_HOST_CHILD};
+pub use launcher_impl::{launch_pty_host, PTY_HOST_CHILD, PTY_HOST_SECRET};
@@ -72,7 +72,7 @@ impl SavedCommandsRepository {
     /// Windows:  %APPDATA%\ProjectMemory
     /// macOS:    ~/Library/Application Support/ProjectMemory
     /// Linux:    $XDG_DATA_HOME/ProjectMemory  (or ~/.local/share/ProjectMemory)
-    fn platform_app_data_root() -> Option<PathBuf> {
+    pub(crate) fn platform_app_data_root() -> Option<PathBuf> {
         #[cfg(target_os = "windows")]
         {
             if let Ok(appdata) = std::env::var("APPDATA") {
@@ -6,6 +6,7 @@
  *   - read_output falls back to local when session is NOT in guiSessions
  *   - kill routes through TCP for GUI sessions
  *   - TCP connection failure during read_output/kill returns proper errors
+ *   - audit_query resolves the workspace path and queries the GUI over TCP
  *
  * Separated from memory-terminal.test.ts because these tests require
  * TcpTerminalAdapter to be fully mocked (vi.mock is hoisted and global),
@@ -23,6 +24,8 @@ const mockClose = vi.fn();
 const mockSendAndAwait = vi.fn();
 const mockSendReadOutput = vi.fn();
 const mockSendKill = vi.fn();
+const mockSendAuditQuery = vi.fn();
+const mockGetWorkspace = vi.fn().mockResolvedValue(null);
 
 vi.mock('../../tools/terminal-tcp-adapter.js', () => ({
   TcpTerminalAdapter: vi.fn().mockImplementation(() => ({
@@ -31,13 +34,14 @@ vi.mock('../../tools/terminal-tcp-adapter.js', () => ({
     sendAndAwait: mockSendAndAwait,
     sendReadOutput: mockSendReadOutput,
     sendKill: mockSendKill,
+    sendAuditQuery: mockSendAuditQuery,
   })),
 }));
 
 // Mock file-store to avoid disk operations
 vi.mock('../../storage/db-store.js', () => ({
   getWorkspacePath: (wsId: string) => `/tmp/test-data/${wsId}`,
-  getWorkspace: vi.fn().mockResolvedValue(null),
+  getWorkspace: (...args: unknown[]) => mockGetWorkspace(...args),
 }));
 
 // Mock node:fs/promises to avoid real disk I/O
@@ -311,3 +315,85 @@ describe('GUI session routing: kill', () => {
     expect(result.error).toContain('Process may have already exited');
   });
 });
+
+describe('audit_query', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    mockConnect.mockResolvedValue(undefined);
+  });
+
+  it('sends the filters with the workspace path and returns the records', async () => {
+    mockGetWorkspace.mockResolvedValueOnce({ workspace_id: 'ws-audit', path: '/repo/ws-audit' });
+    const record = {
+      seq: 0,
+      prev_hash: '0'.repeat(64),
+      hash: 'a'.repeat(64),
+      timestamp: '2026-03-01T10:00:00Z',
+      event: 'command_declined',
+      requesting_agent: 'Executor',
+    };
+    mockSendAuditQuery.mockResolvedValueOnce({
+      type: 'audit_query_response',
+      id: 'audit-1',
+      success: true,
+      records: [record],
+      verification: { intact: true, summary: { records: 1, legacy_lines: 0, head_hash: record.hash } },
+    });
+
+    const result = await memoryTerminal({
+      action: 'audit_query',
+      workspace_id: 'ws-audit',
+      agent: 'executor',
+      status: ['command_declined'],
+      limit: 5,
+      verify: true,
+    });
+
+    expect(mockGetWorkspace).toHaveBeenCalledWith('ws-audit');
+    expect(mockSendAuditQuery).toHaveBeenCalledWith(
+      expect.objectContaining({
+        workspace_path: '/repo/ws-audit',
+        agent: 'executor',
+        status: ['command_declined'],
+        limit: 5,
+        verify: true,
+      }),
+    );
+    expect(result.success).toBe(true);
+    expect(result.data).toEqual({
+      action: 'audit_query',
+      data: {
+        records: [record],
+        verification: { intact: true, summary: { records: 1, legacy_lines: 0, head_hash: record.hash } },
+      },
+    });
+    expect(mockClose).toHaveBeenCalled();
+  });
+
+  it('requires a known workspace', async () => {
+    const missingId = await memoryTerminal({ action: 'audit_query' });
+    expect(missingId.success).toBe(false);
+    expect(missingId.error).toContain('workspace_id is required');
+
+    const unknown = await memoryTerminal({ action: 'audit_query', workspace_id: 'ws-none' });
+    expect(unknown.success).toBe(false);
+    expect(unknown.error).toContain('Workspace not found');
+    expect(mockSendAuditQuery).not.toHaveBeenCalled();
+  });
+
+  it('surfaces a failed query from the GUI', async () => {
+    mockGetWorkspace.mockResolvedValueOnce({ workspace_id: 'ws-audit', path: '/repo/ws-audit' });
+    mockSendAuditQuery.mockResolvedValueOnce({
+      type: 'audit_query_response',
+      id: 'audit-2',
+      success: false,
+      records: [],
+      error: 'failed to open audit log',
+    });
+
+    const result = await memoryTerminal({ action: 'audit_query', workspace_id: 'ws-audit' });
+
+    expect(result.success).toBe(false);
+    expect(result.error).toBe('failed to open audit log');
+  });
+});
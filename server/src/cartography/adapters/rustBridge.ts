@@ -1,4 +1,4 @@
-import { spawn } from 'node:child_process';
+import { spawn, type ChildProcessWithoutNullStreams } from 'node:child_process';
 import { existsSync } from 'node:fs';
 import path from 'node:path';
 import { fileURLToPath } from 'node:url';
@@ -26,7 +26,40 @@ export interface RustScanRequest {
   max_files?: number;
   max_seconds?: number;
   include_extensions?: string[];
+  /** Names excluded at any depth; same syntax as `exclude_globs`. */
   exclude_paths?: string[];
+  /** Gitignore-style patterns relative to `root`; only matching files are scanned. */
+  include_globs?: string[];
+  /** Gitignore-style patterns relative to `root`, excluded regardless of ignore files. */
+  exclude_globs?: string[];
+  /** Skip larger files (default 1 MiB, 0 for no limit). */
+  max_file_bytes?: number;
+  /** Honour .gitignore, .ignore and .cartographerignore (default true). */
+  use_ignore_files?: boolean;
+  /** Reuse and update the on-disk scan cache (default true). */
+  cache?: boolean;
+  cache_path?: string;
+}
+
+/** Reverse-dependency query: scans `root`, then reports on `target`. */
+export interface RustDepsRequest extends Omit<RustScanRequest, 'action'> {
+  action: 'deps';
+  /** Absolute, or relative to `root`. */
+  target: string;
+  transitive?: boolean;
+}
+
+export interface RustImportRecord {
+  module: string;
+  kind: 'use' | 'mod' | 'import' | 'require' | 'export_from' | 'from' | 'using' | 'dot_source';
+  line: number;
+  names?: string[];
+  resolved?: string[];
+}
+
+export interface RustDependencyGraph {
+  edges: Array<{ from: string; to: string }>;
+  cycles: string[][];
 }
 
 export interface RustScanResponse {
@@ -51,22 +84,56 @@ export interface RustScanResponse {
         docstring?: string;
         body_fragment?: string;
       }>;
+      imports: RustImportRecord[];
     }>;
+    dependency_graph: RustDependencyGraph;
+    /** Present when the scan ran against the cache. */
+    changes?: {
+      baseline: boolean;
+      added: string[];
+      changed: string[];
+      removed: string[];
+      reused: number;
+    };
     diagnostics: {
       elapsed_seconds: number;
       file_count: number;
       symbol_count: number;
       budget_hit?: boolean;
       errors: string[];
+      skipped_ignored?: number;
+      skipped_too_large?: number;
+      skipped_binary?: number;
     };
   };
   error?: string;
 }
 
+export interface RustDepsResponse {
+  ok: boolean;
+  result?: {
+    root: string;
+    target: string;
+    dependencies: string[];
+    dependents: string[];
+    transitive_dependents?: string[];
+    cycles: string[][];
+  };
+  error?: string;
+}
+
 const TIMEOUT_MS = 35_000;
 
 export class RustBridge {
   async invoke(cmd: RustScanRequest): Promise<RustScanResponse> {
+    return this.run<RustScanResponse>(cmd);
+  }
+
+  async deps(cmd: RustDepsRequest): Promise<RustDepsResponse> {
+    return this.run<RustDepsResponse>(cmd);
+  }
+
+  private async run<T>(cmd: RustScanRequest | RustDepsRequest): Promise<T> {
     const binaryPath = getRustBinaryPath();
     if (!existsSync(binaryPath)) {
       throw new Error(`cartographer-core binary not found at ${binaryPath}`);
@@ -74,7 +141,7 @@ export class RustBridge {
 
     const inputLine = JSON.stringify(cmd) + '\n';
 
-    return new Promise<RustScanResponse>((resolve, reject) => {
+    return new Promise<T>((resolve, reject) => {
       let settled = false;
       let stdoutBuffer = '';
       let stderrBuffer = '';
@@ -121,7 +188,7 @@ export class RustBridge {
             return;
           }
           try {
-            resolve(JSON.parse(line) as RustScanResponse);
+            resolve(JSON.parse(line) as T);
           } catch {
             reject(new Error(`cartographer-core returned invalid JSON: ${line.slice(0, 200)}`));
           }
@@ -133,3 +200,116 @@ export class RustBridge {
     });
   }
 }
+
+// ---------------------------------------------------------------------------
+// `cartographer-core --serve`: one long-lived process, NDJSON requests with ids
+// ---------------------------------------------------------------------------
+
+type RustScanOptions = Omit<RustScanRequest, 'action' | 'root' | 'scan_mode'> & { scan_mode?: string };
+
+export type RustDaemonRequest =
+  | ({ action: 'scan'; root: string } & RustScanOptions)
+  | ({ action: 'symbols_in_file'; root: string; path: string } & RustScanOptions)
+  | ({ action: 'find_symbol'; root: string; name: string; kind?: string; exact?: boolean; limit?: number } & RustScanOptions)
+  | ({ action: 'deps'; root: string; target: string; transitive?: boolean } & RustScanOptions)
+  | { action: 'invalidate'; root: string; scan_mode?: string; paths?: string[] };
+
+export type RustFileResult = NonNullable<RustScanResponse['result']>['files'][number];
+
+export interface RustSymbolMatch {
+  path: string;
+  symbol: RustFileResult['symbols'][number];
+}
+
+export interface RustDaemonResponse {
+  id: number | string | null;
+  ok: boolean;
+  result?: unknown;
+  error?: string;
+}
+
+/**
+ * Client for a `cartographer-core --serve` process.  The daemon keeps parsed
+ * workspaces in memory, so repeated queries skip re-scanning.  Responses may
+ * arrive out of order and are matched to requests by id.
+ */
+export class RustDaemon {
+  private child: ChildProcessWithoutNullStreams | null = null;
+  private nextId = 1;
+  private stdoutBuffer = '';
+  private readonly pending = new Map<number, { resolve: (value: unknown) => void; reject: (err: Error) => void }>();
+
+  request<T = unknown>(req: RustDaemonRequest): { id: number; result: Promise<T> } {
+    const child = this.ensureStarted();
+    const id = this.nextId++;
+    const result = new Promise<T>((resolve, reject) => {
+      this.pending.set(id, { resolve: resolve as (value: unknown) => void, reject });
+    });
+    child.stdin.write(JSON.stringify({ ...req, id }) + '\n', 'utf8');
+    return { id, result };
+  }
+
+  /** Ask the daemon to stop request `id`; it then rejects with "Cancelled". */
+  cancel(id: number): void {
+    this.child?.stdin.write(JSON.stringify({ id: null, action: 'cancel', cancel_id: id }) + '\n', 'utf8');
+  }
+
+  /** Close stdin; the daemon finishes in-flight requests, then exits. */
+  close(): void {
+    this.child?.stdin.end();
+    this.child = null;
+  }
+
+  private ensureStarted(): ChildProcessWithoutNullStreams {
+    if (this.child) return this.child;
+    const binaryPath = getRustBinaryPath();
+    if (!existsSync(binaryPath)) {
+      throw new Error(`cartographer-core binary not found at ${binaryPath}`);
+    }
+    const child = spawn(binaryPath, ['--serve'], {
+      stdio: ['pipe', 'pipe', 'pipe'],
+      windowsHide: true,
+    });
+    child.stdout.on('data', (chunk: Buffer) => this.onStdout(chunk.toString()));
+    child.stdin.on('error', () => { /* surfaced through 'exit' */ });
+    child.once('error', (err) => this.failAll(err));
+    child.once('exit', (code) => {
+      // After close() a replacement process may already own `pending`.
+      if (this.child !== child && this.child !== null) return;
+      this.child = null;
+      this.failAll(new Error(`cartographer-core --serve exited (code ${code})`));
+    });
+    this.child = child;
+    return child;
+  }
+
+  private onStdout(text: string): void {
+    this.stdoutBuffer += text;
+    let newline: number;
+    while ((newline = this.stdoutBuffer.indexOf('\n')) >= 0) {
+      const line = this.stdoutBuffer.slice(0, newline).trim();
+      this.stdoutBuffer = this.stdoutBuffer.slice(newline + 1);
+      if (!line) continue;
+      let response: RustDaemonResponse;
+      try {
+        response = JSON.parse(line) as RustDaemonResponse;
+      } catch {
+        continue;
+      }
+      if (typeof response.id !== 'number') continue;
+      const waiter = this.pending.get(response.id);
+      if (!waiter) continue;
+      this.pending.delete(response.id);
+      if (response.ok) {
+        waiter.resolve(response.result);
+      } else {
+        waiter.reject(new Error(response.error ?? 'cartographer-core request failed'));
+      }
+    }
+  }
+
+  private failAll(err: Error): void {
+    for (const waiter of this.pending.values()) waiter.reject(err);
+    this.pending.clear();
+  }
+}
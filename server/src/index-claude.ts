@@ -403,9 +403,9 @@ server.tool(
 
 server.tool(
   'memory_terminal',
-  'Terminal tool with GUI approval flow. Actions: run (execute command), spawn_cli_session (launch a provider CLI session — use provider:"claude" to fork a new Claude Code conversation with optional --resume support), read_output (get output from a session), kill (terminate a session), list_forks (list Claude conversation forks for a workspace), get_allowlist, update_allowlist.',
+  'Terminal tool with GUI approval flow. Actions: run (execute command), spawn_cli_session (launch a provider CLI session — use provider:"claude" to fork a new Claude Code conversation with optional --resume support), read_output (get output from a session), kill (terminate a session), list_forks (list Claude conversation forks for a workspace), get_allowlist, update_allowlist, audit_query (read/verify the workspace audit log).',
   {
-    action: z.enum(['run', 'spawn_cli_session', 'read_output', 'kill', 'get_allowlist', 'update_allowlist', 'list_forks']).describe('The action to perform'),
+    action: z.enum(['run', 'spawn_cli_session', 'read_output', 'kill', 'get_allowlist', 'update_allowlist', 'list_forks', 'audit_query']).describe('The action to perform'),
     command: z.string().optional().describe('Command to execute (for run)'),
     args: z.array(z.string()).optional().describe('Command arguments (for run)'),
     cwd: z.string().optional().describe('Working directory'),
@@ -415,6 +415,12 @@ server.tool(
     session_target: z.enum(['selected', 'default', 'specific']).optional(),
     patterns: z.array(z.string()).optional().describe('Allowlist patterns'),
     operation: z.enum(['add', 'remove', 'set']).optional(),
+    agent: z.string().optional().describe('Requesting agent (for audit_query)'),
+    since: z.string().optional(),
+    until: z.string().optional(),
+    status: z.array(z.enum(['launch_requested', 'launch_approved', 'launch_denied', 'launch_cancelled', 'launch_started', 'session_completed', 'session_exited', 'command_approved', 'command_auto_approved', 'command_declined', 'command_completed', 'command_failed', 'command_killed', 'command_timed_out'])).optional(),
+    limit: z.number().optional(),
+    verify: z.boolean().optional(),
     env: z.record(z.string()).optional().describe('Per-request environment variables'),
     provider: z.string().optional().describe('Provider to launch (for spawn_cli_session): "claude", "gemini", "copilot"'),
     prompt: z.string().optional().describe('Startup prompt for spawn_cli_session'),
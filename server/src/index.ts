@@ -654,9 +654,9 @@ server.tool(
 
 server.tool(
   'memory_terminal',
-  'Terminal tool with GUI approval flow. Actions: run (execute command — auto-approves allowlisted, blocks destructive, shows GUI approval for others), spawn_cli_session (validated provider session spawn with provider/cwd/prompt/context payload), read_output (get output from a session), kill (terminate a session), get_allowlist (view allowlist), update_allowlist (manage allowlist patterns). SEQUENTIAL RULE: You MUST wait for each run response before calling run again. Concurrent run calls targeting the same session are automatically rerouted to a new terminal tab and will include a rate_limit_note in the response data.',
+  'Terminal tool with GUI approval flow. Actions: run (execute command — auto-approves allowlisted, blocks destructive, shows GUI approval for others), spawn_cli_session (validated provider session spawn with provider/cwd/prompt/context payload), read_output (get output from a session), kill (terminate a session), get_allowlist (view allowlist), update_allowlist (manage allowlist patterns), audit_query (read and optionally verify the workspace's approval/execution audit log). SEQUENTIAL RULE: You MUST wait for each run response before calling run again. Concurrent run calls targeting the same session are automatically rerouted to a new terminal tab and will include a rate_limit_note in the response data.',
   {
-    action: z.enum(['run', 'spawn_cli_session', 'read_output', 'kill', 'get_allowlist', 'update_allowlist']).describe('The action to perform'),
+    action: z.enum(['run', 'spawn_cli_session', 'read_output', 'kill', 'get_allowlist', 'update_allowlist', 'audit_query']).describe('The action to perform'),
     command: z.string().optional().describe('Command to execute (for run)'),
     args: z.array(z.string()).optional().describe('Command arguments (for run)'),
     cwd: z.string().optional().describe('Working directory (for run)'),
@@ -666,6 +666,12 @@ server.tool(
     session_target: z.enum(['selected', 'default', 'specific']).optional().describe('Run-session routing behavior: selected (default), default, or specific (requires session_id).'),
     patterns: z.array(z.string()).optional().describe('Allowlist patterns (for update_allowlist)'),
     operation: z.enum(['add', 'remove', 'set']).optional().describe('How to modify the allowlist (for update_allowlist)'),
+    agent: z.string().optional().describe('Requesting agent, case-insensitive (for audit_query)'),
+    since: z.string().optional().describe('Inclusive lower bound YYYY-MM-DDTHH:MM:SSZ (for audit_query)'),
+    until: z.string().optional().describe('Exclusive upper bound YYYY-MM-DDTHH:MM:SSZ (for audit_query)'),
+    status: z.array(z.enum(['launch_requested', 'launch_approved', 'launch_denied', 'launch_cancelled', 'launch_started', 'session_completed', 'session_exited', 'command_approved', 'command_auto_approved', 'command_declined', 'command_completed', 'command_failed', 'command_killed', 'command_timed_out'])).optional().describe('Event kinds to include (for audit_query)'),
+    limit: z.number().optional().describe('Keep only the most recent N records (for audit_query)'),
+    verify: z.boolean().optional().describe('Also check the audit hash chain (for audit_query)'),
     env: z.record(z.string()).optional().describe('Per-request environment variables injected into the spawned process (for run). Supports Gemini/Google API key alias auto-expansion.'),
     provider: z.string().optional().describe('Provider to launch for spawn_cli_session (gemini or copilot)'),
     prompt: z.string().optional().describe('Startup prompt for spawn_cli_session'),
@@ -22,8 +22,8 @@
  * 6. memory_filesystem - Workspace-scoped filesystem operations (11 actions)
  *    Actions: read, write, search, discover_codebase, list, tree, delete, move, copy, append, exists
  * 
- * 7. memory_terminal - Terminal tool with GUI approval flow (5 actions)
- *    Actions: run, read_output, kill, get_allowlist, update_allowlist
+ * 7. memory_terminal - Terminal tool with GUI approval flow (6 actions)
+ *    Actions: run, read_output, kill, get_allowlist, update_allowlist, audit_query
  * 
  * 8. memory_session - Agent session management & spawn preparation (3 actions)
  *    Actions: prep, list_sessions, get_session
@@ -26,9 +26,9 @@ import {
   getEffectiveAllowlist,
   ensureAllowlistLoaded,
 } from '../terminal-auth.js';
-import type { CommandRequest, CommandResponse } from '../terminal-ipc-protocol.js';
+import type { AuditEventKind, CommandRequest, CommandResponse } from '../terminal-ipc-protocol.js';
 import { TcpTerminalAdapter } from '../terminal-tcp-adapter.js';
-import { getAllWorkspaces } from '../../storage/db-store.js';
+import { getAllWorkspaces, getWorkspace } from '../../storage/db-store.js';
 
 // =========================================================================
 // GUI Session Tracking
@@ -73,7 +73,8 @@ export type MemoryTerminalAction =
   | 'read_output'
   | 'kill'
   | 'get_allowlist'
-  | 'update_allowlist';
+  | 'update_allowlist'
+  | 'audit_query';
 
 export interface SpawnCliSessionContext {
   requesting_agent?: string;
@@ -148,6 +149,18 @@ export interface MemoryTerminalParams {
   patterns?: string[];
   /** How to modify the allowlist (for update_allowlist). */
   operation?: 'add' | 'remove' | 'set';
+  /** Requesting agent, case-insensitive (for audit_query). */
+  agent?: string;
+  /** Inclusive lower bound, `YYYY-MM-DDTHH:MM:SSZ` (for audit_query). */
+  since?: string;
+  /** Exclusive upper bound, `YYYY-MM-DDTHH:MM:SSZ` (for audit_query). */
+  until?: string;
+  /** Event kinds to include; all when omitted (for audit_query). */
+  status?: AuditEventKind[];
+  /** Keep only the most recent `limit` records (for audit_query). */
+  limit?: number;
+  /** Also check the audit log's hash chain (for audit_query). */
+  verify?: boolean;
   /**
    * Per-request environment variables injected into the spawned process
    * (for run). Supports Gemini/Google alias auto-expansion on the
@@ -1188,6 +1201,64 @@ async function handleKillViaTcp(sessionId: string): Promise<ToolResponse> {
   }
 }
 
+/**
+ * Read (and optionally verify) a workspace's audit log over TCP.
+ * The GUI owns the log and its key, so the query always goes through it.
+ */
+async function handleAuditQueryAction(
+  params: MemoryTerminalParams,
+): Promise<ToolResponse> {
+  if (!params.workspace_id) {
+    return {
+      success: false,
+      error: 'workspace_id is required for action: audit_query',
+    };
+  }
+  const workspace = await getWorkspace(params.workspace_id);
+  if (!workspace?.path) {
+    return {
+      success: false,
+      error: `Workspace not found: ${params.workspace_id}`,
+    };
+  }
+
+  const adapter = new TcpTerminalAdapter();
+  try {
+    await adapter.connect();
+    const response = await adapter.sendAuditQuery({
+      workspace_path: workspace.path,
+      session_id: params.session_id,
+      agent: params.agent,
+      since: params.since,
+      until: params.until,
+      status: params.status,
+      limit: params.limit,
+      verify: params.verify,
+    });
+    adapter.close();
+
+    if (!response.success) {
+      return {
+        success: false,
+        error: response.error ?? 'Audit query failed',
+      };
+    }
+    return {
+      success: true,
+      data: {
+        records: response.records,
+        verification: response.verification ?? null,
+      },
+    };
+  } catch (err) {
+    adapter.close();
+    return {
+      success: false,
+      error: `audit_query via GUI failed: ${(err as Error).message}`,
+    };
+  }
+}
+
 async function handleGetAllowlistAction(
   params: MemoryTerminalParams,
 ): Promise<ToolResponse> {
@@ -1244,10 +1315,13 @@ export async function memoryTerminal(
     case 'update_allowlist':
       result = await handleUpdateAllowlistAction(params);
       break;
+    case 'audit_query':
+      result = await handleAuditQueryAction(params);
+      break;
     default:
       return {
         success: false,
-        error: `Unknown action: "${(params as { action: string }).action}". Valid actions: run, spawn_cli_session, read_output, kill, get_allowlist, update_allowlist`,
+        error: `Unknown action: "${(params as { action: string }).action}". Valid actions: run, spawn_cli_session, read_output, kill, get_allowlist, update_allowlist, audit_query`,
       };
   }
 
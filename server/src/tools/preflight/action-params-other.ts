@@ -199,6 +199,21 @@ export const TERMINAL_PARAMS: Record<string, ActionParamDef> = {
       { name: 'workspace_id', type: 'string', description: 'Workspace ID' },
     ],
   },
+
+  audit_query: {
+    required: [
+      { name: 'workspace_id', type: 'string', description: 'Workspace whose audit log is read' },
+    ],
+    optional: [
+      { name: 'session_id', type: 'string', description: 'Context-pack or terminal session' },
+      { name: 'agent', type: 'string', description: 'Requesting agent' },
+      { name: 'since', type: 'string', description: 'Inclusive lower bound (YYYY-MM-DDTHH:MM:SSZ)' },
+      { name: 'until', type: 'string', description: 'Exclusive upper bound (YYYY-MM-DDTHH:MM:SSZ)' },
+      { name: 'status', type: 'string[]', description: 'Event kinds to include' },
+      { name: 'limit', type: 'number', description: 'Most recent N records' },
+      { name: 'verify', type: 'boolean', description: 'Also check the hash chain' },
+    ],
+  },
 };
 
 // =============================================================================
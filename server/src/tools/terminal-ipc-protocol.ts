@@ -13,6 +13,8 @@
  *   - ReadOutputResponse    — GUI → MCP server: session output data
  *   - KillSessionRequest    — MCP server → GUI: kill a session/process
  *   - KillSessionResponse   — GUI → MCP server: kill result
+ *   - AuditQueryRequest     — MCP server → GUI: query/verify the audit log
+ *   - AuditQueryResponse    — GUI → MCP server: matching audit records
  *
  * Created in Phase 1 (Protocol Alignment) — replaces interactive-terminal-protocol.ts.
  */
@@ -132,6 +134,95 @@ export interface KillSessionResponse {
   error?: string;
 }
 
+/** Audited event kinds (Rust `terminal_audit::AuditEvent`). */
+export type AuditEventKind =
+  | 'launch_requested'
+  | 'launch_approved'
+  | 'launch_denied'
+  | 'launch_cancelled'
+  | 'launch_started'
+  | 'session_completed'
+  | 'session_exited'
+  | 'command_approved'
+  | 'command_auto_approved'
+  | 'command_declined'
+  | 'command_completed'
+  | 'command_failed'
+  | 'command_killed'
+  | 'command_timed_out';
+
+/** MCP server → GUI: read (and optionally verify) a workspace's audit log. */
+export interface AuditQueryRequest {
+  type: 'audit_query_request';
+  /** Unique request ID (correlates with AuditQueryResponse.id). */
+  id: string;
+  /** Workspace root whose `logs/agent_launch_audit.jsonl` is read. */
+  workspace_path: string;
+  /** Only entries for this Project Memory workspace ID. */
+  workspace_id?: string;
+  /** Matches the context-pack session or the terminal session. */
+  session_id?: string;
+  /** Requesting agent, case-insensitive. */
+  agent?: string;
+  /** Inclusive lower bound, `YYYY-MM-DDTHH:MM:SSZ`. */
+  since?: string;
+  /** Exclusive upper bound, `YYYY-MM-DDTHH:MM:SSZ`. */
+  until?: string;
+  /** Event kinds to include; all when omitted. */
+  status?: AuditEventKind[];
+  /** Keep only the most recent `limit` matches. */
+  limit?: number;
+  /** Also walk the hash chain and report whether it is intact. */
+  verify?: boolean;
+}
+
+/** One line of the audit log. Optional fields are omitted when unset. */
+export interface AuditRecord {
+  seq: number;
+  prev_hash: string;
+  hash: string;
+  timestamp: string;
+  event: AuditEventKind;
+  provider?: string;
+  autonomy_mode?: string;
+  requesting_agent?: string;
+  plan_id?: string;
+  session_id?: string;
+  context_pack_summary?: {
+    has_step_notes: boolean;
+    file_count: number;
+    has_custom_instructions: boolean;
+  };
+  terminal_session_id?: string;
+  risk_tier?: string;
+  reason?: string;
+  exit_code?: number;
+  request_id?: string;
+  command?: string;
+  workspace_id?: string;
+  policy_rule?: string;
+}
+
+/** Outcome of walking the audit log's hash chain. */
+export interface AuditVerification {
+  intact: boolean;
+  summary?: { records: number; legacy_lines: number; head_hash: string };
+  /** First broken line (1-based) and what is wrong with it. */
+  error?: { line: number; message: string };
+}
+
+/** GUI → MCP server: matching audit records, oldest first. */
+export interface AuditQueryResponse {
+  type: 'audit_query_response';
+  /** Correlates to the original AuditQueryRequest.id. */
+  id: string;
+  success: boolean;
+  records: AuditRecord[];
+  /** Present when the request set `verify`. */
+  verification?: AuditVerification;
+  error?: string;
+}
+
 /** GUI → MCP server: streaming output chunk for an active command. */
 export interface OutputChunk {
   type: 'output_chunk';
@@ -216,6 +307,8 @@ export type TerminalIpcMessage =
   | ReadOutputResponse
   | KillSessionRequest
   | KillSessionResponse
+  | AuditQueryRequest
+  | AuditQueryResponse
   | OutputChunk
   | StartAgentSessionRequest
   | StartAgentSessionResponse;
@@ -279,6 +372,11 @@ export function isKillSessionResponse(msg: TerminalIpcMessage): msg is KillSessi
   return msg.type === 'kill_session_response';
 }
 
+/** Returns true if `msg` is an AuditQueryResponse. */
+export function isAuditQueryResponse(msg: TerminalIpcMessage): msg is AuditQueryResponse {
+  return msg.type === 'audit_query_response';
+}
+
 /** Returns true if `msg` is an OutputChunk. */
 export function isOutputChunk(msg: TerminalIpcMessage): msg is OutputChunk {
   return msg.type === 'output_chunk';
@@ -313,6 +411,8 @@ const KNOWN_TYPES = new Set([
   'read_output_response',
   'kill_session_request',
   'kill_session_response',
+  'audit_query_request',
+  'audit_query_response',
   'output_chunk',
   'start_agent_session_request',
   'start_agent_session_response',
@@ -20,6 +20,8 @@ import {
   type ReadOutputResponse,
   type KillSessionRequest,
   type KillSessionResponse,
+  type AuditQueryRequest,
+  type AuditQueryResponse,
   type OutputChunk,
   type TerminalIpcMessage,
   type WorkspaceEntry,
@@ -31,6 +33,7 @@ import {
   isOutputChunk,
   isReadOutputResponse,
   isKillSessionResponse,
+  isAuditQueryResponse,
   isStartAgentSessionResponse,
   type StartAgentSessionRequest,
   type StartAgentSessionResponse,
@@ -515,6 +518,31 @@ export class TcpTerminalAdapter {
     );
   }
 
+  // -----------------------------------------------------------------------
+  // Audit Query (AuditQueryRequest → AuditQueryResponse)
+  // -----------------------------------------------------------------------
+
+  /**
+   * Send an AuditQueryRequest and wait for the matching AuditQueryResponse.
+   * Throws on timeout, disconnect, or socket error.
+   */
+  async sendAuditQuery(
+    query: Omit<AuditQueryRequest, 'type' | 'id'>,
+  ): Promise<AuditQueryResponse> {
+    const requestId = `audit_${Date.now()}_${Math.random().toString(36).slice(2, 8)}`;
+    const request: AuditQueryRequest = {
+      ...query,
+      type: 'audit_query_request',
+      id: requestId,
+    };
+    return this.sendAndAwaitTyped<AuditQueryRequest, AuditQueryResponse>(
+      request,
+      requestId,
+      isAuditQueryResponse,
+      (msg) => msg.id,
+    );
+  }
+
   // -----------------------------------------------------------------------
   // Start Agent Session (StartAgentSessionRequest → StartAgentSessionResponse)
   // -----------------------------------------------------------------------
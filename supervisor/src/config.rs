@@ -7,6 +7,8 @@ use std::io;
 use std::path::{Path, PathBuf};
 
 use crate::control::protocol::BackendKind;
+use crate::events::journal::EventJournalConfig;
+use crate::service_logs::ServiceLogConfig;
 
 // ---------------------------------------------------------------------------
 // Control-transport selector
@@ -177,6 +179,11 @@ pub struct ReconnectSection {
     pub cooldown_dependency_group_ms: u64,
     /// Extra cooldown delay for global failures.
     pub cooldown_global_ms: u64,
+    /// Crash-triggered restarts within `crash_loop_window_secs` that open a
+    /// service's circuit and pause automatic restarts (0 = disabled).
+    pub crash_loop_max_restarts: u32,
+    /// Sliding window for `crash_loop_max_restarts`, in seconds.
+    pub crash_loop_window_secs: u64,
 }
 
 impl Default for ReconnectSection {
@@ -191,6 +198,8 @@ impl Default for ReconnectSection {
             cooldown_child_local_ms: 0,
             cooldown_dependency_group_ms: 0,
             cooldown_global_ms: 0,
+            crash_loop_max_restarts: 5,
+            crash_loop_window_secs: 300,
         }
     }
 }
@@ -295,8 +304,9 @@ impl Default for ContainerRunnerConfig {
 /// Configuration for the MCP instance pool (`[mcp.pool]` subsection).
 ///
 /// When `max_connections_per_instance` is reached on all running instances the
-/// supervisor spawns a new instance (up to `max_instances`) on the next
-/// available port starting at `base_port`.
+/// supervisor spawns a new instance (up to `max_instances`) on the lowest free
+/// port in `base_port..base_port + port_span`.  Instances that sit idle for
+/// `scale_down_idle_secs` are drained and retired, down to `min_instances`.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct PoolConfig {
@@ -309,6 +319,16 @@ pub struct PoolConfig {
     /// First port assigned to pool worker instances (default: 3460).
     /// The supervisor proxy occupies the primary MCP port (e.g. 3457).
     pub base_port: u16,
+    /// Number of consecutive ports from `base_port` the pool may allocate
+    /// instance ports from (default: 20).  Ports held by other processes are
+    /// skipped; the effective span is never smaller than `max_instances`.
+    pub port_span: u16,
+    /// Seconds an instance must have zero connections before it is selected
+    /// for scale-down (default: 300).  `0` disables idle reaping.
+    pub scale_down_idle_secs: u64,
+    /// Maximum seconds to wait for a draining instance's sessions to close
+    /// before it is stopped anyway (default: 60).
+    pub drain_timeout_secs: u64,
 }
 
 impl Default for PoolConfig {
@@ -318,6 +338,9 @@ impl Default for PoolConfig {
             max_instances: 4,
             max_connections_per_instance: 5,
             base_port: 3460,
+            port_span: 20,
+            scale_down_idle_secs: 300,
+            drain_timeout_secs: 60,
         }
     }
 }
@@ -879,6 +902,8 @@ pub struct EventsSection {
     pub heartbeat_interval: u64,
     /// Ring-buffer size for `Last-Event-Id` replay on reconnect.
     pub replay_buffer_size: usize,
+    /// Optional on-disk event journal (`[events.journal]`).
+    pub journal: EventJournalConfig,
 }
 
 impl Default for EventsSection {
@@ -888,6 +913,7 @@ impl Default for EventsSection {
             buffer_size: 256,
             heartbeat_interval: 30,
             replay_buffer_size: 100,
+            journal: EventJournalConfig::default(),
         }
     }
 }
@@ -945,11 +971,16 @@ pub struct RuntimeOutputSection {
     /// When `false`, runtime output events are not buffered or broadcast.
     /// Subprocess stdout/stderr is still drained to avoid pipe backpressure.
     pub enabled: bool,
+    /// Per-service rotating log files (`[runtime_output.files]`).
+    pub files: ServiceLogConfig,
 }
 
 impl Default for RuntimeOutputSection {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            files: ServiceLogConfig::default(),
+        }
     }
 }
 
@@ -1050,6 +1081,47 @@ pub struct ServerDefinition {
     /// Restart policy for this server.
     #[serde(default)]
     pub restart_policy: RestartPolicy,
+    /// Services that must be running before this one is started.  Names may
+    /// refer to other `[[servers]]` entries or to built-in services
+    /// (`mcp`, `dashboard`, `interactive_terminal`, `fallback_api`, `cli_mcp`).
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Probe that decides when the server is ready (and, later, healthy).
+    /// Defaults to a TCP connect on `port` when a port is declared.
+    pub readiness: Option<ReadinessProbe>,
+    /// How long startup may take before the probe is considered failed.
+    /// Defaults to 30 000 ms.
+    pub startup_timeout_ms: Option<u64>,
+}
+
+/// Readiness probe for a `[[servers]]` entry (`[servers.readiness]`).
+///
+/// `port` falls back to the server's own `port` when omitted.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReadinessProbe {
+    /// Ready once a TCP connection to `127.0.0.1:port` succeeds.
+    Tcp {
+        #[serde(default)]
+        port: Option<u16>,
+    },
+    /// Ready once `GET http://127.0.0.1:port{path}` returns `expected_status`.
+    Http {
+        #[serde(default)]
+        port: Option<u16>,
+        #[serde(default = "default_probe_path")]
+        path: String,
+        #[serde(default = "default_probe_status")]
+        expected_status: u16,
+    },
+}
+
+fn default_probe_path() -> String {
+    "/".to_string()
+}
+
+fn default_probe_status() -> u16 {
+    200
 }
 
 // ---------------------------------------------------------------------------
@@ -1367,6 +1439,41 @@ restart_policy = "never_restart"
         assert!(matches!(cfg.servers[1].restart_policy, RestartPolicy::NeverRestart));
     }
 
+    #[test]
+    fn server_definition_dependencies_and_readiness_parse() {
+        let toml = r#"
+[[servers]]
+name = "helper"
+command = "node"
+port = 4100
+depends_on = ["mcp", "dashboard"]
+startup_timeout_ms = 5000
+
+[servers.readiness]
+kind = "http"
+path = "/ready"
+
+[[servers]]
+name = "tcp-helper"
+command = "helper"
+readiness = { kind = "tcp", port = 4200 }
+"#;
+        let cfg: SupervisorConfig = toml::from_str(toml).expect("parse");
+        let helper = &cfg.servers[0];
+        assert_eq!(helper.depends_on, vec!["mcp", "dashboard"]);
+        assert_eq!(helper.startup_timeout_ms, Some(5000));
+        assert_eq!(
+            helper.readiness,
+            Some(ReadinessProbe::Http {
+                port: None,
+                path: "/ready".to_string(),
+                expected_status: 200,
+            })
+        );
+        assert_eq!(cfg.servers[1].readiness, Some(ReadinessProbe::Tcp { port: Some(4200) }));
+        assert!(cfg.servers[1].depends_on.is_empty());
+    }
+
     #[test]
     fn server_definition_with_env_vars_parses_correctly() {
         let toml = r#"
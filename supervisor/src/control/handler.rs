@@ -12,7 +12,7 @@ use crate::config::FormAppConfig;
 use crate::control::mcp_admin;
 use crate::control::mcp_runtime::McpSubprocessRuntime;
 use crate::control::protocol::{ControlRequest, ControlResponse, FormAppResponse};
-use crate::control::registry::{Registry, ServiceStatus};
+use crate::control::registry::{McpSlotState, Registry, ServiceStatus};
 use crate::runner::form_app::{continue_form_app, launch_form_app};
 
 fn deprecated_pool_response(command: &str) -> ControlResponse {
@@ -42,6 +42,20 @@ fn form_app_control_response(resp: FormAppResponse) -> ControlResponse {
     }
 }
 
+/// `{base}/runtime/logs/{service}?follow=true&lines=0`, with the service name
+/// percent-encoded as a single path segment.
+fn follow_logs_url(base: &str, service: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(base).ok()?;
+    url.path_segments_mut()
+        .ok()?
+        .pop_if_empty()
+        .extend(["runtime", "logs", service]);
+    url.query_pairs_mut()
+        .append_pair("follow", "true")
+        .append_pair("lines", "0");
+    Some(url.into())
+}
+
 /// Resolved form-app configuration map, keyed by app name.
 ///
 /// Callers construct this from [`SupervisorConfig`] at startup and share it
@@ -302,6 +316,28 @@ pub async fn handle_request_with_runtime(
             }
         }
 
+        // ---------------------------------------------------------------
+        // TailLogs — last N lines of a service's on-disk log
+        // ---------------------------------------------------------------
+        ControlRequest::TailLogs { service, lines, follow } => {
+            let lines = lines.unwrap_or(crate::service_logs::DEFAULT_TAIL_LINES);
+            match crate::service_logs::tail(&service, lines).await {
+                Ok(items) => {
+                    let follow_url = follow
+                        .then(crate::gui_server::local_base_url)
+                        .flatten()
+                        .and_then(|base| follow_logs_url(base, &service));
+                    ControlResponse::ok(json!({
+                        "service": service,
+                        "count": items.len(),
+                        "items": items,
+                        "follow_url": follow_url,
+                    }))
+                }
+                Err(e) => ControlResponse::err(e),
+            }
+        }
+
         // ---------------------------------------------------------------
         // HealthSnapshot — aggregate + per-child status for minimal GUI
         // ---------------------------------------------------------------
@@ -322,6 +358,25 @@ pub async fn handle_request_with_runtime(
             ControlResponse::ok(json!({ "health_window_visible": visible }))
         }
 
+        // ---------------------------------------------------------------
+        // ResetCircuit — re-arm a service whose crash-loop circuit opened
+        // ---------------------------------------------------------------
+        ControlRequest::ResetCircuit { service } => {
+            let name = crate::runner::dependency::canonical_builtin(&service)
+                .map(str::to_string)
+                .unwrap_or_else(|| service.trim().to_string());
+            let reset = registry.lock().await.reset_circuit(&name);
+            let restart_requested = match (&restart_tx, reset) {
+                (Some(tx), true) => tx.send(format!("reset:{name}")).await.is_ok(),
+                _ => false,
+            };
+            ControlResponse::ok(json!({
+                "service": name,
+                "reset": reset,
+                "restart_requested": restart_requested,
+            }))
+        }
+
         // ---------------------------------------------------------------
         // ShutdownSupervisor — signal graceful shutdown in main runtime
         // ---------------------------------------------------------------
@@ -412,7 +467,10 @@ pub async fn handle_request_with_runtime(
         }
 
         // ---------------------------------------------------------------
-        // ListMcpInstances — list instance ports known to the pool
+        // ListMcpInstances — list pool port slots with their lifecycle
+        // state (reserved / starting / healthy / draining).  Ports that
+        // carry connections but are not (yet) in the slot table are
+        // reported with a `null` state.
         // ---------------------------------------------------------------
         ControlRequest::ListMcpInstances => {
             if mcp_runtime.is_some() {
@@ -420,14 +478,19 @@ pub async fn handle_request_with_runtime(
             }
 
             let reg = registry.lock().await;
-            let conns = reg.list_mcp_connections();
-            let mut by_port: std::collections::HashMap<u16, usize> = std::collections::HashMap::new();
-            for c in &conns {
-                *by_port.entry(c.instance_port).or_insert(0) += 1;
+            let mut by_port: std::collections::BTreeMap<u16, (Option<McpSlotState>, usize)> =
+                std::collections::BTreeMap::new();
+            for slot in reg.list_mcp_slots() {
+                by_port.insert(slot.port, (Some(slot.state), 0));
+            }
+            for c in &reg.list_mcp_connections() {
+                by_port.entry(c.instance_port).or_insert((None, 0)).1 += 1;
             }
             let instances: Vec<serde_json::Value> = by_port
                 .into_iter()
-                .map(|(port, count)| json!({ "port": port, "connection_count": count }))
+                .map(|(port, (state, count))| {
+                    json!({ "port": port, "state": state, "connection_count": count })
+                })
                 .collect();
             ControlResponse::ok(serde_json::Value::Array(instances))
         }
@@ -443,6 +506,38 @@ pub async fn handle_request_with_runtime(
             ControlResponse::ok(json!({ "scale_up": "requested" }))
         }
 
+        // ---------------------------------------------------------------
+        // ScaleDownMcp — manual drain + retire of one pool instance.
+        // Dispatched to the main loop, which owns the pool handle.
+        // ---------------------------------------------------------------
+        ControlRequest::ScaleDownMcp { port } => {
+            if mcp_runtime.is_some() {
+                return deprecated_pool_response("ScaleDownMcp");
+            }
+
+            if let Some(ref tx) = restart_tx {
+                let target = port.map(|p| p.to_string()).unwrap_or_else(|| "auto".to_string());
+                match tx.send(format!("scale_down_mcp:{target}")).await {
+                    Ok(()) => ControlResponse::ok(json!({ "scale_down": "requested", "port": port })),
+                    Err(e) => ControlResponse::err(format!("scale_down dispatch failed: {e}")),
+                }
+            } else {
+                ControlResponse::ok(json!({ "scale_down": "requested", "port": port }))
+            }
+        }
+
+        // ---------------------------------------------------------------
+        // ListMcpAffinity — session → owning instance table kept by the
+        // MCP reverse proxy.
+        // ---------------------------------------------------------------
+        ControlRequest::ListMcpAffinity => {
+            if mcp_runtime.is_some() {
+                return deprecated_pool_response("ListMcpAffinity");
+            }
+
+            ControlResponse::ok(json!(crate::proxy::session_affinity().snapshot()))
+        }
+
         ControlRequest::SetMcpRuntimePolicy {
             enabled,
             wave_cohorts,
@@ -604,9 +699,9 @@ pub async fn handle_request_with_runtime(
         })),
 
         ControlRequest::EventStats => {
-            let (enabled, subscriber_count, events_emitted) = match &events_handle {
-                Some(h) => (true, h.subscriber_count(), h.events_emitted()),
-                None => (false, 0, 0),
+            let (enabled, subscriber_count, events_emitted, subscriptions) = match &events_handle {
+                Some(h) => (true, h.subscriber_count(), h.events_emitted(), h.subscriptions()),
+                None => (false, 0, 0, Vec::new()),
             };
             let runtime_telemetry = mcp_runtime
                 .as_ref()
@@ -617,6 +712,7 @@ pub async fn handle_request_with_runtime(
                 "subscriber_count": subscriber_count,
                 "events_emitted": events_emitted,
                 "events_url": events_url,
+                "subscriptions": subscriptions,
                 "runtime_telemetry": runtime_telemetry,
             }))
         }
@@ -877,6 +973,170 @@ mod tests {
         assert_eq!(dispatched, "start:interactive_terminal");
     }
 
+    #[tokio::test]
+    async fn list_mcp_instances_reports_slot_states() {
+        use crate::control::registry::McpInstanceSlot;
+
+        let reg = make_registry();
+        reg.lock().await.sync_mcp_slots(vec![
+            McpInstanceSlot { port: 3460, state: McpSlotState::Healthy, connection_count: 0 },
+            McpInstanceSlot { port: 3462, state: McpSlotState::Reserved, connection_count: 0 },
+        ]);
+
+        let resp = handle_request(
+            ControlRequest::ListMcpInstances,
+            Arc::clone(&reg),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(resp.ok);
+        let rows = resp.data.as_array().expect("array");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["port"], 3460);
+        assert_eq!(rows[0]["state"], "healthy");
+        assert_eq!(rows[1]["state"], "reserved");
+    }
+
+    #[tokio::test]
+    async fn scale_down_mcp_dispatches_to_main_loop() {
+        let reg = make_registry();
+        let (restart_tx, mut restart_rx) = tokio::sync::mpsc::channel::<String>(4);
+
+        let resp = handle_request(
+            ControlRequest::ScaleDownMcp { port: Some(3461) },
+            Arc::clone(&reg),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            None,
+            None,
+            Some(restart_tx.clone()),
+        )
+        .await;
+        assert!(resp.ok);
+        assert_eq!(resp.data["scale_down"], "requested");
+        assert_eq!(resp.data["port"], 3461);
+
+        let resp = handle_request(
+            ControlRequest::ScaleDownMcp { port: None },
+            Arc::clone(&reg),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            None,
+            None,
+            Some(restart_tx),
+        )
+        .await;
+        assert!(resp.ok);
+
+        assert_eq!(restart_rx.recv().await.as_deref(), Some("scale_down_mcp:3461"));
+        assert_eq!(restart_rx.recv().await.as_deref(), Some("scale_down_mcp:auto"));
+    }
+
+    #[tokio::test]
+    async fn reset_circuit_rearms_and_requests_restart() {
+        let reg = make_registry();
+        {
+            let mut r = reg.lock().await;
+            r.set_crash_loop_policy(crate::runner::crash_loop::CrashLoopPolicy {
+                max_restarts: 1,
+                window: std::time::Duration::from_secs(60),
+            });
+            r.record_crash_restart("interactive_terminal");
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(4);
+
+        let resp = handle_request_with_runtime(
+            ControlRequest::ResetCircuit { service: "terminal".to_string() },
+            Arc::clone(&reg),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            None,
+            None,
+            Some(tx),
+            None,
+        )
+        .await;
+
+        assert!(resp.ok);
+        assert_eq!(resp.data["service"], "interactive_terminal");
+        assert_eq!(resp.data["reset"], true);
+        assert_eq!(rx.try_recv().unwrap(), "reset:interactive_terminal");
+        assert!(reg.lock().await.circuit("interactive_terminal").is_none());
+    }
+
+    #[tokio::test]
+    async fn tail_logs_reports_disabled_log_files() {
+        // No test installs the process-wide service logs.
+        let resp = handle_request(
+            ControlRequest::TailLogs {
+                service: "mcp".to_string(),
+                lines: Some(10),
+                follow: false,
+            },
+            make_registry(),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(!resp.ok);
+        assert!(resp.error.unwrap_or_default().contains("disabled"));
+    }
+
+    #[test]
+    fn follow_logs_url_encodes_the_service_name() {
+        assert_eq!(
+            follow_logs_url("http://127.0.0.1:3464", "mcp:3460").as_deref(),
+            Some("http://127.0.0.1:3464/runtime/logs/mcp:3460?follow=true&lines=0")
+        );
+        assert_eq!(
+            follow_logs_url("http://127.0.0.1:3464/", "a b/../c?x#y").as_deref(),
+            Some("http://127.0.0.1:3464/runtime/logs/a%20b%2F..%2Fc%3Fx%23y?follow=true&lines=0")
+        );
+    }
+
+    #[tokio::test]
+    async fn list_mcp_affinity_reports_proxy_table() {
+        crate::proxy::session_affinity().bind("handler-affinity-test", 3462);
+
+        let resp = handle_request(
+            ControlRequest::ListMcpAffinity,
+            make_registry(),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        crate::proxy::session_affinity().unbind("handler-affinity-test");
+        assert!(resp.ok);
+        let row = resp
+            .data
+            .as_array()
+            .expect("array")
+            .iter()
+            .find(|e| e["session_id"] == "handler-affinity-test")
+            .expect("bound session listed");
+        assert_eq!(row["port"], 3462);
+        assert!(row["bound_at_ms"].is_number());
+    }
+
     #[tokio::test]
     async fn start_interactive_terminal_dispatches_even_when_registry_is_stale_running() {
         let reg = make_registry();
@@ -1492,6 +1752,34 @@ mod tests {
         assert_eq!(scale_up.data["runtime_mode"], "native_supervisor");
     }
 
+    #[tokio::test]
+    async fn event_stats_lists_active_subscription_filters() {
+        let events = crate::events::EventsHandle::new(crate::events::EventsConfig::default());
+        let filter = crate::events::filter::EventFilter {
+            workspace_id: Some("ws1".into()),
+            ..Default::default()
+        };
+        events.register_subscription("127.0.0.1", filter);
+
+        let resp = handle_request(
+            ControlRequest::EventStats,
+            make_registry(),
+            empty_form_apps(),
+            shutdown_channel(),
+            None,
+            Some(events),
+            None,
+            None,
+        )
+        .await;
+
+        assert!(resp.ok);
+        let subs = resp.data["subscriptions"].as_array().expect("subscriptions");
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs[0]["filter"]["workspace_id"], "ws1");
+        assert_eq!(subs[0]["client_ip"], "127.0.0.1");
+    }
+
     #[tokio::test]
     async fn event_stats_includes_runtime_telemetry() {
         let reg = make_registry();
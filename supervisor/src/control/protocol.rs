@@ -63,6 +63,18 @@ pub enum ControlRequest {
     /// `limit` defaults to 50 when absent.
     StateEvents { service: String, limit: Option<usize> },
 
+    /// Return the last `lines` lines (default 200) of a service's on-disk
+    /// log, reading back into rotated files as needed.  With `follow`, the
+    /// response also carries a `follow_url` for the GUI server's
+    /// `/runtime/logs/:service?follow=true` SSE stream.
+    TailLogs {
+        service: String,
+        #[serde(default)]
+        lines: Option<usize>,
+        #[serde(default)]
+        follow: bool,
+    },
+
     /// Return aggregate connection health and per-child status for the
     /// minimal supervisor health GUI.
     HealthSnapshot,
@@ -70,6 +82,10 @@ pub enum ControlRequest {
     /// Update health window visibility state (`true` = shown, `false` = hidden).
     SetHealthWindowVisibility { visible: bool },
 
+    /// Close a service's crash-loop circuit and request a restart, re-arming
+    /// automatic restarts.
+    ResetCircuit { service: String },
+
     /// Request graceful supervisor shutdown.
     ShutdownSupervisor,
 
@@ -94,6 +110,18 @@ pub enum ControlRequest {
     /// The supervisor will reject this if `max_instances` is already reached.
     ScaleUpMcp,
 
+    /// Manually drain and retire one MCP instance.  When `port` is absent the
+    /// highest-port instance is chosen.  The supervisor will reject this if
+    /// the pool is already at `min_instances`.
+    ScaleDownMcp {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        port: Option<u16>,
+    },
+
+    /// List the proxy's session-affinity table: which pool instance port
+    /// owns each MCP session id.
+    ListMcpAffinity,
+
     /// Execute one MCP payload through the supervisor-hosted async subprocess
     /// runtime when enabled via feature flag/environment.
     ///
@@ -238,6 +266,9 @@ pub struct EventStatsData {
     pub events_emitted: u64,
     /// URL clients should connect to for the SSE event stream.
     pub events_url: Option<String>,
+    /// Live SSE subscriptions and the filters each one applies.
+    #[serde(default)]
+    pub subscriptions: Vec<crate::events::filter::SubscriptionInfo>,
 }
 
 /// Result of a `LaunchApp` or `ContinueApp` request, returned inside `ControlResponse.data`.
@@ -356,6 +387,14 @@ pub struct ChildHealthStatus {
     pub pid: Option<u32>,
     pub last_health_error: Option<String>,
     pub updated_at: Option<u64>,
+    /// Dependencies this service is waiting on; non-empty only while the
+    /// service is held back (`status == "blocked"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_on: Vec<String>,
+    /// Open crash-loop circuit (`status == "circuit_open"`); automatic
+    /// restarts stay paused until `ResetCircuit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit: Option<crate::runner::crash_loop::CircuitState>,
 }
 
 /// Full minimal health snapshot for supervisor UI consumption.
@@ -565,6 +604,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_scale_down_mcp_with_and_without_port() {
+        let req = decode_request(r#"{"type":"ScaleDownMcp","port":3461}"#).expect("parse");
+        assert!(matches!(req, ControlRequest::ScaleDownMcp { port: Some(3461) }));
+
+        let req = decode_request(r#"{"type":"ScaleDownMcp"}"#).expect("parse");
+        assert!(matches!(req, ControlRequest::ScaleDownMcp { port: None }));
+    }
+
     #[test]
     fn encode_response_ends_with_newline() {
         let resp = ControlResponse::ok(serde_json::json!({"status": "running"}));
@@ -3,12 +3,15 @@
 //! [`Registry`] tracks the runtime state of every managed service and the set
 //! of VS Code windows currently attached to this supervisor.
 
-use std::collections::{HashMap, VecDeque};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
 use crate::control::protocol::{BackendKind, ChildHealthStatus, ConnectionHealthSummary, HealthSnapshot};
+use crate::events::{self, DataChangeEvent};
+use crate::runner::crash_loop::{CircuitState, CrashLoopDetector, CrashLoopPolicy, RestartDecision};
 
 // ---------------------------------------------------------------------------
 // Service state
@@ -34,6 +37,9 @@ pub struct ServiceState {
     pub last_error: Option<String>,
     /// Unix timestamp (seconds) of the most recent successful health check.
     pub last_health: Option<u64>,
+    /// Dependencies the service is waiting on before it can start.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked_on: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -72,6 +78,10 @@ pub struct StateEvent {
 /// Maximum number of state events retained in the ring buffer.
 const MAX_EVENTS: usize = 200;
 
+/// Service name under which MCP pool scale events are recorded.  Query with
+/// `StateEvents { service: "mcp_pool" }`.
+pub const MCP_POOL_EVENT_SERVICE: &str = "mcp_pool";
+
 // ---------------------------------------------------------------------------
 // Client entry
 // ---------------------------------------------------------------------------
@@ -117,6 +127,33 @@ pub struct McpConnectionEntry {
 }
 
 
+// ---------------------------------------------------------------------------
+// MCP pool slots
+// ---------------------------------------------------------------------------
+
+/// Lifecycle state of one MCP pool port slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpSlotState {
+    /// Port is reserved by the pool but no instance process is running on it
+    /// (e.g. awaiting respawn after a crash).
+    Reserved,
+    /// Process is running but has not yet passed a health probe.
+    Starting,
+    /// Process passed its most recent health probe.
+    Healthy,
+    /// Selected for scale-down; receives no new sessions.
+    Draining,
+}
+
+/// One port reservation held by the MCP instance pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpInstanceSlot {
+    pub port: u16,
+    pub state: McpSlotState,
+    /// Active connections assigned to this instance at the last poll.
+    pub connection_count: usize,
+}
 
 /// Central registry — wraps service states and client list.
 ///
@@ -135,8 +172,15 @@ pub struct Registry {
     health_window_visible: bool,
     /// Active VS Code ↔ MCP HTTP sessions, keyed by MCP session UUID.
     mcp_connections: HashMap<String, McpConnectionEntry>,
+    /// Port reservations held by the MCP instance pool, keyed by port.
+    mcp_slots: BTreeMap<u16, McpInstanceSlot>,
     /// Most recently generated focused workspace.
     pub focused_workspace: Option<crate::tray_tooltip::FocusedWorkspaceState>,
+    /// Crash-restart counters and open circuits.
+    crash_loop: CrashLoopDetector,
+    /// Woken whenever a service's status label changes; see
+    /// [`status_changes`](Self::status_changes).
+    status_changed: Arc<tokio::sync::Notify>,
 }
 
 impl Registry {
@@ -147,6 +191,7 @@ impl Registry {
             pid: None,
             last_error: None,
             last_health: None,
+            blocked_on: Vec::new(),
         }
     }
 
@@ -166,7 +211,10 @@ impl Registry {
             upgrade_pending: false,
             health_window_visible: true,
             mcp_connections: HashMap::new(),
+            mcp_slots: BTreeMap::new(),
             focused_workspace: None,
+            crash_loop: CrashLoopDetector::default(),
+            status_changed: Arc::new(tokio::sync::Notify::new()),
             }
             }
 
@@ -227,10 +275,101 @@ impl Registry {
     /// Update the status of a named service.  If the service is not tracked
     /// it is silently ignored (the control handler returns ok regardless so
     /// the caller can handle the distinction if needed).
+    ///
+    /// An actual change of status is also recorded as a state event (see
+    /// [`push_state_event`](Self::push_state_event)).
     pub fn set_service_status(&mut self, name: &str, status: ServiceStatus) {
-        if let Some(entry) = self.services.get_mut(name) {
-            entry.status = status;
+        let Some(entry) = self.services.get_mut(name) else {
+            return;
+        };
+        let old_label = entry_status_label(entry);
+        let new_label = service_status_label(&status);
+        entry.status = status;
+        entry.blocked_on.clear();
+        if old_label != new_label {
+            self.push_state_event(name, &old_label, &new_label, "status_changed");
+            self.status_changed.notify_one();
+        }
+    }
+
+    /// Notifier woken (one stored permit) after any service changes status,
+    /// so services blocked on dependencies can be retried without polling.
+    pub fn status_changes(&self) -> Arc<tokio::sync::Notify> {
+        Arc::clone(&self.status_changed)
+    }
+
+    /// Hold `name` back until `dependencies` are running.  The service is
+    /// reported as `blocked` (with `blocked_on`) in the health snapshot until
+    /// its status is next set.
+    pub fn set_service_blocked(&mut self, name: &str, dependencies: Vec<String>) {
+        let Some(entry) = self.services.get_mut(name) else {
+            return;
+        };
+        let old_label = entry_status_label(entry);
+        entry.status = ServiceStatus::Stopped;
+        entry.blocked_on = dependencies;
+        let reason = format!("waiting on {}", entry.blocked_on.join(", "));
+        if old_label != "blocked" {
+            self.push_state_event(name, &old_label, "blocked", &reason);
+        }
+    }
+
+    /// Replace the crash-loop thresholds.
+    pub fn set_crash_loop_policy(&mut self, policy: CrashLoopPolicy) {
+        self.crash_loop.set_policy(policy);
+    }
+
+    /// Count a crash-triggered restart of `name`.  When this opens the
+    /// circuit, a `circuit_open` state event is recorded, the service's last
+    /// error is set and an operator alert is published on the events bus.
+    pub fn record_crash_restart(&mut self, name: &str) -> RestartDecision {
+        let decision = self.crash_loop.record_restart(name, Instant::now());
+        if let RestartDecision::Tripped(circuit) = &decision {
+            let message = circuit.describe(name);
+            let old_label = self
+                .services
+                .get(name)
+                .map(entry_status_label)
+                .unwrap_or_else(|| "unknown".to_string());
+            if let Some(entry) = self.services.get_mut(name) {
+                entry.last_error = Some(message.clone());
+            }
+            self.push_state_event(name, &old_label, "circuit_open", &circuit.reason_code);
+            events::publish(DataChangeEvent::OperatorAlert {
+                service: name.to_string(),
+                reason_code: circuit.reason_code.clone(),
+                message,
+            });
+        }
+        decision
+    }
+
+    /// Close `name`'s crash-loop circuit.  Returns `true` if it was open.
+    pub fn reset_circuit(&mut self, name: &str) -> bool {
+        if !self.crash_loop.reset(name) {
+            return false;
         }
+        let new_label = match self.services.get_mut(name) {
+            Some(entry) => {
+                entry.last_error = None;
+                entry_status_label(entry)
+            }
+            None => "unknown".to_string(),
+        };
+        self.push_state_event(name, "circuit_open", &new_label, "circuit_reset");
+        true
+    }
+
+    /// The open crash-loop circuit for `name`, if any.
+    pub fn circuit(&self, name: &str) -> Option<&CircuitState> {
+        self.crash_loop.circuit(name)
+    }
+
+    /// `true` when `name` is tracked and currently running.
+    pub fn is_service_running(&self, name: &str) -> bool {
+        self.services
+            .get(name)
+            .is_some_and(|e| matches!(e.status, ServiceStatus::Running))
     }
 
     /// Record a successful health check for a service, updating `last_health`
@@ -267,7 +406,8 @@ impl Registry {
     }
 
     /// Append a state-transition event to the ring buffer, evicting the oldest
-    /// entry when the buffer exceeds [`MAX_EVENTS`].
+    /// entry when the buffer exceeds [`MAX_EVENTS`], and publish it on the
+    /// events channel as [`DataChangeEvent::ServiceStateChanged`].
     pub fn push_state_event(
         &mut self,
         service: &str,
@@ -275,6 +415,35 @@ impl Registry {
         new_state: &str,
         reason: &str,
     ) {
+        self.record_state_event(service, old_state, new_state, reason);
+        events::publish(DataChangeEvent::ServiceStateChanged {
+            service: service.to_owned(),
+            old_state: old_state.to_owned(),
+            new_state: new_state.to_owned(),
+            reason: reason.to_owned(),
+        });
+    }
+
+    /// Record an MCP pool instance transition (spawn, drain, reclaim, retire)
+    /// under [`MCP_POOL_EVENT_SERVICE`] and publish it as
+    /// [`DataChangeEvent::McpPoolChanged`].  The instance port is prefixed to
+    /// the recorded `reason`.
+    pub fn push_pool_event(&mut self, port: u16, old_state: &str, new_state: &str, reason: &str) {
+        self.record_state_event(
+            MCP_POOL_EVENT_SERVICE,
+            old_state,
+            new_state,
+            &format!("port {port}: {reason}"),
+        );
+        events::publish(DataChangeEvent::McpPoolChanged {
+            port,
+            old_state: old_state.to_owned(),
+            new_state: new_state.to_owned(),
+            reason: reason.to_owned(),
+        });
+    }
+
+    fn record_state_event(&mut self, service: &str, old_state: &str, new_state: &str, reason: &str) {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_secs())
@@ -333,12 +502,21 @@ impl Registry {
         let mut children: Vec<ChildHealthStatus> = self
             .services
             .values()
-            .map(|svc| ChildHealthStatus {
-                service_name: svc.name.clone(),
-                status: service_status_label(&svc.status),
-                pid: svc.pid,
-                last_health_error: svc.last_error.clone(),
-                updated_at: svc.last_health,
+            .map(|svc| {
+                let circuit = self.crash_loop.circuit(&svc.name).cloned();
+                ChildHealthStatus {
+                    service_name: svc.name.clone(),
+                    status: if circuit.is_some() {
+                        "circuit_open".to_string()
+                    } else {
+                        entry_status_label(svc)
+                    },
+                    pid: svc.pid,
+                    last_health_error: svc.last_error.clone(),
+                    updated_at: svc.last_health,
+                    blocked_on: svc.blocked_on.clone(),
+                    circuit,
+                }
             })
             .collect();
         children.sort_by(|a, b| a.service_name.cmp(&b.service_name));
@@ -525,6 +703,17 @@ impl Registry {
         conns
     }
 
+    /// Replace the pool slot table with the pool's latest view.  Ports absent
+    /// from `slots` have been released by the pool.
+    pub fn sync_mcp_slots(&mut self, slots: Vec<McpInstanceSlot>) {
+        self.mcp_slots = slots.into_iter().map(|s| (s.port, s)).collect();
+    }
+
+    /// Return all pool slots in port order.
+    pub fn list_mcp_slots(&self) -> Vec<McpInstanceSlot> {
+        self.mcp_slots.values().cloned().collect()
+    }
+
     /// Return the number of connections assigned to a specific instance port.
     pub fn connection_count_for_port(&self, port: u16) -> usize {
         self.mcp_connections
@@ -541,6 +730,16 @@ impl Default for Registry {
     }
 }
 
+/// Like [`service_status_label`], but reports a stopped service that is
+/// waiting on dependencies as `blocked`.
+fn entry_status_label(entry: &ServiceState) -> String {
+    if !entry.blocked_on.is_empty() && matches!(entry.status, ServiceStatus::Stopped) {
+        "blocked".to_string()
+    } else {
+        service_status_label(&entry.status)
+    }
+}
+
 fn service_status_label(status: &ServiceStatus) -> String {
     match status {
         ServiceStatus::Running => "running".to_string(),
@@ -749,6 +948,79 @@ mod tests {
         assert_eq!(evts[1].new_state, "Connected");
     }
 
+    #[test]
+    fn set_service_status_records_only_actual_changes() {
+        let mut r = Registry::new();
+        r.set_service_status("mcp", ServiceStatus::Starting);
+        r.set_service_status("mcp", ServiceStatus::Starting);
+        r.set_service_status("mcp", ServiceStatus::Running);
+        let evts = r.state_events("mcp", 50);
+        assert_eq!(evts.len(), 2);
+        assert_eq!(evts[0].old_state, "stopped");
+        assert_eq!(evts[1].new_state, "running");
+        assert_eq!(evts[1].reason, "status_changed");
+    }
+
+    #[tokio::test]
+    async fn status_changes_wake_dependency_waiters() {
+        let wait = std::time::Duration::from_millis(100);
+        let mut r = Registry::new();
+        let changes = r.status_changes();
+
+        r.set_service_status("dashboard", ServiceStatus::Running);
+        assert!(tokio::time::timeout(wait, changes.notified()).await.is_ok());
+
+        // Re-setting the same status is not a change.
+        r.set_service_status("dashboard", ServiceStatus::Running);
+        assert!(tokio::time::timeout(wait, changes.notified()).await.is_err());
+    }
+
+    #[test]
+    fn blocked_service_is_reported_until_status_changes() {
+        let mut r = Registry::with_backend_and_services(BackendKind::Node, ["helper"]);
+        r.set_service_blocked("helper", vec!["dashboard".to_string()]);
+
+        let snapshot = r.health_snapshot();
+        let row = snapshot.children.iter().find(|c| c.service_name == "helper").unwrap();
+        assert_eq!(row.status, "blocked");
+        assert_eq!(row.blocked_on, vec!["dashboard"]);
+        assert_eq!(r.state_events("helper", 10)[0].new_state, "blocked");
+
+        r.set_service_status("helper", ServiceStatus::Starting);
+        let snapshot = r.health_snapshot();
+        let row = snapshot.children.iter().find(|c| c.service_name == "helper").unwrap();
+        assert_eq!(row.status, "starting");
+        assert!(row.blocked_on.is_empty());
+        assert_eq!(r.state_events("helper", 10)[1].old_state, "blocked");
+    }
+
+    #[test]
+    fn crash_loop_opens_circuit_in_snapshot_until_reset() {
+        let mut r = Registry::new();
+        r.set_crash_loop_policy(CrashLoopPolicy {
+            max_restarts: 2,
+            window: std::time::Duration::from_secs(60),
+        });
+        assert_eq!(r.record_crash_restart("dashboard"), RestartDecision::Allow);
+        assert!(matches!(r.record_crash_restart("dashboard"), RestartDecision::Tripped(_)));
+        assert_eq!(r.record_crash_restart("dashboard"), RestartDecision::Open);
+
+        let snapshot = r.health_snapshot();
+        let row = snapshot.children.iter().find(|c| c.service_name == "dashboard").unwrap();
+        assert_eq!(row.status, "circuit_open");
+        assert_eq!(row.circuit.as_ref().unwrap().restart_count, 2);
+        assert!(row.last_health_error.as_deref().unwrap().contains("crashed 2 times"));
+        assert_eq!(r.state_events("dashboard", 1)[0].new_state, "circuit_open");
+
+        assert!(r.reset_circuit("dashboard"));
+        assert!(!r.reset_circuit("dashboard"));
+        let snapshot = r.health_snapshot();
+        let row = snapshot.children.iter().find(|c| c.service_name == "dashboard").unwrap();
+        assert_eq!(row.status, "stopped");
+        assert!(row.circuit.is_none());
+        assert_eq!(r.record_crash_restart("dashboard"), RestartDecision::Allow);
+    }
+
     #[test]
     fn state_events_filters_by_service() {
         let mut r = Registry::new();
@@ -759,6 +1031,16 @@ mod tests {
         assert_eq!(r.state_events("mcp", 50)[0].service, "mcp");
     }
 
+    #[test]
+    fn push_pool_event_records_under_mcp_pool() {
+        let mut r = Registry::new();
+        r.push_pool_event(3461, "healthy", "draining", "idle for 300s");
+        let evts = r.state_events(MCP_POOL_EVENT_SERVICE, 50);
+        assert_eq!(evts.len(), 1);
+        assert_eq!(evts[0].new_state, "draining");
+        assert_eq!(evts[0].reason, "port 3461: idle for 300s");
+    }
+
     #[test]
     fn state_events_limit_respected() {
         let mut r = Registry::new();
@@ -787,6 +1069,30 @@ mod tests {
         assert_eq!(all[199].reason, "reason_249");
     }
 
+    // -----------------------------------------------------------------------
+    // MCP pool slot tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn sync_mcp_slots_replaces_table_in_port_order() {
+        let mut r = Registry::new();
+        r.sync_mcp_slots(vec![
+            McpInstanceSlot { port: 3462, state: McpSlotState::Starting, connection_count: 0 },
+            McpInstanceSlot { port: 3460, state: McpSlotState::Healthy, connection_count: 2 },
+        ]);
+        let slots = r.list_mcp_slots();
+        assert_eq!(slots.iter().map(|s| s.port).collect::<Vec<_>>(), vec![3460, 3462]);
+
+        r.sync_mcp_slots(vec![McpInstanceSlot {
+            port: 3460,
+            state: McpSlotState::Draining,
+            connection_count: 0,
+        }]);
+        let slots = r.list_mcp_slots();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].state, McpSlotState::Draining);
+    }
+
     // -----------------------------------------------------------------------
     // upgrade_pending tests
     // -----------------------------------------------------------------------
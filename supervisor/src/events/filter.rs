@@ -0,0 +1,164 @@
+//! Server-side subscription filters for `GET /supervisor/events`.
+//!
+//! Clients narrow their stream with query parameters:
+//!
+//! ```text
+//! /supervisor/events?workspace_id=ws1&plan_id=p1&types=plan_*,step_changed
+//! ```
+//!
+//! * `workspace_id` / `plan_id` — exact match.  Events that are not scoped to
+//!   a workspace (or plan) at all, such as a global `metrics_invalidated`,
+//!   still pass so clients do not miss invalidations that affect them.
+//! * `types` — comma-separated list of `event_type` tags.  An entry ending in
+//!   `*` matches by prefix.  For `raw` events the original MCP `type` is
+//!   matched too, so `types=note_*` also selects pass-through events.
+//!
+//! The same filter is applied to `Last-Event-Id` replay and to the live
+//! stream.  Active subscriptions are tracked on the
+//! [`EventsHandle`](super::EventsHandle) and reported by `EventStats`.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::DataChangeEvent;
+
+/// Filter parsed from the SSE request's query string.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_id: Option<String>,
+    #[serde(deserialize_with = "comma_list", skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+}
+
+impl EventFilter {
+    /// `true` when the filter lets every event through.
+    pub fn is_empty(&self) -> bool {
+        self.workspace_id.is_none() && self.plan_id.is_none() && self.types.is_empty()
+    }
+
+    /// Whether `event` should be delivered to this subscriber.
+    pub fn matches(&self, event: &DataChangeEvent) -> bool {
+        if let (Some(want), Some(have)) = (&self.workspace_id, event.workspace_id()) {
+            if want != have {
+                return false;
+            }
+        }
+        if let (Some(want), Some(have)) = (&self.plan_id, event.plan_id()) {
+            if want != have {
+                return false;
+            }
+        }
+        if self.types.is_empty() {
+            return true;
+        }
+
+        let raw_type = match event {
+            DataChangeEvent::Raw { payload } => payload
+                .get("type")
+                .or_else(|| payload.get("event_type"))
+                .and_then(|v| v.as_str()),
+            _ => None,
+        };
+        self.types.iter().any(|pattern| {
+            type_matches(pattern, event.event_type())
+                || raw_type.is_some_and(|t| type_matches(pattern, t))
+        })
+    }
+}
+
+/// One live SSE subscription, as reported by `EventStats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub id: u64,
+    pub client_ip: String,
+    pub connected_at_ms: u64,
+    pub filter: EventFilter,
+}
+
+fn type_matches(pattern: &str, event_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event_type.starts_with(prefix),
+        None => pattern == event_type,
+    }
+}
+
+/// Accept `types=a,b` (query string) as well as a JSON array.
+fn comma_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let items = match Raw::deserialize(deserializer)? {
+        Raw::One(s) => s.split(',').map(str::to_string).collect(),
+        Raw::Many(v) => v,
+    };
+    Ok(items
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_updated(ws: &str, plan: &str) -> DataChangeEvent {
+        DataChangeEvent::PlanUpdated {
+            workspace_id: ws.into(),
+            plan_id: plan.into(),
+        }
+    }
+
+    #[test]
+    fn parses_query_string_types_as_comma_list() {
+        let filter: EventFilter =
+            serde_json::from_value(serde_json::json!({ "types": "plan_*, step_changed,," }))
+                .expect("parse");
+        assert_eq!(filter.types, vec!["plan_*", "step_changed"]);
+        assert!(EventFilter::default().is_empty());
+    }
+
+    #[test]
+    fn workspace_and_plan_filters_match_exactly() {
+        let filter = EventFilter {
+            workspace_id: Some("ws1".into()),
+            plan_id: Some("p1".into()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&plan_updated("ws1", "p1")));
+        assert!(!filter.matches(&plan_updated("ws2", "p1")));
+        assert!(!filter.matches(&plan_updated("ws1", "p2")));
+        // Unscoped events pass.
+        assert!(filter.matches(&DataChangeEvent::MetricsInvalidated { workspace_id: None }));
+    }
+
+    #[test]
+    fn type_filter_supports_prefixes_and_raw_inner_type() {
+        let filter = EventFilter {
+            types: vec!["plan_*".into(), "note_added".into()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&plan_updated("ws1", "p1")));
+        assert!(!filter.matches(&DataChangeEvent::WorkspaceChanged { workspace_id: "ws1".into() }));
+        assert!(filter.matches(&DataChangeEvent::Raw {
+            payload: serde_json::json!({ "type": "note_added" }),
+        }));
+        assert!(!filter.matches(&DataChangeEvent::Raw {
+            payload: serde_json::json!({ "type": "context_stored" }),
+        }));
+    }
+}
@@ -0,0 +1,362 @@
+//! Append-only on-disk journal behind the events channel.
+//!
+//! Events are written as one JSON object per line into segment files named
+//! `events-<first id>.jsonl` (the id is zero-padded so lexical order matches
+//! numeric order).  The active segment is rolled once it reaches
+//! `segment_max_bytes`; closed segments are deleted oldest-first when the
+//! journal exceeds `max_total_bytes` or a segment has not been written to for
+//! `max_age_secs`.
+//!
+//! On open the journal scans the last segment for the highest stored id so
+//! [`EventsHandle`](super::EventsHandle) can keep ids monotonic across
+//! supervisor restarts.  A torn trailing line (crash mid-write) is truncated
+//! away before new events are appended.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::{DataChangeEvent, EventId, StampedEvent};
+
+const SEGMENT_PREFIX: &str = "events-";
+const SEGMENT_SUFFIX: &str = ".jsonl";
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Parsed from `[events.journal]` in the supervisor TOML.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EventJournalConfig {
+    /// Persist events to disk.  Off by default; replay is then memory-only.
+    pub enabled: bool,
+    /// Segment directory.  Defaults to `<data_dir>/events` when unset.
+    pub dir: Option<PathBuf>,
+    /// Roll to a new segment once the active one reaches this size.
+    pub segment_max_bytes: u64,
+    /// Delete the oldest closed segments while the journal exceeds this size.
+    pub max_total_bytes: u64,
+    /// Delete closed segments last written more than this many seconds ago.
+    /// `0` disables age-based retention.
+    pub max_age_secs: u64,
+}
+
+impl Default for EventJournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            segment_max_bytes: 1024 * 1024,
+            max_total_bytes: 64 * 1024 * 1024,
+            max_age_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+/// One journal line.
+#[derive(Serialize, Deserialize)]
+struct JournalRecord {
+    id: EventId,
+    ts_ms: u64,
+    data: DataChangeEvent,
+}
+
+/// A segment file and the id of its first event.
+#[derive(Debug, Clone)]
+struct Segment {
+    first_id: EventId,
+    path: PathBuf,
+}
+
+// ---------------------------------------------------------------------------
+// EventJournal
+// ---------------------------------------------------------------------------
+
+/// Segmented JSONL event store.  Not internally synchronised — the
+/// [`EventsHandle`](super::EventsHandle) journal writer thread owns it.
+pub struct EventJournal {
+    dir: PathBuf,
+    config: EventJournalConfig,
+    /// Closed and active segments, oldest first.  The last entry is active.
+    segments: Vec<Segment>,
+    active: Option<File>,
+    active_bytes: u64,
+    last_id: EventId,
+}
+
+impl EventJournal {
+    /// Open (creating if needed) the journal in `dir`.
+    pub fn open(dir: impl Into<PathBuf>, config: EventJournalConfig) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let segments = list_segments(&dir)?;
+
+        let mut last_id = 0;
+        let mut active_bytes = 0;
+        if let Some(seg) = segments.last() {
+            active_bytes = truncate_torn_tail(&seg.path)?;
+            last_id = read_segment(&seg.path)?
+                .last()
+                .map(|e| e.id)
+                .unwrap_or(seg.first_id.saturating_sub(1));
+        }
+
+        let mut journal = Self {
+            dir,
+            config,
+            segments,
+            active: None,
+            active_bytes,
+            last_id,
+        };
+        journal.enforce_retention()?;
+        Ok(journal)
+    }
+
+    /// Highest event id stored in the journal (`0` when empty).
+    pub fn last_id(&self) -> EventId {
+        self.last_id
+    }
+
+    /// Append `event`, rolling to a new segment when the active one is full.
+    pub fn append(&mut self, event: &StampedEvent) -> io::Result<()> {
+        let record = JournalRecord {
+            id: event.id,
+            ts_ms: now_ms(),
+            data: event.data.clone(),
+        };
+        let mut line = serde_json::to_vec(&record).map_err(io::Error::other)?;
+        line.push(b'\n');
+
+        let needs_roll = self.segments.is_empty()
+            || self.active_bytes >= self.config.segment_max_bytes.max(1);
+        if needs_roll {
+            self.roll(event.id)?;
+        }
+
+        let file = match self.active.as_mut() {
+            Some(f) => f,
+            None => {
+                let path = &self.segments.last().expect("segment after roll").path;
+                self.active.insert(OpenOptions::new().create(true).append(true).open(path)?)
+            }
+        };
+        file.write_all(&line)?;
+        file.flush()?;
+
+        self.active_bytes += line.len() as u64;
+        self.last_id = event.id;
+        Ok(())
+    }
+
+    /// All stored events with `id > since_id`, oldest first.
+    pub fn read_since(&self, since_id: EventId) -> io::Result<Vec<StampedEvent>> {
+        // Skip segments whose successor starts at or below the first wanted id.
+        let start = self
+            .segments
+            .windows(2)
+            .take_while(|w| w[1].first_id <= since_id + 1)
+            .count();
+
+        let mut out = Vec::new();
+        for seg in &self.segments[start..] {
+            out.extend(read_segment(&seg.path)?.into_iter().filter(|e| e.id > since_id));
+        }
+        Ok(out)
+    }
+
+    /// Close the active segment and start a new one beginning at `first_id`.
+    fn roll(&mut self, first_id: EventId) -> io::Result<()> {
+        self.active = None;
+        let path = self.dir.join(segment_name(first_id));
+        self.active = Some(OpenOptions::new().create(true).append(true).open(&path)?);
+        self.segments.push(Segment { first_id, path });
+        self.active_bytes = 0;
+        self.enforce_retention()
+    }
+
+    /// Delete closed segments that are too old or push the journal over its
+    /// size budget.  The active segment is never deleted.
+    fn enforce_retention(&mut self) -> io::Result<()> {
+        let max_age = Duration::from_secs(self.config.max_age_secs);
+        let mut sizes: Vec<u64> = self
+            .segments
+            .iter()
+            .map(|s| fs::metadata(&s.path).map(|m| m.len()).unwrap_or(0))
+            .collect();
+        let mut total: u64 = sizes.iter().sum();
+
+        while self.segments.len() > 1 {
+            let oldest = &self.segments[0];
+            let expired = self.config.max_age_secs > 0
+                && fs::metadata(&oldest.path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|t| t.elapsed().ok())
+                    .is_some_and(|age| age > max_age);
+            if !expired && total <= self.config.max_total_bytes {
+                break;
+            }
+            match fs::remove_file(&oldest.path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            total -= sizes.remove(0);
+            self.segments.remove(0);
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn segment_name(first_id: EventId) -> String {
+    format!("{SEGMENT_PREFIX}{first_id:020}{SEGMENT_SUFFIX}")
+}
+
+fn list_segments(dir: &Path) -> io::Result<Vec<Segment>> {
+    let mut segments: Vec<Segment> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let first_id = name
+                .strip_prefix(SEGMENT_PREFIX)?
+                .strip_suffix(SEGMENT_SUFFIX)?
+                .parse()
+                .ok()?;
+            Some(Segment { first_id, path: entry.path() })
+        })
+        .collect();
+    segments.sort_by_key(|s| s.first_id);
+    Ok(segments)
+}
+
+/// Cut a partially written last line off `path`.  Returns the new length.
+fn truncate_torn_tail(path: &Path) -> io::Result<u64> {
+    let bytes = fs::read(path)?;
+    let keep = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    if keep < bytes.len() {
+        OpenOptions::new().write(true).open(path)?.set_len(keep as u64)?;
+    }
+    Ok(keep as u64)
+}
+
+/// Read every well-formed record in `path`; malformed lines are skipped.
+fn read_segment(path: &Path) -> io::Result<Vec<StampedEvent>> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut out = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if let Ok(rec) = serde_json::from_str::<JournalRecord>(&line) {
+            out.push(StampedEvent { id: rec.id, data: rec.data });
+        }
+    }
+    Ok(out)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: EventId) -> StampedEvent {
+        StampedEvent {
+            id,
+            data: DataChangeEvent::Test { message: format!("msg-{id}") },
+        }
+    }
+
+    fn config(segment_max_bytes: u64, max_total_bytes: u64) -> EventJournalConfig {
+        EventJournalConfig {
+            enabled: true,
+            dir: None,
+            segment_max_bytes,
+            max_total_bytes,
+            max_age_secs: 0,
+        }
+    }
+
+    #[test]
+    fn appends_and_reads_back_since_id() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let mut journal = EventJournal::open(tmp.path(), config(1024 * 1024, u64::MAX)).expect("open");
+        for id in 1..=5 {
+            journal.append(&event(id)).expect("append");
+        }
+
+        let ids: Vec<EventId> = journal.read_since(2).expect("read").iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn reopen_resumes_last_id_and_drops_torn_line() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        {
+            let mut journal = EventJournal::open(tmp.path(), config(1024 * 1024, u64::MAX)).expect("open");
+            for id in 1..=3 {
+                journal.append(&event(id)).expect("append");
+            }
+        }
+        let seg = tmp.path().join(segment_name(1));
+        let mut f = OpenOptions::new().append(true).open(&seg).expect("open segment");
+        f.write_all(b"{\"id\":4,\"ts_").expect("write torn line");
+
+        let mut journal = EventJournal::open(tmp.path(), config(1024 * 1024, u64::MAX)).expect("reopen");
+        assert_eq!(journal.last_id(), 3);
+        journal.append(&event(4)).expect("append after reopen");
+
+        let ids: Vec<EventId> = journal.read_since(0).expect("read").iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn rolls_segments_and_reads_across_them() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        // Tiny segments: every append after the first rolls.
+        let mut journal = EventJournal::open(tmp.path(), config(1, u64::MAX)).expect("open");
+        for id in 1..=4 {
+            journal.append(&event(id)).expect("append");
+        }
+
+        assert_eq!(list_segments(tmp.path()).expect("list").len(), 4);
+        let ids: Vec<EventId> = journal.read_since(1).expect("read").iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn size_retention_drops_oldest_closed_segments() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let mut journal = EventJournal::open(tmp.path(), config(1, 1)).expect("open");
+        for id in 1..=4 {
+            journal.append(&event(id)).expect("append");
+        }
+
+        // Only the active segment survives a 1-byte budget.
+        let segments = list_segments(tmp.path()).expect("list");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].first_id, 4);
+        let ids: Vec<EventId> = journal.read_since(0).expect("read").iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![4]);
+    }
+}
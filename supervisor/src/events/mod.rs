@@ -9,18 +9,27 @@
 //! disconnects, automatically decrementing `tx.receiver_count()`.
 //!
 //! A compact ring-buffer stores the last N events so clients that reconnect
-//! with `Last-Event-Id` can catch up on missed events.
+//! with `Last-Event-Id` can catch up on missed events.  When the optional
+//! [`journal`] is enabled every event is also appended to disk, replay falls
+//! back to it once the ring buffer no longer reaches back far enough, and
+//! ids continue from the journal's last id after a restart.
 
+pub mod filter;
 pub mod ingestion;
+pub mod journal;
 pub mod sse;
 
+use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicU64, Ordering},
-    Arc,
+    Arc, Mutex, OnceLock,
 };
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
+
+use filter::{EventFilter, SubscriptionInfo};
+use journal::{EventJournal, EventJournalConfig};
 
 // ---------------------------------------------------------------------------
 // Configuration
@@ -39,6 +48,8 @@ pub struct EventsConfig {
     pub heartbeat_interval: u64,
     /// Ring-buffer capacity for `Last-Event-Id` replay on reconnect.
     pub replay_buffer_size: usize,
+    /// On-disk journal settings (`[events.journal]`).
+    pub journal: EventJournalConfig,
 }
 
 impl Default for EventsConfig {
@@ -48,6 +59,7 @@ impl Default for EventsConfig {
             buffer_size: 256,
             heartbeat_interval: 30,
             replay_buffer_size: 100,
+            journal: EventJournalConfig::default(),
         }
     }
 }
@@ -88,10 +100,111 @@ pub enum DataChangeEvent {
     /// that don't match a known variant.  The full JSON payload (including the
     /// original `event_type` / `type` key) is preserved in `payload`.
     Raw { payload: serde_json::Value },
+    /// A managed service (MCP, interactive terminal, dashboard, custom
+    /// server, …) changed state.  Mirrors the registry's state-event log.
+    ServiceStateChanged {
+        service:   String,
+        old_state: String,
+        new_state: String,
+        reason:    String,
+    },
+    /// An MCP pool instance was spawned, drained, reclaimed or retired.
+    McpPoolChanged {
+        port:      u16,
+        old_state: String,
+        new_state: String,
+        reason:    String,
+    },
+    /// A form app (approval dialog, brainstorm GUI, …) window was spawned.
+    FormAppLaunched { app_name: String, pid: Option<u32> },
+    /// A form-app round finished.  `outcome` is one of `completed`,
+    /// `failed`, `timed_out` or `awaiting_refinement`.
+    FormAppFinished {
+        app_name:   String,
+        outcome:    String,
+        elapsed_ms: u64,
+    },
+    /// Something needs an operator's attention.  `reason_code` is a stable
+    /// machine-readable tag (e.g. `crash_loop`); `message` is for humans.
+    OperatorAlert {
+        service:     String,
+        reason_code: String,
+        message:     String,
+    },
     /// Synthetic event emitted by the `EmitTestEvent` control command.
     Test { message: String },
 }
 
+impl DataChangeEvent {
+    /// The serialised `event_type` tag (e.g. `"plan_created"`).
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::PlanCreated { .. } => "plan_created",
+            Self::PlanUpdated { .. } => "plan_updated",
+            Self::PlanArchived { .. } => "plan_archived",
+            Self::PlanDeleted { .. } => "plan_deleted",
+            Self::StepChanged { .. } => "step_changed",
+            Self::AgentSessionChanged { .. } => "agent_session_changed",
+            Self::WorkspaceChanged { .. } => "workspace_changed",
+            Self::FocusedWorkspaceGenerated { .. } => "focused_workspace_generated",
+            Self::MetricsInvalidated { .. } => "metrics_invalidated",
+            Self::ServiceStateChanged { .. } => "service_state_changed",
+            Self::McpPoolChanged { .. } => "mcp_pool_changed",
+            Self::FormAppLaunched { .. } => "form_app_launched",
+            Self::FormAppFinished { .. } => "form_app_finished",
+            Self::OperatorAlert { .. } => "operator_alert",
+            Self::Raw { .. } => "raw",
+            Self::Test { .. } => "test",
+        }
+    }
+
+    /// Workspace the event is scoped to, if any.  For [`Self::Raw`] this is
+    /// the payload's top-level `workspace_id`.
+    pub fn workspace_id(&self) -> Option<&str> {
+        match self {
+            Self::PlanCreated { workspace_id, .. }
+            | Self::PlanUpdated { workspace_id, .. }
+            | Self::PlanArchived { workspace_id, .. }
+            | Self::PlanDeleted { workspace_id, .. }
+            | Self::StepChanged { workspace_id, .. }
+            | Self::AgentSessionChanged { workspace_id, .. }
+            | Self::WorkspaceChanged { workspace_id }
+            | Self::FocusedWorkspaceGenerated { workspace_id, .. } => Some(workspace_id),
+            Self::MetricsInvalidated { workspace_id } => workspace_id.as_deref(),
+            Self::Raw { payload } => payload.get("workspace_id").and_then(|v| v.as_str()),
+            Self::ServiceStateChanged { .. }
+            | Self::McpPoolChanged { .. }
+            | Self::FormAppLaunched { .. }
+            | Self::FormAppFinished { .. }
+            | Self::OperatorAlert { .. }
+            | Self::Test { .. } => None,
+        }
+    }
+
+    /// Plan the event is scoped to, if any.  For [`Self::Raw`] this is the
+    /// payload's top-level `plan_id`.
+    pub fn plan_id(&self) -> Option<&str> {
+        match self {
+            Self::PlanCreated { plan_id, .. }
+            | Self::PlanUpdated { plan_id, .. }
+            | Self::PlanArchived { plan_id, .. }
+            | Self::PlanDeleted { plan_id, .. }
+            | Self::StepChanged { plan_id, .. }
+            | Self::AgentSessionChanged { plan_id, .. }
+            | Self::FocusedWorkspaceGenerated { plan_id, .. } => Some(plan_id),
+            Self::Raw { payload } => payload.get("plan_id").and_then(|v| v.as_str()),
+            Self::WorkspaceChanged { .. }
+            | Self::MetricsInvalidated { .. }
+            | Self::ServiceStateChanged { .. }
+            | Self::McpPoolChanged { .. }
+            | Self::FormAppLaunched { .. }
+            | Self::FormAppFinished { .. }
+            | Self::OperatorAlert { .. }
+            | Self::Test { .. } => None,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Stamped envelope
 // ---------------------------------------------------------------------------
@@ -120,37 +233,74 @@ pub struct EventsHandle {
     pub tx:     broadcast::Sender<StampedEvent>,
     /// Monotonically-increasing counter; next ID = fetch_add(1) + 1.
     counter:    Arc<AtomicU64>,
+    /// Counter value at startup (the journal's last id, or 0).
+    resumed_from: u64,
     pub config: EventsConfig,
     /// Ring buffer; protected by a read-write lock for concurrent replay reads.
     replay:     Arc<RwLock<std::collections::VecDeque<StampedEvent>>>,
+    /// Queue to the journal writer thread, present when `journal.enabled`
+    /// and the journal opened cleanly.
+    journal:    Option<mpsc::UnboundedSender<JournalOp>>,
+    /// Live SSE subscriptions keyed by subscription id.
+    subscriptions: Arc<Mutex<std::collections::BTreeMap<u64, SubscriptionInfo>>>,
+    next_subscription_id: Arc<AtomicU64>,
 }
 
 impl EventsHandle {
     /// Create a new handle with the given configuration.
+    ///
+    /// When `config.journal.enabled` is set the journal is opened in
+    /// `config.journal.dir` and handed to a dedicated writer thread; if
+    /// opening fails the handle logs the error and runs memory-only.
     pub fn new(config: EventsConfig) -> Self {
+        let journal = if config.journal.enabled {
+            let dir = config.journal.dir.clone().unwrap_or_else(|| PathBuf::from("events"));
+            match EventJournal::open(&dir, config.journal.clone()) {
+                Ok(j) => Some(j),
+                Err(e) => {
+                    eprintln!("[events] journal disabled — cannot open {}: {e}", dir.display());
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let resumed_from = journal.as_ref().map(EventJournal::last_id).unwrap_or(0);
+
         let (tx, _) = broadcast::channel(config.buffer_size);
         Self {
             tx,
-            counter: Arc::new(AtomicU64::new(0)),
+            counter: Arc::new(AtomicU64::new(resumed_from)),
+            resumed_from,
             config,
             replay: Arc::new(RwLock::new(std::collections::VecDeque::new())),
+            journal: journal.map(spawn_journal_writer),
+            subscriptions: Arc::new(Mutex::new(std::collections::BTreeMap::new())),
+            next_subscription_id: Arc::new(AtomicU64::new(0)),
         }
     }
 
     /// Emit a data-change event.
     ///
-    /// The event is stamped with the next monotonic ID, appended to the replay
-    /// ring buffer (evicting the oldest entry when full), and broadcast to all
-    /// live subscribers.
+    /// The event is stamped with the next monotonic ID, appended to the
+    /// replay ring buffer (evicting the oldest entry when full), queued for
+    /// the journal (if enabled), and broadcast to all live subscribers.  This
+    /// happens under the ring-buffer lock so ids reach the journal and
+    /// subscribers in order; the disk write itself runs on the journal
+    /// writer thread, outside the lock.
     pub async fn emit(&self, event: DataChangeEvent) {
+        let mut buf = self.replay.write().await;
         let id = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
         let stamped = StampedEvent { id, data: event };
 
-        {
-            let mut buf = self.replay.write().await;
-            buf.push_back(stamped.clone());
-            while buf.len() > self.config.replay_buffer_size {
-                buf.pop_front();
+        buf.push_back(stamped.clone());
+        while buf.len() > self.config.replay_buffer_size {
+            buf.pop_front();
+        }
+
+        if let Some(journal) = &self.journal {
+            if journal.send(JournalOp::Append(stamped.clone())).is_err() {
+                eprintln!("[events] journal writer gone — id {id} not persisted");
             }
         }
 
@@ -158,23 +308,107 @@ impl EventsHandle {
         let _ = self.tx.send(stamped);
     }
 
-    /// Return all events with `id > since_id` from the replay ring buffer,
-    /// oldest first, for `Last-Event-Id` reconnect replay.
+    /// Return all events with `id > since_id`, oldest first, for
+    /// `Last-Event-Id` reconnect replay.
+    ///
+    /// Served from the ring buffer when it reaches back far enough; otherwise
+    /// the older part is read from the journal.  The read is queued behind
+    /// every append already handed to the writer, so it sees them all.
     pub async fn replay_since(&self, since_id: EventId) -> Vec<StampedEvent> {
-        self.replay
-            .read()
-            .await
-            .iter()
-            .filter(|e| e.id > since_id)
-            .cloned()
-            .collect()
+        let buf = self.replay.read().await;
+        let ring: Vec<StampedEvent> = buf.iter().filter(|e| e.id > since_id).cloned().collect();
+        let ring_start = buf
+            .front()
+            .map(|e| e.id)
+            .unwrap_or_else(|| self.counter.load(Ordering::Relaxed) + 1);
+        drop(buf);
+
+        let Some(journal) = &self.journal else {
+            return ring;
+        };
+        if since_id + 1 >= ring_start {
+            return ring;
+        }
+
+        let (reply, from_disk) = oneshot::channel();
+        let from_disk = match journal.send(JournalOp::ReadSince(since_id, reply)) {
+            Ok(()) => from_disk.await,
+            Err(_) => {
+                eprintln!("[events] journal writer gone — replay since {since_id} skipped");
+                return ring;
+            }
+        };
+        let mut events: Vec<StampedEvent> = match from_disk {
+            Ok(Ok(events)) => events.into_iter().filter(|e| e.id < ring_start).collect(),
+            Ok(Err(e)) => {
+                eprintln!("[events] journal replay since {since_id} failed: {e}");
+                Vec::new()
+            }
+            Err(_) => {
+                eprintln!("[events] journal writer stopped during replay since {since_id}");
+                Vec::new()
+            }
+        };
+        events.extend(ring);
+        events
+    }
+
+    /// Wait until every event emitted so far has been written to the
+    /// journal.  Returns immediately when the journal is disabled.
+    pub async fn flush_journal(&self) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let (done, flushed) = oneshot::channel();
+        if journal.send(JournalOp::Flush(done)).is_ok() {
+            let _ = flushed.await;
+        }
     }
 
     /// Total events emitted since the supervisor started.
     pub fn events_emitted(&self) -> u64 {
+        self.counter.load(Ordering::Relaxed) - self.resumed_from
+    }
+
+    /// Highest event id issued so far, including ids restored from the
+    /// journal on startup.
+    pub fn last_event_id(&self) -> EventId {
         self.counter.load(Ordering::Relaxed)
     }
 
+    /// Record a new SSE subscription and return its id.  Pair with
+    /// [`unregister_subscription`](Self::unregister_subscription) on disconnect.
+    pub fn register_subscription(&self, client_ip: &str, filter: EventFilter) -> u64 {
+        let id = self.next_subscription_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let info = SubscriptionInfo {
+            id,
+            client_ip: client_ip.to_string(),
+            connected_at_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            filter,
+        };
+        if let Ok(mut subs) = self.subscriptions.lock() {
+            subs.insert(id, info);
+        }
+        id
+    }
+
+    pub fn unregister_subscription(&self, id: u64) {
+        if let Ok(mut subs) = self.subscriptions.lock() {
+            subs.remove(&id);
+        }
+    }
+
+    /// Live SSE subscriptions and their filters, oldest first.
+    pub fn subscriptions(&self) -> Vec<SubscriptionInfo> {
+        self.subscriptions
+            .lock()
+            .map(|subs| subs.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Current number of live SSE subscribers.
     ///
     /// Uses the broadcast channel's own receiver count, which Tokio
@@ -184,6 +418,83 @@ impl EventsHandle {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Journal writer
+// ---------------------------------------------------------------------------
+
+/// Work for the journal writer thread, processed in queue order.
+enum JournalOp {
+    Append(StampedEvent),
+    ReadSince(EventId, oneshot::Sender<std::io::Result<Vec<StampedEvent>>>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Move `journal` onto its own thread so disk writes never run under the
+/// ring-buffer lock or on a runtime worker.  The thread exits once every
+/// [`EventsHandle`] clone (and so every sender) has been dropped.
+fn spawn_journal_writer(mut journal: EventJournal) -> mpsc::UnboundedSender<JournalOp> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<JournalOp>();
+    let spawned = std::thread::Builder::new()
+        .name("events-journal".into())
+        .spawn(move || {
+            while let Some(op) = rx.blocking_recv() {
+                match op {
+                    JournalOp::Append(event) => {
+                        if let Err(e) = journal.append(&event) {
+                            eprintln!("[events] journal append failed for id {}: {e}", event.id);
+                        }
+                    }
+                    JournalOp::ReadSince(since_id, reply) => {
+                        let _ = reply.send(journal.read_since(since_id));
+                    }
+                    JournalOp::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        // The receiver was dropped with the closure, so sends fail and the
+        // handle logs them instead of persisting.
+        eprintln!("[events] cannot start journal writer: {e}");
+    }
+    tx
+}
+
+// ---------------------------------------------------------------------------
+// Process-wide publisher
+// ---------------------------------------------------------------------------
+
+/// Queue feeding the installed [`EventsHandle`].  A single forwarding task
+/// drains it, so events published from synchronous code (e.g. the registry)
+/// keep their order.
+static PUBLISH_TX: OnceLock<mpsc::UnboundedSender<DataChangeEvent>> = OnceLock::new();
+
+/// Route [`publish`] calls to `handle`.  Must be called from within a Tokio
+/// runtime; only the first call takes effect.
+pub fn install_publisher(handle: &EventsHandle) {
+    if PUBLISH_TX.get().is_some() {
+        return;
+    }
+    let (tx, mut rx) = mpsc::unbounded_channel::<DataChangeEvent>();
+    if PUBLISH_TX.set(tx).is_ok() {
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                handle.emit(event).await;
+            }
+        });
+    }
+}
+
+/// Publish `event` on the installed events handle.  A no-op until
+/// [`install_publisher`] has been called (e.g. in unit tests).
+pub fn publish(event: DataChangeEvent) {
+    if let Some(tx) = PUBLISH_TX.get() {
+        let _ = tx.send(event);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -224,6 +535,125 @@ mod tests {
         assert_eq!(replayed[0].id, 3);
     }
 
+    fn journaled_config(dir: &std::path::Path, replay_buffer_size: usize) -> EventsConfig {
+        EventsConfig {
+            replay_buffer_size,
+            journal: EventJournalConfig {
+                enabled: true,
+                dir: Some(dir.to_path_buf()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_since_falls_back_to_journal_past_ring_buffer() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let handle = EventsHandle::new(journaled_config(tmp.path(), 2));
+
+        for i in 0..6u64 {
+            handle
+                .emit(DataChangeEvent::Test { message: format!("msg-{i}") })
+                .await;
+        }
+
+        let ids: Vec<EventId> = handle.replay_since(1).await.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3, 4, 5, 6]);
+    }
+
+    #[tokio::test]
+    async fn ids_stay_monotonic_across_restart() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        {
+            let handle = EventsHandle::new(journaled_config(tmp.path(), 100));
+            for i in 0..3u64 {
+                handle
+                    .emit(DataChangeEvent::Test { message: format!("before-{i}") })
+                    .await;
+            }
+            handle.flush_journal().await;
+        }
+
+        let handle = EventsHandle::new(journaled_config(tmp.path(), 100));
+        assert_eq!(handle.last_event_id(), 3);
+        assert_eq!(handle.events_emitted(), 0);
+
+        let mut rx = handle.tx.subscribe();
+        handle
+            .emit(DataChangeEvent::Test { message: "after".into() })
+            .await;
+        assert_eq!(rx.recv().await.expect("event").id, 4);
+
+        // A client that last saw id 1 before the restart still catches up.
+        let ids: Vec<EventId> = handle.replay_since(1).await.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_emits_reach_the_journal_in_id_order() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let handle = EventsHandle::new(journaled_config(tmp.path(), 100));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|t| {
+                let handle = handle.clone();
+                tokio::spawn(async move {
+                    for i in 0..25u64 {
+                        handle
+                            .emit(DataChangeEvent::Test { message: format!("{t}-{i}") })
+                            .await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("emit task");
+        }
+        handle.flush_journal().await;
+
+        let journal = EventJournal::open(tmp.path(), journaled_config(tmp.path(), 100).journal)
+            .expect("reopen journal");
+        let ids: Vec<EventId> = journal.read_since(0).expect("read").iter().map(|e| e.id).collect();
+        assert_eq!(ids, (1..=200).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn registry_transition_reaches_an_installed_subscriber() {
+        use crate::control::protocol::BackendKind;
+        use crate::control::registry::{Registry, ServiceStatus};
+
+        // The only test that installs the process-wide publisher; other tests
+        // may publish through it concurrently, so look for our own service.
+        let handle = EventsHandle::new(EventsConfig::default());
+        install_publisher(&handle);
+        let mut rx = handle.tx.subscribe();
+
+        let mut registry =
+            Registry::with_backend_and_services(BackendKind::Node, ["publish-probe"]);
+        registry.set_service_status("publish-probe", ServiceStatus::Running);
+
+        let received = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+            loop {
+                let event = rx.recv().await.expect("event");
+                if let DataChangeEvent::ServiceStateChanged { service, .. } = &event.data {
+                    if service == "publish-probe" {
+                        return event;
+                    }
+                }
+            }
+        })
+        .await
+        .expect("published event reaches the subscriber");
+        match received.data {
+            DataChangeEvent::ServiceStateChanged { old_state, new_state, .. } => {
+                assert_eq!(old_state, "stopped");
+                assert_eq!(new_state, "running");
+            }
+            other => panic!("unexpected variant: {other:?}"),
+        }
+    }
+
     #[tokio::test]
     async fn subscriber_count_reflects_receivers() {
         let handle = EventsHandle::new(EventsConfig::default());
@@ -237,6 +667,42 @@ mod tests {
         assert_eq!(handle.subscriber_count(), 1);
     }
 
+    #[test]
+    fn lifecycle_variants_roundtrip_with_snake_case_tags() {
+        let events = [
+            DataChangeEvent::ServiceStateChanged {
+                service:   "interactive_terminal".into(),
+                old_state: "starting".into(),
+                new_state: "running".into(),
+                reason:    "status_changed".into(),
+            },
+            DataChangeEvent::McpPoolChanged {
+                port:      3461,
+                old_state: "healthy".into(),
+                new_state: "draining".into(),
+                reason:    "idle".into(),
+            },
+            DataChangeEvent::FormAppLaunched { app_name: "approval_gui".into(), pid: Some(42) },
+            DataChangeEvent::FormAppFinished {
+                app_name:   "approval_gui".into(),
+                outcome:    "completed".into(),
+                elapsed_ms: 10,
+            },
+            DataChangeEvent::OperatorAlert {
+                service:     "dashboard".into(),
+                reason_code: "crash_loop".into(),
+                message:     "restarted 5 times in 300s".into(),
+            },
+        ];
+        for event in events {
+            let json = serde_json::to_value(&event).unwrap();
+            assert_eq!(json["event_type"], event.event_type());
+            let decoded: DataChangeEvent = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded.event_type(), event.event_type());
+            assert_eq!(decoded.workspace_id(), None);
+        }
+    }
+
     #[test]
     fn focused_workspace_generated_roundtrip() {
         let e = DataChangeEvent::FocusedWorkspaceGenerated {
@@ -10,6 +10,9 @@
 //! Each event carries its `id:` field so the browser (or reconnecting client)
 //! can track the last-seen event automatically.
 //!
+//! Query parameters (`workspace_id`, `plan_id`, `types`) narrow both the
+//! replay and the live stream; see [`super::filter`].
+//!
 //! ## Access logging
 //!
 //! On connect the client's IP address is extracted from `X-Forwarded-For` /
@@ -21,30 +24,35 @@
 use std::time::{Duration, Instant};
 
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::{HeaderMap, StatusCode},
     response::{sse::Event, IntoResponse, Sse},
 };
 use futures_util::stream::Stream;
 use tokio::sync::broadcast;
 
+use super::filter::EventFilter;
 use super::{EventsHandle, StampedEvent};
 
 // ---------------------------------------------------------------------------
 // Disconnect guard — logs duration when the stream is dropped
 // ---------------------------------------------------------------------------
 
-/// RAII guard that emits a `tracing::info!` disconnect log when dropped.
+/// RAII guard that emits a `tracing::info!` disconnect log and removes the
+/// subscription from the handle's registry when dropped.
 ///
 /// Placed inside the `unfold` state tuple so it is destroyed exactly once,
 /// when axum tears down the response stream after the client disconnects.
 struct DisconnectGuard {
-    client_ip:    String,
-    connect_time: Instant,
+    client_ip:       String,
+    connect_time:    Instant,
+    handle:          EventsHandle,
+    subscription_id: u64,
 }
 
 impl Drop for DisconnectGuard {
     fn drop(&mut self) {
+        self.handle.unregister_subscription(self.subscription_id);
         tracing::info!(
             "[events/sse] client disconnected ip={} duration={}s",
             self.client_ip,
@@ -62,6 +70,7 @@ impl Drop for DisconnectGuard {
 /// Returns 503 when `events.enabled = false` in the supervisor configuration.
 pub async fn events_sse_handler(
     State(handle): State<EventsHandle>,
+    Query(filter): Query<EventFilter>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
     if !handle.config.enabled {
@@ -78,9 +87,19 @@ pub async fn events_sse_handler(
         .unwrap_or_else(|| "unknown".to_string());
 
     let connect_time = Instant::now();
-    tracing::info!("[events/sse] client connected ip={client_ip}");
+    if filter.is_empty() {
+        tracing::info!("[events/sse] client connected ip={client_ip}");
+    } else {
+        tracing::info!("[events/sse] client connected ip={client_ip} filter={filter:?}");
+    }
 
-    let guard = DisconnectGuard { client_ip, connect_time };
+    let subscription_id = handle.register_subscription(&client_ip, filter.clone());
+    let guard = DisconnectGuard {
+        client_ip,
+        connect_time,
+        handle: handle.clone(),
+        subscription_id,
+    };
 
     // Parse Last-Event-Id for replay.
     let last_id: Option<u64> = headers
@@ -91,7 +110,9 @@ pub async fn events_sse_handler(
     // Collect replay backlog *before* subscribing to the live channel so we
     // don't introduce a gap between replay and live events.
     let backlog: Vec<StampedEvent> = if let Some(since) = last_id {
-        handle.replay_since(since).await
+        let mut events = handle.replay_since(since).await;
+        events.retain(|e| filter.matches(&e.data));
+        events
     } else {
         vec![]
     };
@@ -100,7 +121,7 @@ pub async fn events_sse_handler(
     let rx = handle.tx.subscribe();
 
     let heartbeat_secs = handle.config.heartbeat_interval;
-    let stream = event_stream(backlog, rx, guard);
+    let stream = event_stream(backlog, rx, filter, guard);
 
     Sse::new(stream)
         .keep_alive(
@@ -116,30 +137,33 @@ pub async fn events_sse_handler(
 // ---------------------------------------------------------------------------
 
 /// Produces an SSE stream that first replays `backlog` events, then follows
-/// the live `broadcast::Receiver<StampedEvent>`.
+/// the live `broadcast::Receiver<StampedEvent>`, skipping live events that
+/// `filter` rejects.  `backlog` is expected to be pre-filtered.
 ///
 /// `guard` is held in the state tuple and dropped (logging the disconnect)
 /// when the returned stream is dropped by axum.
 fn event_stream(
     backlog: Vec<StampedEvent>,
     rx: broadcast::Receiver<StampedEvent>,
+    filter: EventFilter,
     guard: DisconnectGuard,
 ) -> impl Stream<Item = Result<Event, std::convert::Infallible>> {
     futures_util::stream::unfold(
-        (backlog, rx, guard),
-        |(mut backlog, mut rx, guard)| async move {
+        (backlog, rx, filter, guard),
+        |(mut backlog, mut rx, filter, guard)| async move {
             // --- Phase 1: drain the replay backlog ---
             if !backlog.is_empty() {
                 let stamped = backlog.remove(0);
-                return Some((Ok(encode_event(&stamped)), (backlog, rx, guard)));
+                return Some((Ok(encode_event(&stamped)), (backlog, rx, filter, guard)));
             }
 
             // --- Phase 2: live broadcast ---
             loop {
                 match rx.recv().await {
-                    Ok(stamped) => {
-                        return Some((Ok(encode_event(&stamped)), (vec![], rx, guard)));
+                    Ok(stamped) if filter.matches(&stamped.data) => {
+                        return Some((Ok(encode_event(&stamped)), (vec![], rx, filter, guard)));
                     }
+                    Ok(_) => continue,
                     // Lagged: the receiver fell behind; skip the missed events
                     // and continue.  The `Last-Event-Id` mechanism on the
                     // *next* reconnect will handle gaps.
@@ -266,4 +290,74 @@ mod tests {
 
         assert_eq!(response.status(), 503);
     }
+
+    /// Verify query filters apply to both `Last-Event-Id` replay and the live
+    /// stream, and that the subscription shows up on the handle.
+    #[tokio::test]
+    async fn sse_endpoint_filters_replay_and_live_events() {
+        use http_body_util::BodyExt as _;
+
+        let handle = EventsHandle::new(EventsConfig::default());
+        for (ws, plan) in [("ws1", "p1"), ("ws2", "p2"), ("ws1", "p3")] {
+            handle
+                .emit(DataChangeEvent::PlanUpdated { workspace_id: ws.into(), plan_id: plan.into() })
+                .await;
+        }
+
+        let app = Router::new()
+            .route("/supervisor/events", get(events_sse_handler))
+            .with_state(handle.clone());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/supervisor/events?workspace_id=ws1&types=plan_*")
+                    .header("last-event-id", "0")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let subs = handle.subscriptions();
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs[0].filter.workspace_id.as_deref(), Some("ws1"));
+        assert_eq!(subs[0].filter.types, vec!["plan_*"]);
+
+        let emit_handle = handle.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            emit_handle
+                .emit(DataChangeEvent::PlanUpdated { workspace_id: "ws2".into(), plan_id: "p4".into() })
+                .await;
+            emit_handle
+                .emit(DataChangeEvent::WorkspaceChanged { workspace_id: "ws1".into() })
+                .await;
+            emit_handle
+                .emit(DataChangeEvent::PlanCreated { workspace_id: "ws1".into(), plan_id: "p5".into() })
+                .await;
+        });
+
+        let mut body = response.into_body();
+        let mut seen = String::new();
+        let _ = tokio::time::timeout(Duration::from_secs(2), async {
+            while let Some(Ok(frame)) = body.frame().await {
+                if let Ok(bytes) = frame.into_data() {
+                    seen.push_str(&String::from_utf8_lossy(&bytes));
+                    if seen.contains("p5") {
+                        break;
+                    }
+                }
+            }
+        })
+        .await;
+
+        assert!(seen.contains("\"p1\"") && seen.contains("\"p3\"") && seen.contains("\"p5\""), "{seen}");
+        assert!(!seen.contains("ws2"), "filtered workspace leaked: {seen}");
+        assert!(!seen.contains("workspace_changed"), "filtered type leaked: {seen}");
+
+        drop(body);
+        assert!(handle.subscriptions().is_empty());
+    }
 }
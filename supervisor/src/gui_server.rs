@@ -12,13 +12,14 @@
 //! | GET    | `/runtime/recent` | Recent per-component runtime output    |
 //! | GET    | `/runtime/capture` | Runtime capture on/off state           |
 //! | POST   | `/runtime/capture` | Runtime capture on/off toggle          |
+//! | GET    | `/runtime/logs/:service` | Tail (or follow) a service's log file |
 //!
 //! The request body for `/gui/launch` may include optional routing metadata
 //! (`workspace_id`, `session_id`, `agent`) that is logged for observability
 //! but does not affect the underlying `launch_form_app` call.
 
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex as StdMutex, RwLock};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock, RwLock};
 
 use axum::{
     Json, Router,
@@ -114,6 +115,17 @@ pub struct RuntimeRecentQuery {
     pub limit: Option<usize>,
 }
 
+/// Query for `GET /runtime/logs/:service`.
+#[derive(Debug, Deserialize)]
+pub struct RuntimeLogsQuery {
+    /// Lines of history to return first (default 200; `0` = none).
+    #[serde(default)]
+    pub lines: Option<usize>,
+    /// Keep the connection open as an SSE stream of new lines.
+    #[serde(default)]
+    pub follow: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RuntimeCaptureRequest {
     pub enabled: bool,
@@ -249,6 +261,69 @@ async fn runtime_recent_handler(
     }))
 }
 
+/// Tail a service's on-disk log.  With `follow=true` the history is sent as
+/// SSE `data:` frames followed by live lines for every component written to
+/// the same log file (live lines require runtime output capture to be
+/// enabled).
+async fn runtime_logs_handler(
+    Path(service): Path<String>,
+    Query(query): Query<RuntimeLogsQuery>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use axum::response::sse::{Event, KeepAlive, Sse};
+
+    let lines = query.lines.unwrap_or(crate::service_logs::DEFAULT_TAIL_LINES);
+    let items = if lines == 0 {
+        Vec::new()
+    } else {
+        match crate::service_logs::tail(&service, lines).await {
+            Ok(items) => items,
+            Err(e) => {
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({ "ok": false, "error": e })),
+                )
+                    .into_response();
+            }
+        }
+    };
+
+    if !query.follow {
+        return Json(json!({
+            "ok": true,
+            "data": {
+                "service": service,
+                "count": items.len(),
+                "items": items,
+            }
+        }))
+        .into_response();
+    }
+
+    let rx = crate::runtime_output::subscribe();
+    let key = crate::service_logs::file_key(&service);
+    let stream = futures_util::stream::unfold(
+        (items.into_iter(), rx, key),
+        |(mut backlog, mut rx, key)| async move {
+            if let Some(item) = backlog.next() {
+                let data = serde_json::to_string(&item).unwrap_or_default();
+                return Some((Ok::<_, std::convert::Infallible>(Event::default().data(data)), (backlog, rx, key)));
+            }
+            loop {
+                match rx.recv().await {
+                    Ok(event) if crate::service_logs::file_key(&event.component) == key => {
+                        let data = serde_json::to_string(&event).unwrap_or_default();
+                        return Some((Ok(Event::default().data(data)), (backlog, rx, key)));
+                    }
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
 async fn runtime_capture_get_handler() -> Json<serde_json::Value> {
     Json(json!({
         "ok": true,
@@ -540,6 +615,7 @@ pub fn build_router(state: GuiServerState) -> Router {
         .route("/runtime/recent", get(runtime_recent_handler))
         .route("/runtime/capture", get(runtime_capture_get_handler))
         .route("/runtime/capture", post(runtime_capture_set_handler))
+        .route("/runtime/logs/:service", get(runtime_logs_handler))
         .route("/chatbot/chat", post(chatbot_chat_handler))
         .route("/chatbot/config", get(chatbot_config_get_handler).post(chatbot_config_set_handler))
         .route("/chatbot/status/:id", get(chatbot_status_handler))
@@ -564,6 +640,14 @@ pub fn build_router(state: GuiServerState) -> Router {
 // Entry point
 // ---------------------------------------------------------------------------
 
+static LOCAL_BASE_URL: OnceLock<String> = OnceLock::new();
+
+/// `http://host:port` of the running GUI server as reachable from this
+/// machine, once [`start`] has bound its listener.
+pub fn local_base_url() -> Option<&'static str> {
+    LOCAL_BASE_URL.get().map(String::as_str)
+}
+
 /// Bind and serve the GUI HTTP server on `{bind_address}:{port}`.
 ///
 /// Pass `bind_address = "0.0.0.0"` (the default) so the server is reachable
@@ -586,6 +670,11 @@ pub async fn start(
     let addr = format!("{bind_address}:{port}");
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     eprintln!("[supervisor] GUI HTTP server listening on http://{addr}");
+    let local_host = match bind_address {
+        "0.0.0.0" | "::" | "[::]" => "127.0.0.1",
+        other => other,
+    };
+    let _ = LOCAL_BASE_URL.set(format!("http://{local_host}:{}", listener.local_addr()?.port()));
     let state = GuiServerState {
         form_apps,
         chatbot_config,
@@ -12,6 +12,7 @@ pub mod proxy;
 pub mod registry;
 pub mod runtime_output;
 pub mod runner;
+pub mod service_logs;
 pub mod tray_tooltip;
 
 use std::sync::{Arc, RwLock};
@@ -22,6 +22,8 @@ use supervisor::runner::ServiceRunner;
 use supervisor::runner::configured::ConfiguredProcessRunner;
 use supervisor::runner::container::ContainerRunner;
 use supervisor::runner::dashboard::DashboardRunner;
+use supervisor::runner::crash_loop::{CrashLoopPolicy, RestartDecision};
+use supervisor::runner::dependency;
 use supervisor::runner::node::NodeRunner;
 use supervisor::runner::terminal::InteractiveTerminalRunner;
 use supervisor::tray_tooltip::{ServiceSummary, TrayAction, TrayComponent,
@@ -121,6 +123,13 @@ struct ManagedCustomService {
     runner: ConfiguredProcessRunner,
     started_at: Option<std::time::Instant>,
     health_failures: u32,
+    /// Registry names of the services this one waits for before starting.
+    dependencies: Vec<String>,
+    /// Set when the configured dependency graph is invalid; the service is
+    /// never started and reports this as its error.
+    dependency_error: Option<String>,
+    /// Waiting for dependencies; retried on the custom health tick.
+    blocked: bool,
 }
 
 impl ManagedCustomService {
@@ -129,6 +138,9 @@ impl ManagedCustomService {
             runner: ConfiguredProcessRunner::new(definition),
             started_at: None,
             health_failures: 0,
+            dependencies: Vec::new(),
+            dependency_error: None,
+            blocked: false,
         }
     }
 
@@ -195,7 +207,61 @@ fn build_custom_services(definitions: &[ServerDefinition]) -> Vec<ManagedCustomS
         services.push(ManagedCustomService::new(normalized));
     }
 
-    services
+    let graph: Vec<(&str, &[String])> = services
+        .iter()
+        .map(|service| (service.service_name(), service.runner.depends_on()))
+        .collect();
+    let order = match dependency::start_order(&graph) {
+        Ok(order) => order,
+        Err(error) => {
+            eprintln!("[supervisor] configured services will not start: {error}");
+            for service in &mut services {
+                service.dependency_error = Some(error.to_string());
+            }
+            return services;
+        }
+    };
+
+    // Resolve each dependency to the name the registry tracks it under.
+    let names: Vec<String> = services
+        .iter()
+        .map(|service| service.service_name().to_string())
+        .collect();
+    for service in &mut services {
+        service.dependencies = service
+            .runner
+            .depends_on()
+            .iter()
+            .filter_map(|dep| {
+                let dep = dep.trim();
+                names
+                    .iter()
+                    .find(|name| name.eq_ignore_ascii_case(dep))
+                    .cloned()
+                    .or_else(|| dependency::canonical_builtin(dep).map(str::to_string))
+            })
+            .collect();
+    }
+
+    let mut slots: Vec<Option<ManagedCustomService>> = services.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|index| slots[index].take())
+        .collect()
+}
+
+/// Dependencies of `service` that are not running yet.
+async fn pending_dependencies(
+    service: &ManagedCustomService,
+    registry: &Arc<tokio::sync::Mutex<supervisor::control::registry::Registry>>,
+) -> Vec<String> {
+    let reg = registry.lock().await;
+    service
+        .dependencies
+        .iter()
+        .filter(|dep| !reg.is_service_running(dep))
+        .cloned()
+        .collect()
 }
 
 fn find_custom_service_mut<'a>(
@@ -214,6 +280,28 @@ async fn start_custom_service(
     tray: &mut supervisor::tray_tooltip::TrayLifecycle,
 ) {
     let service_name = service.service_name().to_string();
+    if let Some(error) = service.dependency_error.clone() {
+        set_service_failed(registry, tray, &service_name, error).await;
+        return;
+    }
+
+    // A service whose dependencies are not running yet is left blocked; the
+    // main loop retries it when the registry reports a status change.
+    let pending = pending_dependencies(service, registry).await;
+    if !pending.is_empty() {
+        if !service.blocked {
+            println!(
+                "[supervisor] configured service '{}' waiting on: {}",
+                service_name,
+                pending.join(", ")
+            );
+        }
+        service.blocked = true;
+        set_service_blocked(registry, tray, &service_name, pending).await;
+        return;
+    }
+    service.blocked = false;
+
     println!("[supervisor] starting configured service '{}'...", service_name);
     set_service_status(registry, tray, &service_name, ServiceStatus::Starting).await;
     match service.runner.start().await {
@@ -226,14 +314,7 @@ async fn start_custom_service(
         Err(error) => {
             service.started_at = None;
             service.health_failures = 0;
-            set_service_status(
-                registry,
-                tray,
-                &service_name,
-                ServiceStatus::Error(error.to_string()),
-            )
-            .await;
-            set_service_error(registry, tray, &service_name, error.to_string()).await;
+            set_service_failed(registry, tray, &service_name, error.to_string()).await;
         }
     }
 }
@@ -244,6 +325,7 @@ async fn stop_custom_service(
     tray: &mut supervisor::tray_tooltip::TrayLifecycle,
 ) {
     let service_name = service.service_name().to_string();
+    service.blocked = false;
     set_service_status(registry, tray, &service_name, ServiceStatus::Stopping).await;
     match service.runner.stop().await {
         Ok(()) => {
@@ -254,14 +336,7 @@ async fn stop_custom_service(
         Err(error) => {
             service.started_at = None;
             service.health_failures = 0;
-            set_service_status(
-                registry,
-                tray,
-                &service_name,
-                ServiceStatus::Error(error.to_string()),
-            )
-            .await;
-            set_service_error(registry, tray, &service_name, error.to_string()).await;
+            set_service_failed(registry, tray, &service_name, error.to_string()).await;
         }
     }
 }
@@ -286,14 +361,7 @@ async fn restart_custom_service(
         Err(error) => {
             service.started_at = None;
             service.health_failures = 0;
-            set_service_status(
-                registry,
-                tray,
-                &service_name,
-                ServiceStatus::Error(error.to_string()),
-            )
-            .await;
-            set_service_error(registry, tray, &service_name, error.to_string()).await;
+            set_service_failed(registry, tray, &service_name, error.to_string()).await;
         }
     }
 }
@@ -349,7 +417,7 @@ async fn check_custom_service_health(
                         "[supervisor] configured service '{}' failed health check twice — requesting restart",
                         service_name
                     );
-                    let _ = restart_tx.send(service_name).await;
+                    let _ = restart_tx.send(format!("crash:{service_name}")).await;
                 }
             }
         }
@@ -616,6 +684,18 @@ async fn supervisor_main() {
             }
 
             supervisor::runtime_output::set_enabled(cfg.runtime_output.enabled);
+            if cfg.runtime_output.files.enabled {
+                let log_dir = cfg
+                    .runtime_output
+                    .files
+                    .dir
+                    .clone()
+                    .unwrap_or_else(|| cfg.supervisor.data_dir.join("logs"));
+                match supervisor::service_logs::install(log_dir.clone(), cfg.runtime_output.files.clone()) {
+                    Ok(()) => println!("[supervisor] service logs: {}", log_dir.display()),
+                    Err(e) => eprintln!("[supervisor] service logs disabled ({}): {e}", log_dir.display()),
+                }
+            }
             println!(
                 "[supervisor] runtime output capture: {}",
                 if cfg.runtime_output.enabled {
@@ -826,6 +906,10 @@ async fn supervisor_main() {
                     custom_service_names.iter().cloned(),
                 ),
             ));
+            registry.lock().await.set_crash_loop_policy(CrashLoopPolicy {
+                max_restarts: cfg.reconnect.crash_loop_max_restarts,
+                window: Duration::from_secs(cfg.reconnect.crash_loop_window_secs),
+            });
 
             let _service_registry = supervisor::registry::ServiceRegistry::shared();
 
@@ -1086,8 +1170,20 @@ async fn supervisor_main() {
                 buffer_size: cfg.events.buffer_size,
                 heartbeat_interval: cfg.events.heartbeat_interval,
                 replay_buffer_size: cfg.events.replay_buffer_size,
+                journal: supervisor::events::journal::EventJournalConfig {
+                    dir: Some(
+                        cfg.events
+                            .journal
+                            .dir
+                            .clone()
+                            .unwrap_or_else(|| cfg.supervisor.data_dir.join("events")),
+                    ),
+                    ..cfg.events.journal.clone()
+                },
             };
             let events_handle = supervisor::events::EventsHandle::new(events_config);
+            // Let the registry and form-app runner publish lifecycle events.
+            supervisor::events::install_publisher(&events_handle);
             let events_url = if cfg.events.enabled {
                 Some(format!("http://127.0.0.1:{}/supervisor/events", cfg.mcp.port))
             } else {
@@ -1237,13 +1333,16 @@ async fn supervisor_main() {
                         // One broadcast channel; VS Code instances subscribe via
                         // GET /supervisor/heartbeat (SSE) instead of polling.
                         let heartbeat_tx = supervisor::proxy::heartbeat_channel();
-                        let pool_for_hb = Arc::clone(&pool);
+                        // Routing reads the pool's published snapshot, never the
+                        // pool lock, which health probes hold across awaits.
+                        let pool_routing = pool.read().await.routing();
+                        let routing_for_hb = pool_routing.clone();
                         supervisor::proxy::start_heartbeat_ticker(
                             heartbeat_tx.clone(),
                             Duration::from_secs(10),
                             proxy_port,
                             base_port,
-                            Arc::new(move || pool_for_hb.try_read().map(|g| g.ports().len()).unwrap_or(0)),
+                            Arc::new(move || routing_for_hb.borrow().size),
                             {
                                 let flag = Arc::clone(&mcp_healthy_flag);
                                 Arc::new(move || flag.load(Ordering::Relaxed))
@@ -1259,8 +1358,23 @@ async fn supervisor_main() {
                             None
                         };
                         let mcp_health_tx_for_proxy = mcp_health_tx.clone();
+                        let routing_for_dispatch = pool_routing.clone();
+                        let routing_for_liveness = pool_routing.clone();
+                        let routing_for_primary = pool_routing;
                         tokio::spawn(async move {
-                            let dispatch_port_fn: Arc<dyn Fn() -> u16 + Send + Sync> = Arc::new(move || base_port);
+                            // New sessions go to the least-loaded instance; the
+                            // proxy keeps existing sessions pinned to their owner.
+                            let dispatch_port_fn: Arc<dyn Fn() -> u16 + Send + Sync> = Arc::new(move || {
+                                routing_for_dispatch.borrow().dispatch_port
+                            });
+                            let instance_live_fn: Arc<dyn Fn(u16) -> bool + Send + Sync> = Arc::new(move |port| {
+                                routing_for_liveness.borrow().is_live(port)
+                            });
+                            // Session-less endpoints follow the primary, which moves
+                            // off base_port if that port is lost to another process.
+                            let primary_port_fn: Arc<dyn Fn() -> u16 + Send + Sync> = Arc::new(move || {
+                                routing_for_primary.borrow().primary_port
+                            });
                             // Retry the proxy bind up to 3 times with a 2-second delay.
                             // A single transient failure (e.g. the SO_REUSEADDR race on
                             // Windows before the TIME_WAIT entry expires) should not
@@ -1274,9 +1388,10 @@ async fn supervisor_main() {
                                 }
                                 match supervisor::proxy::start_proxy(
                                     proxy_bind.clone(),
-                                    base_port,
+                                    Arc::clone(&primary_port_fn),
                                     fallback_proxy_port,
                                     Arc::clone(&dispatch_port_fn),
+                                    Arc::clone(&instance_live_fn),
                                     heartbeat_tx.clone(),
                                     Some(events_for_proxy.clone()),
                                 )
@@ -1322,8 +1437,20 @@ async fn supervisor_main() {
                             loop {
                                 tick.tick().await;
 
-                                // Refresh health of all instances.
-                                pool_for_poll.write().await.refresh_health().await;
+                                // Refresh health of all instances.  Crashed instances
+                                // count towards the MCP crash-loop circuit, which may
+                                // refuse to respawn them.
+                                let crashed = pool_for_poll.write().await.refresh_health().await;
+                                if !crashed.is_empty() {
+                                    let decisions: Vec<_> = {
+                                        let mut reg = reg_for_poll.lock().await;
+                                        crashed
+                                            .into_iter()
+                                            .map(|port| (port, reg.record_crash_restart("mcp")))
+                                            .collect()
+                                    };
+                                    pool_for_poll.write().await.respawn_crashed(&decisions).await;
+                                }
 
                                 // Collect connections from every instance.
                                 let ports = pool_for_poll.read().await.ports();
@@ -1383,10 +1510,24 @@ async fn supervisor_main() {
 
                                 // Auto-scale if needed.
                                 let scaled = pool_for_poll.write().await.maybe_scale_up(&all_connections).await;
-                                if scaled {
+                                if let Some(t) = scaled {
                                     eprintln!("[pool] scaled up to {} instance(s)", pool_for_poll.read().await.ports().len());
+                                    reg_for_poll.lock().await.push_pool_event(t.port, t.old_state, t.new_state, &t.reason);
+                                } else {
+                                    // Drain idle instances / retire drained ones.
+                                    let transitions = pool_for_poll.write().await.maybe_scale_down(&all_connections).await;
+                                    if !transitions.is_empty() {
+                                        let mut reg = reg_for_poll.lock().await;
+                                        for t in &transitions {
+                                            reg.push_pool_event(t.port, t.old_state, t.new_state, &t.reason);
+                                        }
+                                    }
                                 }
 
+                                // Publish port reservations + slot states for ListMcpInstances.
+                                let slots = pool_for_poll.read().await.slots();
+                                reg_for_poll.lock().await.sync_mcp_slots(slots);
+
                                 // ── Push MCP monitoring + event stats to the Qt GUI ────
                                 {
                                     let total_conns = all_connections.len() as i32;
@@ -1525,7 +1666,7 @@ async fn supervisor_main() {
                                 if failures >= 2 {
                                     eprintln!("[supervisor] dashboard appears dead — requesting restart");
                                     failures = 0;
-                                    let _ = restart_tx_dash.send("dashboard".to_string()).await;
+                                    let _ = restart_tx_dash.send("crash:dashboard".to_string()).await;
                                     tokio::time::sleep(Duration::from_secs(90)).await;
                                     interval.reset();
                                 }
@@ -1575,7 +1716,7 @@ async fn supervisor_main() {
                                 if failures >= 2 {
                                     eprintln!("[supervisor] fallback API appears dead — requesting restart");
                                     failures = 0;
-                                    let _ = restart_tx_fallback.send("fallback_api".to_string()).await;
+                                    let _ = restart_tx_fallback.send("crash:fallback_api".to_string()).await;
                                     tokio::time::sleep(Duration::from_secs(90)).await;
                                     interval.reset();
                                 }
@@ -1623,7 +1764,7 @@ async fn supervisor_main() {
                                 if failures >= 2 {
                                     eprintln!("[supervisor] CLI MCP appears dead — requesting restart");
                                     failures = 0;
-                                    let _ = restart_tx_cli_mcp.send("cli_mcp".to_string()).await;
+                                    let _ = restart_tx_cli_mcp.send("crash:cli_mcp".to_string()).await;
                                     tokio::time::sleep(Duration::from_secs(90)).await;
                                     interval.reset();
                                 }
@@ -1645,6 +1786,18 @@ async fn supervisor_main() {
             let fw_tx_clone = fw_tx.clone();
             tokio::spawn(async move {
                 while let Ok(event) = event_rx.recv().await {
+                    if let supervisor::events::DataChangeEvent::OperatorAlert { message, .. } = &event.data {
+                        if let Some(qt) = supervisor::cxxqt_bridge::SUPERVISOR_QT.get() {
+                            let msg = message.clone();
+                            let _ = qt.queue(move |mut obj| {
+                                obj.as_mut()
+                                    .set_tray_notification_text(cxx_qt_lib::QString::from(&msg));
+                                obj.as_mut()
+                                    .set_tray_notification_text(cxx_qt_lib::QString::from(""));
+                            });
+                        }
+                        continue;
+                    }
                     if let supervisor::events::DataChangeEvent::FocusedWorkspaceGenerated {
                         plan_id,
                         file_path,
@@ -1689,6 +1842,7 @@ async fn supervisor_main() {
             let mut tray_poll_tick = tokio::time::interval(Duration::from_millis(150));
             let mut uptime_interval = tokio::time::interval(Duration::from_secs(30));
             let mut custom_health_interval = tokio::time::interval(Duration::from_secs(5));
+            let status_changes = registry.lock().await.status_changes();
             loop {
                 tokio::select! {
                     _ = tokio::signal::ctrl_c() => {
@@ -1716,6 +1870,48 @@ async fn supervisor_main() {
                     }
                     restart_command = restart_dispatch_rx.recv() => {
                         if let Some(service_name) = restart_command {
+                            // "crash:<service>" prefix — watchdog restart, counted by the
+                            // crash-loop breaker, which may refuse it.
+                            let service_name = match service_name.strip_prefix("crash:") {
+                                Some(svc) => {
+                                    let name = dependency::canonical_builtin(svc)
+                                        .map(str::to_string)
+                                        .or_else(|| {
+                                            find_custom_service_mut(&mut custom_services, svc)
+                                                .map(|service| service.service_name().to_string())
+                                        })
+                                        .unwrap_or_else(|| svc.trim().to_string());
+                                    let decision = registry.lock().await.record_crash_restart(&name);
+                                    match decision {
+                                        RestartDecision::Allow => svc.to_string(),
+                                        RestartDecision::Tripped(circuit) => {
+                                            let message = circuit.describe(&name);
+                                            eprintln!("[supervisor] crash loop: {message}");
+                                            set_service_error(&registry, &mut tray, &name, message).await;
+                                            continue;
+                                        }
+                                        RestartDecision::Open => {
+                                            eprintln!("[supervisor] crash-loop circuit open for '{name}' — restart skipped");
+                                            continue;
+                                        }
+                                    }
+                                }
+                                None => service_name,
+                            };
+                            // "reset:<service>" prefix — its crash-loop circuit was reset.
+                            // The MCP pool respawns only the instances the circuit
+                            // refused; any other service restarts as usual.
+                            let service_name = match service_name.strip_prefix("reset:") {
+                                Some("mcp") if mcp_pool_runtime.is_some() => {
+                                    if let Some(pool) = mcp_pool_runtime.as_ref() {
+                                        let ports = pool.write().await.respawn_refused().await;
+                                        eprintln!("[pool] circuit reset — respawned MCP instance(s) on {ports:?}");
+                                    }
+                                    continue;
+                                }
+                                Some(svc) => svc.to_string(),
+                                None => service_name,
+                            };
                             // "stop:<service>" prefix — shut down without restarting
                             if let Some(svc) = service_name.strip_prefix("stop:") {
                                 let svc = svc.to_string();
@@ -1786,6 +1982,20 @@ async fn supervisor_main() {
                                 }
                                 continue;
                             }
+                            // "scale_down_mcp:<port|auto>" — drain one pool instance
+                            if let Some(target) = service_name.strip_prefix("scale_down_mcp:") {
+                                let port = target.trim().parse::<u16>().ok();
+                                match mcp_pool_runtime.as_ref() {
+                                    Some(pool) => match pool.write().await.force_scale_down(port) {
+                                        Ok(t) => {
+                                            registry.lock().await.push_pool_event(t.port, t.old_state, t.new_state, &t.reason);
+                                        }
+                                        Err(e) => eprintln!("[pool] scale-down rejected: {e}"),
+                                    },
+                                    None => eprintln!("[pool] scale-down ignored: MCP pool is not running"),
+                                }
+                                continue;
+                            }
                             // "set_dashboard_variant:<variant>" — hot-swap the dashboard variant
                             if let Some(variant_str) = service_name.strip_prefix("set_dashboard_variant:") {
                                 use supervisor::config::DashboardVariant;
@@ -1901,9 +2111,21 @@ async fn supervisor_main() {
                     }
                     _ = custom_health_interval.tick() => {
                         for service in &mut custom_services {
+                            if service.blocked {
+                                continue;
+                            }
                             check_custom_service_health(service, &registry, &mut tray, &restart_tx).await;
                         }
                     }
+                    // A service changed status: retry configured services that were
+                    // blocked on their dependencies, in dependency order.
+                    _ = status_changes.notified(), if custom_services.iter().any(|s| s.blocked) => {
+                        for service in &mut custom_services {
+                            if service.blocked {
+                                start_custom_service(service, &registry, &mut tray).await;
+                            }
+                        }
+                    }
                     mcp_health_signal = mcp_health_rx.recv() => {
                         if let Some(signal) = mcp_health_signal {
                             match signal {
@@ -1970,8 +2192,15 @@ async fn supervisor_main() {
             }
             println!("[supervisor] shutting down...");
 
-            // Stop services in reverse dependency order: dashboard first
-            // (depends on MCP), then MCP, then terminal pool.
+            // Stop services in reverse of startup order.  [[servers]] entries
+            // started last and may depend on built-ins, so they stop first,
+            // dependents before the services they depend on.
+            for service in custom_services.iter_mut().rev() {
+                stop_custom_service(service, &registry, &mut tray).await;
+            }
+
+            // Then the built-ins: dashboard (depends on MCP), then MCP, then
+            // terminal pool.
             if cfg.dashboard.enabled {
                 set_service_status(&registry, &mut tray, "dashboard", ServiceStatus::Stopping).await;
                 if let Err(e) = dashboard_runner.stop().await {
@@ -1984,10 +2213,6 @@ async fn supervisor_main() {
                 }
             }
 
-            for service in &mut custom_services {
-                stop_custom_service(service, &registry, &mut tray).await;
-            }
-
             if cfg.fallback_api.enabled {
                 set_service_status(&registry, &mut tray, "fallback_api", ServiceStatus::Stopping).await;
                 if let Err(e) = fallback_api_runner.stop().await {
@@ -2047,6 +2272,9 @@ async fn supervisor_main() {
                 delete_ports_manifest(dir);
             }
 
+            // Persist events still queued for the journal before exiting.
+            events_handle.flush_journal().await;
+
             println!("Supervisor stopped.");
 
             // Signal Qt to hide the tray icon before the process exits.  This
@@ -2239,6 +2467,43 @@ async fn set_service_error(
     push_qt_status(&snapshot);
 }
 
+/// Mark `service` as failed with `error`: sets both its status and its last
+/// error in one registry update.
+async fn set_service_failed(
+    registry: &Arc<tokio::sync::Mutex<supervisor::control::registry::Registry>>,
+    tray: &mut supervisor::tray_tooltip::TrayLifecycle,
+    service: &str,
+    error: String,
+) {
+    let (snapshot, focused_ws) = {
+        let mut reg = registry.lock().await;
+        reg.set_service_status(service, ServiceStatus::Error(error.clone()));
+        reg.set_service_error(service, error);
+        (reg.health_snapshot(), reg.focused_workspace.clone())
+    };
+    let tooltip = build_runtime_tooltip(&snapshot, focused_ws.as_ref());
+    tray.update_tooltip_text(&tooltip);
+    tray.update_icon_for_health_snapshot(&snapshot);
+    push_qt_status(&snapshot);
+}
+
+async fn set_service_blocked(
+    registry: &Arc<tokio::sync::Mutex<supervisor::control::registry::Registry>>,
+    tray: &mut supervisor::tray_tooltip::TrayLifecycle,
+    service: &str,
+    dependencies: Vec<String>,
+) {
+    let (snapshot, focused_ws) = {
+        let mut reg = registry.lock().await;
+        reg.set_service_blocked(service, dependencies);
+        (reg.health_snapshot(), reg.focused_workspace.clone())
+    };
+    let tooltip = build_runtime_tooltip(&snapshot, focused_ws.as_ref());
+    tray.update_tooltip_text(&tooltip);
+    tray.update_icon_for_health_snapshot(&snapshot);
+    push_qt_status(&snapshot);
+}
+
 async fn set_service_health_ok(
     registry: &Arc<tokio::sync::Mutex<supervisor::control::registry::Registry>>,
     tray: &mut supervisor::tray_tooltip::TrayLifecycle,
@@ -2323,6 +2588,12 @@ fn display_state_for_service(status: &str) -> String {
     if status.starts_with("error:") {
         return "Error".to_string();
     }
+    if status == "blocked" {
+        return "Waiting on dependencies".to_string();
+    }
+    if status == "circuit_open" {
+        return "Crash loop — restarts paused".to_string();
+    }
     status.to_string()
 }
 
@@ -2619,7 +2890,7 @@ async fn handle_restart_command(
                                 if failures >= 2 {
                                     eprintln!("[supervisor] terminal appears dead — requesting restart");
                                     failures = 0;
-                                    let _ = restart_tx_term.send("terminal".to_string()).await;
+                                    let _ = restart_tx_term.send("crash:terminal".to_string()).await;
                                     tokio::time::sleep(Duration::from_secs(90)).await;
                                     interval.reset();
                                 }
@@ -3,18 +3,22 @@
 //! VS Code (and any other client) connects to this proxy on the primary MCP
 //! port (e.g. 3457).  The proxy:
 //!
-//! 1. Routes `POST /mcp` requests to the current supervisor dispatch target.
+//! 1. Routes `POST /mcp` requests to the current supervisor dispatch target,
+//!    pinning each `Mcp-Session-Id` to the instance that created it (see
+//!    [`SessionAffinity`]).
 //! 2. Preserves MCP transport headers/body and streams chunked/SSE responses.
-//! 3. Proxies `/sse`, `/messages`, `/health`, `/sessions/*`, and
-//!    `/admin/*` to the primary instance (port `base_port`).
+//! 3. Proxies `/sse`, `/messages`, `/sessions/*`, `/admin/*` and other
+//!    session-less endpoints to the pool's current primary instance (the one
+//!    on `base_port`, or its replacement once that port has been lost).
 //! 4. Serves a pub/sub Server-Sent Events heartbeat on
 //!    `GET /supervisor/heartbeat` that all VS Code instances subscribe to
 //!    instead of doing individual health polls.
 //!
 //! The proxy runs as a plain `axum` HTTP server inside a `tokio::spawn`.
 
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use axum::body::Body;
 use axum::extract::{Request, State};
@@ -45,6 +49,107 @@ pub struct HeartbeatEvent {
 /// Channel capacity — we only need to buffer a handful of ticks.
 const HEARTBEAT_CAPACITY: usize = 4;
 
+// ---------------------------------------------------------------------------
+// Session affinity
+// ---------------------------------------------------------------------------
+
+/// MCP streamable-http session header (matched case-insensitively).
+const MCP_SESSION_HEADER: &str = "mcp-session-id";
+
+/// One row of the affinity table, as reported by `ListMcpAffinity`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct AffinityEntry {
+    pub session_id: String,
+    pub port: u16,
+    pub bound_at_ms: u64,
+    pub last_seen_ms: u64,
+}
+
+/// Maps `Mcp-Session-Id` values to the pool instance port that owns them.
+///
+/// A session is bound when an instance answers with a new session header and
+/// unbound on `DELETE /mcp`, when the owner stops accepting connections, or
+/// when the owner itself reports the session as unknown.  Cloning shares the
+/// underlying table.
+#[derive(Clone, Default)]
+pub struct SessionAffinity {
+    inner: Arc<Mutex<HashMap<String, AffinityEntry>>>,
+}
+
+impl SessionAffinity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Owning port for `session_id`, refreshing its `last_seen_ms`.
+    pub fn owner(&self, session_id: &str) -> Option<u16> {
+        let mut map = self.inner.lock().ok()?;
+        let entry = map.get_mut(session_id)?;
+        entry.last_seen_ms = now_ms();
+        Some(entry.port)
+    }
+
+    /// Pin `session_id` to `port`.  Re-binding an existing session to the
+    /// same port only refreshes `last_seen_ms`.
+    pub fn bind(&self, session_id: &str, port: u16) {
+        let Ok(mut map) = self.inner.lock() else { return };
+        let now = now_ms();
+        match map.get_mut(session_id) {
+            Some(entry) if entry.port == port => entry.last_seen_ms = now,
+            _ => {
+                map.insert(
+                    session_id.to_string(),
+                    AffinityEntry {
+                        session_id: session_id.to_string(),
+                        port,
+                        bound_at_ms: now,
+                        last_seen_ms: now,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Drop the mapping for `session_id`.  Returns the port it was bound to.
+    pub fn unbind(&self, session_id: &str) -> Option<u16> {
+        self.inner.lock().ok()?.remove(session_id).map(|e| e.port)
+    }
+
+    /// Drop every session whose owner `is_live` rejects.  Returns the number
+    /// of entries removed.
+    pub fn prune(&self, is_live: impl Fn(u16) -> bool) -> usize {
+        let Ok(mut map) = self.inner.lock() else { return 0 };
+        let before = map.len();
+        map.retain(|_, e| is_live(e.port));
+        before - map.len()
+    }
+
+    /// Snapshot of the table, ordered by port then session id.
+    pub fn snapshot(&self) -> Vec<AffinityEntry> {
+        let mut entries: Vec<AffinityEntry> = self
+            .inner
+            .lock()
+            .map(|m| m.values().cloned().collect())
+            .unwrap_or_default();
+        entries.sort_by(|a, b| (a.port, &a.session_id).cmp(&(b.port, &b.session_id)));
+        entries
+    }
+}
+
+static SESSION_AFFINITY: OnceLock<SessionAffinity> = OnceLock::new();
+
+/// Process-wide affinity table shared by the proxy and the control API.
+pub fn session_affinity() -> &'static SessionAffinity {
+    SESSION_AFFINITY.get_or_init(SessionAffinity::new)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 // ---------------------------------------------------------------------------
 // Shared proxy state
 // ---------------------------------------------------------------------------
@@ -54,10 +159,15 @@ const HEARTBEAT_CAPACITY: usize = 4;
 pub struct ProxyState {
     /// Callback that returns the current supervisor-native dispatch port.
     pub dispatch_port: Arc<dyn Fn() -> u16 + Send + Sync>,
+    /// Callback reporting whether a pool instance is still serving `port`.
+    pub instance_live: Arc<dyn Fn(u16) -> bool + Send + Sync>,
+    /// Session → owning instance table consulted by `/mcp`.
+    pub affinity: SessionAffinity,
     /// HTTP client used to forward requests to backends.
     pub client: reqwest::Client,
-    /// Base port — used for endpoints that are not session-specific.
-    pub base_port: u16,
+    /// Callback returning the primary instance's port — used for endpoints
+    /// that are not session-specific.
+    pub primary_port: Arc<dyn Fn() -> u16 + Send + Sync>,
     /// Optional fallback REST backend port used for `/api/fallback/*` routes.
     pub fallback_port: Option<u16>,
     /// Broadcast sender for heartbeat SSE events.
@@ -145,21 +255,92 @@ async fn forward(
 // /mcp handler — session-aware routing
 // ---------------------------------------------------------------------------
 
+/// Response for a request whose session can no longer be served.
+///
+/// The streamable-http transport treats `404` on a session-bearing request
+/// as "session terminated": the client drops its session id and sends a
+/// fresh `initialize`, which the proxy then routes to a live instance.
+fn session_lost_response(session_id: &str) -> Response<Body> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": {
+            "code": -32001,
+            "message": format!("Session not found: {session_id} (owning MCP instance is gone; re-initialize)")
+        }
+    })
+    .to_string();
+
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap_or_else(|_| Response::new(Body::from("{\"jsonrpc\":\"2.0\",\"id\":null,\"error\":{\"code\":-32001,\"message\":\"Session not found\"}}")))
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
 async fn mcp_handler(
     State(state): State<ProxyState>,
     req: Request,
 ) -> Result<Response<Body>, StatusCode> {
     let method = req.method().clone();
     let headers = req.headers().clone();
-
-    let backend_port = (state.dispatch_port)();
+    let session_id = header_str(&headers, MCP_SESSION_HEADER).map(str::to_string);
+
+    // Sticky routing: a known session goes to its owner, or is failed over
+    // with a clean re-init if the owner has gone away.  Unknown sessions
+    // (e.g. created before a supervisor restart) fall through to dispatch
+    // and are left for the backend to accept or reject.
+    let owner = session_id.as_deref().and_then(|id| state.affinity.owner(id));
+    if let (Some(id), Some(port)) = (session_id.as_deref(), owner) {
+        if !(state.instance_live)(port) {
+            state.affinity.unbind(id);
+            eprintln!("[proxy] session {id} owner on port {port} is gone — requesting re-init");
+            return Ok(session_lost_response(id));
+        }
+    }
+    let backend_port = owner.unwrap_or_else(|| (state.dispatch_port)());
 
     let target_url = format!("http://127.0.0.1:{backend_port}/mcp");
     let body = axum::body::to_bytes(req.into_body(), 16 * 1024 * 1024)
         .await
         .map_err(|_| StatusCode::BAD_REQUEST)?;
 
-    forward(&state.client, method, target_url, &headers, body).await
+    let response = match forward(&state.client, method.clone(), target_url, &headers, body).await {
+        Ok(response) => response,
+        Err(StatusCode::BAD_GATEWAY) if owner.is_some() => {
+            // The owner stopped accepting connections before the pool noticed.
+            let id = session_id.as_deref().unwrap_or_default();
+            state.affinity.unbind(id);
+            eprintln!("[proxy] session {id} owner on port {backend_port} unreachable — requesting re-init");
+            return Ok(session_lost_response(id));
+        }
+        Err(status) => return Err(status),
+    };
+
+    if let Some(id) = session_id.as_deref() {
+        let ended = method == Method::DELETE && response.status().is_success();
+        if ended || response.status() == StatusCode::NOT_FOUND {
+            state.affinity.unbind(id);
+        }
+    }
+    if let Some(new_id) = header_str(response.headers(), MCP_SESSION_HEADER) {
+        if method != Method::DELETE && response.status().is_success() {
+            if owner.is_none() {
+                state.affinity.prune(|p| (state.instance_live)(p));
+            }
+            state.affinity.bind(new_id, backend_port);
+        }
+    }
+
+    Ok(response)
 }
 
 // ---------------------------------------------------------------------------
@@ -209,7 +390,7 @@ async fn passthrough_handler(
     let uri = req.uri().clone();
     let headers = req.headers().clone();
     let path_and_query = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
-    let target_url = format!("http://127.0.0.1:{}{}", state.base_port, path_and_query);
+    let target_url = format!("http://127.0.0.1:{}{}", (state.primary_port)(), path_and_query);
     let body = axum::body::to_bytes(req.into_body(), 16 * 1024 * 1024)
         .await
         .map_err(|_| StatusCode::BAD_REQUEST)?;
@@ -345,12 +526,16 @@ pub fn start_heartbeat_ticker(
 /// Bind the proxy on `bind_addr` (e.g. `"127.0.0.1:3457"`).
 ///
 /// `dispatch_port` is a closure returning the active supervisor-native MCP
-/// dispatch port at call time.
+/// dispatch port at call time; it picks the instance for new sessions only.
+/// `instance_live` reports whether a pool instance still serves a port, so
+/// sessions pinned to a retired or dead instance can be failed over.
+/// `primary_port` returns the instance that serves session-less endpoints.
 pub async fn start_proxy(
     bind_addr: String,
-    base_port: u16,
+    primary_port: Arc<dyn Fn() -> u16 + Send + Sync>,
     fallback_port: Option<u16>,
     dispatch_port: Arc<dyn Fn() -> u16 + Send + Sync>,
+    instance_live: Arc<dyn Fn(u16) -> bool + Send + Sync>,
     heartbeat_tx: broadcast::Sender<HeartbeatEvent>,
     events_handle: Option<EventsHandle>,
 ) -> anyhow::Result<()> {
@@ -364,8 +549,10 @@ pub async fn start_proxy(
 
     let state = ProxyState {
         dispatch_port,
+        instance_live,
+        affinity: session_affinity().clone(),
         client,
-        base_port,
+        primary_port: Arc::clone(&primary_port),
         fallback_port,
         heartbeat_tx,
         events_handle,
@@ -387,7 +574,7 @@ pub async fn start_proxy(
     socket.set_reuseaddr(true)?;
     socket.bind(bind_addr_parsed)?;
     let listener = socket.listen(1024)?;
-    eprintln!("[proxy] MCP proxy listening on {bind_addr} → dispatch base_port={base_port}");
+    eprintln!("[proxy] MCP proxy listening on {bind_addr} → primary port={}", primary_port());
     axum::serve(listener, app).await?;
     Ok(())
 }
@@ -408,8 +595,10 @@ mod tests {
 
         ProxyState {
             dispatch_port: Arc::new(move || base_port),
+            instance_live: Arc::new(|_| true),
+            affinity: SessionAffinity::new(),
             client,
-            base_port,
+            primary_port: Arc::new(move || base_port),
             fallback_port,
             heartbeat_tx: heartbeat_channel(),
             events_handle: None,
@@ -499,4 +688,172 @@ mod tests {
         let body = body_to_string(response.into_body()).await;
         assert!(body.contains("fallback-backend"));
     }
+
+    // -----------------------------------------------------------------------
+    // Session affinity
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn affinity_binds_rebinds_and_unbinds() {
+        let affinity = SessionAffinity::new();
+        assert_eq!(affinity.owner("s1"), None);
+
+        affinity.bind("s1", 3460);
+        affinity.bind("s2", 3461);
+        assert_eq!(affinity.owner("s1"), Some(3460));
+
+        affinity.bind("s1", 3462);
+        assert_eq!(affinity.owner("s1"), Some(3462));
+
+        assert_eq!(affinity.unbind("s1"), Some(3462));
+        assert_eq!(affinity.owner("s1"), None);
+        assert_eq!(affinity.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn affinity_prune_drops_sessions_of_dead_owners() {
+        let affinity = SessionAffinity::new();
+        affinity.bind("a", 3460);
+        affinity.bind("b", 3461);
+        affinity.bind("c", 3461);
+
+        assert_eq!(affinity.prune(|p| p == 3460), 2);
+        let snapshot = affinity.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].session_id, "a");
+    }
+
+    /// Spawn a fake MCP backend that answers `/mcp` with its own port in the
+    /// body and hands out `session-<port>` on requests without a session.
+    async fn spawn_mcp_backend() -> (u16, tokio::task::JoinHandle<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind backend");
+        let port = listener.local_addr().expect("local addr").port();
+        let backend = Router::new().route(
+            "/mcp",
+            any(move |headers: HeaderMap| async move {
+                let mut builder = Response::builder().status(StatusCode::OK);
+                if !headers.contains_key(MCP_SESSION_HEADER) {
+                    builder = builder.header("Mcp-Session-Id", format!("session-{port}"));
+                }
+                builder.body(Body::from(port.to_string())).expect("response")
+            }),
+        );
+        let task = tokio::spawn(async move {
+            let _ = axum::serve(listener, backend).await;
+        });
+        (port, task)
+    }
+
+    fn mcp_request(method: &str, session_id: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().uri("/mcp").method(method);
+        if let Some(id) = session_id {
+            builder = builder.header("Mcp-Session-Id", id);
+        }
+        builder.body(Body::from("{}")).expect("request")
+    }
+
+    #[tokio::test]
+    async fn mcp_session_sticks_to_owner_after_rebalance() {
+        use std::sync::atomic::{AtomicU16, Ordering};
+
+        let (port_a, task_a) = spawn_mcp_backend().await;
+        let (port_b, task_b) = spawn_mcp_backend().await;
+        let dispatch = Arc::new(AtomicU16::new(port_a));
+        let mut state = make_state(port_a, None);
+        state.dispatch_port = {
+            let dispatch = Arc::clone(&dispatch);
+            Arc::new(move || dispatch.load(Ordering::Relaxed))
+        };
+        let app = build_router(state.clone());
+
+        let init = app.clone().oneshot(mcp_request("POST", None)).await.expect("response");
+        let session = init.headers()[MCP_SESSION_HEADER].to_str().expect("header").to_string();
+        assert_eq!(state.affinity.owner(&session), Some(port_a));
+
+        // Rebalance: new sessions now go to B, but the existing one stays on A.
+        dispatch.store(port_b, Ordering::Relaxed);
+        let call = app.clone().oneshot(mcp_request("POST", Some(&session))).await.expect("response");
+        assert_eq!(body_to_string(call.into_body()).await, port_a.to_string());
+
+        let delete = app.oneshot(mcp_request("DELETE", Some(&session))).await.expect("response");
+        assert_eq!(delete.status(), StatusCode::OK);
+        assert_eq!(state.affinity.owner(&session), None);
+
+        task_a.abort();
+        task_b.abort();
+    }
+
+    #[tokio::test]
+    async fn mcp_session_on_dead_owner_requests_reinit() {
+        let mut state = make_state(3460, None);
+        state.instance_live = Arc::new(|port| port != 65_531);
+        state.affinity.bind("orphan", 65_531);
+        let app = build_router(state.clone());
+
+        let response = app.oneshot(mcp_request("POST", Some("orphan"))).await.expect("response");
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = body_to_string(response.into_body()).await;
+        assert!(body.contains("Session not found"));
+        assert_eq!(state.affinity.owner("orphan"), None);
+    }
+
+    #[tokio::test]
+    async fn sessionless_endpoints_follow_the_current_primary() {
+        use std::sync::atomic::{AtomicU16, Ordering};
+
+        async fn spawn_admin_backend() -> (u16, tokio::task::JoinHandle<()>) {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("bind backend");
+            let port = listener.local_addr().expect("local addr").port();
+            let backend = Router::new()
+                .route("/admin/connections", get(move || async move { port.to_string() }));
+            let task = tokio::spawn(async move {
+                let _ = axum::serve(listener, backend).await;
+            });
+            (port, task)
+        }
+
+        let (port_a, task_a) = spawn_admin_backend().await;
+        let (port_b, task_b) = spawn_admin_backend().await;
+        let primary = Arc::new(AtomicU16::new(port_a));
+        let mut state = make_state(port_a, None);
+        state.primary_port = {
+            let primary = Arc::clone(&primary);
+            Arc::new(move || primary.load(Ordering::Relaxed))
+        };
+        let app = build_router(state);
+        let admin = || {
+            Request::builder()
+                .uri("/admin/connections")
+                .body(Body::empty())
+                .expect("request")
+        };
+
+        let before = app.clone().oneshot(admin()).await.expect("response");
+        assert_eq!(body_to_string(before.into_body()).await, port_a.to_string());
+
+        // The primary was relocated off base_port.
+        primary.store(port_b, Ordering::Relaxed);
+        let after = app.oneshot(admin()).await.expect("response");
+        assert_eq!(body_to_string(after.into_body()).await, port_b.to_string());
+
+        task_a.abort();
+        task_b.abort();
+    }
+
+    #[tokio::test]
+    async fn mcp_session_on_unreachable_owner_requests_reinit() {
+        let state = make_state(3460, None);
+        state.affinity.bind("stale", 65_532);
+        let app = build_router(state.clone());
+
+        let response = app.oneshot(mcp_request("POST", Some("stale"))).await.expect("response");
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(state.affinity.owner("stale"), None);
+    }
 }
@@ -163,6 +163,8 @@ mod tests {
             cooldown_child_local_ms: 0,
             cooldown_dependency_group_ms: 0,
             cooldown_global_ms: 0,
+            crash_loop_max_restarts: 5,
+            crash_loop_window_secs: 300,
         };
         let b = BackoffState::from_config(&config);
         assert_eq!(b.jitter_ratio, 0.2);
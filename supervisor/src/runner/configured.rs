@@ -9,7 +9,7 @@ use async_trait::async_trait;
 use tokio::process::Child;
 use tokio::time::{Instant, sleep};
 
-use crate::config::{RestartPolicy, ServerDefinition};
+use crate::config::{ReadinessProbe, RestartPolicy, ServerDefinition};
 use crate::control::registry::ServiceStatus;
 use crate::runner::{HealthStatus, ServiceRunner};
 
@@ -26,12 +26,17 @@ pub struct ConfiguredProcessRunner {
     env: std::collections::HashMap<String, String>,
     port: Option<u16>,
     restart_policy: RestartPolicy,
+    depends_on: Vec<String>,
+    /// Resolved readiness probe (port filled in); `None` when the service
+    /// declares neither a probe nor a port.
+    readiness: Option<ReadinessProbe>,
     startup_timeout_ms: u64,
     state: RunnerInternalState,
 }
 
 impl ConfiguredProcessRunner {
     pub fn new(cfg: ServerDefinition) -> Self {
+        let readiness = resolve_readiness(cfg.readiness, cfg.port);
         Self {
             service_name: cfg.name.trim().to_string(),
             command: cfg.command,
@@ -40,7 +45,14 @@ impl ConfiguredProcessRunner {
             env: cfg.env,
             port: cfg.port,
             restart_policy: cfg.restart_policy,
-            startup_timeout_ms: 30_000,
+            depends_on: cfg
+                .depends_on
+                .iter()
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty())
+                .collect(),
+            readiness,
+            startup_timeout_ms: cfg.startup_timeout_ms.unwrap_or(30_000),
             state: RunnerInternalState::Stopped,
         }
     }
@@ -57,6 +69,15 @@ impl ConfiguredProcessRunner {
         self.restart_policy.clone()
     }
 
+    /// Names of the services this one must start after.
+    pub fn depends_on(&self) -> &[String] {
+        &self.depends_on
+    }
+
+    pub fn startup_timeout(&self) -> Duration {
+        Duration::from_millis(self.startup_timeout_ms.max(500))
+    }
+
     pub fn pid(&self) -> Option<u32> {
         match self.state {
             RunnerInternalState::Running { pid, .. } => Some(pid),
@@ -79,11 +100,11 @@ impl ConfiguredProcessRunner {
     }
 
     async fn wait_for_startup_ready(&mut self) -> anyhow::Result<()> {
-        let Some(port) = self.port else {
+        let Some(probe) = self.readiness.clone() else {
             return Ok(());
         };
 
-        let deadline = Instant::now() + Duration::from_millis(self.startup_timeout_ms.max(500));
+        let deadline = Instant::now() + self.startup_timeout();
 
         loop {
             match self.state {
@@ -94,8 +115,8 @@ impl ConfiguredProcessRunner {
                     {
                         self.state = RunnerInternalState::Stopped;
                         anyhow::bail!(
-                            "process exited before port {} became ready (code={:?}, success={})",
-                            port,
+                            "process exited before {} became ready (code={:?}, success={})",
+                            probe_target(&probe),
                             status.code(),
                             status.success()
                         );
@@ -104,16 +125,16 @@ impl ConfiguredProcessRunner {
                 RunnerInternalState::Stopped => anyhow::bail!("process stopped before becoming ready"),
             }
 
-            if probe_tcp(port).await {
+            if run_probe(&probe).await.is_ok() {
                 return Ok(());
             }
 
             if Instant::now() >= deadline {
                 anyhow::bail!(
-                    "service {} did not become ready on tcp://127.0.0.1:{} within {} ms",
+                    "service {} did not become ready on {} within {} ms",
                     self.service_name,
-                    port,
-                    self.startup_timeout_ms.max(500)
+                    probe_target(&probe),
+                    self.startup_timeout().as_millis()
                 );
             }
 
@@ -195,11 +216,11 @@ impl ServiceRunner for ConfiguredProcessRunner {
             return Err(error);
         }
 
-        if let Some(port) = self.port {
+        if let Some(ref probe) = self.readiness {
             crate::runtime_output::emit(
                 &self.service_name,
                 "status",
-                format!("startup ready on tcp://127.0.0.1:{port}"),
+                format!("startup ready on {}", probe_target(probe)),
             );
         }
 
@@ -243,16 +264,11 @@ impl ServiceRunner for ConfiguredProcessRunner {
             return HealthStatus::Unhealthy("not running".into());
         }
 
-        match self.port {
-            Some(port) => {
-                if probe_tcp(port).await {
-                    HealthStatus::Healthy
-                } else {
-                    HealthStatus::Unhealthy(format!(
-                        "tcp://127.0.0.1:{port} is not reachable"
-                    ))
-                }
-            }
+        match self.readiness {
+            Some(ref probe) => match run_probe(probe).await {
+                Ok(()) => HealthStatus::Healthy,
+                Err(reason) => HealthStatus::Unhealthy(reason),
+            },
             None => HealthStatus::Healthy,
         }
     }
@@ -269,6 +285,59 @@ impl ServiceRunner for ConfiguredProcessRunner {
     }
 }
 
+/// Fill in the probe port from the server's `port`, or default to a TCP
+/// probe on `port` when no probe is configured.  Probes without any port to
+/// check are dropped.
+fn resolve_readiness(probe: Option<ReadinessProbe>, port: Option<u16>) -> Option<ReadinessProbe> {
+    match probe {
+        Some(ReadinessProbe::Tcp { port: probe_port }) => {
+            Some(ReadinessProbe::Tcp { port: Some(probe_port.or(port)?) })
+        }
+        Some(ReadinessProbe::Http { port: probe_port, path, expected_status }) => {
+            Some(ReadinessProbe::Http { port: Some(probe_port.or(port)?), path, expected_status })
+        }
+        None => port.map(|p| ReadinessProbe::Tcp { port: Some(p) }),
+    }
+}
+
+fn probe_target(probe: &ReadinessProbe) -> String {
+    match probe {
+        ReadinessProbe::Tcp { port } => format!("tcp://127.0.0.1:{}", port.unwrap_or_default()),
+        ReadinessProbe::Http { port, path, .. } => {
+            format!("http://127.0.0.1:{}{}", port.unwrap_or_default(), path)
+        }
+    }
+}
+
+/// Run one probe attempt.  `Err` carries a human-readable failure reason.
+async fn run_probe(probe: &ReadinessProbe) -> Result<(), String> {
+    match probe {
+        ReadinessProbe::Tcp { port } => {
+            let port = port.unwrap_or_default();
+            if probe_tcp(port).await {
+                Ok(())
+            } else {
+                Err(format!("tcp://127.0.0.1:{port} is not reachable"))
+            }
+        }
+        ReadinessProbe::Http { expected_status, .. } => {
+            let url = probe_target(probe);
+            let response = reqwest::Client::new()
+                .get(&url)
+                .timeout(Duration::from_secs(3))
+                .send()
+                .await
+                .map_err(|e| format!("{url} is not reachable: {e}"))?;
+            let status = response.status().as_u16();
+            if status == *expected_status {
+                Ok(())
+            } else {
+                Err(format!("{url} returned {status}, expected {expected_status}"))
+            }
+        }
+    }
+}
+
 async fn probe_tcp(port: u16) -> bool {
     tokio::time::timeout(
         Duration::from_secs(3),
@@ -293,6 +362,9 @@ mod tests {
             env: std::collections::HashMap::new(),
             port: None,
             restart_policy: RestartPolicy::AlwaysRestart,
+            depends_on: Vec::new(),
+            readiness: None,
+            startup_timeout_ms: None,
         })
     }
 
@@ -364,6 +436,9 @@ mod tests {
             env: std::collections::HashMap::new(),
             port: None,
             restart_policy: RestartPolicy::AlwaysRestart,
+            depends_on: Vec::new(),
+            readiness: None,
+            startup_timeout_ms: None,
         });
         assert_eq!(runner.service_name(), "mobile-app");
     }
@@ -378,6 +453,9 @@ mod tests {
             env: std::collections::HashMap::new(),
             port: Some(5173),
             restart_policy: RestartPolicy::AlwaysRestart,
+            depends_on: Vec::new(),
+            readiness: None,
+            startup_timeout_ms: None,
         });
         assert_eq!(runner.port(), Some(5173));
     }
@@ -387,4 +465,45 @@ mod tests {
         let runner = make_runner("my-headless-server");
         assert!(runner.port().is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn readiness_defaults_to_tcp_on_declared_port() {
+        assert_eq!(
+            resolve_readiness(None, Some(4100)),
+            Some(ReadinessProbe::Tcp { port: Some(4100) })
+        );
+        assert_eq!(resolve_readiness(None, None), None);
+    }
+
+    #[test]
+    fn readiness_probe_port_falls_back_to_server_port() {
+        let probe = ReadinessProbe::Http {
+            port: None,
+            path: "/ready".to_string(),
+            expected_status: 204,
+        };
+        let resolved = resolve_readiness(Some(probe), Some(4100)).expect("resolved");
+        assert_eq!(probe_target(&resolved), "http://127.0.0.1:4100/ready");
+        assert_eq!(resolve_readiness(Some(ReadinessProbe::Tcp { port: None }), None), None);
+    }
+
+    #[tokio::test]
+    async fn http_probe_checks_expected_status() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+        let app = axum::Router::new().route(
+            "/ready",
+            axum::routing::get(|| async { axum::http::StatusCode::NO_CONTENT }),
+        );
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let ok = ReadinessProbe::Http { port: Some(port), path: "/ready".into(), expected_status: 204 };
+        let wrong = ReadinessProbe::Http { port: Some(port), path: "/ready".into(), expected_status: 200 };
+        assert!(run_probe(&ok).await.is_ok());
+        assert!(run_probe(&wrong).await.unwrap_err().contains("returned 204"));
+
+        server.abort();
+    }
+}
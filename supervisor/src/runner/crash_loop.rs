@@ -0,0 +1,196 @@
+//! Crash-loop circuit breaker.
+//!
+//! Watchdogs ask for a restart every time a service dies.  A service that
+//! keeps dying right after it starts would otherwise be restarted forever,
+//! hiding the failure.  [`CrashLoopDetector`] counts crash-triggered restarts
+//! per service in a sliding window; once a service reaches `max_restarts`
+//! within `window`, its circuit opens and further crash restarts are refused
+//! until the circuit is reset (the `ResetCircuit` control command).
+//!
+//! Manual restarts are not counted; only callers that know a restart was
+//! caused by a crash should call [`CrashLoopDetector::record_restart`].  MCP
+//! pool instances that crash are counted under `mcp` before they are
+//! respawned (see [`ManagedPool::refresh_health`](super::mcp_pool::ManagedPool::refresh_health));
+//! resetting the `mcp` circuit respawns only the instances it refused.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Reason code carried by the operator alert when a circuit opens.
+pub const CRASH_LOOP_REASON: &str = "crash_loop";
+
+/// Thresholds for the detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrashLoopPolicy {
+    /// Crash restarts within `window` that open the circuit (0 = disabled).
+    pub max_restarts: u32,
+    pub window: Duration,
+}
+
+impl Default for CrashLoopPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            window: Duration::from_secs(300),
+        }
+    }
+}
+
+/// An open circuit, as reported in the health snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitState {
+    /// Crash restarts counted in the window when the circuit opened.
+    pub restart_count: u32,
+    pub window_secs: u64,
+    /// Unix timestamp (ms) at which the circuit opened.
+    pub opened_at_ms: u64,
+    pub reason_code: String,
+}
+
+impl CircuitState {
+    /// One-line human description, used for alerts and the tray.
+    pub fn describe(&self, service: &str) -> String {
+        format!(
+            "'{service}' crashed {} times in {}s — automatic restarts paused until the circuit is reset",
+            self.restart_count, self.window_secs
+        )
+    }
+}
+
+/// Outcome of [`CrashLoopDetector::record_restart`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestartDecision {
+    /// Go ahead and restart.
+    Allow,
+    /// This restart opened the circuit; do not restart and alert an operator.
+    Tripped(CircuitState),
+    /// The circuit was already open; do not restart.
+    Open,
+}
+
+#[derive(Debug, Default)]
+pub struct CrashLoopDetector {
+    policy: CrashLoopPolicy,
+    restarts: HashMap<String, VecDeque<Instant>>,
+    open: HashMap<String, CircuitState>,
+}
+
+impl CrashLoopDetector {
+    pub fn new(policy: CrashLoopPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: CrashLoopPolicy) {
+        self.policy = policy;
+    }
+
+    /// Record a crash-triggered restart of `service` at `now`.
+    pub fn record_restart(&mut self, service: &str, now: Instant) -> RestartDecision {
+        if self.open.contains_key(service) {
+            return RestartDecision::Open;
+        }
+        if self.policy.max_restarts == 0 {
+            return RestartDecision::Allow;
+        }
+
+        let window = self.policy.window;
+        let history = self.restarts.entry(service.to_string()).or_default();
+        while history
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) > window)
+        {
+            history.pop_front();
+        }
+        history.push_back(now);
+
+        if (history.len() as u32) < self.policy.max_restarts {
+            return RestartDecision::Allow;
+        }
+
+        let circuit = CircuitState {
+            restart_count: history.len() as u32,
+            window_secs: window.as_secs(),
+            opened_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            reason_code: CRASH_LOOP_REASON.to_string(),
+        };
+        history.clear();
+        self.open.insert(service.to_string(), circuit.clone());
+        RestartDecision::Tripped(circuit)
+    }
+
+    /// Close `service`'s circuit and forget its restart history.  Returns
+    /// `true` if a circuit was open.
+    pub fn reset(&mut self, service: &str) -> bool {
+        self.restarts.remove(service);
+        self.open.remove(service).is_some()
+    }
+
+    pub fn circuit(&self, service: &str) -> Option<&CircuitState> {
+        self.open.get(service)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(max_restarts: u32, window_secs: u64) -> CrashLoopDetector {
+        CrashLoopDetector::new(CrashLoopPolicy {
+            max_restarts,
+            window: Duration::from_secs(window_secs),
+        })
+    }
+
+    #[test]
+    fn trips_after_max_restarts_in_window_and_stays_open() {
+        let mut d = detector(3, 60);
+        let t0 = Instant::now();
+        assert_eq!(d.record_restart("dashboard", t0), RestartDecision::Allow);
+        assert_eq!(d.record_restart("dashboard", t0 + Duration::from_secs(1)), RestartDecision::Allow);
+        let RestartDecision::Tripped(circuit) = d.record_restart("dashboard", t0 + Duration::from_secs(2)) else {
+            panic!("third restart should trip");
+        };
+        assert_eq!(circuit.restart_count, 3);
+        assert_eq!(circuit.reason_code, CRASH_LOOP_REASON);
+        assert_eq!(d.record_restart("dashboard", t0 + Duration::from_secs(3)), RestartDecision::Open);
+        // Other services are unaffected.
+        assert_eq!(d.record_restart("mcp", t0), RestartDecision::Allow);
+    }
+
+    #[test]
+    fn restarts_outside_the_window_are_forgotten() {
+        let mut d = detector(3, 60);
+        let t0 = Instant::now();
+        d.record_restart("svc", t0);
+        d.record_restart("svc", t0 + Duration::from_secs(10));
+        assert_eq!(d.record_restart("svc", t0 + Duration::from_secs(100)), RestartDecision::Allow);
+        assert!(d.circuit("svc").is_none());
+    }
+
+    #[test]
+    fn reset_rearms_and_zero_disables() {
+        let mut d = detector(1, 60);
+        let t0 = Instant::now();
+        assert!(matches!(d.record_restart("svc", t0), RestartDecision::Tripped(_)));
+        assert!(d.reset("svc"));
+        assert!(!d.reset("svc"));
+        assert!(matches!(d.record_restart("svc", t0), RestartDecision::Tripped(_)));
+
+        let mut off = detector(0, 60);
+        for _ in 0..10 {
+            assert_eq!(off.record_restart("svc", t0), RestartDecision::Allow);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Start ordering for configured `[[servers]]` entries.
+//!
+//! Each server may list `depends_on` names.  Dependencies on other configured
+//! servers are ordered here; dependencies on built-in services (MCP,
+//! dashboard, …) are started by the supervisor before any configured server
+//! and are only checked at start time.  Names match case-insensitively.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Built-in service names a configured server may depend on, with aliases
+/// mapped to the registry name.
+pub fn canonical_builtin(name: &str) -> Option<&'static str> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "mcp" => Some("mcp"),
+        "dashboard" => Some("dashboard"),
+        "interactive_terminal" | "terminal" => Some("interactive_terminal"),
+        "fallback_api" | "fallback" => Some("fallback_api"),
+        "cli_mcp" => Some("cli_mcp"),
+        _ => None,
+    }
+}
+
+/// Why a set of servers cannot be ordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyError {
+    /// `service` depends on a name that is neither configured nor built-in.
+    Unknown { service: String, dependency: String },
+    /// The listed services form a dependency cycle (first name repeated last).
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyError::Unknown { service, dependency } => {
+                write!(f, "service '{service}' depends on unknown service '{dependency}'")
+            }
+            DependencyError::Cycle(path) => write!(f, "dependency cycle: {}", path.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+/// Return the indices of `services` (`(name, depends_on)` pairs) in an order
+/// where every server comes after the configured servers it depends on.
+/// Ties keep configuration order.
+pub fn start_order(services: &[(&str, &[String])]) -> Result<Vec<usize>, DependencyError> {
+    let index: HashMap<String, usize> = services
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (name.to_ascii_lowercase(), i))
+        .collect();
+
+    // edges[i] = configured servers that `i` depends on.
+    let mut edges: Vec<Vec<usize>> = Vec::with_capacity(services.len());
+    for (name, deps) in services {
+        let mut targets = Vec::new();
+        for dep in deps.iter() {
+            if let Some(&j) = index.get(&dep.trim().to_ascii_lowercase()) {
+                targets.push(j);
+            } else if canonical_builtin(dep).is_none() {
+                return Err(DependencyError::Unknown {
+                    service: name.to_string(),
+                    dependency: dep.clone(),
+                });
+            }
+        }
+        edges.push(targets);
+    }
+
+    // Depth-first post-order; `visiting` marks the current path for cycle
+    // detection.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Visiting,
+        Done,
+    }
+    fn visit(
+        i: usize,
+        edges: &[Vec<usize>],
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), Vec<usize>> {
+        match marks[i] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                let start = path.iter().position(|&p| p == i).unwrap_or(0);
+                let mut cycle = path[start..].to_vec();
+                cycle.push(i);
+                return Err(cycle);
+            }
+            Mark::New => {}
+        }
+        marks[i] = Mark::Visiting;
+        path.push(i);
+        for &j in &edges[i] {
+            visit(j, edges, marks, path, order)?;
+        }
+        path.pop();
+        marks[i] = Mark::Done;
+        order.push(i);
+        Ok(())
+    }
+
+    let mut marks = vec![Mark::New; services.len()];
+    let mut order = Vec::with_capacity(services.len());
+    for i in 0..services.len() {
+        visit(i, &edges, &mut marks, &mut Vec::new(), &mut order).map_err(|cycle| {
+            DependencyError::Cycle(cycle.into_iter().map(|k| services[k].0.to_string()).collect())
+        })?;
+    }
+    Ok(order)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deps(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn orders_dependencies_first_and_keeps_config_order_otherwise() {
+        let a = deps(&["b"]);
+        let b = deps(&["mcp"]);
+        let c = deps(&[]);
+        let services = [("a", a.as_slice()), ("b", b.as_slice()), ("c", c.as_slice())];
+        assert_eq!(start_order(&services).expect("order"), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn names_match_case_insensitively_and_builtin_aliases_resolve() {
+        let a = deps(&["Helper", "terminal"]);
+        let helper = deps(&[]);
+        let services = [("a", a.as_slice()), ("helper", helper.as_slice())];
+        assert_eq!(start_order(&services).expect("order"), vec![1, 0]);
+        assert_eq!(canonical_builtin("Fallback"), Some("fallback_api"));
+    }
+
+    #[test]
+    fn rejects_unknown_dependency() {
+        let a = deps(&["ghost"]);
+        let err = start_order(&[("a", a.as_slice())]).unwrap_err();
+        assert_eq!(
+            err,
+            DependencyError::Unknown { service: "a".into(), dependency: "ghost".into() }
+        );
+    }
+
+    #[test]
+    fn rejects_cycles_with_path() {
+        let a = deps(&["b"]);
+        let b = deps(&["c"]);
+        let c = deps(&["a"]);
+        let services = [("a", a.as_slice()), ("b", b.as_slice()), ("c", c.as_slice())];
+        let err = start_order(&services).unwrap_err();
+        assert_eq!(err.to_string(), "dependency cycle: a -> b -> c -> a");
+    }
+}
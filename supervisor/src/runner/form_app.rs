@@ -29,6 +29,7 @@ use crate::config::FormAppConfig;
 use crate::control::protocol::{
     FormAppLifecycle, FormAppLifecycleState, FormAppResponse,
 };
+use crate::events::{self, DataChangeEvent};
 
 // ---------------------------------------------------------------------------
 // Refinement session registry
@@ -123,6 +124,17 @@ pub async fn launch_form_app(
     app_name: &str,
     payload: &serde_json::Value,
     timeout_override: Option<u64>,
+) -> FormAppResponse {
+    let response = launch_form_app_internal(config, app_name, payload, timeout_override).await;
+    publish_finished(&response);
+    response
+}
+
+async fn launch_form_app_internal(
+    config: &FormAppConfig,
+    app_name: &str,
+    payload: &serde_json::Value,
+    timeout_override: Option<u64>,
 ) -> FormAppResponse {
     // Serialise concurrent launches for the same app — wait for any running
     // instance to finish before spawning a new window.  The guard is held
@@ -166,6 +178,10 @@ pub async fn launch_form_app(
     };
 
     let pid = child.id();
+    events::publish(DataChangeEvent::FormAppLaunched {
+        app_name: app_name.to_string(),
+        pid,
+    });
 
     let mut lifecycle = FormAppLifecycle {
         app_name: app_name.to_string(),
@@ -297,7 +313,27 @@ pub async fn continue_form_app(
     refinement_payload: &serde_json::Value,
     timeout_override: Option<u64>,
 ) -> FormAppResponse {
-    continue_form_app_internal(session_id, refinement_payload, timeout_override).await
+    let response = continue_form_app_internal(session_id, refinement_payload, timeout_override).await;
+    publish_finished(&response);
+    response
+}
+
+/// Publish the outcome of one form-app round on the events channel.
+fn publish_finished(response: &FormAppResponse) {
+    let outcome = if response.pending_refinement {
+        "awaiting_refinement"
+    } else if response.timed_out {
+        "timed_out"
+    } else if response.success {
+        "completed"
+    } else {
+        "failed"
+    };
+    events::publish(DataChangeEvent::FormAppFinished {
+        app_name: response.app_name.clone(),
+        outcome: outcome.to_string(),
+        elapsed_ms: response.elapsed_ms,
+    });
 }
 
 async fn continue_form_app_internal(
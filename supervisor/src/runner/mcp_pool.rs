@@ -8,15 +8,30 @@
 //!   (`least_loaded_port`) so the proxy can route new VS Code sessions there.
 //! - Scaling up (spawning a new instance) when every existing instance has
 //!   reached `max_connections_per_instance` and the pool is below
-//!   `max_instances`.
+//!   `max_instances`.  Instance ports come from a [`PortAllocator`] over
+//!   `base_port..base_port + port_span`, so ports held by other processes are
+//!   skipped and holes left by dead instances are reused.
+//! - Scaling down: instances idle for `scale_down_idle_secs` are drained
+//!   (no new sessions are routed to them) and stopped once their remaining
+//!   connections close or `drain_timeout_secs` elapses.  The pool never
+//!   shrinks below `min_instances`, and the primary instance on `base_port`
+//!   is never retired because non-session endpoints are proxied to it.  If
+//!   the primary dies and another process takes `base_port`, the lowest-port
+//!   active instance stands in as primary (`primary_port`) until an instance
+//!   runs on `base_port` again.
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use crate::config::{NodeRunnerConfig, PoolConfig};
-use crate::control::registry::McpConnectionEntry;
+use crate::control::registry::{McpConnectionEntry, McpInstanceSlot, McpSlotState};
+use crate::runner::crash_loop::RestartDecision;
 use crate::runner::node::NodeRunner;
+use crate::runner::port_allocator::{is_port_free, PortAllocator};
 use crate::runner::ServiceRunner;
 
+use tokio::sync::watch;
+
 // ---------------------------------------------------------------------------
 // ManagedInstance
 // ---------------------------------------------------------------------------
@@ -34,6 +49,19 @@ pub struct ManagedInstance {
     /// Consecutive failed health probes.  When this reaches 2 the instance is
     /// considered dead and will be respawned.
     pub consecutive_failures: u32,
+    /// When the instance last dropped to zero connections, or `None` while it
+    /// is serving at least one session.
+    idle_since: Option<Instant>,
+    /// When the instance was selected for retirement.  Draining instances are
+    /// excluded from routing and are not respawned if they die.
+    draining_since: Option<Instant>,
+    /// `true` when an operator asked for the drain (`ScaleDownMcp`); such
+    /// instances are never reclaimed by a scale-up.
+    drained_manually: bool,
+    /// `true` once the crash-loop circuit refused to respawn this instance.
+    /// It stays stopped, out of routing and out of health checks until
+    /// [`ManagedPool::respawn_refused`] runs.
+    respawn_refused: bool,
 }
 
 impl ManagedInstance {
@@ -52,9 +80,23 @@ impl ManagedInstance {
             connection_count: 0,
             healthy: false,
             consecutive_failures: 0,
+            idle_since: Some(Instant::now()),
+            draining_since: None,
+            drained_manually: false,
+            respawn_refused: false,
         }
     }
 
+    /// `true` once the instance has been selected for scale-down.
+    pub fn is_draining(&self) -> bool {
+        self.draining_since.is_some()
+    }
+
+    /// `true` while the instance has a running process.
+    fn is_live(&self) -> bool {
+        self.runner.pid().is_some()
+    }
+
     pub async fn start(&mut self) -> anyhow::Result<()> {
         self.runner.start().await
     }
@@ -225,6 +267,67 @@ fn kill_pid(pid: u32, port: u16) {
     }
 }
 
+// ---------------------------------------------------------------------------
+// PoolTransition
+// ---------------------------------------------------------------------------
+
+/// A scale step taken by the pool, reported back to the caller so it can be
+/// recorded as a registry state event (see
+/// [`Registry::push_pool_event`](crate::control::registry::Registry::push_pool_event)).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolTransition {
+    pub port: u16,
+    pub old_state: &'static str,
+    pub new_state: &'static str,
+    pub reason: String,
+}
+
+impl PoolTransition {
+    fn drain_started(port: u16, reason: String) -> Self {
+        Self { port, old_state: "healthy", new_state: "draining", reason }
+    }
+
+    fn retired(port: u16, reason: String) -> Self {
+        Self { port, old_state: "draining", new_state: "stopped", reason }
+    }
+
+    fn reclaimed(port: u16, reason: String) -> Self {
+        Self { port, old_state: "draining", new_state: "healthy", reason }
+    }
+
+    fn spawned(port: u16, reason: String) -> Self {
+        Self { port, old_state: "stopped", new_state: "starting", reason }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PoolRouting
+// ---------------------------------------------------------------------------
+
+/// Routing view of the pool, republished after every change so the proxy can
+/// route requests without taking the pool lock (which health probes and
+/// scale steps hold across awaits).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolRouting {
+    /// Port new sessions are routed to (see
+    /// [`ManagedPool::least_loaded_port`]).
+    pub dispatch_port: u16,
+    /// Port session-less endpoints are proxied to (see
+    /// [`ManagedPool::primary_port`]).
+    pub primary_port: u16,
+    /// Ports with a running process, draining instances included (see
+    /// [`ManagedPool::is_instance_live`]).
+    pub live_ports: Vec<u16>,
+    /// Number of instances in the pool.
+    pub size: usize,
+}
+
+impl PoolRouting {
+    pub fn is_live(&self, port: u16) -> bool {
+        self.live_ports.contains(&port)
+    }
+}
+
 /// Pool of managed MCP instances.
 pub struct ManagedPool {
     pool_cfg: PoolConfig,
@@ -232,86 +335,185 @@ pub struct ManagedPool {
     health_timeout_ms: u64,
     /// Instances keyed by port.
     instances: HashMap<u16, ManagedInstance>,
+    /// Port reservations for `instances`.
+    port_alloc: PortAllocator,
+    /// Latest [`PoolRouting`]; see [`routing`](Self::routing).
+    routing: watch::Sender<PoolRouting>,
 }
 
 impl ManagedPool {
     /// Create a new pool from config.  Call `init().await` to spawn the
     /// initial instances.
     pub fn new(pool_cfg: PoolConfig, node_cfg: NodeRunnerConfig, health_timeout_ms: u64) -> Self {
+        let span = pool_cfg.port_span.max(pool_cfg.max_instances);
+        let port_alloc = PortAllocator::new(pool_cfg.base_port, span);
+        let (routing, _) = watch::channel(PoolRouting {
+            dispatch_port: pool_cfg.base_port,
+            primary_port: pool_cfg.base_port,
+            live_ports: Vec::new(),
+            size: 0,
+        });
         Self {
             pool_cfg,
             node_cfg,
             health_timeout_ms,
             instances: HashMap::new(),
+            port_alloc,
+            routing,
         }
     }
 
-    /// Spawn `min_instances` instances starting at `base_port`.
+    /// Subscribe to the pool's routing view.  The receiver always holds the
+    /// state after the most recent pool change and never blocks on the pool
+    /// lock.
+    pub fn routing(&self) -> watch::Receiver<PoolRouting> {
+        self.routing.subscribe()
+    }
+
+    /// Republish the routing view after instances, connection counts or
+    /// drain flags changed.
+    fn publish_routing(&self) {
+        let mut live_ports: Vec<u16> = self
+            .instances
+            .keys()
+            .copied()
+            .filter(|&port| self.is_instance_live(port))
+            .collect();
+        live_ports.sort();
+        let routing = PoolRouting {
+            dispatch_port: self.least_loaded_port(),
+            primary_port: self.primary_port(),
+            live_ports,
+            size: self.instances.len(),
+        };
+        self.routing.send_if_modified(|current| {
+            let changed = *current != routing;
+            *current = routing;
+            changed
+        });
+    }
+
+    /// Spawn `min_instances` instances on the lowest free ports from
+    /// `base_port`.
     ///
     /// Before spawning, any orphaned processes from a previous run that are
-    /// still holding these ports are killed.
+    /// still holding the first `min_instances` ports are killed.
     pub async fn init(&mut self) {
-        let ports: Vec<u16> = (0..self.pool_cfg.min_instances)
-            .map(|i| self.pool_cfg.base_port + i)
+        let ports: Vec<u16> = self
+            .port_alloc
+            .range()
+            .take(self.pool_cfg.min_instances as usize)
             .collect();
         kill_orphans_on_ports(&ports).await;
-        for &port in &ports {
-            self.spawn_instance(port).await;
+        for _ in 0..self.pool_cfg.min_instances {
+            self.spawn_next().await;
         }
     }
 
-    /// Spawn a single new instance on `port`.  No-op if already present.
-    async fn spawn_instance(&mut self, port: u16) {
-        if self.instances.contains_key(&port) {
-            return;
-        }
+    /// Reserve the next free port and spawn an instance on it.  The port is
+    /// released again if the instance fails to start.
+    async fn spawn_next(&mut self) -> Option<u16> {
+        let Some(port) = self.port_alloc.allocate() else {
+            let range = self.port_alloc.range();
+            eprintln!(
+                "[pool] no free port in {}-{} — cannot spawn MCP instance",
+                range.start(),
+                range.end()
+            );
+            return None;
+        };
         let mut instance = ManagedInstance::new(port, &self.node_cfg, self.health_timeout_ms);
         match instance.start().await {
             Ok(()) => {
                 eprintln!("[pool] MCP instance started on port {port}");
                 self.instances.insert(port, instance);
+                self.publish_routing();
+                Some(port)
             }
             Err(e) => {
                 eprintln!("[pool] failed to start MCP instance on port {port}: {e}");
+                self.port_alloc.release(port);
+                None
             }
         }
     }
 
-    /// Returns `true` if a scale-up was triggered (new instance spawned).
-    ///
-    /// A scale-up is performed when every current instance has reached
-    /// `max_connections_per_instance` AND the pool size is below
-    /// `max_instances`.
-    pub async fn maybe_scale_up(&mut self, connections: &[McpConnectionEntry]) -> bool {
-        // Update per-instance connection counts.
+    /// Update per-instance connection counts and idle timestamps.
+    fn update_connection_counts(&mut self, connections: &[McpConnectionEntry]) {
+        let now = Instant::now();
         for instance in self.instances.values_mut() {
             instance.connection_count = connections
                 .iter()
                 .filter(|c| c.instance_port == instance.port)
                 .count();
+            if instance.connection_count == 0 {
+                instance.idle_since.get_or_insert(now);
+            } else {
+                instance.idle_since = None;
+            }
         }
+        self.publish_routing();
+    }
 
-        let total = self.instances.len() as u16;
-        if total >= self.pool_cfg.max_instances {
-            return false;
-        }
+    /// Number of instances that are not draining (i.e. eligible for routing).
+    fn active_count(&self) -> u16 {
+        self.instances.values().filter(|i| !i.is_draining()).count() as u16
+    }
 
-        let all_at_capacity = self.instances.values().all(|inst| {
-            inst.connection_count >= self.pool_cfg.max_connections_per_instance
-        });
+    /// Scale up if the pool is saturated, returning the transition performed
+    /// (new instance spawned, or a draining instance returned to service).
+    ///
+    /// A scale-up is performed when every active instance has reached
+    /// `max_connections_per_instance` AND the pool size is below
+    /// `max_instances`.  An instance drained by idle scale-down is reclaimed in
+    /// preference to spawning a new process; one an operator drained is not.
+    pub async fn maybe_scale_up(
+        &mut self,
+        connections: &[McpConnectionEntry],
+    ) -> Option<PoolTransition> {
+        self.update_connection_counts(connections);
+
+        let all_at_capacity = self
+            .instances
+            .values()
+            .filter(|inst| !inst.is_draining() && inst.is_live())
+            .all(|inst| inst.connection_count >= self.pool_cfg.max_connections_per_instance);
 
         if !all_at_capacity {
-            return false;
+            return None;
+        }
+
+        if let Some(instance) = self
+            .instances
+            .values_mut()
+            .find(|i| i.is_draining() && !i.drained_manually)
+        {
+            instance.draining_since = None;
+            let port = instance.port;
+            eprintln!(
+                "[pool] all active instances at capacity — returning draining instance on port {port} to service"
+            );
+            self.publish_routing();
+            return Some(PoolTransition::reclaimed(
+                port,
+                "all active instances at capacity".to_string(),
+            ));
+        }
+
+        let total = self.instances.len() as u16;
+        if total >= self.pool_cfg.max_instances {
+            return None;
         }
 
-        // Find the next free port.
-        let next_port = self.pool_cfg.base_port + total;
         eprintln!(
-            "[pool] all {total} instances at capacity ({} conns each) — spawning new instance on port {next_port}",
+            "[pool] all {total} instances at capacity ({} conns each) — spawning new instance",
             self.pool_cfg.max_connections_per_instance
         );
-        self.spawn_instance(next_port).await;
-        true
+        let port = self.spawn_next().await?;
+        Some(PoolTransition::spawned(
+            port,
+            format!("all {total} instances at capacity"),
+        ))
     }
 
     /// Force a scale-up regardless of load (used by `ScaleUpMcp` command).
@@ -324,28 +526,202 @@ impl ManagedPool {
                 self.pool_cfg.max_instances
             );
         }
-        let next_port = self.pool_cfg.base_port + total;
-        self.spawn_instance(next_port).await;
-        Ok(next_port)
+        self.spawn_next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("failed to spawn MCP instance (see supervisor log)"))
+    }
+
+    /// Drain idle instances and retire drained ones.
+    ///
+    /// An instance becomes a scale-down candidate once it has had zero
+    /// connections for `scale_down_idle_secs`.  Candidates are drained
+    /// highest-port first while more than `min_instances` remain active; a
+    /// draining instance is stopped on a later tick once its connection count
+    /// is zero or `drain_timeout_secs` has elapsed.
+    ///
+    /// Returns the transitions performed so the caller can record them.
+    pub async fn maybe_scale_down(
+        &mut self,
+        connections: &[McpConnectionEntry],
+    ) -> Vec<PoolTransition> {
+        self.update_connection_counts(connections);
+        let mut transitions = self.retire_drained().await;
+
+        if self.pool_cfg.scale_down_idle_secs == 0 {
+            return transitions;
+        }
+        let grace = Duration::from_secs(self.pool_cfg.scale_down_idle_secs);
+        let now = Instant::now();
+
+        let mut candidates: Vec<(u16, Duration)> = self
+            .instances
+            .values()
+            .filter(|i| !i.is_draining() && i.port != self.pool_cfg.base_port)
+            .filter_map(|i| {
+                let idle = now.duration_since(i.idle_since?);
+                (idle >= grace).then_some((i.port, idle))
+            })
+            .collect();
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+        for (port, idle) in candidates {
+            if self.active_count() <= self.pool_cfg.min_instances {
+                break;
+            }
+            if let Some(instance) = self.instances.get_mut(&port) {
+                instance.draining_since = Some(now);
+                instance.drained_manually = false;
+                let reason = format!("idle for {}s", idle.as_secs());
+                eprintln!("[pool] draining MCP instance on port {port}: {reason}");
+                transitions.push(PoolTransition::drain_started(port, reason));
+            }
+        }
+        self.publish_routing();
+
+        transitions
+    }
+
+    /// Manually select an instance for scale-down (used by `ScaleDownMcp`).
+    ///
+    /// When `port` is `None` the highest-port active instance is chosen.  The
+    /// instance is drained rather than stopped immediately; the next
+    /// [`maybe_scale_down`](Self::maybe_scale_down) tick retires it, and a
+    /// saturated pool will not reclaim it in the meantime.
+    pub fn force_scale_down(&mut self, port: Option<u16>) -> anyhow::Result<PoolTransition> {
+        if self.active_count() <= self.pool_cfg.min_instances {
+            anyhow::bail!(
+                "already at min_instances ({})",
+                self.pool_cfg.min_instances
+            );
+        }
+        let base_port = self.pool_cfg.base_port;
+        let port = match port {
+            Some(p) if p == base_port => {
+                anyhow::bail!("instance on base_port {base_port} cannot be scaled down")
+            }
+            Some(p) => p,
+            None => self
+                .instances
+                .values()
+                .filter(|i| !i.is_draining() && i.port != base_port)
+                .map(|i| i.port)
+                .max()
+                .ok_or_else(|| anyhow::anyhow!("no instance eligible for scale-down"))?,
+        };
+        let instance = self
+            .instances
+            .get_mut(&port)
+            .ok_or_else(|| anyhow::anyhow!("no MCP instance on port {port}"))?;
+        if instance.is_draining() {
+            anyhow::bail!("instance on port {port} is already draining");
+        }
+        instance.draining_since = Some(Instant::now());
+        instance.drained_manually = true;
+        let reason = format!(
+            "manual scale-down ({} connection(s) to drain)",
+            instance.connection_count
+        );
+        eprintln!("[pool] draining MCP instance on port {port}: {reason}");
+        self.publish_routing();
+        Ok(PoolTransition::drain_started(port, reason))
+    }
+
+    /// Stop and remove draining instances whose sessions have closed or whose
+    /// drain timeout has expired.
+    async fn retire_drained(&mut self) -> Vec<PoolTransition> {
+        let timeout = Duration::from_secs(self.pool_cfg.drain_timeout_secs);
+        let ready: Vec<(u16, String)> = self
+            .instances
+            .values()
+            .filter_map(|i| {
+                let elapsed = i.draining_since?.elapsed();
+                if i.connection_count == 0 {
+                    Some((i.port, "drained".to_string()))
+                } else if elapsed >= timeout {
+                    Some((
+                        i.port,
+                        format!(
+                            "drain timeout after {}s with {} connection(s) open",
+                            elapsed.as_secs(),
+                            i.connection_count
+                        ),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let mut transitions = Vec::with_capacity(ready.len());
+        for (port, reason) in ready {
+            if let Some(mut instance) = self.instances.remove(&port) {
+                if let Err(e) = instance.stop().await {
+                    eprintln!("[pool] error stopping draining instance on port {port}: {e}");
+                }
+                self.port_alloc.release(port);
+                eprintln!("[pool] retired MCP instance on port {port}: {reason}");
+                transitions.push(PoolTransition::retired(port, reason));
+            }
+        }
+        self.publish_routing();
+        transitions
     }
 
-    /// Return the port of the instance with the fewest active connections.
+    /// Return the port of the live, non-draining instance with the fewest
+    /// active connections.
     ///
     /// Falls back to `base_port` if no instances are running (should not
     /// happen after `init()` succeeds).
     pub fn least_loaded_port(&self) -> u16 {
         self.instances
             .values()
+            .filter(|i| !i.is_draining() && i.is_live())
             .min_by_key(|i| i.connection_count)
             .map(|i| i.port)
             .unwrap_or(self.pool_cfg.base_port)
     }
 
-    /// Refresh health flags for all instances.  Instances that fail 2
-    /// consecutive probes (or whose process is detected as already dead) are
-    /// automatically restarted.
-    pub async fn refresh_health(&mut self) {
+    /// Port of the instance serving session-less endpoints: the one on
+    /// `base_port`, or — while that instance is stopped, or after it was
+    /// relocated because another process took the port — the lowest-port
+    /// live, non-draining instance.
+    ///
+    /// Falls back to `base_port` when no instance is running.
+    pub fn primary_port(&self) -> u16 {
+        let base_port = self.pool_cfg.base_port;
+        if self.instances.get(&base_port).is_some_and(ManagedInstance::is_live) {
+            return base_port;
+        }
+        self.instances
+            .values()
+            .filter(|i| !i.is_draining() && i.is_live())
+            .map(|i| i.port)
+            .min()
+            .unwrap_or(base_port)
+    }
+
+    /// `true` while the pool holds a running instance on `port`.  Draining
+    /// instances count as live so their remaining sessions can finish.
+    pub fn is_instance_live(&self, port: u16) -> bool {
+        self.instances.get(&port).is_some_and(ManagedInstance::is_live)
+    }
+
+    /// Refresh health flags for all instances and return the ports of
+    /// instances that crashed: those that failed 2 consecutive probes or whose
+    /// process is detected as already dead.  Draining instances are left for
+    /// [`maybe_scale_down`](Self::maybe_scale_down) to retire and, like
+    /// instances refused a respawn, are never reported.
+    ///
+    /// Crashed instances are not restarted here: the caller counts each one
+    /// with the crash-loop tracker
+    /// ([`Registry::record_crash_restart`](crate::control::registry::Registry::record_crash_restart))
+    /// and passes the decisions to [`respawn_crashed`](Self::respawn_crashed).
+    pub async fn refresh_health(&mut self) -> Vec<u16> {
+        let mut crashed: Vec<u16> = Vec::new();
         for instance in self.instances.values_mut() {
+            if instance.respawn_refused {
+                continue;
+            }
             let alive = instance.health_probe().await;
             if alive {
                 instance.healthy = true;
@@ -354,15 +730,115 @@ impl ManagedPool {
                 instance.healthy = false;
                 instance.consecutive_failures += 1;
                 let dead = instance.runner.is_process_dead();
+                if instance.is_draining() {
+                    continue;
+                }
                 if dead || instance.consecutive_failures >= 2 {
                     eprintln!(
-                        "[pool] instance on port {} is dead (failures={}, process_dead={}) — respawning",
+                        "[pool] instance on port {} is dead (failures={}, process_dead={})",
                         instance.port, instance.consecutive_failures, dead
                     );
-                    instance.restart().await;
+                    crashed.push(instance.port);
                 }
             }
         }
+        crashed.sort();
+        self.publish_routing();
+        crashed
+    }
+
+    /// Respawn the crashed instances whose crash-loop decision is
+    /// [`RestartDecision::Allow`].  Refused instances are stopped and stay in
+    /// the pool, excluded from routing, until
+    /// [`respawn_refused`](Self::respawn_refused) runs after their circuit is
+    /// reset.
+    pub async fn respawn_crashed(&mut self, decisions: &[(u16, RestartDecision)]) {
+        for (port, decision) in decisions {
+            let port = *port;
+            match decision {
+                RestartDecision::Allow => self.respawn(port).await,
+                RestartDecision::Tripped(_) | RestartDecision::Open => {
+                    let Some(instance) = self.instances.get_mut(&port) else {
+                        continue;
+                    };
+                    if instance.runner.is_process_dead() {
+                        instance.runner.mark_stopped();
+                    } else if let Err(e) = instance.stop().await {
+                        eprintln!("[pool] error stopping crashed instance on port {port}: {e}");
+                        instance.runner.mark_stopped();
+                    }
+                    instance.healthy = false;
+                    instance.respawn_refused = true;
+                    if matches!(decision, RestartDecision::Tripped(_)) {
+                        eprintln!("[pool] crash loop — no longer respawning MCP instance on port {port}");
+                    }
+                }
+            }
+        }
+        self.publish_routing();
+    }
+
+    /// Respawn the instances the crash-loop circuit refused, once it has
+    /// been reset (`ResetCircuit`).  Returns the ports respawned.
+    pub async fn respawn_refused(&mut self) -> Vec<u16> {
+        let mut ports: Vec<u16> = self
+            .instances
+            .values()
+            .filter(|i| i.respawn_refused)
+            .map(|i| i.port)
+            .collect();
+        ports.sort();
+        for &port in &ports {
+            if let Some(instance) = self.instances.get_mut(&port) {
+                instance.respawn_refused = false;
+            }
+            self.respawn(port).await;
+        }
+        self.publish_routing();
+        ports
+    }
+
+    /// Restart the instance on `port`.  If its process is dead and the port
+    /// has since been taken by another process, the instance is respawned on
+    /// a freshly allocated port instead.
+    async fn respawn(&mut self, port: u16) {
+        let Some(instance) = self.instances.get_mut(&port) else {
+            return;
+        };
+        if instance.runner.is_process_dead() && !is_port_free(port) {
+            if let Some(mut instance) = self.instances.remove(&port) {
+                instance.runner.mark_stopped();
+            }
+            self.port_alloc.release(port);
+            eprintln!("[pool] port {port} was taken by another process — relocating MCP instance");
+            self.spawn_next().await;
+            return;
+        }
+        instance.restart().await;
+    }
+
+    /// Port slot table for the registry: one entry per reserved port with the
+    /// instance's lifecycle state.
+    pub fn slots(&self) -> Vec<McpInstanceSlot> {
+        let mut slots: Vec<McpInstanceSlot> = self
+            .instances
+            .values()
+            .map(|i| McpInstanceSlot {
+                port: i.port,
+                state: if i.is_draining() {
+                    McpSlotState::Draining
+                } else if i.runner.pid().is_none() {
+                    McpSlotState::Reserved
+                } else if i.healthy {
+                    McpSlotState::Healthy
+                } else {
+                    McpSlotState::Starting
+                },
+                connection_count: i.connection_count,
+            })
+            .collect();
+        slots.sort_by_key(|s| s.port);
+        slots
     }
 
     /// Return the base URL of the least-loaded healthy instance.
@@ -385,5 +861,252 @@ impl ManagedPool {
             }
         }
         self.instances.clear();
+        self.port_alloc.clear();
+        self.publish_routing();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: u16 = 3460;
+
+    /// A sleeping process standing in for an instance's Node server.
+    fn stand_in() -> tokio::process::Child {
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("powershell");
+            cmd.args(["-NoProfile", "-Command", "Start-Sleep -Seconds 60"]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("sleep");
+            cmd.arg("60");
+            cmd
+        };
+        cmd.kill_on_drop(true).spawn().expect("spawn stand-in process")
+    }
+
+    /// A pool holding live instances on `ports`, each running a
+    /// [`stand_in`] instead of Node.  Must run inside a Tokio runtime.
+    fn pool_with(ports: &[u16], pool_cfg: PoolConfig) -> ManagedPool {
+        let mut pool = ManagedPool::new(
+            PoolConfig { base_port: BASE, ..pool_cfg },
+            NodeRunnerConfig::default(),
+            1_000,
+        );
+        for &port in ports {
+            let mut instance = ManagedInstance::new(port, &pool.node_cfg, pool.health_timeout_ms);
+            instance.runner.adopt(stand_in());
+            pool.instances.insert(port, instance);
+        }
+        pool.publish_routing();
+        pool
+    }
+
+    /// One open session per entry in `ports`.
+    fn connections(ports: &[u16]) -> Vec<McpConnectionEntry> {
+        ports
+            .iter()
+            .enumerate()
+            .map(|(i, &port)| McpConnectionEntry {
+                session_id: format!("session-{i}"),
+                transport_type: "streamable-http".into(),
+                connected_at: "2026-01-01T00:00:00Z".into(),
+                last_activity: None,
+                call_count: 0,
+                linked_client_id: None,
+                instance_port: port,
+                client_type: None,
+                workspace_id: None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn routing_snapshot_follows_load_and_drains() {
+        let mut pool = pool_with(&[BASE, BASE + 1], PoolConfig::default());
+        let routing = pool.routing();
+        assert_eq!(routing.borrow().size, 2);
+
+        pool.update_connection_counts(&connections(&[BASE, BASE]));
+        assert_eq!(routing.borrow().dispatch_port, BASE + 1);
+
+        pool.force_scale_down(Some(BASE + 1)).unwrap();
+        assert_eq!(routing.borrow().dispatch_port, BASE);
+    }
+
+    /// Pretend every instance in `pool` has been idle for `secs`.
+    fn idle_for(pool: &mut ManagedPool, secs: u64) {
+        let since = Instant::now().checked_sub(Duration::from_secs(secs)).unwrap();
+        for instance in pool.instances.values_mut() {
+            instance.idle_since = Some(since);
+        }
+    }
+
+    #[tokio::test]
+    async fn idle_instances_drain_highest_port_first_down_to_min() {
+        let cfg = PoolConfig { min_instances: 2, scale_down_idle_secs: 60, ..Default::default() };
+        let mut pool = pool_with(&[BASE, BASE + 1, BASE + 2, BASE + 3], cfg);
+        // BASE + 1 is busy; BASE is never drained.
+        idle_for(&mut pool, 120);
+        let busy = connections(&[BASE + 1]);
+
+        let transitions = pool.maybe_scale_down(&busy).await;
+        let drained: Vec<u16> = transitions.iter().map(|t| t.port).collect();
+        assert_eq!(drained, vec![BASE + 3, BASE + 2]);
+        assert!(transitions.iter().all(|t| t.new_state == "draining"));
+        assert_eq!(pool.active_count(), 2);
+
+        // Instances idle for less than the grace period stay active.
+        let cfg = PoolConfig { scale_down_idle_secs: 60, ..Default::default() };
+        let mut fresh = pool_with(&[BASE, BASE + 1], cfg);
+        idle_for(&mut fresh, 10);
+        assert!(fresh.maybe_scale_down(&[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drained_instances_retire_once_empty_or_timed_out() {
+        let cfg = PoolConfig { drain_timeout_secs: 30, scale_down_idle_secs: 0, ..Default::default() };
+        let mut pool = pool_with(&[BASE, BASE + 1, BASE + 2], cfg);
+        pool.force_scale_down(Some(BASE + 1)).unwrap();
+        pool.force_scale_down(Some(BASE + 2)).unwrap();
+        assert!(pool.force_scale_down(Some(BASE + 2)).is_err());
+
+        // BASE + 1 still has a session and its drain has not timed out.
+        let transitions = pool.maybe_scale_down(&connections(&[BASE + 1])).await;
+        assert_eq!(transitions, vec![PoolTransition::retired(BASE + 2, "drained".into())]);
+        assert_eq!(pool.ports(), vec![BASE, BASE + 1]);
+
+        pool.instances.get_mut(&(BASE + 1)).unwrap().draining_since =
+            Instant::now().checked_sub(Duration::from_secs(31));
+        let transitions = pool.maybe_scale_down(&connections(&[BASE + 1])).await;
+        assert_eq!(transitions.len(), 1);
+        assert!(transitions[0].reason.starts_with("drain timeout"));
+        assert_eq!(pool.ports(), vec![BASE]);
+        assert_eq!(pool.routing().borrow().size, 1);
+    }
+
+    #[tokio::test]
+    async fn manual_drain_refuses_base_port_and_min_instances() {
+        let mut pool = pool_with(&[BASE, BASE + 1], PoolConfig::default());
+        assert!(pool.force_scale_down(Some(BASE)).is_err());
+        let transition = pool.force_scale_down(None).unwrap();
+        assert_eq!(transition.port, BASE + 1);
+        assert_eq!(transition.new_state, "draining");
+        assert!(pool.force_scale_down(None).is_err());
+    }
+
+    #[tokio::test]
+    async fn saturated_pool_reclaims_only_autoscale_drains() {
+        let cfg = PoolConfig {
+            max_instances: 2,
+            max_connections_per_instance: 1,
+            scale_down_idle_secs: 60,
+            ..Default::default()
+        };
+        let mut pool = pool_with(&[BASE, BASE + 1], cfg.clone());
+        pool.force_scale_down(Some(BASE + 1)).unwrap();
+        // Saturated, but the only draining instance was drained by an operator
+        // and the pool is at max_instances.
+        assert_eq!(pool.maybe_scale_up(&connections(&[BASE])).await, None);
+        assert!(pool.instances[&(BASE + 1)].is_draining());
+
+        let mut pool = pool_with(&[BASE, BASE + 1], cfg);
+        idle_for(&mut pool, 120);
+        assert_eq!(pool.maybe_scale_down(&[]).await.len(), 1);
+        let reclaimed = pool.maybe_scale_up(&connections(&[BASE])).await.unwrap();
+        assert_eq!(reclaimed.port, BASE + 1);
+        assert_eq!(reclaimed.new_state, "healthy");
+        assert!(!pool.instances[&(BASE + 1)].is_draining());
+    }
+
+    #[tokio::test]
+    async fn crashed_instances_count_towards_the_mcp_circuit() {
+        use crate::control::registry::Registry;
+        use crate::runner::crash_loop::CrashLoopPolicy;
+
+        let mut registry = Registry::new();
+        registry.set_crash_loop_policy(CrashLoopPolicy {
+            max_restarts: 2,
+            window: Duration::from_secs(60),
+        });
+        // Both processes die; the draining one is left for scale-down and
+        // not reported.
+        let mut pool = pool_with(&[BASE, BASE + 1], PoolConfig::default());
+        pool.force_scale_down(Some(BASE + 1)).unwrap();
+        for instance in pool.instances.values_mut() {
+            instance.runner.crash().await;
+        }
+
+        let mut decisions = Vec::new();
+        for _ in 0..2 {
+            let crashed = pool.refresh_health().await;
+            assert_eq!(crashed, vec![BASE]);
+            decisions = crashed
+                .into_iter()
+                .map(|port| (port, registry.record_crash_restart("mcp")))
+                .collect();
+        }
+        assert!(matches!(decisions[0].1, RestartDecision::Tripped(_)));
+
+        pool.respawn_crashed(&decisions).await;
+        assert!(!pool.is_instance_live(BASE));
+        assert_eq!(pool.ports(), vec![BASE, BASE + 1]);
+        assert_eq!(pool.slots()[0].state, McpSlotState::Reserved);
+        assert_eq!(registry.record_crash_restart("mcp"), RestartDecision::Open);
+        // Refused instances are not reported again while the circuit is open.
+        assert!(pool.refresh_health().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn refused_instances_are_never_routed_to() {
+        let cfg = PoolConfig { max_instances: 3, max_connections_per_instance: 1, ..Default::default() };
+        let mut pool = pool_with(&[BASE, BASE + 1, BASE + 2], cfg);
+        let routing = pool.routing();
+        // BASE + 2 was drained as idle and can be reclaimed.
+        let idle = pool.instances.get_mut(&(BASE + 2)).unwrap();
+        idle.draining_since = Some(Instant::now());
+
+        // The instance on base_port crashes and its respawn is refused.
+        pool.instances.get_mut(&BASE).unwrap().runner.crash().await;
+        pool.respawn_crashed(&[(BASE, RestartDecision::Open)]).await;
+
+        let busy = connections(&[BASE + 1]);
+        pool.update_connection_counts(&busy);
+        let snapshot = routing.borrow().clone();
+        assert_eq!(snapshot.dispatch_port, BASE + 1, "idle but stopped BASE must not take new sessions");
+        assert_eq!(snapshot.primary_port, BASE + 1);
+        assert!(!snapshot.is_live(BASE));
+        assert_eq!(pool.least_loaded_base_url(), format!("http://127.0.0.1:{}", BASE + 1));
+
+        // BASE has no connections but does not keep the pool from counting
+        // as saturated.
+        let reclaimed = pool.maybe_scale_up(&busy).await.unwrap();
+        assert_eq!(reclaimed.port, BASE + 2);
+        assert_eq!(routing.borrow().dispatch_port, BASE + 2);
+    }
+
+    #[tokio::test]
+    async fn primary_moves_off_base_port_only_while_it_is_lost() {
+        let mut pool = pool_with(&[BASE, BASE + 1, BASE + 2], PoolConfig::default());
+        assert_eq!(pool.primary_port(), BASE);
+
+        // Relocation: the instance on base_port is gone, its replacement got
+        // a higher port.
+        pool.instances.remove(&BASE);
+        pool.force_scale_down(Some(BASE + 1)).unwrap();
+        assert_eq!(pool.primary_port(), BASE + 2);
+        assert_eq!(pool.routing().borrow().primary_port, BASE + 2);
+
+        let mut instance = ManagedInstance::new(BASE, &pool.node_cfg, pool.health_timeout_ms);
+        let stopped = ManagedInstance::new(BASE, &pool.node_cfg, pool.health_timeout_ms);
+        pool.instances.insert(BASE, stopped);
+        assert_eq!(pool.primary_port(), BASE + 2, "a stopped instance on base_port is not primary");
+        instance.runner.adopt(stand_in());
+        pool.instances.insert(BASE, instance);
+        assert_eq!(pool.primary_port(), BASE);
     }
 }
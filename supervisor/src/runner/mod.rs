@@ -8,11 +8,14 @@
 pub mod backoff;
 pub mod configured;
 pub mod container;
+pub mod crash_loop;
 pub mod dashboard;
+pub mod dependency;
 pub mod form_app;
 pub mod job_object;
 pub mod mcp_pool;
 pub mod node;
+pub mod port_allocator;
 pub mod state_machine;
 pub mod terminal;
 
@@ -234,6 +234,24 @@ impl NodeRunner {
     }
 }
 
+#[cfg(test)]
+impl NodeRunner {
+    /// Adopt an already spawned `child` as the running process, skipping
+    /// `start()`'s readiness probe, so pool tests can run without Node.
+    pub(crate) fn adopt(&mut self, child: Child) {
+        let pid = child.id().unwrap_or_default();
+        self.state = RunnerInternalState::Running { child, pid };
+    }
+
+    /// Kill the running process but keep the `Running` state, as a crash
+    /// does until the pool notices.
+    pub(crate) async fn crash(&mut self) {
+        if let RunnerInternalState::Running { ref mut child, .. } = self.state {
+            let _ = child.kill().await;
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -0,0 +1,127 @@
+//! Port allocation for MCP pool instances.
+//!
+//! [`PortAllocator`] hands out ports from a fixed inclusive range.  A port is
+//! eligible when the pool has not already reserved it and it can be bound on
+//! `127.0.0.1`, so ports held by unrelated processes are skipped rather than
+//! collided with.  Released ports return to the free set and are reused
+//! lowest-first, which fills holes left by dead or retired instances.
+
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+
+/// Allocates and tracks port reservations within `[start, end]`.
+#[derive(Debug, Clone)]
+pub struct PortAllocator {
+    start: u16,
+    end: u16,
+    reserved: BTreeSet<u16>,
+}
+
+impl PortAllocator {
+    /// Create an allocator covering `span` consecutive ports from `start`.
+    /// A `span` of zero is treated as one.
+    pub fn new(start: u16, span: u16) -> Self {
+        let end = start.saturating_add(span.max(1) - 1);
+        Self {
+            start,
+            end,
+            reserved: BTreeSet::new(),
+        }
+    }
+
+    /// The full range of ports this allocator may hand out.
+    pub fn range(&self) -> RangeInclusive<u16> {
+        self.start..=self.end
+    }
+
+    /// Reserve the lowest port that is neither reserved nor bound by another
+    /// process.  Returns `None` when the range is exhausted.
+    pub fn allocate(&mut self) -> Option<u16> {
+        self.allocate_with(is_port_free)
+    }
+
+    /// [`allocate`](Self::allocate) with an injectable "is this port free on
+    /// the host" predicate.
+    fn allocate_with(&mut self, is_free: impl Fn(u16) -> bool) -> Option<u16> {
+        let port = self
+            .range()
+            .find(|p| !self.reserved.contains(p) && is_free(*p))?;
+        self.reserved.insert(port);
+        Some(port)
+    }
+
+    /// Return `port` to the free set.  No-op if it was not reserved.
+    pub fn release(&mut self, port: u16) {
+        self.reserved.remove(&port);
+    }
+
+    /// Release every reservation.
+    pub fn clear(&mut self) {
+        self.reserved.clear();
+    }
+
+    /// `true` if `port` is currently reserved by this allocator.
+    pub fn is_reserved(&self, port: u16) -> bool {
+        self.reserved.contains(&port)
+    }
+}
+
+/// Return `true` if nothing is listening on `127.0.0.1:port`.
+pub fn is_port_free(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_lowest_free_port_first() {
+        let mut alloc = PortAllocator::new(4000, 4);
+        assert_eq!(alloc.allocate_with(|_| true), Some(4000));
+        assert_eq!(alloc.allocate_with(|_| true), Some(4001));
+        assert!(alloc.is_reserved(4000));
+    }
+
+    #[test]
+    fn skips_ports_held_by_other_processes() {
+        let mut alloc = PortAllocator::new(4000, 4);
+        assert_eq!(alloc.allocate_with(|p| p != 4000 && p != 4001), Some(4002));
+    }
+
+    #[test]
+    fn released_hole_is_reused() {
+        let mut alloc = PortAllocator::new(4000, 4);
+        for _ in 0..3 {
+            alloc.allocate_with(|_| true);
+        }
+        alloc.release(4001);
+        assert!(!alloc.is_reserved(4001));
+        assert_eq!(alloc.allocate_with(|_| true), Some(4001));
+    }
+
+    #[test]
+    fn returns_none_when_range_exhausted() {
+        let mut alloc = PortAllocator::new(4000, 2);
+        assert_eq!(alloc.allocate_with(|_| true), Some(4000));
+        assert_eq!(alloc.allocate_with(|_| true), Some(4001));
+        assert_eq!(alloc.allocate_with(|_| true), None);
+    }
+
+    #[test]
+    fn range_saturates_at_u16_max() {
+        let alloc = PortAllocator::new(u16::MAX - 1, 10);
+        assert_eq!(alloc.range(), (u16::MAX - 1)..=u16::MAX);
+    }
+
+    #[test]
+    fn is_port_free_detects_bound_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+        assert!(!is_port_free(port));
+    }
+}
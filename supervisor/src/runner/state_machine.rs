@@ -583,6 +583,8 @@ mod tests {
             cooldown_child_local_ms: 0,
             cooldown_dependency_group_ms: 0,
             cooldown_global_ms: 0,
+            crash_loop_max_restarts: 5,
+            crash_loop_window_secs: 300,
         };
         let mut sm = ServiceStateMachine::new(&config, "test-service", RestartPolicy::AlwaysRestart);
         sm.on_start();
@@ -606,6 +608,8 @@ mod tests {
             cooldown_child_local_ms: 0,
             cooldown_dependency_group_ms: 2_500,
             cooldown_global_ms: 0,
+            crash_loop_max_restarts: 5,
+            crash_loop_window_secs: 300,
         };
 
         let mut sm = ServiceStateMachine::new(&config, "test-service", RestartPolicy::AlwaysRestart);
@@ -1,7 +1,8 @@
 //! Runtime output event bus for managed supervisor components.
 //!
 //! This module keeps an in-memory ring buffer of recent output lines and
-//! exposes a broadcast channel for live subscribers (SSE stream).
+//! exposes a broadcast channel for live subscribers (SSE stream).  Every line
+//! is also handed to [`crate::service_logs`] for the on-disk per-service logs.
 
 use std::collections::VecDeque;
 use std::sync::{
@@ -59,17 +60,23 @@ pub fn set_enabled(enabled: bool) {
 }
 
 pub fn emit(component: &str, stream: &str, line: impl Into<String>) {
-    if !is_enabled() {
+    let content = line.into();
+    if content.trim().is_empty() {
         return;
     }
 
-    let content = line.into();
-    if content.trim().is_empty() {
+    // Per-service log files are written even when in-memory capture is off;
+    // the line is only queued here, the file I/O happens on the log writer
+    // thread.
+    let timestamp_ms = now_ms();
+    crate::service_logs::append(component, stream, timestamp_ms, &content);
+
+    if !is_enabled() {
         return;
     }
 
     let event = RuntimeOutputEvent {
-        timestamp_ms: now_ms(),
+        timestamp_ms,
         component: component.to_string(),
         stream: stream.to_string(),
         line: content,
@@ -127,22 +134,14 @@ where
         let mut lines = BufReader::new(reader).lines();
         loop {
             match lines.next_line().await {
-                Ok(Some(line)) => {
-                    if !is_enabled() {
-                        continue;
-                    }
-
-                    emit(&component, &stream_name, line);
-                }
+                Ok(Some(line)) => emit(&component, &stream_name, line),
                 Ok(None) => break,
                 Err(err) => {
-                    if is_enabled() {
-                        emit(
-                            &component,
-                            "status",
-                            format!("runtime output read error ({stream_name}): {err}"),
-                        );
-                    }
+                    emit(
+                        &component,
+                        "status",
+                        format!("runtime output read error ({stream_name}): {err}"),
+                    );
                     break;
                 }
             }
@@ -0,0 +1,455 @@
+//! Per-service rotating log files.
+//!
+//! Every line that passes through [`crate::runtime_output`] is also appended
+//! to `<dir>/<service>.log`, independently of the in-memory ring, so a
+//! component's output from before a crash survives the respawn.
+//!
+//! When the active file would exceed `max_file_bytes` it is rotated:
+//!
+//! ```text
+//! mcp_3460.log          active file
+//! mcp_3460.1.log.gz     most recent rotation (gzip when `compress = true`)
+//! mcp_3460.2.log.gz
+//! …                     up to `max_files` rotations; older ones are deleted
+//! ```
+//!
+//! Each line is written as `<timestamp_ms> [<stream>] <text>` and parsed back
+//! into a [`RuntimeOutputEvent`] by [`ServiceLogs::tail`].
+//!
+//! Lines arrive from pipe readers on the tokio workers, so the installed
+//! instance does no file I/O on the caller: [`append`] queues the line for a
+//! dedicated writer thread ([`LogWriter`]) that owns every write and
+//! rotation, including the gzip of a rotated file.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::Deserialize;
+
+use crate::runtime_output::RuntimeOutputEvent;
+
+/// `[runtime_output.files]` section.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServiceLogConfig {
+    /// Write per-service log files (default: `true`).
+    pub enabled: bool,
+    /// Directory for the log files.  `None` → `<data_dir>/logs`.
+    pub dir: Option<PathBuf>,
+    /// Rotate the active file once it reaches this size (default: 10 MiB).
+    pub max_file_bytes: u64,
+    /// Rotated files kept per service; older ones are deleted (default: 5).
+    pub max_files: usize,
+    /// Gzip rotated files (default: `true`).
+    pub compress: bool,
+}
+
+impl Default for ServiceLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+            max_file_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            compress: true,
+        }
+    }
+}
+
+struct ActiveFile {
+    file: File,
+    size: u64,
+}
+
+/// Rotating log files for all services under one directory.
+pub struct ServiceLogs {
+    dir: PathBuf,
+    config: ServiceLogConfig,
+    files: Mutex<HashMap<String, ActiveFile>>,
+}
+
+impl ServiceLogs {
+    pub fn new(dir: PathBuf, config: ServiceLogConfig) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            config,
+            files: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Append one line for `service`, rotating first if it would not fit.
+    pub fn append(&self, service: &str, stream: &str, timestamp_ms: u64, line: &str) -> io::Result<()> {
+        let key = file_key(service);
+        let record = format!("{timestamp_ms} [{stream}] {line}\n");
+
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let needs_rotation = files.get(&key).map_or_else(
+            || {
+                fs::metadata(self.active_path(&key))
+                    .map(|m| m.len() > 0 && m.len() + record.len() as u64 > self.config.max_file_bytes)
+                    .unwrap_or(false)
+            },
+            |active| active.size > 0 && active.size + record.len() as u64 > self.config.max_file_bytes,
+        );
+        if needs_rotation {
+            files.remove(&key);
+            self.rotate(&key)?;
+        }
+
+        let active = match files.entry(key) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                let path = self.active_path(e.key());
+                let file = OpenOptions::new().create(true).append(true).open(&path)?;
+                let size = file.metadata()?.len();
+                e.insert(ActiveFile { file, size })
+            }
+        };
+        active.file.write_all(record.as_bytes())?;
+        active.size += record.len() as u64;
+        Ok(())
+    }
+
+    /// The last `lines` entries for `service`, oldest first, reading back
+    /// into rotated files when the active one is too short.
+    pub fn tail(&self, service: &str, lines: usize) -> io::Result<Vec<RuntimeOutputEvent>> {
+        let key = file_key(service);
+        let mut collected: Vec<String> = Vec::new();
+
+        let mut sources = vec![self.active_path(&key)];
+        sources.extend((1..=self.config.max_files).filter_map(|i| self.existing_rotation(&key, i)));
+
+        for path in sources {
+            if collected.len() >= lines {
+                break;
+            }
+            let mut chunk = match read_lines(&path) {
+                Ok(chunk) => chunk,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            chunk.append(&mut collected);
+            collected = chunk;
+        }
+
+        let start = collected.len().saturating_sub(lines);
+        Ok(collected[start..]
+            .iter()
+            .map(|line| parse_line(service, line))
+            .collect())
+    }
+
+    fn active_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.log"))
+    }
+
+    fn rotation_path(&self, key: &str, index: usize, compressed: bool) -> PathBuf {
+        let ext = if compressed { "log.gz" } else { "log" };
+        self.dir.join(format!("{key}.{index}.{ext}"))
+    }
+
+    fn existing_rotation(&self, key: &str, index: usize) -> Option<PathBuf> {
+        [true, false]
+            .into_iter()
+            .map(|compressed| self.rotation_path(key, index, compressed))
+            .find(|path| path.exists())
+    }
+
+    fn rotate(&self, key: &str) -> io::Result<()> {
+        let active = self.active_path(key);
+        if self.config.max_files == 0 {
+            return File::create(&active).map(|_| ());
+        }
+
+        if let Some(oldest) = self.existing_rotation(key, self.config.max_files) {
+            fs::remove_file(oldest)?;
+        }
+        for index in (1..self.config.max_files).rev() {
+            if let Some(path) = self.existing_rotation(key, index) {
+                let compressed = path.extension().is_some_and(|e| e == "gz");
+                fs::rename(&path, self.rotation_path(key, index + 1, compressed))?;
+            }
+        }
+
+        if self.config.compress {
+            let target = self.rotation_path(key, 1, true);
+            let mut input = File::open(&active)?;
+            let mut encoder = GzEncoder::new(File::create(&target)?, Compression::fast());
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?;
+            fs::remove_file(&active)?;
+        } else {
+            fs::rename(&active, self.rotation_path(key, 1, false))?;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Writer thread
+// ---------------------------------------------------------------------------
+
+/// Lines waiting for the writer thread; further lines are dropped (and
+/// counted) until it catches up.
+const WRITE_QUEUE_CAPACITY: usize = 8192;
+
+struct QueuedLine {
+    service: String,
+    stream: String,
+    timestamp_ms: u64,
+    line: String,
+}
+
+/// Queue in front of a thread that performs all writes to a [`ServiceLogs`].
+pub struct LogWriter {
+    tx: SyncSender<QueuedLine>,
+    dropped: Arc<AtomicU64>,
+    thread: JoinHandle<()>,
+}
+
+impl LogWriter {
+    pub fn spawn(logs: Arc<ServiceLogs>) -> io::Result<Self> {
+        let (tx, rx) = mpsc::sync_channel::<QueuedLine>(WRITE_QUEUE_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread = {
+            let dropped = Arc::clone(&dropped);
+            thread::Builder::new().name("service-logs".into()).spawn(move || {
+                for queued in rx {
+                    if let Err(e) = logs.append(&queued.service, &queued.stream, queued.timestamp_ms, &queued.line) {
+                        eprintln!("[service_logs] failed to write log for '{}': {e}", queued.service);
+                    }
+                    let lost = dropped.swap(0, Ordering::Relaxed);
+                    if lost > 0 {
+                        eprintln!("[service_logs] write queue full; dropped {lost} line(s)");
+                    }
+                }
+            })?
+        };
+        Ok(Self { tx, dropped, thread })
+    }
+
+    /// Queue a line without blocking; dropped if the queue is full.
+    pub fn append(&self, service: &str, stream: &str, timestamp_ms: u64, line: &str) {
+        let queued = QueuedLine {
+            service: service.to_string(),
+            stream: stream.to_string(),
+            timestamp_ms,
+            line: line.to_string(),
+        };
+        if let Err(TrySendError::Full(_)) = self.tx.try_send(queued) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Stop accepting lines and wait for the queued ones to be written.
+    pub fn close(self) {
+        drop(self.tx);
+        let _ = self.thread.join();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Process-wide instance
+// ---------------------------------------------------------------------------
+
+struct Installed {
+    logs: Arc<ServiceLogs>,
+    writer: LogWriter,
+}
+
+static SERVICE_LOGS: OnceLock<Installed> = OnceLock::new();
+
+/// Start writing per-service log files.  Only the first call takes effect.
+pub fn install(dir: PathBuf, config: ServiceLogConfig) -> io::Result<()> {
+    if SERVICE_LOGS.get().is_some() {
+        return Ok(());
+    }
+    let logs = Arc::new(ServiceLogs::new(dir, config)?);
+    let writer = LogWriter::spawn(Arc::clone(&logs))?;
+    let _ = SERVICE_LOGS.set(Installed { logs, writer });
+    Ok(())
+}
+
+/// The installed log files, if any.
+pub fn installed() -> Option<&'static ServiceLogs> {
+    SERVICE_LOGS.get().map(|installed| installed.logs.as_ref())
+}
+
+/// Queue a line for `service`'s log file; a no-op until [`install`] is
+/// called.  Never blocks on disk I/O.
+pub fn append(service: &str, stream: &str, timestamp_ms: u64, line: &str) {
+    if let Some(installed) = SERVICE_LOGS.get() {
+        installed.writer.append(service, stream, timestamp_ms, line);
+    }
+}
+
+/// Lines returned by a tail request that does not ask for a count.
+pub const DEFAULT_TAIL_LINES: usize = 200;
+/// Upper bound on lines returned by a single tail request.
+pub const MAX_TAIL_LINES: usize = 10_000;
+
+/// [`ServiceLogs::tail`] on the installed instance, off the async runtime.
+///
+/// Lines still queued for the writer thread are not included.
+pub async fn tail(service: &str, lines: usize) -> Result<Vec<RuntimeOutputEvent>, String> {
+    let Some(logs) = installed() else {
+        return Err("service log files are disabled".to_string());
+    };
+    let owned = service.to_string();
+    let lines = lines.clamp(1, MAX_TAIL_LINES);
+    tokio::task::spawn_blocking(move || logs.tail(&owned, lines))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("failed to read log for '{service}': {e}"))
+}
+
+/// File-name stem for a service: `mcp:3460` → `mcp_3460`.
+///
+/// Two names with the same key share a log file, so this is also the key
+/// live output is matched on when following a log.
+pub fn file_key(service: &str) -> String {
+    let key: String = service
+        .trim()
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if key.is_empty() { "_".to_string() } else { key }
+}
+
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|e| e == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    BufReader::new(reader)
+        .lines()
+        .filter(|line| !matches!(line, Ok(l) if l.is_empty()))
+        .collect()
+}
+
+/// Parse `<timestamp_ms> [<stream>] <text>`; lines that do not match are
+/// returned whole with timestamp 0 and stream `unknown`.
+fn parse_line(service: &str, line: &str) -> RuntimeOutputEvent {
+    let parsed = line.split_once(' ').and_then(|(ts, rest)| {
+        let ts = ts.parse::<u64>().ok()?;
+        let rest = rest.strip_prefix('[')?;
+        let (stream, text) = rest.split_once("] ")?;
+        Some((ts, stream.to_string(), text.to_string()))
+    });
+    let (timestamp_ms, stream, line) =
+        parsed.unwrap_or_else(|| (0, "unknown".to_string(), line.to_string()));
+    RuntimeOutputEvent {
+        timestamp_ms,
+        component: service.to_string(),
+        stream,
+        line,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config(compress: bool) -> ServiceLogConfig {
+        ServiceLogConfig {
+            max_file_bytes: 64,
+            max_files: 2,
+            compress,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn appends_and_tails_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = ServiceLogs::new(dir.path().to_path_buf(), ServiceLogConfig::default()).unwrap();
+        logs.append("mcp:3460", "stdout", 1, "hello").unwrap();
+        logs.append("mcp:3460", "stderr", 2, "boom [x]").unwrap();
+
+        assert!(dir.path().join("mcp_3460.log").exists());
+        let tail = logs.tail("mcp:3460", 10).unwrap();
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[1].timestamp_ms, 2);
+        assert_eq!(tail[1].stream, "stderr");
+        assert_eq!(tail[1].line, "boom [x]");
+        assert_eq!(logs.tail("mcp:3460", 1).unwrap()[0].line, "boom [x]");
+    }
+
+    #[test]
+    fn rotates_compresses_and_caps_file_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = ServiceLogs::new(dir.path().to_path_buf(), small_config(true)).unwrap();
+        for i in 0..20 {
+            logs.append("dashboard", "stdout", i, &format!("line number {i:02}")).unwrap();
+        }
+
+        assert!(dir.path().join("dashboard.1.log.gz").exists());
+        assert!(dir.path().join("dashboard.2.log.gz").exists());
+        assert!(!dir.path().join("dashboard.3.log.gz").exists());
+
+        // Tail reads back across the active file and the rotations.
+        let tail = logs.tail("dashboard", 4).unwrap();
+        let text: Vec<&str> = tail.iter().map(|e| e.line.as_str()).collect();
+        assert_eq!(text, ["line number 16", "line number 17", "line number 18", "line number 19"]);
+    }
+
+    #[test]
+    fn writer_thread_appends_queued_lines_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = Arc::new(ServiceLogs::new(dir.path().to_path_buf(), small_config(true)).unwrap());
+        let writer = LogWriter::spawn(Arc::clone(&logs)).unwrap();
+        for i in 0..10 {
+            writer.append("mcp:3460", "stdout", i, &format!("queued line {i}"));
+        }
+        writer.close();
+
+        assert!(dir.path().join("mcp_3460.1.log.gz").exists());
+        let tail = logs.tail("MCP_3460", 4).unwrap();
+        let text: Vec<&str> = tail.iter().map(|e| e.line.as_str()).collect();
+        assert_eq!(text, ["queued line 6", "queued line 7", "queued line 8", "queued line 9"]);
+    }
+
+    #[test]
+    fn names_that_share_a_file_share_a_key() {
+        assert_eq!(file_key("mcp:3460"), "mcp_3460");
+        assert_eq!(file_key(" MCP:3460 "), file_key("mcp_3460"));
+        assert_ne!(file_key("mcp:3460"), file_key("mcp:3461"));
+    }
+
+    #[test]
+    fn rotation_survives_reopen_without_compression() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let logs = ServiceLogs::new(dir.path().to_path_buf(), small_config(false)).unwrap();
+            logs.append("helper", "stdout", 1, "before restart, long enough line").unwrap();
+        }
+        let logs = ServiceLogs::new(dir.path().to_path_buf(), small_config(false)).unwrap();
+        logs.append("helper", "stdout", 2, "after restart, long enough line!").unwrap();
+
+        assert!(dir.path().join("helper.1.log").exists());
+        let tail = logs.tail("helper", 10).unwrap();
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[0].line, "before restart, long enough line");
+    }
+}
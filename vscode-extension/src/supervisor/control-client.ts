@@ -41,14 +41,62 @@ export interface McpConnectionInfo {
 
 export interface McpInstanceInfo {
   port: number;
+  /** Pool slot state; `null` when the port is not (yet) in the pool's slot table. */
+  state: 'reserved' | 'starting' | 'healthy' | 'draining' | null;
   connection_count: number;
 }
 
+export interface McpAffinityEntry {
+  session_id: string;
+  /** Pool instance port that owns the session. */
+  port: number;
+  bound_at_ms: number;
+  last_seen_ms: number;
+}
+
+export interface CircuitResetResult {
+  service: string;
+  /** `false` when the service's circuit was not open. */
+  reset: boolean;
+  restart_requested: boolean;
+}
+
+export interface ServiceLogLine {
+  timestamp_ms: number;
+  component: string;
+  /** `stdout`, `stderr` or `status`. */
+  stream: string;
+  line: string;
+}
+
+export interface ServiceLogTail {
+  service: string;
+  count: number;
+  items: ServiceLogLine[];
+  /** SSE URL for new lines; set only when `follow` was requested. */
+  follow_url: string | null;
+}
+
+export interface EventSubscriptionFilter {
+  workspace_id?: string;
+  plan_id?: string;
+  /** `event_type` tags; entries ending in `*` match by prefix. */
+  types?: string[];
+}
+
+export interface EventSubscriptionInfo {
+  id: number;
+  client_ip: string;
+  connected_at_ms: number;
+  filter: EventSubscriptionFilter;
+}
+
 export interface EventStatsInfo {
   enabled: boolean;
   subscriber_count: number;
   events_emitted: number;
   events_url: string | null;
+  subscriptions?: EventSubscriptionInfo[];
 }
 
 interface ControlResponse {
@@ -260,6 +308,51 @@ export class SupervisorControlClient implements vscode.Disposable {
     return [];
   }
 
+  /**
+   * Close a service's crash-loop circuit so automatic restarts resume.
+   * The Supervisor also restarts the service when the circuit was open.
+   */
+  async resetCircuit(service: string): Promise<CircuitResetResult | null> {
+    try {
+      const resp = await this.sendRequest({ type: 'ResetCircuit', service });
+      if (resp.ok && resp.data) {
+        return resp.data as CircuitResetResult;
+      }
+    } catch (e) {
+      console.warn('[SupervisorClient] ResetCircuit failed:', e);
+    }
+    return null;
+  }
+
+  /**
+   * Read the last `lines` lines of a service's on-disk log.  Returns `null`
+   * when log files are disabled or the request fails.
+   */
+  async tailLogs(service: string, lines?: number, follow = false): Promise<ServiceLogTail | null> {
+    try {
+      const resp = await this.sendRequest({ type: 'TailLogs', service, lines, follow });
+      if (resp.ok && resp.data) {
+        return resp.data as ServiceLogTail;
+      }
+    } catch (e) {
+      console.warn('[SupervisorClient] TailLogs failed:', e);
+    }
+    return null;
+  }
+
+  /** List the proxy's MCP session → owning instance table. */
+  async listMcpAffinity(): Promise<McpAffinityEntry[]> {
+    try {
+      const resp = await this.sendRequest({ type: 'ListMcpAffinity' });
+      if (resp.ok && Array.isArray(resp.data)) {
+        return resp.data as McpAffinityEntry[];
+      }
+    } catch (e) {
+      console.warn('[SupervisorClient] ListMcpAffinity failed:', e);
+    }
+    return [];
+  }
+
   /**
    * Manually trigger a pool scale-up (spawn one additional MCP instance).
    * Returns `true` if the Supervisor accepted the request.